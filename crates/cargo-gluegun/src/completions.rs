@@ -0,0 +1,71 @@
+use std::{collections::BTreeSet, ffi::OsStr, io::Write, path::Path};
+
+use clap::{builder::PossibleValuesParser, CommandFactory};
+use clap_complete::Shell;
+
+use crate::Cli;
+
+/// Write a completion script for `shell` to `out`.
+///
+/// Completion scripts are static, so the list of `plugins` offered for the plugins positional is
+/// fixed when the script is generated (see [`discover_plugins`][]).
+pub fn write_completions(shell: Shell, plugins: &[String], out: &mut dyn Write) -> anyhow::Result<()> {
+    let mut command = Cli::command();
+    if !plugins.is_empty() {
+        // Not `mut_arg`, which would move the plugins positional after the arguments given after `--`.
+        command = command.mut_args(|arg| {
+            if arg.get_id() == "plugins" {
+                arg.value_parser(PossibleValuesParser::new(plugins.to_vec()))
+            } else {
+                arg
+            }
+        });
+    }
+    clap_complete::generate(shell, &mut command, "cargo-gluegun", out);
+    Ok(())
+}
+
+/// Find the names of the plugins installed in the directories of `path` (in the format of the `PATH` variable).
+/// A plugin `x` is installed as an executable named `gluegun-x`.
+pub fn discover_plugins(path: Option<&OsStr>) -> Vec<String> {
+    let mut plugins = BTreeSet::new();
+
+    for dir in path.iter().flat_map(|path| std::env::split_paths(path)) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+
+            let Some(plugin) = file_name
+                .strip_suffix(std::env::consts::EXE_SUFFIX)
+                .and_then(|name| name.strip_prefix("gluegun-"))
+            else {
+                continue;
+            };
+
+            if !plugin.is_empty() && is_executable(&entry.path()) {
+                plugins.insert(plugin.to_string());
+            }
+        }
+    }
+
+    plugins.into_iter().collect()
+}
+
+/// True if `path` is a file that can be run.
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,30 @@
+use gluegun_core::protocol::SourceDependency;
+
+/// The direct dependencies of `package` in cargo's resolved dependency graph,
+/// i.e., with the versions cargo selected rather than the requirements written in the manifest.
+pub(crate) fn resolved_dependencies(
+    metadata: &cargo_metadata::Metadata,
+    package: &cargo_metadata::Package,
+) -> anyhow::Result<Vec<SourceDependency>> {
+    let Some(resolve) = &metadata.resolve else {
+        anyhow::bail!("cargo did not resolve the dependency graph");
+    };
+    let Some(node) = resolve.nodes.iter().find(|node| node.id == package.id) else {
+        anyhow::bail!("{pkg}: package missing from the resolved dependency graph", pkg = package.name);
+    };
+
+    node.deps
+        .iter()
+        .map(|dep| {
+            let Some(dep_package) = metadata.packages.iter().find(|p| p.id == dep.pkg) else {
+                anyhow::bail!("{pkg}: dependency `{name}` has no package", pkg = package.name, name = dep.name);
+            };
+            Ok(SourceDependency::new(
+                dep_package.name.clone(),
+                dep_package.version.to_string(),
+                dep_package.source.as_ref().map(|source| source.repr.clone()),
+                dep.dep_kinds.iter().map(|info| info.kind.to_string()).collect(),
+            ))
+        })
+        .collect()
+}
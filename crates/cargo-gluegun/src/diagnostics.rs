@@ -0,0 +1,103 @@
+use cargo_metadata::{camino::Utf8Path, PackageId, Target};
+use serde::Serialize;
+
+/// How errors are reported, selected with `--message-format`.
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum MessageFormat {
+    /// Human-readable messages on stderr.
+    #[default]
+    Human,
+
+    /// One cargo-compatible `compiler-message` JSON object per line on stdout.
+    Json,
+}
+
+/// A diagnostic in the format emitted by `rustc --error-format json`.
+/// Only the fields that gluegun can fill in meaningfully are populated.
+#[derive(Serialize, Debug)]
+pub struct Diagnostic {
+    message: String,
+    code: Option<()>,
+    level: &'static str,
+    spans: Vec<DiagnosticSpan>,
+    children: Vec<Diagnostic>,
+    rendered: Option<String>,
+}
+
+/// A source location within a [`Diagnostic`][].
+#[derive(Serialize, Debug)]
+struct DiagnosticSpan {
+    file_name: String,
+    byte_start: usize,
+    byte_end: usize,
+    line_start: usize,
+    line_end: usize,
+    column_start: usize,
+    column_end: usize,
+    is_primary: bool,
+    text: Vec<()>,
+    label: Option<String>,
+    suggested_replacement: Option<String>,
+    suggestion_applicability: Option<String>,
+    expansion: Option<()>,
+}
+
+impl Diagnostic {
+    /// Create an error diagnostic from `err`.
+    /// If the error was caused by a [`gluegun_idl::Error`][] with a span, that span is included.
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        let spans = err
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<gluegun_idl::Error>())
+            .and_then(|idl_err| idl_err.span())
+            .map(|span| DiagnosticSpan {
+                file_name: span.path().display().to_string(),
+                byte_start: *span.start().byte(),
+                byte_end: *span.end().byte(),
+                line_start: *span.start().line(),
+                line_end: *span.end().line(),
+                column_start: *span.start().column(),
+                column_end: *span.end().column(),
+                is_primary: true,
+                text: vec![],
+                label: None,
+                suggested_replacement: None,
+                suggestion_applicability: None,
+                expansion: None,
+            })
+            .into_iter()
+            .collect();
+
+        Diagnostic {
+            message: format!("{err:#}"),
+            code: None,
+            level: "error",
+            spans,
+            children: vec![],
+            rendered: Some(format!("error: {err:#}\n")),
+        }
+    }
+}
+
+/// The message cargo emits for a diagnostic under `--message-format json`.
+#[derive(Serialize)]
+struct CompilerMessage<'a> {
+    reason: &'static str,
+    package_id: &'a PackageId,
+    manifest_path: &'a Utf8Path,
+    target: Option<&'a Target>,
+    message: Diagnostic,
+}
+
+/// Print `err` as a cargo `compiler-message` for `package` on stdout.
+pub(crate) fn emit_json(package: &cargo_metadata::Package, err: &anyhow::Error) -> anyhow::Result<()> {
+    let message = CompilerMessage {
+        reason: "compiler-message",
+        package_id: &package.id,
+        manifest_path: &package.manifest_path,
+        target: package.targets.iter().find(|target| target.is_lib()),
+        message: Diagnostic::from_error(err),
+    };
+    println!("{}", serde_json::to_string(&message)?);
+    Ok(())
+}
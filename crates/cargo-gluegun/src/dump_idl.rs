@@ -0,0 +1,38 @@
+use std::{io::Write, path::Path};
+
+/// Write `idl` as JSON for `dump-idl`, pretty-printed unless `compact`.
+/// The items are sorted by name and the source paths are relative to the package root,
+/// so dumps of the same interface are identical wherever the package lives and can be diffed.
+pub fn write_idl(idl: &gluegun_idl::Idl, compact: bool, out: &mut impl Write) -> anyhow::Result<()> {
+    let mut json = serde_json::to_value(idl)?;
+    let root = idl.crate_path();
+    json["crate_path"] = serde_json::Value::from(".");
+    relativize_spans(&mut json, root);
+
+    if compact {
+        serde_json::to_writer(&mut *out, &json)?;
+    } else {
+        serde_json::to_writer_pretty(&mut *out, &json)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Make the `path` of every span in `json` relative to `root`.
+/// Paths outside of `root` (e.g., a module included with `#[path]`) are left as they are.
+fn relativize_spans(json: &mut serde_json::Value, root: &Path) {
+    match json {
+        serde_json::Value::Object(map) => {
+            if map.contains_key("start") && map.contains_key("end") {
+                if let Some(serde_json::Value::String(path)) = map.get_mut("path") {
+                    if let Ok(relative) = Path::new(path.as_str()).strip_prefix(root) {
+                        *path = relative.display().to_string();
+                    }
+                }
+            }
+            map.values_mut().for_each(|value| relativize_spans(value, root));
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(|value| relativize_spans(value, root)),
+        _ => {}
+    }
+}
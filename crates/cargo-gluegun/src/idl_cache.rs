@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// The interface of a package as last extracted, saved in `target/gluegun/idl/` so that later runs
+/// can skip parsing packages whose sources have not changed since.
+/// A failed extraction is saved too, without an interface, to record the source files it read (see `--watch`).
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CachedIdl {
+    /// The modification time of the `cargo gluegun` executable that parsed the sources,
+    /// as a change to the parser may change the IDL extracted from the same sources.
+    parser: Option<SystemTime>,
+
+    /// The root source file of the library target, which `[lib].path` may change.
+    root: PathBuf,
+
+    /// The modification time of each source file looked for while parsing (`None` if it did not exist).
+    sources: BTreeMap<PathBuf, Option<SystemTime>>,
+
+    /// `None` if extracting the interface failed.
+    idl: Option<gluegun_idl::Idl>,
+}
+
+impl CachedIdl {
+    /// Record `idl` as extracted from `root` by the current executable, reading `source_files`
+    /// (or the failure to extract it, if `None`).
+    pub(crate) fn new(idl: Option<gluegun_idl::Idl>, root: &Utf8Path, source_files: &[PathBuf]) -> Self {
+        Self {
+            parser: parser_modified(),
+            root: root.into(),
+            sources: source_files.iter().map(|path| (path.clone(), modified(path))).collect(),
+            idl,
+        }
+    }
+
+    /// Load the IDL cached at `path`, if any, and return it if it was extracted from `root` and is still up to date.
+    /// Unreadable caches (e.g., from a different version) are treated as missing.
+    pub(crate) fn load(path: &Utf8Path, root: &Utf8Path) -> Option<gluegun_idl::Idl> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let cached: CachedIdl = serde_json::from_str(&text).ok()?;
+        let up_to_date = cached.root == root
+            && cached.parser.is_some()
+            && cached.parser == parser_modified()
+            && cached.sources.iter().all(|(path, time)| modified(path) == *time);
+        up_to_date.then_some(cached.idl).flatten()
+    }
+
+    /// The source files read when the IDL cached at `path` was extracted (or failed to be), if there is a readable cache.
+    pub(crate) fn sources(path: &Utf8Path) -> Option<Vec<PathBuf>> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let cached: CachedIdl = serde_json::from_str(&text).ok()?;
+        Some(cached.sources.into_keys().collect())
+    }
+
+    /// Save to `path`, creating its directory as needed.
+    pub(crate) fn save(&self, path: &Utf8Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// The file in which the IDL of `package` is cached, under the `target_directory` of the workspace.
+pub(crate) fn cache_path(target_directory: &Utf8Path, package: &cargo_metadata::Package) -> Utf8PathBuf {
+    // Packages of the same name can appear in one workspace, so the name is disambiguated by a hash
+    // of the manifest path (64-bit FNV-1a, as `std`'s hasher may change across releases).
+    let manifest_hash = package
+        .manifest_path
+        .as_str()
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3));
+    target_directory
+        .join("gluegun")
+        .join("idl")
+        .join(format!("{}-{manifest_hash:016x}.json", package.name))
+}
+
+pub(crate) fn modified(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn parser_modified() -> Option<SystemTime> {
+    modified(&std::env::current_exe().ok()?)
+}
@@ -1,28 +1,94 @@
+use std::collections::{BTreeMap, VecDeque};
 use std::ffi::OsString;
-use std::io::Write;
+use std::io::{BufRead, BufReader, Write};
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::process::{ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use anyhow::Context;
-use cargo_metadata::camino::Utf8PathBuf;
+use cargo_metadata::camino::{Utf8Path, Utf8PathBuf};
 use clap::Parser;
+use gluegun_core::cli::UnsupportedErrors;
+use gluegun_core::protocol::{self, GlueGunDestinationCrate, PluginInfo, PluginInput, UnsupportedError, PROTOCOL_VERSION};
 use serde::{Deserialize, Serialize};
 
+mod completions;
+pub use completions::{discover_plugins, write_completions};
+
+mod dependencies;
+
+mod dump_idl;
+pub use dump_idl::write_idl;
+
+mod idl_cache;
+use idl_cache::CachedIdl;
+
+mod log;
+use log::{Log, Verbosity};
+
+mod diagnostics;
+pub use diagnostics::Diagnostic;
+use diagnostics::MessageFormat;
+
+mod plugin_list;
+pub use plugin_list::{query_plugins, write_plugin_list, ListFormat, PluginListing};
+
+mod plugin_search;
+pub use plugin_search::find_plugin;
+
+mod report;
+use report::{GeneratedCrate, Report};
+
+mod watch;
+use watch::WatchedSources;
+
+mod workspace;
+
 /// Main function for the gluegun CLI.
 pub fn cli_main() -> anyhow::Result<()> {
-    Builder::from_env()?.execute()
+    Builder::from_env()?.execute()?;
+    Ok(())
 }
 
 /// Struct to customize GlueGun CLI execution.
 pub struct Builder {
     current_directory: Utf8PathBuf,
     args: Vec<OsString>,
-    plugin_command: Box<dyn Fn(
-        &serde_json::Value,
-        &str,
-    ) -> anyhow::Result<Command>>,
+    plugin_command: Option<Box<PluginCommandFn>>,
+    plugin_dirs: Vec<Utf8PathBuf>,
+    plugin_args: Vec<String>,
+    log: Log,
+    capture_output: bool,
+    stop_watching: Arc<AtomicBool>,
+
+    /// What each plugin reported about itself, queried the first time it is run (`None` if it could not be queried),
+    /// as it cannot change during a run; see [`Self::check_metadata_keys`][].
+    plugin_infos: Mutex<BTreeMap<String, Option<PluginInfo>>>,
+}
+
+/// The output of a plugin run, returned by [`Builder::execute`][] when [`Builder::capture_output`][] is set.
+#[derive(Clone, Debug)]
+pub struct PluginOutput {
+    /// The package the plugin was run on.
+    pub package: String,
+
+    /// The plugin, without the `gluegun-` prefix.
+    pub plugin: String,
+
+    /// What the plugin printed on stdout, less its handshake and the constructs it reported as unsupported.
+    pub stdout: Vec<u8>,
+
+    /// What the plugin printed on stderr.
+    pub stderr: Vec<u8>,
 }
 
+/// Hook creating the command for a plugin, see [`Builder::plugin_command_with_dest`][].
+type PluginCommandFn =
+    dyn Fn(&serde_json::Value, &str, Option<&GlueGunDestinationCrate>) -> anyhow::Result<Command> + Send + Sync;
+
 impl Builder {
     /// Create builder with given directory and arguments.
     /// Note that `args` should begin with the command name (like `argv[0]` in C).
@@ -33,7 +99,13 @@ impl Builder {
         Ok(Self {
             current_directory: Utf8PathBuf::try_from(current_directory.as_ref().to_path_buf())?,
             args: args.into_iter().map(Into::into).collect(),
-            plugin_command: Box::new(Self::default_plugin_command),
+            plugin_command: None,
+            plugin_dirs: vec![],
+            plugin_args: vec![],
+            log: Log::new(std::io::stderr()),
+            capture_output: false,
+            stop_watching: Arc::new(AtomicBool::new(false)),
+            plugin_infos: Mutex::new(BTreeMap::new()),
         })
     }
 
@@ -46,160 +118,808 @@ impl Builder {
     /// 
     /// The function will be invoked with the workspace/package `metadata.gluegun` field
     /// along with the name of the plugin. It should return a new `Command` object.
+    /// With `--jobs`, it may be invoked from several threads at once.
+    /// It replaces the default, which is the `plugin-command` configuration or else the plugin's executable
+    /// found by [`find_plugin`][] (so `--plugin-dir` has no effect).
     pub fn plugin_command(mut self, 
         plugin_command: impl Fn(
             &serde_json::Value,
             &str,
-        ) -> anyhow::Result<Command> + 'static,
+        ) -> anyhow::Result<Command> + Send + Sync + 'static,
+    ) -> Self {
+        self.plugin_command = Some(Box::new(move |gluegun_metadata, plugin, _dest_crate| {
+            plugin_command(gluegun_metadata, plugin)
+        }));
+        self
+    }
+
+    /// Like [`Self::plugin_command`][], but the function is also given the crate the plugin is to generate
+    /// (its name and path in particular), so that the command can depend on it.
+    /// The crate is `None` when the plugin is only asked about itself outside of a run (`list-plugins`).
+    pub fn plugin_command_with_dest(mut self,
+        plugin_command: impl Fn(
+            &serde_json::Value,
+            &str,
+            Option<&GlueGunDestinationCrate>,
+        ) -> anyhow::Result<Command> + Send + Sync + 'static,
     ) -> Self {
-        self.plugin_command = Box::new(plugin_command);
+        self.plugin_command = Some(Box::new(plugin_command));
+        self
+    }
+
+    /// Write the messages of cargo-gluegun itself (progress, warnings, and errors) to `sink` instead of stderr.
+    /// How many messages there are depends on `--quiet` and `--verbose`; the output of plugins is not affected.
+    pub fn log_to(mut self, sink: impl Write + Send + 'static) -> Self {
+        self.log = Log::new(sink);
+        self
+    }
+
+    /// Capture what plugins print on stdout and stderr and return it from [`Self::execute`][],
+    /// rather than letting them print to our own stdout and stderr (the default).
+    /// If a plugin fails, what it printed on stderr is included in the error instead.
+    pub fn capture_output(mut self, capture_output: bool) -> Self {
+        self.capture_output = capture_output;
+        self
+    }
+
+    /// With `--watch`, stop watching once `stop` is set, so that [`Self::execute`][] returns
+    /// (after the regeneration in progress, if any); otherwise watching goes on until the process is interrupted.
+    pub fn stop_watching(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop_watching = stop;
         self
     }
 
     /// Execute cargo-gluegun.
-    pub fn execute(self) -> anyhow::Result<()> {
+    ///
+    /// Returns the output of each plugin run if [`Self::capture_output`][] is set, in the order the runs were scheduled
+    /// (by package, then in the order the plugins were given), or nothing otherwise.
+    /// Unless `--quiet` is given, a successful run ends by logging a summary of the crates generated.
+    /// With `--watch`, it only returns once stopped (see [`Self::stop_watching`][]), and returns no output.
+    pub fn execute(mut self) -> anyhow::Result<Vec<PluginOutput>> {
         let cli = Cli::try_parse_from(&self.args)?;
+        self.log.set_verbosity(cli.verbosity());
+        self.plugin_dirs = cli.plugin_dir.iter().map(|dir| self.current_directory.join(dir)).collect();
+        self.plugin_args = cli.plugin_args.clone();
+
+        if let Some(shell) = cli.generate_completions {
+            let plugins = discover_plugins(std::env::var_os("PATH").as_deref());
+            write_completions(shell, &plugins, &mut std::io::stdout())?;
+            return Ok(vec![]);
+        }
+
+        if let Some(CliCommand::ListPlugins { format }) = cli.command {
+            self.list_plugins(&cli, format)?;
+            return Ok(vec![]);
+        }
+
+        if let Some(CliCommand::DumpIdl { manifest, package, compact }) = &cli.command {
+            self.dump_idl(manifest, package, *compact)?;
+            return Ok(vec![]);
+        }
+
+        if cli.raw.raw {
+            let (output, generated) = self.execute_raw(&cli)?;
+            self.log_report(&cli, Report { crates: generated.into_iter().collect() });
+            return Ok(output.into_iter().collect());
+        }
 
         let metadata = cli
             .manifest
             .metadata()
             .current_dir(&self.current_directory)
             .exec()?;
-        let (selected, _excluded) = cli.workspace.partition_packages(&metadata);
+        let packages = self.selected_packages(&cli, &metadata)?;
+
+        if cli.plugins.is_empty() {
+            anyhow::bail!("no plugins specified");
+        }
+
+        if cli.watch {
+            self.execute_watch(&cli, &metadata, &packages)?;
+            return Ok(vec![]);
+        }
+
+        if cli.jobs.get() > 1 {
+            let (outputs, report) = self.execute_parallel(&cli, &metadata, &packages)?;
+            self.log_report(&cli, report);
+            return Ok(outputs);
+        }
+
+        let run_mode = self.serial_run_mode(&cli);
+        let mut outputs = vec![];
+        let mut report = Report::default();
+        for package in packages {
+            let (package_outputs, generated) = self.generate_package(&cli, &metadata, package, run_mode)?;
+            outputs.extend(package_outputs);
+            report.crates.extend(generated);
+        }
+
+        self.log_report(&cli, report);
+        Ok(outputs)
+    }
+
+    /// Run each plugin on `package`, one at a time, stopping at the first failure.
+    /// With `--message-format json`, the failure is also emitted as a diagnostic.
+    fn generate_package(
+        &self,
+        cli: &Cli,
+        metadata: &cargo_metadata::Metadata,
+        package: &cargo_metadata::Package,
+        run_mode: RunMode,
+    ) -> anyhow::Result<(Vec<PluginOutput>, Vec<GeneratedCrate>)> {
+        let mut outputs = vec![];
+        let mut generated = vec![];
+        let result = self.package_idl(metadata, package).and_then(|idl| {
+            cli.plugins.iter().try_for_each(|plugin| {
+                let (output, generated_crate) = self.apply_plugin(plugin, metadata, package, &idl, run_mode, cli)?;
+                outputs.extend(output);
+                generated.extend(generated_crate);
+                Ok(())
+            })
+        });
+        if let Err(err) = &result {
+            if cli.message_format == MessageFormat::Json {
+                diagnostics::emit_json(package, err)?;
+            }
+        }
+        result?;
+        Ok((outputs, generated))
+    }
+
+    /// Generate the crates of each package, then regenerate those of a package each time one of its source files changes (`--watch`),
+    /// until stopped (see [`Self::stop_watching`][]). Only the packages that changed are parsed again and have their plugins rerun.
+    /// A failure is reported with the status line of its regeneration and the packages are watched on, so that it can be fixed.
+    fn execute_watch(
+        &self,
+        cli: &Cli,
+        metadata: &cargo_metadata::Metadata,
+        packages: &[&cargo_metadata::Package],
+    ) -> anyhow::Result<()> {
+        let run_mode = self.serial_run_mode(cli);
+        let mut watched: Vec<_> = packages.iter().map(|&package| (package, WatchedSources::default())).collect();
+        let mut changed = vec![true; watched.len()];
+        let mut announced = false;
+        loop {
+            for ((package, sources), _) in watched.iter_mut().zip(&changed).filter(|(_, changed)| **changed) {
+                let start = Instant::now();
+                let result = self.generate_package(cli, metadata, package, run_mode);
+
+                // The sources are those read by the parse just done, even if it failed.
+                *sources = WatchedSources::snapshot(&metadata.target_directory, package);
+
+                let elapsed = start.elapsed().as_secs_f64();
+                match result {
+                    Ok((_, generated)) => self.log.info(format_args!(
+                        "regenerated {count} crate(s) for `{pkg}` in {elapsed:.2}s",
+                        count = generated.len(),
+                        pkg = package.name,
+                    )),
+                    Err(err) => self.log.error(format_args!(
+                        "failed to regenerate the crates for `{pkg}` after {elapsed:.2}s: {err:#}",
+                        pkg = package.name,
+                    )),
+                }
+            }
+
+            // Said once, as the files may not change for a while; after that, only regenerations are reported.
+            if !announced {
+                announced = true;
+                let files = watched.iter().map(|(_, sources)| sources.len()).sum::<usize>();
+                self.log.info(format_args!(
+                    "watching {files} source file(s) of {count} package(s) for changes",
+                    count = watched.len(),
+                ));
+            }
+            let sources = watched.iter().map(|(_, sources)| sources).collect::<Vec<_>>();
+            match watch::wait_for_changes(&sources, &self.stop_watching) {
+                Some(now_changed) => changed = now_changed,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Log the summary of a run, unless it was a `--dry-run` (which generates nothing).
+    fn log_report(&self, cli: &Cli, report: Report) {
+        if !cli.dry_run {
+            self.log.info(report);
+        }
+    }
+
+    /// Extract the interface of each package selected by the arguments, as given to plugins, without running any plugin.
+    /// Packages are selected and parsed as by [`Self::execute`][] (which also caches the interfaces),
+    /// so the plugins given in the arguments, if any, are ignored.
+    pub fn extract_idl(&self) -> anyhow::Result<Vec<(cargo_metadata::PackageId, gluegun_idl::Idl)>> {
+        let cli = Cli::try_parse_from(&self.args)?;
+        let metadata = cli
+            .manifest
+            .metadata()
+            .current_dir(&self.current_directory)
+            .exec()?;
+        self.selected_packages(&cli, &metadata)?
+            .into_iter()
+            .map(|package| Ok((package.id.clone(), self.package_idl(&metadata, package)?)))
+            .collect()
+    }
+
+    /// The packages selected on the command line (`-p`, `--workspace`, `--package-glob`, etc.).
+    fn selected_packages<'m>(
+        &self,
+        cli: &Cli,
+        metadata: &'m cargo_metadata::Metadata,
+    ) -> anyhow::Result<Vec<&'m cargo_metadata::Package>> {
+        let patterns = cli
+            .package_glob
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern).with_context(|| format!("invalid `--package-glob` pattern `{pattern}`"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Glob matches add to the packages selected by the other flags, if any are given;
+        // otherwise they are the whole selection, rather than adding to the default packages.
+        let workspace = &cli.workspace;
+        let (flagged, _excluded) =
+            if patterns.is_empty() || !workspace.package.is_empty() || workspace.workspace || workspace.all {
+                workspace.partition_packages(metadata)
+            } else {
+                (vec![], vec![])
+            };
+        let selected = metadata
+            .packages
+            .iter()
+            .filter(|package| {
+                flagged.iter().any(|flagged| flagged.id == package.id)
+                    || (metadata.workspace_members.contains(&package.id)
+                        && !workspace.exclude.contains(&package.name)
+                        && patterns.iter().any(|pattern| pattern.matches(&package.name)))
+            })
+            .collect::<Vec<_>>();
 
         if selected.is_empty() {
             anyhow::bail!("no packages selected -- you may have misspelled the package name?");
         }
 
-        if cli.plugins.is_empty() {
-            anyhow::bail!("no plugins specified");
+        // When several packages are selected, binary-only ones are skipped rather than failing the run.
+        // A single selected package without a library is reported as an error by `package_idl`.
+        let multiple_packages = selected.len() > 1;
+        Ok(selected
+            .into_iter()
+            .filter(|package| {
+                let skip = multiple_packages && library_target(package).is_none();
+                if skip {
+                    self.log.warning(format_args!("skipping `{pkg}`, which has no library target", pkg = package.name));
+                }
+                !skip
+            })
+            .collect())
+    }
+
+    /// How to run plugins one at a time: printing what they print as they go, unless it is captured.
+    fn serial_run_mode(&self, cli: &Cli) -> RunMode {
+        RunMode::new(cli, if self.capture_output { RunMode::CaptureOutput } else { RunMode::InheritOutput })
+    }
+
+    /// Run each plugin on each package with up to `cli.jobs` plugins running at once (`--jobs`).
+    /// Rather than stopping at the first failure, every failure is reported once all runs are done.
+    fn execute_parallel(
+        &self,
+        cli: &Cli,
+        metadata: &cargo_metadata::Metadata,
+        packages: &[&cargo_metadata::Package],
+    ) -> anyhow::Result<(Vec<PluginOutput>, Report)> {
+        let mut failures = vec![];
+        let mut outputs = vec![];
+        let mut generated = vec![];
+
+        // The interface of each package is extracted once and shared by all of its plugin runs.
+        let mut idls = vec![];
+        for &package in packages {
+            match self.package_idl(metadata, package) {
+                Ok(idl) => idls.push((package, idl)),
+                Err(err) => failures.push((package, err)),
+            }
         }
 
-        for package in selected {
-            for plugin in &cli.plugins {
-                self.apply_plugin(plugin, &metadata.workspace_metadata, package)?;
+        let queue: Mutex<VecDeque<_>> = Mutex::new(
+            idls.iter()
+                .flat_map(|(package, idl)| cli.plugins.iter().map(move |plugin| (*package, plugin, idl)))
+                .enumerate()
+                .collect(),
+        );
+        let run_mode = RunMode::new(cli, RunMode::CaptureOutput);
+        let workers = cli.jobs.get().min(queue.lock().unwrap().len());
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut failures = vec![];
+                        let mut outputs = vec![];
+                        let mut generated = vec![];
+                        loop {
+                            let Some((index, (package, plugin, idl))) = queue.lock().unwrap().pop_front() else {
+                                break (failures, outputs, generated);
+                            };
+                            match self.apply_plugin(plugin, metadata, package, idl, run_mode, cli) {
+                                Ok((output, generated_crate)) => {
+                                    outputs.extend(output.map(|output| (index, output)));
+                                    generated.extend(generated_crate.map(|generated_crate| (index, generated_crate)));
+                                }
+                                Err(err) => failures.push((package, err)),
+                            }
+                        }
+                    })
+                })
+                .collect();
+            for worker in workers {
+                match worker.join() {
+                    Ok((worker_failures, worker_outputs, worker_generated)) => {
+                        failures.extend(worker_failures);
+                        outputs.extend(worker_outputs);
+                        generated.extend(worker_generated);
+                    }
+                    Err(panic) => std::panic::resume_unwind(panic),
+                }
             }
+        });
+
+        if failures.is_empty() {
+            outputs.sort_by_key(|(index, _)| *index);
+            generated.sort_by_key(|(index, _)| *index);
+            let report = Report { crates: generated.into_iter().map(|(_, generated)| generated).collect() };
+            return Ok((outputs.into_iter().map(|(_, output)| output).collect(), report));
         }
 
-        Ok(())
+        for (package, err) in &failures {
+            if cli.message_format == MessageFormat::Json {
+                diagnostics::emit_json(package, err)?;
+            }
+            self.log.error(format_args!("{pkg}: {err:#}", pkg = package.name));
+        }
+        anyhow::bail!("{count} failure(s) while running plugins", count = failures.len())
     }
 
-    fn apply_plugin(
+    /// List the plugins installed on the `PATH` (`list-plugins`).
+    /// Plugins are queried with the same command used to run them, so a custom `plugin-command` is respected.
+    fn list_plugins(&self, cli: &Cli, format: ListFormat) -> anyhow::Result<()> {
+        // Outside of a workspace, there is no `plugin-command` setting, so plugins are run with the default command.
+        let gluegun_metadata = cli
+            .manifest
+            .metadata()
+            .current_dir(&self.current_directory)
+            .no_deps()
+            .exec()
+            .ok()
+            .and_then(|metadata| metadata.workspace_metadata.get("gluegun").cloned())
+            .unwrap_or_default();
+
+        let plugins = discover_plugins(std::env::var_os("PATH").as_deref());
+        let queried = query_plugins(&plugins, |plugin| {
+            let mut command = self.create_plugin_command(&gluegun_metadata, plugin, None)?;
+            command.current_dir(&self.current_directory);
+            Ok(command)
+        });
+        let mut listings = vec![];
+        for (listing, err) in queried {
+            if let Some(err) = err {
+                self.log.warning(format_args!("could not query plugin `{name}`: {err:#}", name = listing.name));
+            }
+            listings.push(listing);
+        }
+        write_plugin_list(&listings, format, &mut std::io::stdout())
+    }
+
+    /// Print the interface extracted from `package_name` as JSON (`dump-idl`), without running any plugin.
+    fn dump_idl(&self, manifest: &clap_cargo::Manifest, package_name: &str, compact: bool) -> anyhow::Result<()> {
+        let metadata = manifest.metadata().current_dir(&self.current_directory).exec()?;
+        let Some(package) = metadata.workspace_packages().into_iter().find(|package| package.name == package_name) else {
+            anyhow::bail!("no package named `{package_name}` in the workspace");
+        };
+        let idl = self.package_idl(&metadata, package)?;
+        write_idl(&idl, compact, &mut std::io::stdout())
+    }
+
+    /// Run a single plugin on a source file given on the command line (`--raw`),
+    /// without consulting `cargo metadata`. There is no workspace or package metadata in this mode.
+    fn execute_raw(&self, cli: &Cli) -> anyhow::Result<(Option<PluginOutput>, Option<GeneratedCrate>)> {
+        let raw = &cli.raw;
+        let (Some(package_name), Some(src), Some(plugin), Some(out)) =
+            (&raw.package_name, &raw.src, &raw.plugin, &raw.out)
+        else {
+            anyhow::bail!("`--raw` requires `--package-name`, `--src`, `--plugin`, and `--out`");
+        };
+        let src = self.current_directory.join(src);
+        let crate_path = self.current_directory.join(out);
+
+        // The crate directory is the one with the `Cargo.toml`, if there is one.
+        let Some(src_dir) = src.parent() else {
+            anyhow::bail!("cannot compute parent path for `{src}`");
+        };
+        let manifest_dir = src_dir
+            .ancestors()
+            .find(|dir| dir.join("Cargo.toml").exists())
+            .unwrap_or(src_dir);
+
+        let idl = gluegun_idl::Parser::new()
+            .parse_crate_named(package_name, manifest_dir, &src)
+            .with_context(|| format!("extracting interface from `{src}`"))?;
+
+        let crate_name = match crate_path.file_name() {
+            Some(name) => name.to_string(),
+            None => format!("{package_name}-{plugin}"),
+        };
+        if !cli.dry_run {
+            check_destination(&crate_path, cli.force)?;
+        }
+        let generated = GeneratedCrate {
+            package: package_name.clone(),
+            plugin: plugin.clone(),
+            crate_name: crate_name.clone(),
+            path: crate_path.clone(),
+            items_bound: idl.definitions().len(),
+            items_skipped: 0,
+        };
+
+        let input = PluginInput {
+            protocol_version: PROTOCOL_VERSION,
+            idl,
+            metadata: serde_json::Value::Null,
+            dependencies: None,
+            dest_crate: GlueGunDestinationCrate {
+                path: crate_path.into_std_path_buf(),
+                crate_name,
+                split_modules: false,
+                edition: Some("2021".to_string()),
+                rust_version: None,
+                no_format: cli.no_format,
+                merge: false,
+            },
+        };
+        let (exit_status, unsupported, output) = self
+            .execute_plugin(plugin, package_name, &serde_json::Value::Null, &input, self.serial_run_mode(cli))
+            .with_context(|| format!("executing plugin `{plugin}`"))?;
+
+        check_unsupported(&self.log, plugin, package_name, unsupported)?;
+        check_exit_status(plugin, exit_status, output.as_ref())?;
+        Ok((output, Some(generated)))
+    }
+
+    /// Extract the interface of `package`, which is shared by all the plugins run on it.
+    /// It is cached in the target directory of the workspace and only parsed again once its sources change.
+    fn package_idl(
         &self,
-        plugin: &str,
-        workspace_metadata: &serde_json::Value,
+        metadata: &cargo_metadata::Metadata,
         package: &cargo_metadata::Package,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<gluegun_idl::Idl> {
         if let Some(_) = package.source {
             anyhow::bail!("{pkg}: can only process local packages", pkg = package.name);
         }
 
-        // FIXME: Don't be so hacky. My god Niko, you should be ashamed of yourself.
+        // The interface is extracted from the library target, wherever `[lib].path` puts it.
+        let Some(lib_target) = library_target(package) else {
+            anyhow::bail!(
+                "package `{pkg}` has no library target; gluegun only processes libraries",
+                pkg = package.name,
+            );
+        };
         let cargo_toml_path = &package.manifest_path;
         let manifest_dir = cargo_toml_path.parent().unwrap();
-        let src_lib_rs = manifest_dir.join("src/lib.rs");
+        let src_lib_rs = &lib_target.src_path;
 
-        let idl = gluegun_idl::Parser::new()
-            .parse_crate_named(&package.name, &manifest_dir, &src_lib_rs)
-            .with_context(|| format!("extracting interface from `{src_lib_rs}`"))?;
+        let cache_path = idl_cache::cache_path(&metadata.target_directory, package);
+        if let Some(idl) = CachedIdl::load(&cache_path, src_lib_rs) {
+            return Ok(idl);
+        }
+
+        let mut parser = gluegun_idl::Parser::new();
+        let result = parser.parse_crate_named(&package.name, &manifest_dir, src_lib_rs);
 
+        // Failing to cache the interface only costs parsing it again next time.
+        // A failure is cached too, so that `--watch` knows all the files read before it.
+        if let Err(err) = CachedIdl::new(result.as_ref().ok().cloned(), src_lib_rs, parser.source_files()).save(&cache_path) {
+            self.log.warning(format_args!("failed to cache the interface of `{pkg}`: {err:#}", pkg = package.name));
+        }
+        result.with_context(|| format!("extracting interface from `{src_lib_rs}`"))
+    }
+
+    /// Run `plugin` on `package`, whose interface is `idl`.
+    /// Returns its output (see [`Self::execute_plugin`][]) and the crate it generated (none for a `--dry-run`).
+    fn apply_plugin(
+        &self,
+        plugin: &str,
+        metadata: &cargo_metadata::Metadata,
+        package: &cargo_metadata::Package,
+        idl: &gluegun_idl::Idl,
+        run_mode: RunMode,
+        cli: &Cli,
+    ) -> anyhow::Result<(Option<PluginOutput>, Option<GeneratedCrate>)> {
         // Extract gluegun metadata (if any).
-        let gluegun_workspace_metadata = workspace_metadata.get("gluegun");
+        let gluegun_workspace_metadata = metadata.workspace_metadata.get("gluegun");
         let gluegun_package_metadata = package.metadata.get("gluegun");
-        let gluegun_metadata = merge_metadata(gluegun_workspace_metadata, gluegun_package_metadata)
+        let gluegun_metadata = merge_gluegun_metadata(gluegun_workspace_metadata, gluegun_package_metadata)
             .with_context(|| format!("merging workspace and package metadata"))?;
 
         // Search for `workspace.metadata.gluegun.tool_name` and
         // `package.metadata.gluegun.tool_name`.
-        let plugin_workspace_metadata = gluegun_workspace_metadata.and_then(|v| v.get(plugin));
-        let plugin_package_metadata = gluegun_package_metadata.and_then(|v| v.get(plugin));
-        let plugin_metadata = merge_metadata(plugin_workspace_metadata, plugin_package_metadata)
+        let mut plugin_metadata = merge_plugin_metadata(gluegun_workspace_metadata, gluegun_package_metadata, plugin)
             .with_context(|| format!("merging workspace and package metadata"))?;
 
         // Compute destination crate name and path
         let (crate_name, crate_path) =
-            dest_crate_name_and_path(plugin, &gluegun_metadata, package)
+            dest_crate_name_and_path(plugin, &gluegun_metadata, &mut plugin_metadata, package)
                 .with_context(|| format!("computing destination crate name and path"))?;
 
-        // Execute the plugin
-        let exit_status = self
-            .execute_plugin(
-                plugin,
-                &gluegun_metadata,
-                &idl,
-                &plugin_metadata,
-                &crate_name,
-                &crate_path,
+        // An existing crate to generate into instead, if any
+        let merge_destination = merge_destination(plugin, &gluegun_metadata, &mut plugin_metadata, package)
+            .context("computing the crate to merge into")?;
+        let merge = merge_destination.is_some();
+        let (crate_name, crate_path) = merge_destination.unwrap_or((crate_name, crate_path));
+
+        // The items to bind (consumed here, not passed on to the plugin)
+        let item_filter = ItemFilter::take_from(plugin, &mut plugin_metadata)?;
+
+        // Settings for the generated manifest (consumed here, not passed on to the plugin)
+        let package_settings = PackageSettings::take_from(plugin, &mut plugin_metadata, package)?;
+
+        // The dependency graph is only walked for plugins that asked for it.
+        let dependencies = if bool_setting(&gluegun_metadata, "include-dependencies")? {
+            Some(
+                dependencies::resolved_dependencies(metadata, package)
+                    .with_context(|| format!("computing dependencies of `{pkg}`", pkg = package.name))?,
             )
+        } else {
+            None
+        };
+
+        // Execute the plugin
+        let bound_idl = item_filter.apply(idl);
+        let generated = (run_mode != RunMode::DryRun).then(|| GeneratedCrate {
+            package: package.name.clone(),
+            plugin: plugin.to_string(),
+            crate_name: crate_name.clone(),
+            path: crate_path.clone(),
+            items_bound: bound_idl.definitions().len(),
+            items_skipped: idl.definitions().len() - bound_idl.definitions().len(),
+        });
+        let input = PluginInput {
+            protocol_version: PROTOCOL_VERSION,
+            idl: bound_idl,
+            metadata: plugin_metadata,
+            dependencies,
+            dest_crate: GlueGunDestinationCrate {
+                path: crate_path.clone().into_std_path_buf(),
+                crate_name,
+                split_modules: bool_setting(&gluegun_metadata, "split-modules")?,
+                edition: Some(package_settings.edition),
+                rust_version: package_settings.rust_version,
+                no_format: cli.no_format,
+                merge,
+            },
+        };
+        if run_mode != RunMode::DryRun {
+            check_destination(&crate_path, cli.force)?;
+        }
+        let (exit_status, unsupported, output) = self
+            .execute_plugin(plugin, &package.name, &gluegun_metadata, &input, run_mode)
             .with_context(|| format!("executing plugin `{plugin}`"))?;
 
-        if exit_status.success() {
-            Ok(())
-        } else {
-            anyhow::bail!("gluegun-{plugin} failed with code {exit_status}");
+        check_unsupported(&self.log, plugin, &package.name, unsupported)?;
+        check_exit_status(plugin, exit_status, output.as_ref())?;
+
+        // A crate merged into is not ours to exclude.
+        if bool_setting(&gluegun_metadata, "exclude-from-workspace")? && !merge && run_mode != RunMode::DryRun {
+            workspace::exclude_from_workspace(&metadata.workspace_root, &crate_path)
+                .with_context(|| format!("excluding `{crate_path}` from the workspace"))?;
         }
+
+        Ok((output, generated))
     }
 
+    /// Run `plugin` on the interface of `package_name` given in `input`.
+    /// Its output is returned rather than printed if [`Self::capture_output`][] is set.
     fn execute_plugin(
         &self,
         plugin: &str,
+        package_name: &str,
         gluegun_metadata: &serde_json::Value,
-        idl: &gluegun_idl::Idl,
-        metadata: &serde_json::Value,
-        crate_name: &str,
-        crate_path: &Utf8PathBuf,
-    ) -> anyhow::Result<ExitStatus> {
+        input: &PluginInput,
+        run_mode: RunMode,
+    ) -> anyhow::Result<(ExitStatus, Vec<UnsupportedError>, Option<PluginOutput>)> {
         // Create the plugin command using the hook supplied by configuration.
         // Default is to run `Self::default_plugin_command` below.
-        let mut plugin_command = (self.plugin_command)(
+        let mut plugin_command = self.create_plugin_command(
             gluegun_metadata,
             plugin,
+            Some(&input.dest_crate),
         ).with_context(|| format!("creating plugin command"))?;
 
         // Configure the command.
+        // Stdout is always piped, as its first line is the plugin's handshake (see `check_handshake`).
+        let capture_output = run_mode == RunMode::CaptureOutput;
         plugin_command
             .current_dir(&self.current_directory)
             .arg(format!("gg-{}", plugin))
+            .args(&self.plugin_args)
             .stdin(Stdio::piped()) // Configure stdin
-            .stdout(Stdio::inherit()) // Configure stdout
-            .stderr(Stdio::inherit());
-        
+            .stdout(Stdio::piped()) // Configure stdout
+            .stderr(if capture_output { Stdio::piped() } else { Stdio::inherit() });
+
+        // Stop short of spawning the plugin, printing what would be run (in one piece, in case of `--jobs`).
+        if run_mode == RunMode::DryRun {
+            println!(
+                "{plugin}:\n    crate_name: {crate_name}\n    crate_path: {crate_path}\n    command: {plugin_command:?}",
+                crate_name = input.dest_crate.crate_name,
+                crate_path = input.dest_crate.path.display(),
+            );
+            return Ok((ExitStatus::default(), vec![], None));
+        }
+
+        self.check_metadata_keys(plugin, gluegun_metadata, input)?;
 
         // Execute the helper
-        eprintln!("{plugin_command:?}");
+        let payload = serde_json::to_vec(input).with_context(|| format!("serializing the input of gluegun-{plugin}"))?;
+        self.log.info(format_args!(
+            "gluegun-{plugin}: generating `{crate_name}` in `{crate_path}`",
+            crate_name = input.dest_crate.crate_name,
+            crate_path = input.dest_crate.path.display(),
+        ));
+        self.log.verbose(format_args!("gluegun-{plugin}: running {plugin_command:?}"));
         let mut child = plugin_command 
             .spawn()
             .with_context(|| format!("spawning gluegun-{plugin}"))?;
 
-        // Write the data to the child's stdin.
-        // This has to be kept in sync with the definition from `gluegun_core::cli`.
+        // Write the data to the child's stdin, to be read by `gluegun_core::cli::run`.
         let Some(stdin) = child.stdin.take() else {
             anyhow::bail!("failed to take stdin");
         };
         let write_data = |mut stdin: ChildStdin| -> anyhow::Result<()> {
-            writeln!(stdin, r#"{{"#)?;
-            writeln!(stdin, r#"  "idl": {},"#, serde_json::to_string(&idl)?)?;
-            writeln!(
-                stdin,
-                r#"  "metadata": {},"#,
-                serde_json::to_string(&metadata)?
-            )?;
-            writeln!(stdin, r#"  "dest_crate": {{"#)?;
-            writeln!(stdin, r#"    "crate_name": {crate_name:?},"#)?;
-            writeln!(stdin, r#"    "path": {crate_path:?}"#)?;
-            writeln!(stdin, r#"  }}"#)?;
-            writeln!(stdin, r#"}}"#)?;
+            stdin.write_all(&payload)?;
+            stdin.flush()?;
+            self.log.verbose(format_args!("gluegun-{plugin}: sent {len} bytes of input", len = payload.len()));
             Ok(())
         };
-        write_data(stdin).with_context(|| format!("writing data to gluegun-{plugin}"))?;
-        eprintln!("output data successful");
 
-        Ok(child
-            .wait()
-            .with_context(|| format!("waiting for gluegun-{plugin}"))?)
+        if !capture_output {
+            write_data(stdin).with_context(|| format!("writing data to gluegun-{plugin}"))?;
+
+            // Check the handshake, then pass the rest of the plugin's stdout through as it comes.
+            let Some(stdout) = child.stdout.take() else {
+                anyhow::bail!("failed to take stdout");
+            };
+            let mut stdout = BufReader::new(stdout);
+            let mut handshake = String::new();
+            stdout
+                .read_line(&mut handshake)
+                .with_context(|| format!("reading handshake from gluegun-{plugin}"))?;
+            if let Err(err) = check_handshake(plugin, &handshake) {
+                let _ = child.kill();
+                child.wait().with_context(|| format!("waiting for gluegun-{plugin}"))?;
+                return Err(err);
+            }
+            let unsupported = pass_output_through(stdout, &mut std::io::stdout())
+                .with_context(|| format!("reading output of gluegun-{plugin}"))?;
+
+            let exit_status = child.wait().with_context(|| format!("waiting for gluegun-{plugin}"))?;
+            return Ok((exit_status, unsupported, None));
+        }
+
+        // The data is written from another thread so that the plugin cannot block
+        // on a full stdout pipe while we are still writing to its stdin.
+        let (written, output) = std::thread::scope(|scope| {
+            let writer = scope.spawn(|| write_data(stdin));
+            let output = child.wait_with_output();
+            (writer.join(), output)
+        });
+        let output = output.with_context(|| format!("waiting for gluegun-{plugin}"))?;
+        let (handshake, stdout) = match output.stdout.iter().position(|&b| b == b'\n') {
+            Some(newline) => output.stdout.split_at(newline + 1),
+            None => (&output.stdout[..], &[][..]),
+        };
+        let (unsupported, captured) = if self.capture_output {
+            let mut captured = vec![];
+            let unsupported = pass_output_through(stdout, &mut captured)?;
+            let captured = PluginOutput {
+                package: package_name.to_string(),
+                plugin: plugin.to_string(),
+                stdout: captured,
+                stderr: output.stderr,
+            };
+            (unsupported, Some(captured))
+        } else {
+            let unsupported = pass_output_through(stdout, &mut std::io::stdout().lock())?;
+            std::io::stderr().lock().write_all(&output.stderr)?;
+            (unsupported, None)
+        };
+        match written {
+            Ok(result) => result.with_context(|| format!("writing data to gluegun-{plugin}"))?,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+        check_handshake(plugin, &String::from_utf8_lossy(handshake))?;
+
+        Ok((output.status, unsupported, captured))
+    }
+
+    /// Check the metadata in `input` against the keys `plugin` declares (see [`protocol::MetadataKey`][]),
+    /// failing if a required key is missing and warning about keys the plugin does not know about,
+    /// so that a misconfiguration is not reported by the plugin as some error of its own.
+    /// The metadata of a plugin that declares no keys, or cannot be asked for them, is not checked;
+    /// the latter is a warning, unless the plugin predates [`protocol::INFO_FLAG`][].
+    /// Each plugin is asked for its keys once per run, whatever the number of packages it generates crates for.
+    fn check_metadata_keys(
+        &self,
+        plugin: &str,
+        gluegun_metadata: &serde_json::Value,
+        input: &PluginInput,
+    ) -> anyhow::Result<()> {
+        // The lock is held while querying, so that plugins run by parallel jobs are still queried once.
+        let mut plugin_infos = self.plugin_infos.lock().unwrap();
+        let info = plugin_infos.entry(plugin.to_string()).or_insert_with(|| {
+            let info = plugin_list::query_plugin(plugin, &|plugin: &str| {
+                let mut command = self.create_plugin_command(gluegun_metadata, plugin, Some(&input.dest_crate))?;
+                command.current_dir(&self.current_directory);
+                Ok(command)
+            });
+            match info {
+                Ok(Some(info)) => Some(info),
+                Ok(None) => {
+                    self.log.verbose(format_args!(
+                        "gluegun-{plugin}: not checking its metadata, as it predates {flag}",
+                        flag = protocol::INFO_FLAG,
+                    ));
+                    None
+                }
+                Err(err) => {
+                    self.log.warning(format_args!(
+                        "could not ask gluegun-{plugin} for its metadata keys, so its metadata is not checked: {err:#}"
+                    ));
+                    None
+                }
+            }
+        });
+        let keys = match info {
+            Some(info) if !info.metadata_keys.is_empty() => info.metadata_keys.clone(),
+            _ => return Ok(()),
+        };
+        drop(plugin_infos);
+
+        let metadata = input.metadata.as_object();
+        let given = |name: &str| metadata.is_some_and(|map| map.contains_key(name));
+        for name in metadata.into_iter().flat_map(|map| map.keys()) {
+            if !keys.iter().any(|key| key.name == *name) {
+                self.log.warning(format_args!("gluegun-{plugin} does not know the key `{name}` of `gluegun.{plugin}`"));
+            }
+        }
+        let missing: Vec<_> = keys.iter().filter(|key| key.required && !given(&key.name)).collect();
+        match &missing[..] {
+            [] => Ok(()),
+            [key] => anyhow::bail!(
+                "missing key `{name}` required by plugin {plugin} (set `gluegun.{plugin}.{name}` in the package or workspace metadata)",
+                name = key.name,
+            ),
+            _ => anyhow::bail!(
+                "missing keys {names} required by plugin {plugin} (set them in `gluegun.{plugin}` in the package or workspace metadata)",
+                names = missing.iter().map(|key| format!("`{}`", key.name)).collect::<Vec<_>>().join(", "),
+            ),
+        }
     }
 
+    /// Create the command for `plugin` with the hook given to [`Self::plugin_command`][], if any,
+    /// or else with [`Self::default_plugin_command`][].
+    fn create_plugin_command(
+        &self,
+        gluegun_metadata: &serde_json::Value,
+        plugin: &str,
+        dest_crate: Option<&GlueGunDestinationCrate>,
+    ) -> anyhow::Result<Command> {
+        match &self.plugin_command {
+            Some(plugin_command) => plugin_command(gluegun_metadata, plugin, dest_crate),
+            None => self.default_plugin_command(gluegun_metadata, plugin),
+        }
+    }
+
+    /// The `plugin-command` configuration if there is one,
+    /// else the plugin's executable, looked for in the `--plugin-dir` directories and then on the `PATH`.
     fn default_plugin_command(
+        &self,
         gluegun_metadata: &serde_json::Value,
         plugin: &str,
     ) -> anyhow::Result<Command> {
@@ -209,7 +929,8 @@ impl Builder {
             return Ok(c);
         }
 
-        Ok(Command::new(format!("gluegun-{plugin}")))
+        let executable = find_plugin(plugin, &self.plugin_dirs, std::env::var_os("PATH").as_deref())?;
+        Ok(Command::new(executable))
     }
 
     fn customized_plugin_command(
@@ -224,13 +945,12 @@ impl Builder {
             anyhow::bail!("expected a string for workspace configuration `gluegun.plugin_command`")
         };
 
-        // should probably...do something better...
-        let s = plugin_command.replace("{plugin}", plugin);
-        if s.contains("'") {
-            anyhow::bail!("`gluegun.plugin_command` cannot contain `'` characters (FIXME)")
-        }
-
-        let mut words = s.split_whitespace();
+        // Split into words as a POSIX shell would (so that quoted words may contain spaces),
+        // then substitute the plugin name into each word.
+        let Some(words) = shlex::split(plugin_command) else {
+            anyhow::bail!("unterminated quote or trailing backslash in `gluegun.plugin_command`")
+        };
+        let mut words = words.iter().map(|word| word.replace("{plugin}", plugin));
         let Some(word0) = words.next() else {
             anyhow::bail!("expected at least one word in `gluegun.plugin_command`")
         };
@@ -244,27 +964,282 @@ impl Builder {
 
 /// A simple Cli you can use for your own parser.
 #[derive(clap::Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+
     #[command(flatten)]
     manifest: clap_cargo::Manifest,
 
     #[command(flatten)]
     workspace: clap_cargo::Workspace,
 
+    /// Also process the workspace packages whose name matches PATTERN (e.g., `'*-api'`); may be repeated.
+    /// Packages given with `--exclude` are left out even if they match.
+    #[arg(long, value_name = "PATTERN")]
+    package_glob: Vec<String>,
+
     /// Specify a list of plugins to use.
     plugins: Vec<String>,
+
+    /// Look for plugins in DIR before the `PATH` (e.g., `target/debug` for plugins built in the workspace); may be repeated.
+    #[arg(long, value_name = "DIR")]
+    plugin_dir: Vec<Utf8PathBuf>,
+
+    #[command(flatten)]
+    raw: RawArgs,
+
+    /// Print the destination crate and command of each plugin run instead of running it.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Leave the generated Rust code as the plugins write it instead of formatting it with `rustfmt`.
+    #[arg(long)]
+    no_format: bool,
+
+    /// Let plugins overwrite destination directories that gluegun did not generate.
+    #[arg(long)]
+    force: bool,
+
+    /// Keep running after generating, regenerating the crates of a package each time one of its source files changes.
+    #[arg(long, conflicts_with_all = ["dry_run", "jobs"])]
+    watch: bool,
+
+    /// Run up to N plugins at once, reporting every failure at the end (defaults to one at a time).
+    #[arg(long, short = 'j', value_name = "N", default_value = "1")]
+    jobs: NonZeroUsize,
+
+    /// Format of error messages (`json` emits cargo-compatible diagnostics on stdout).
+    #[arg(long, value_enum, default_value_t)]
+    message_format: MessageFormat,
+
+    /// Print only warnings and errors, not a summary of each plugin run.
+    #[arg(long, short = 'q', conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Also print the command run for each plugin and the size of its input.
+    #[arg(long, short = 'v')]
+    verbose: bool,
+
+    /// Print a completion script for the given shell and exit.
+    #[arg(long, hide = true, value_name = "SHELL")]
+    generate_completions: Option<clap_complete::Shell>,
+
+    /// Arguments given after `--` are passed on to every plugin run, after its `gg-<plugin>` argument.
+    #[arg(last = true, value_name = "PLUGIN_ARGS")]
+    plugin_args: Vec<String>,
+}
+
+impl Cli {
+    /// The verbosity selected with `--quiet` or `--verbose`.
+    fn verbosity(&self) -> Verbosity {
+        if self.quiet {
+            Verbosity::Quiet
+        } else if self.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+#[derive(clap::Subcommand)]
+enum CliCommand {
+    /// List the plugins installed on the `PATH`, with their version and language.
+    ListPlugins {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t)]
+        format: ListFormat,
+    },
+
+    /// Print the interface extracted from a package as JSON, as given to plugins, without running any plugin.
+    DumpIdl {
+        #[command(flatten)]
+        manifest: clap_cargo::Manifest,
+
+        /// The package whose interface is printed.
+        package: String,
+
+        /// Print the JSON on one line instead of pretty-printing it.
+        #[arg(long)]
+        compact: bool,
+    },
+}
+
+/// How [`Builder::execute_plugin`][] runs a plugin.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum RunMode {
+    /// The plugin writes directly to our stdout and stderr.
+    InheritOutput,
+
+    /// The plugin's output is printed in one piece once it exits,
+    /// so that it does not interleave with that of plugins running alongside it (`--jobs`),
+    /// or returned if [`Builder::capture_output`][] is set.
+    CaptureOutput,
+
+    /// The plugin is not run; its destination crate and command are printed instead (`--dry-run`).
+    DryRun,
+}
+
+impl RunMode {
+    /// `DryRun` if `--dry-run` was given, else `mode`.
+    fn new(cli: &Cli, mode: RunMode) -> Self {
+        if cli.dry_run {
+            RunMode::DryRun
+        } else {
+            mode
+        }
+    }
+}
+
+/// Options for running a single plugin directly on a source file, for plugin development.
+#[derive(clap::Args)]
+struct RawArgs {
+    /// Run one plugin on a source file directly, skipping `cargo metadata` and workspace resolution.
+    #[arg(long, requires_all = ["package_name", "src", "plugin", "out"], conflicts_with = "plugins")]
+    raw: bool,
+
+    /// Name of the crate being processed (with `--raw`).
+    #[arg(long, requires = "raw", value_name = "NAME")]
+    package_name: Option<String>,
+
+    /// Path to the crate's `lib.rs` (with `--raw`).
+    #[arg(long, requires = "raw", value_name = "PATH")]
+    src: Option<Utf8PathBuf>,
+
+    /// Plugin to run (with `--raw`).
+    #[arg(long, requires = "raw", value_name = "PLUGIN")]
+    plugin: Option<String>,
+
+    /// Path at which to generate the crate (with `--raw`).
+    #[arg(long, requires = "raw", value_name = "PATH")]
+    out: Option<Utf8PathBuf>,
+}
+
+/// Check that the first line a plugin printed is a handshake for our [`PROTOCOL_VERSION`][].
+fn check_handshake(plugin: &str, line: &str) -> anyhow::Result<()> {
+    match protocol::parse_handshake(line) {
+        Some(version) if version == PROTOCOL_VERSION => Ok(()),
+        Some(version) => anyhow::bail!(
+            "gluegun-{plugin} uses plugin protocol version {version}, but cargo-gluegun uses version {PROTOCOL_VERSION}; \
+            install matching versions of the two"
+        ),
+        None => anyhow::bail!(
+            "gluegun-{plugin} did not report its plugin protocol version, \
+            so it predates version {PROTOCOL_VERSION} used by cargo-gluegun; install a newer version of it"
+        ),
+    }
+}
+
+/// Check that the plugin may write the crate at `crate_path`, i.e., that it does not overwrite anything but a crate
+/// generated by gluegun (which holds a [`protocol::GENERATED_MARKER`][] file), unless `force` (`--force`).
+fn check_destination(crate_path: &Utf8Path, force: bool) -> anyhow::Result<()> {
+    if force || crate_path.join(protocol::GENERATED_MARKER).exists() {
+        return Ok(());
+    }
+    let is_empty = match std::fs::read_dir(crate_path) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => true,
+        Err(err) => return Err(err).with_context(|| format!("reading the destination directory `{crate_path}`")),
+    };
+    if !is_empty {
+        anyhow::bail!(
+            "the destination `{crate_path}` already exists and was not generated by gluegun \
+            (it has no `{marker}` file); remove it, or pass `--force` to overwrite it",
+            marker = protocol::GENERATED_MARKER,
+        );
+    }
+    Ok(())
+}
+
+/// Fail if `plugin` exited with an error, quoting what it printed on stderr if that was captured in `output`.
+fn check_exit_status(plugin: &str, exit_status: ExitStatus, output: Option<&PluginOutput>) -> anyhow::Result<()> {
+    if exit_status.success() {
+        return Ok(());
+    }
+    match output {
+        Some(output) if !output.stderr.is_empty() => anyhow::bail!(
+            "gluegun-{plugin} failed with code {exit_status}, printing:\n{stderr}",
+            stderr = String::from_utf8_lossy(&output.stderr).trim_end(),
+        ),
+        _ => anyhow::bail!("gluegun-{plugin} failed with code {exit_status}"),
+    }
+}
+
+/// Report the constructs of `package_name` that `plugin` said it does not support, if any, failing with their count.
+/// (A plugin that does not support some items fails, having reported all of them.)
+fn check_unsupported(
+    log: &Log,
+    plugin: &str,
+    package_name: &str,
+    unsupported: Vec<UnsupportedError>,
+) -> anyhow::Result<()> {
+    if unsupported.is_empty() {
+        return Ok(());
+    }
+    for error in &unsupported {
+        log.error(format_args!("gluegun-{plugin} does not support {error}"));
+    }
+    Err(UnsupportedErrors(unsupported)).with_context(|| format!("binding `{package_name}` with gluegun-{plugin}"))
 }
 
+/// Copy the output of a plugin (after its handshake) from `stdout` to `out`,
+/// except for the lines reporting unsupported constructs (see [`protocol::unsupported_line`][]), which are returned.
+fn pass_output_through(stdout: impl BufRead, out: &mut impl Write) -> std::io::Result<Vec<UnsupportedError>> {
+    let mut unsupported = vec![];
+    for line in stdout.split(b'\n') {
+        let line = line?;
+        match std::str::from_utf8(&line).ok().and_then(protocol::parse_unsupported_line) {
+            Some(error) => unsupported.push(error),
+            None => {
+                out.write_all(&line)?;
+                out.write_all(b"\n")?;
+            }
+        }
+    }
+    Ok(unsupported)
+}
+
+/// The library target of `package`, if it has one.
+/// Procedural macro crates are not included, as their functions cannot be called from other languages.
+fn library_target(package: &cargo_metadata::Package) -> Option<&cargo_metadata::Target> {
+    package.targets.iter().find(|target| {
+        target.is_lib() || target.is_rlib() || target.is_dylib() || target.is_cdylib() || target.is_staticlib()
+    })
+}
+
+/// Compute the name and path of the crate generated by `plugin` for `package`.
+/// A `destination-path` in the plugin's own table takes precedence over the one for all plugins;
+/// it is removed from `plugin_metadata`, as it is not meant for the plugin.
 fn dest_crate_name_and_path(
     plugin: &str,
     gluegun_metadata: &serde_json::Value,
+    plugin_metadata: &mut serde_json::Value,
     package: &cargo_metadata::Package,
 ) -> anyhow::Result<(String, Utf8PathBuf)> {
     // Find the configuration (if any)
-    let dp: DestinationPath = gluegun_metadata.get("destination-path").and_then(|v| Some(serde_json::from_value(v.clone()))).unwrap_or(Ok(DestinationPath::Child))?;
+    let plugin_destination_path = match plugin_metadata {
+        serde_json::Value::Object(map) => map.remove("destination-path"),
+        _ => None,
+    };
+    let dp = match plugin_destination_path.as_ref().or_else(|| gluegun_metadata.get("destination-path")) {
+        Some(value) => serde_json::from_value(value.clone())
+            .with_context(|| format!("expected `\"child\"` or `\"sibling\"` for configuration `destination-path`, found `{value}`"))?,
+        None => DestinationPath::Child,
+    };
 
     // Default crate name is `foo-x`, taken from the plugin
-    let crate_name = format!("{}-{plugin}", package.name);
+    let crate_name = match gluegun_metadata.get("crate-name-template") {
+        None => format!("{}-{plugin}", package.name),
+        Some(serde_json::Value::String(template)) => {
+            let crate_name = template.replace("{package}", &package.name).replace("{plugin}", plugin);
+            check_crate_name(&crate_name)
+                .with_context(|| format!("`crate-name-template` `{template}` gives an invalid crate name `{crate_name}`"))?;
+            crate_name
+        }
+        Some(_) => anyhow::bail!("expected a string for configuration `gluegun.crate-name-template`"),
+    };
 
     // Parent directory: either the directory containing the
     // `Cargo.toml` (child of target crate) or the parent of that
@@ -286,26 +1261,259 @@ fn dest_crate_name_and_path(
     Ok((crate_name, crate_path))
 }
 
-/// Merge metadata from workspace/package
-fn merge_metadata(
+/// Name of the subdirectory of the crate given by `merge-into` that plugins generate into.
+const MERGE_SUBDIRECTORY: &str = "generated";
+
+/// Compute the name of the existing crate that `plugin` generates into for `package`, if `merge-into` is set,
+/// and the path of the [`MERGE_SUBDIRECTORY`][] of it that the plugin generates.
+/// The `merge-into` path is relative to the directory of `package`.
+/// As with `destination-path`, a `merge-into` in the plugin's own table takes precedence over the one for all plugins,
+/// and is removed from `plugin_metadata`.
+fn merge_destination(
+    plugin: &str,
+    gluegun_metadata: &serde_json::Value,
+    plugin_metadata: &mut serde_json::Value,
+    package: &cargo_metadata::Package,
+) -> anyhow::Result<Option<(String, Utf8PathBuf)>> {
+    let plugin_merge_into = match plugin_metadata {
+        serde_json::Value::Object(map) => map.remove("merge-into"),
+        _ => None,
+    };
+    let merge_into = match plugin_merge_into.as_ref().or_else(|| gluegun_metadata.get("merge-into")) {
+        None => return Ok(None),
+        Some(serde_json::Value::String(merge_into)) => merge_into,
+        Some(value) => anyhow::bail!("expected a path for configuration `merge-into`, found `{value}`"),
+    };
+
+    let Some(package_dir) = package.manifest_path.parent() else {
+        anyhow::bail!("cannot compute parent path for crate at `{}`", package.manifest_path);
+    };
+    let merge_path = package_dir.join(merge_into);
+
+    // The crate's name is needed by plugins (e.g., to load the library it builds).
+    let manifest_path = merge_path.join("Cargo.toml");
+    let manifest_text = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("`merge-into` for `{plugin}` must name an existing crate; failed to read `{manifest_path}`"))?;
+    let manifest: toml_edit::DocumentMut = manifest_text
+        .parse()
+        .with_context(|| format!("failed to parse `{manifest_path}`"))?;
+    let Some(crate_name) = manifest.get("package").and_then(|p| p.get("name")).and_then(|n| n.as_str()) else {
+        anyhow::bail!("no `package.name` in `{manifest_path}`");
+    };
+
+    Ok(Some((crate_name.to_string(), merge_path.join(MERGE_SUBDIRECTORY))))
+}
+
+/// Check that `name` can be used as the name of a package, as `cargo new` would.
+fn check_crate_name(name: &str) -> anyhow::Result<()> {
+    let Some(first) = name.chars().next() else {
+        anyhow::bail!("crate names cannot be empty");
+    };
+    if first.is_ascii_digit() {
+        anyhow::bail!("crate names cannot start with a digit");
+    }
+    if let Some(c) = name.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_')) {
+        anyhow::bail!("invalid character `{c}` (only ASCII letters, digits, `-`, and `_` are allowed)");
+    }
+    Ok(())
+}
+
+/// Read a boolean setting such as `gluegun.split-modules` (defaults to `false`).
+fn bool_setting(gluegun_metadata: &serde_json::Value, key: &str) -> anyhow::Result<bool> {
+    match gluegun_metadata.get(key) {
+        None => Ok(false),
+        Some(serde_json::Value::Bool(b)) => Ok(*b),
+        Some(_) => anyhow::bail!("expected a boolean for configuration `gluegun.{key}`"),
+    }
+}
+
+/// Settings for the `[package]` section of the generated crate's manifest.
+struct PackageSettings {
+    edition: String,
+    rust_version: Option<String>,
+}
+
+impl PackageSettings {
+    /// Read `gluegun.<plugin>.edition` and `gluegun.<plugin>.rust-version`, removing them from `plugin_metadata`.
+    /// Unset values default to those of the source crate `package`.
+    fn take_from(
+        plugin: &str,
+        plugin_metadata: &mut serde_json::Value,
+        package: &cargo_metadata::Package,
+    ) -> anyhow::Result<Self> {
+        let mut take = |key: &str| -> anyhow::Result<Option<String>> {
+            let serde_json::Value::Object(map) = plugin_metadata else {
+                return Ok(None);
+            };
+            match map.remove(key) {
+                None => Ok(None),
+                Some(serde_json::Value::String(s)) => Ok(Some(s)),
+                Some(_) => anyhow::bail!("expected a string for configuration `gluegun.{plugin}.{key}`"),
+            }
+        };
+
+        let edition = take("edition")?.unwrap_or_else(|| package.edition.to_string());
+        let rust_version = take("rust-version")?.or_else(|| package.rust_version.as_ref().map(|v| v.to_string()));
+
+        // Plugins without settings of their own expect no metadata at all.
+        if plugin_metadata.as_object().is_some_and(|map| map.is_empty()) {
+            *plugin_metadata = serde_json::Value::Null;
+        }
+
+        Ok(Self { edition, rust_version })
+    }
+}
+
+/// The items of the crate bound by a plugin, from `gluegun.<plugin>.include` and `gluegun.<plugin>.exclude`.
+/// Each is a list of item paths like `my_crate::shapes::Circle`; a module path stands for all the items in the module.
+struct ItemFilter {
+    /// If set, only these items are bound (with the types they refer to).
+    include: Option<Vec<String>>,
+
+    /// Items not to bind, unless an item that is bound refers to them.
+    exclude: Vec<String>,
+}
+
+impl ItemFilter {
+    /// Read `gluegun.<plugin>.include` and `gluegun.<plugin>.exclude`, removing them from `plugin_metadata`.
+    fn take_from(plugin: &str, plugin_metadata: &mut serde_json::Value) -> anyhow::Result<Self> {
+        let mut take = |key: &str| -> anyhow::Result<Option<Vec<String>>> {
+            let serde_json::Value::Object(map) = plugin_metadata else {
+                return Ok(None);
+            };
+            match map.remove(key) {
+                None => Ok(None),
+                Some(value) => serde_json::from_value(value)
+                    .map(Some)
+                    .with_context(|| format!("expected an array of item paths for configuration `gluegun.{plugin}.{key}`")),
+            }
+        };
+
+        Ok(Self {
+            include: take("include")?,
+            exclude: take("exclude")?.unwrap_or_default(),
+        })
+    }
+
+    /// The part of `idl` to give to the plugin, see [`gluegun_idl::Idl::select`][].
+    fn apply(&self, idl: &gluegun_idl::Idl) -> gluegun_idl::Idl {
+        let mut idl = idl.clone();
+        if self.include.is_some() || !self.exclude.is_empty() {
+            idl.select(|qname| {
+                self.include.as_ref().is_none_or(|include| matches_any(include, qname))
+                    && !matches_any(&self.exclude, qname)
+            });
+        }
+        idl
+    }
+}
+
+/// True if `qname` is one of `paths` or is in a module that is.
+fn matches_any(paths: &[String], qname: &gluegun_idl::QualifiedName) -> bool {
+    let qname = qname.colon_colon();
+    paths.iter().any(|path| {
+        qname == *path || qname.strip_prefix(path.as_str()).is_some_and(|rest| rest.starts_with("::"))
+    })
+}
+
+/// Merge the `gluegun` tables of the workspace and package metadata (`workspace.metadata.gluegun` and
+/// `package.metadata.gluegun`) into the configuration that applies to the package, as `cargo gluegun` does.
+///
+/// Keys set in the package override those of the workspace, and keys set only in the workspace are kept.
+/// Values are not merged recursively: a table set in the package replaces the workspace's table
+/// (use [`merge_plugin_metadata`][] for the table of a plugin).
+/// An array set in the package replaces the workspace's array, unless it is given as a `$concat` directive.
+///
+/// ```
+/// use serde_json::json;
+///
+/// let workspace = json!({ "split-modules": true, "destination-path": "child", "java": { "edition": "2021" } });
+/// let package = json!({ "destination-path": "sibling", "java": { "rust-version": "1.80" } });
+/// let merged = cargo_gluegun::merge_gluegun_metadata(Some(&workspace), Some(&package))?;
+/// assert_eq!(
+///     merged,
+///     json!({ "split-modules": true, "destination-path": "sibling", "java": { "rust-version": "1.80" } }),
+/// );
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn merge_gluegun_metadata(
     workspace_metadata: Option<&serde_json::Value>,
     package_metadata: Option<&serde_json::Value>,
 ) -> anyhow::Result<serde_json::Value> {
     match (workspace_metadata, package_metadata) {
         (Some(workspace), Some(package)) => merge_values(workspace, package),
         (Some(workspace), None) => Ok(workspace.clone()),
-        (None, Some(package)) => Ok(package.clone()),
+        (None, Some(package)) => Ok(resolve_directives(package)),
         (None, None) => Ok(serde_json::Value::Null),
     }
 }
 
+/// Merge the tables of `plugin` (e.g., `workspace.metadata.gluegun.java` and `package.metadata.gluegun.java`)
+/// out of the `gluegun` tables of the workspace and package metadata, as for [`merge_gluegun_metadata`][].
+/// This is the configuration of the plugin, before `cargo gluegun` takes out the keys it handles itself
+/// (e.g., `include` and `edition`) and passes the rest on to it.
+pub fn merge_plugin_metadata(
+    workspace_metadata: Option<&serde_json::Value>,
+    package_metadata: Option<&serde_json::Value>,
+    plugin: &str,
+) -> anyhow::Result<serde_json::Value> {
+    merge_gluegun_metadata(
+        workspace_metadata.and_then(|v| v.get(plugin)),
+        package_metadata.and_then(|v| v.get(plugin)),
+    )
+}
+
+/// Key of the merge directive `{ "$concat": [...] }`, which a package can give in place of an array
+/// to append its elements to the workspace array instead of replacing it.
+const CONCAT_DIRECTIVE: &str = "$concat";
+
+/// The elements to append if `value` is a `$concat` directive (see [`CONCAT_DIRECTIVE`][]).
+fn concat_directive(value: &serde_json::Value) -> Option<&Vec<serde_json::Value>> {
+    match value {
+        serde_json::Value::Object(map) if map.len() == 1 => match map.get(CONCAT_DIRECTIVE) {
+            Some(serde_json::Value::Array(elements)) => Some(elements),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Replace every `$concat` directive in a package value with no workspace counterpart by its elements.
+fn resolve_directives(value: &serde_json::Value) -> serde_json::Value {
+    if let Some(elements) = concat_directive(value) {
+        return serde_json::Value::Array(elements.clone());
+    }
+    match value {
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter().map(|(key, value)| (key.clone(), resolve_directives(value))).collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
 /// Merge metadata values from workspace/package.
 ///
 /// Generally speaking, package wins, but for maps we take the keys from workspace that are not present in package.
+/// An array in the package replaces the workspace array, unless it is given as a `$concat` directive
+/// (e.g., `flags = { "$concat" = ["--verbose"] }`), in which case it is appended to it.
 fn merge_values(
     workspace_value: &serde_json::Value,
     package_value: &serde_json::Value,
 ) -> anyhow::Result<serde_json::Value> {
+    if let Some(elements) = concat_directive(package_value) {
+        return match workspace_value {
+            serde_json::Value::Array(workspace_elements) => {
+                Ok(serde_json::Value::Array(workspace_elements.iter().chain(elements).cloned().collect()))
+            }
+            serde_json::Value::Null => Ok(serde_json::Value::Array(elements.clone())),
+            _ => anyhow::bail!(
+                "cannot concatenate package configuration to a workspace value that is not an array:\
+                \n    workspace: {workspace_value}\
+                \n    package: {package_value}"
+            ),
+        };
+    }
+
     match (workspace_value, package_value) {
         (serde_json::Value::Null, serde_json::Value::Null) => Ok(serde_json::Value::Null),
 
@@ -318,7 +1526,14 @@ fn merge_values(
             let mut merged = workspace_map.clone();
 
             for (key, value) in package_map {
-                merged.insert(key.clone(), value.clone());
+                let value = match workspace_map.get(key) {
+                    Some(workspace_value) if concat_directive(value).is_some() => {
+                        merge_values(workspace_value, value)
+                            .with_context(|| format!("merging configuration `{key}`"))?
+                    }
+                    _ => resolve_directives(value),
+                };
+                merged.insert(key.clone(), value);
             }
 
             Ok(serde_json::Value::Object(merged))
@@ -0,0 +1,65 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// How much `cargo gluegun` says about what it is doing, selected with `--quiet` or `--verbose`.
+/// Warnings and errors are reported whatever the verbosity; the output of plugins is passed through as is.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Verbosity {
+    /// Only warnings and errors (`--quiet`).
+    Quiet,
+
+    /// A one-line summary of each plugin run.
+    #[default]
+    Normal,
+
+    /// Also the command run for each plugin and the size of its input (`--verbose`).
+    Verbose,
+}
+
+/// The messages of `cargo gluegun` itself, written to stderr unless redirected with [`crate::Builder::log_to`][].
+/// Each message is written in one piece, so that messages from plugins running alongside each other (`--jobs`) do not interleave.
+pub(crate) struct Log {
+    verbosity: Verbosity,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Log {
+    pub(crate) fn new(sink: impl Write + Send + 'static) -> Self {
+        Self {
+            verbosity: Verbosity::default(),
+            sink: Mutex::new(Box::new(sink)),
+        }
+    }
+
+    pub(crate) fn set_verbosity(&mut self, verbosity: Verbosity) {
+        self.verbosity = verbosity;
+    }
+
+    pub(crate) fn error(&self, message: impl Display) {
+        self.write(Verbosity::Quiet, format_args!("error: {message}"));
+    }
+
+    pub(crate) fn warning(&self, message: impl Display) {
+        self.write(Verbosity::Quiet, format_args!("warning: {message}"));
+    }
+
+    /// A message shown unless `--quiet` is given.
+    pub(crate) fn info(&self, message: impl Display) {
+        self.write(Verbosity::Normal, message);
+    }
+
+    /// A message shown only if `--verbose` is given.
+    pub(crate) fn verbose(&self, message: impl Display) {
+        self.write(Verbosity::Verbose, message);
+    }
+
+    fn write(&self, level: Verbosity, message: impl Display) {
+        if self.verbosity >= level {
+            // There is nowhere left to report a failure to write a message.
+            let mut sink = self.sink.lock().unwrap();
+            let _ = writeln!(sink, "{message}");
+            let _ = sink.flush();
+        }
+    }
+}
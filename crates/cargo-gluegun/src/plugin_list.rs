@@ -0,0 +1,107 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use gluegun_core::protocol::{PluginInfo, INFO_FLAG};
+use serde::Serialize;
+
+/// Format of the output of `list-plugins`, selected with `--format`.
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ListFormat {
+    /// A table with one row per plugin.
+    #[default]
+    Human,
+
+    /// A JSON array with one object per plugin.
+    Json,
+}
+
+/// An installed plugin, with what it reported about itself.
+/// The version and language are `None` if the plugin could not be queried (e.g., because it predates `--gg-info`).
+#[derive(Serialize, Debug)]
+pub struct PluginListing {
+    pub name: String,
+    pub version: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Ask each of `plugins` for its [`PluginInfo`][] by adding [`INFO_FLAG`][] to the command created by `plugin_command`.
+/// A plugin that cannot be queried is still listed, along with the error for the caller to report.
+pub fn query_plugins(
+    plugins: &[String],
+    plugin_command: impl Fn(&str) -> anyhow::Result<Command>,
+) -> Vec<(PluginListing, Option<anyhow::Error>)> {
+    plugins
+        .iter()
+        .map(|plugin| match query_plugin(plugin, &plugin_command).and_then(|info| {
+            info.with_context(|| format!("gluegun-{plugin} does not support {INFO_FLAG}"))
+        }) {
+            Ok(info) => {
+                let listing = PluginListing {
+                    name: plugin.clone(),
+                    version: Some(info.version),
+                    language: Some(info.language),
+                };
+                (listing, None)
+            }
+            Err(err) => (PluginListing { name: plugin.clone(), version: None, language: None }, Some(err)),
+        })
+        .collect()
+}
+
+/// Ask `plugin` for its [`PluginInfo`][], running the command created by `plugin_command` with [`INFO_FLAG`][].
+/// Returns `None` if the plugin predates [`INFO_FLAG`][]: such plugins reject it as an unknown argument,
+/// failing without printing anything on stdout (where a plugin speaking the protocol prints its handshake).
+pub(crate) fn query_plugin(
+    plugin: &str,
+    plugin_command: &impl Fn(&str) -> anyhow::Result<Command>,
+) -> anyhow::Result<Option<PluginInfo>> {
+    let mut command = plugin_command(plugin).context("creating plugin command")?;
+    let output = command
+        .arg(INFO_FLAG)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("spawning gluegun-{plugin}"))?;
+
+    if !output.status.success() {
+        if output.stdout.is_empty() {
+            return Ok(None);
+        }
+        anyhow::bail!(
+            "gluegun-{plugin} {INFO_FLAG} failed with code {status}: {stderr}",
+            status = output.status,
+            stderr = String::from_utf8_lossy(&output.stderr).trim(),
+        );
+    }
+
+    let info = serde_json::from_slice(&output.stdout)
+        .with_context(|| format!("parsing the output of gluegun-{plugin} {INFO_FLAG}"))?;
+    Ok(Some(info))
+}
+
+/// Write `listings` to `out` in the given `format`.
+pub fn write_plugin_list(listings: &[PluginListing], format: ListFormat, out: &mut dyn Write) -> anyhow::Result<()> {
+    match format {
+        ListFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(listings)?)?,
+
+        ListFormat::Human => {
+            let rows: Vec<[&str; 3]> = std::iter::once(["PLUGIN", "VERSION", "LANGUAGE"])
+                .chain(listings.iter().map(|listing| {
+                    [
+                        &listing.name[..],
+                        listing.version.as_deref().unwrap_or("?"),
+                        listing.language.as_deref().unwrap_or("?"),
+                    ]
+                }))
+                .collect();
+            let width = |column: usize| rows.iter().map(|row| row[column].len()).max().unwrap_or(0);
+            let (name_width, version_width) = (width(0), width(1));
+            for [name, version, language] in rows {
+                writeln!(out, "{name:name_width$}  {version:version_width$}  {language}")?;
+            }
+        }
+    }
+    Ok(())
+}
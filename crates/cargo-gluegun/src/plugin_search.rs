@@ -0,0 +1,31 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use crate::completions::is_executable;
+
+/// Find the executable of `plugin` (`gluegun-<plugin>`), looking in each of `plugin_dirs` (`--plugin-dir`) in turn,
+/// then in the directories of `path` (in the format of the `PATH` variable).
+pub fn find_plugin(plugin: &str, plugin_dirs: &[impl AsRef<Path>], path: Option<&OsStr>) -> anyhow::Result<PathBuf> {
+    let file_name = format!("gluegun-{plugin}{suffix}", suffix = std::env::consts::EXE_SUFFIX);
+    let path_dirs = path.into_iter().flat_map(std::env::split_paths);
+    let found = plugin_dirs
+        .iter()
+        .map(|dir| dir.as_ref().to_path_buf())
+        .chain(path_dirs)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate));
+    if let Some(executable) = found {
+        return Ok(executable);
+    }
+
+    let searched: Vec<_> = plugin_dirs.iter().map(|dir| format!("`{}`", dir.as_ref().display())).collect();
+    if searched.is_empty() {
+        anyhow::bail!("plugin `{plugin}` not found: there is no executable `{file_name}` on the `PATH`");
+    }
+    anyhow::bail!(
+        "plugin `{plugin}` not found: there is no executable `{file_name}` in {searched}, or on the `PATH`",
+        searched = searched.join(", "),
+    )
+}
@@ -0,0 +1,54 @@
+use std::fmt::Display;
+
+use cargo_metadata::camino::Utf8PathBuf;
+use serde::Serialize;
+
+/// What a run of `cargo gluegun` generated, printed at the end of the run unless `--quiet` is given.
+/// It serializes to JSON, with one object per generated crate.
+#[derive(Serialize, Debug, Default)]
+pub(crate) struct Report {
+    /// The crates generated, in the order the runs were scheduled (by package, then in the order the plugins were given).
+    pub(crate) crates: Vec<GeneratedCrate>,
+}
+
+/// A crate generated by a plugin run.
+#[derive(Serialize, Debug)]
+pub(crate) struct GeneratedCrate {
+    /// The package the crate binds.
+    pub(crate) package: String,
+
+    /// The plugin that generated the crate, without the `gluegun-` prefix.
+    pub(crate) plugin: String,
+
+    /// The name of the generated crate.
+    pub(crate) crate_name: String,
+
+    /// Where the crate was generated.
+    pub(crate) path: Utf8PathBuf,
+
+    /// The number of items of the package's interface given to the plugin.
+    pub(crate) items_bound: usize,
+
+    /// The number of items left out by the `include` and `exclude` settings of the plugin.
+    /// (Items the plugin does not support are not counted, as they make the run fail.)
+    pub(crate) items_skipped: usize,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "generated {count} crate(s)", count = self.crates.len())?;
+        for generated in &self.crates {
+            write!(
+                f,
+                "\n    `{crate_name}` in `{path}` (gluegun-{plugin} on `{package}`): {bound} item(s) bound, {skipped} skipped",
+                crate_name = generated.crate_name,
+                path = generated.path,
+                plugin = generated.plugin,
+                package = generated.package,
+                bound = generated.items_bound,
+                skipped = generated.items_skipped,
+            )?;
+        }
+        Ok(())
+    }
+}
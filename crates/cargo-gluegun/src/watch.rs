@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+
+use cargo_metadata::camino::Utf8Path;
+
+use crate::idl_cache::{self, CachedIdl};
+
+/// How often the source files are checked for changes (`--watch`).
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the source files must stay unchanged before the packages are regenerated,
+/// so that a burst of writes (e.g., saving several files, or a formatter rewriting them) causes a single regeneration.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The source files of a package, with the modification time each had when the package was last regenerated
+/// (`None` if it did not exist, so that creating a module file that was looked for counts as a change).
+#[derive(Default)]
+pub(crate) struct WatchedSources {
+    times: BTreeMap<PathBuf, Option<SystemTime>>,
+}
+
+impl WatchedSources {
+    /// Snapshot the source files of `package` as they are now.
+    /// They are the files read when its interface was last extracted (or failed to be), as recorded in the IDL cache,
+    /// and always the root source file of its library target, in case the cache could not be saved.
+    pub(crate) fn snapshot(target_directory: &Utf8Path, package: &cargo_metadata::Package) -> Self {
+        let cached = CachedIdl::sources(&idl_cache::cache_path(target_directory, package)).unwrap_or_default();
+        let root = crate::library_target(package).map(|target| target.src_path.clone().into_std_path_buf());
+        Self { times: cached.into_iter().chain(root).map(|path| (path.clone(), idl_cache::modified(&path))).collect() }
+    }
+
+    /// The number of source files watched.
+    pub(crate) fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    /// The modification times of the same files now.
+    fn current(&self) -> BTreeMap<PathBuf, Option<SystemTime>> {
+        self.times.keys().map(|path| (path.clone(), idl_cache::modified(path))).collect()
+    }
+}
+
+/// Wait until the source files of some of the `watched` packages change and then stay unchanged for a while,
+/// returning which packages changed, or `None` if `stop` is set first.
+pub(crate) fn wait_for_changes(watched: &[&WatchedSources], stop: &AtomicBool) -> Option<Vec<bool>> {
+    let snapshot = || watched.iter().map(|sources| sources.current()).collect::<Vec<_>>();
+    let changed = |current: &[BTreeMap<_, _>]| {
+        watched.iter().zip(current).map(|(sources, current)| sources.times != *current).collect::<Vec<_>>()
+    };
+
+    let mut current = loop {
+        std::thread::sleep(POLL_INTERVAL);
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        let current = snapshot();
+        if changed(&current).contains(&true) {
+            break current;
+        }
+    };
+
+    loop {
+        std::thread::sleep(DEBOUNCE);
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        let settled = snapshot();
+        if settled == current {
+            return Some(changed(&current));
+        }
+        current = settled;
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Context;
+use cargo_metadata::camino::Utf8Path;
+use gluegun_core::codegen::WorkspaceLock;
+
+/// Add the crate at `crate_path` to the `workspace.exclude` list of the workspace at `workspace_root`,
+/// so that building the workspace does not build the generated crate.
+///
+/// `cargo new` adds new crates to `workspace.members` when that list is given explicitly;
+/// a crate listed there is a member even if excluded, so it is removed again.
+/// Does nothing if the crate is not within the workspace or is already excluded.
+///
+/// The workspace is locked meanwhile, as plugins generating other crates (with `--jobs`) may be editing it too.
+pub(crate) fn exclude_from_workspace(workspace_root: &Utf8Path, crate_path: &Utf8Path) -> anyhow::Result<()> {
+    let Ok(relative_path) = crate_path.strip_prefix(workspace_root) else {
+        return Ok(());
+    };
+    let relative_path = relative_path.as_str().replace('\\', "/");
+
+    let manifest_path = workspace_root.join("Cargo.toml");
+    let _lock = WorkspaceLock::acquire(manifest_path.as_std_path())?;
+    let manifest_text = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("failed to read `{manifest_path}`"))?;
+    let mut manifest: toml_edit::DocumentMut = manifest_text
+        .parse()
+        .with_context(|| format!("failed to parse `{manifest_path}`"))?;
+
+    let Some(workspace) = manifest.get_mut("workspace").and_then(|w| w.as_table_like_mut()) else {
+        return Ok(());
+    };
+
+    if let Some(members) = workspace.get_mut("members").and_then(|m| m.as_array_mut()) {
+        members.retain(|member| member.as_str() != Some(&relative_path));
+    }
+
+    let exclude = workspace
+        .entry("exclude")
+        .or_insert(toml_edit::value(toml_edit::Array::new()))
+        .as_array_mut()
+        .with_context(|| format!("expected `workspace.exclude` in `{manifest_path}` to be an array"))?;
+    if !exclude.iter().any(|path| path.as_str() == Some(&relative_path)) {
+        exclude.push(relative_path);
+    }
+
+    let new_manifest_text = manifest.to_string();
+    if new_manifest_text != manifest_text {
+        std::fs::write(&manifest_path, new_manifest_text)
+            .with_context(|| format!("failed to write `{manifest_path}`"))?;
+    }
+    Ok(())
+}
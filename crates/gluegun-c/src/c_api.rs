@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+
+use gluegun_core::{
+    cli::{for_each_supported, UnsupportedError},
+    idl::{Function, Idl, IsAsync, Item, OwnedKind, QualifiedName, RefdTy, Record, Scalar, StringRepr, Ty, TypeKind},
+};
+
+/// The C interface of a crate, as mapped from its IDL: the header and the Rust shim are both generated from this.
+pub(crate) struct CApi {
+    /// Name of the crate, which prefixes every C name, like `plain_records`.
+    pub(crate) prefix: String,
+
+    /// The records, ordered so that each comes after the records it contains (as C requires).
+    pub(crate) records: Vec<CRecord>,
+
+    pub(crate) functions: Vec<CFunction>,
+}
+
+/// A record, passed by value as a C struct with the same fields.
+pub(crate) struct CRecord {
+    /// Name of the C struct and of its `#[repr(C)]` mirror in the shim, like `plain_records_Point`.
+    pub(crate) c_name: String,
+
+    /// Path of the record in Rust, like `plain_records::Point`.
+    pub(crate) rust_path: String,
+
+    pub(crate) fields: Vec<CField>,
+}
+
+pub(crate) struct CField {
+    /// Name of the field in C.
+    pub(crate) c_name: String,
+
+    /// Name of the field in Rust.
+    pub(crate) rust_name: String,
+
+    pub(crate) ty: CType,
+}
+
+/// A free function, exported by the shim as an `extern "C"` function calling the Rust one.
+pub(crate) struct CFunction {
+    /// Name of the exported function, like `plain_records_manhattan_distance`.
+    pub(crate) symbol: String,
+
+    /// Path of the function in Rust, like `plain_records::manhattan_distance`.
+    pub(crate) rust_path: String,
+
+    pub(crate) inputs: Vec<CInput>,
+
+    /// `None` for functions returning `()`.
+    pub(crate) output: Option<CType>,
+
+    /// How the Rust function returns the value (e.g., in a `Box`), so the shim can take it out first.
+    pub(crate) output_kind: OwnedKind,
+}
+
+pub(crate) struct CInput {
+    /// Name of the parameter in C.
+    pub(crate) c_name: String,
+
+    /// Name of the parameter in Rust.
+    pub(crate) rust_name: String,
+
+    pub(crate) ty: CType,
+
+    /// Whether the Rust function takes a reference (e.g., `&str`), so the shim passes a reference to the converted value.
+    pub(crate) by_ref: bool,
+
+    /// How the Rust function takes an owned value (e.g., in a `Box`), so the shim can put the converted value in it.
+    pub(crate) owned_kind: OwnedKind,
+}
+
+/// How a value crosses the C ABI.
+pub(crate) enum CType {
+    /// A scalar, which has the same representation in C and Rust.
+    Scalar(Scalar),
+
+    /// A non-null, NUL-terminated UTF-8 string: `const char *` when passed to Rust, `char *` when returned,
+    /// in which case the caller frees it with the crate's `free_string` function (see [`CApi::free_string`][]).
+    /// The shim aborts on a string breaking these rules (see [`gluegun_c::shim::Runtime`][]).
+    String,
+
+    /// A record, passed by value as its C struct.
+    Record(QualifiedName),
+}
+
+impl CApi {
+    pub(crate) fn new(idl: &Idl) -> anyhow::Result<Self> {
+        let mut api = CApi {
+            prefix: idl.crate_name().to_string(),
+            records: vec![],
+            functions: vec![],
+        };
+
+        let mut records = BTreeMap::new();
+        for_each_supported(idl.definitions(), |(qname, item)| match item {
+            Item::Function(function) => {
+                api.functions.push(c_function(idl, qname, function)?);
+                Ok(())
+            }
+            Item::Record(record) => {
+                records.insert(qname.clone(), c_record(idl, qname, record)?);
+                Ok(())
+            }
+            Item::Enum(_) | Item::Variant(_) => {
+                anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "enums cannot be mapped to C yet"))
+            }
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to C yet")),
+        })?;
+
+        // Contained records are listed before the records containing them; records cannot contain themselves.
+        while let Some(qname) = records.keys().next().cloned() {
+            api.push_record(idl, &mut records, &qname);
+        }
+
+        Ok(api)
+    }
+
+    /// Move the record `qname` from `records` to `self.records`, after the records its fields contain.
+    fn push_record(&mut self, idl: &Idl, records: &mut BTreeMap<QualifiedName, CRecord>, qname: &QualifiedName) {
+        let Some(record) = records.remove(qname) else {
+            return;
+        };
+        if let Some(Item::Record(item)) = idl.definitions().get(qname) {
+            for field in item.fields() {
+                for contained in field.ty().user_types() {
+                    self.push_record(idl, records, &contained);
+                }
+            }
+        }
+        self.records.push(record);
+    }
+
+    /// Name of the function that frees the strings returned by the other functions, like `plain_records_gluegun_free_string`.
+    /// The `gluegun` segment keeps it from colliding with a user function.
+    pub(crate) fn free_string(&self) -> String {
+        format!("{}_gluegun_free_string", self.prefix)
+    }
+}
+
+impl CType {
+    /// The C type of a parameter of this type.
+    pub(crate) fn c_input_ty(&self) -> String {
+        match self {
+            CType::Scalar(scalar) => c_scalar_ty(scalar).to_string(),
+            CType::String => "const char *".to_string(),
+            CType::Record(qname) => c_struct_name(qname),
+        }
+    }
+
+    /// The C type of a returned value or a field of this type.
+    pub(crate) fn c_output_ty(&self) -> String {
+        match self {
+            CType::String => "char *".to_string(),
+            _ => self.c_input_ty(),
+        }
+    }
+
+    /// The Rust type in the shim for a parameter of this type.
+    pub(crate) fn rust_input_ty(&self) -> String {
+        match self {
+            CType::Scalar(scalar) => scalar.to_string(),
+            CType::String => "*const std::ffi::c_char".to_string(),
+            CType::Record(qname) => c_struct_name(qname),
+        }
+    }
+
+    /// The Rust type in the shim for a returned value or a field of this type.
+    pub(crate) fn rust_output_ty(&self) -> String {
+        match self {
+            CType::String => "*mut std::ffi::c_char".to_string(),
+            _ => self.rust_input_ty(),
+        }
+    }
+}
+
+/// A C declaration of `name` with type `c_ty`, like `int32_t x` or `char *name`.
+pub(crate) fn c_declaration(c_ty: &str, name: &str) -> String {
+    if c_ty.ends_with('*') {
+        format!("{c_ty}{name}")
+    } else {
+        format!("{c_ty} {name}")
+    }
+}
+
+fn c_function(idl: &Idl, qname: &QualifiedName, function: &Function) -> anyhow::Result<CFunction> {
+    let signature = function.signature();
+    if *signature.is_async() == IsAsync::Yes {
+        anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "async functions cannot be mapped to C yet"));
+    }
+    if signature.output_ty().error_ty().is_some() {
+        anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "`Result` cannot be returned to C yet"));
+    }
+
+    let mut inputs = vec![];
+    for input in signature.inputs() {
+        let (ty, by_ref) = match input.refd_ty() {
+            RefdTy::Owned(_, ty) => (ty, false),
+            RefdTy::Ref(kind, ty) if kind.is_mut() => {
+                anyhow::bail!(UnsupportedError::new(ty, "mutable references cannot be passed from C yet"))
+            }
+            RefdTy::Ref(_, ty) => (ty, true),
+        };
+        inputs.push(CInput {
+            c_name: c_ident(input.name().text()),
+            rust_name: input.name().to_string(),
+            ty: c_type(idl, ty)?,
+            by_ref,
+            owned_kind: input.refd_ty().owned_kind().cloned().unwrap_or(OwnedKind::Owned),
+        });
+    }
+
+    let output = match signature.output_ty().main_ty() {
+        RefdTy::Owned(_, ty) if ty.is_unit() => None,
+        RefdTy::Owned(_, ty) => Some(c_type(idl, ty)?),
+        RefdTy::Ref(_, ty) => anyhow::bail!(UnsupportedError::new(ty, "references cannot be returned to C yet")),
+    };
+
+    Ok(CFunction {
+        symbol: format!("{}_{}", qname.module_name().to_string("_"), function.name()),
+        rust_path: qname.colon_colon(),
+        inputs,
+        output,
+        output_kind: signature.output_ty().main_ty().owned_kind().cloned().unwrap_or(OwnedKind::Owned),
+    })
+}
+
+fn c_record(idl: &Idl, qname: &QualifiedName, record: &Record) -> anyhow::Result<CRecord> {
+    if !record.methods().is_empty() {
+        anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "methods on records cannot be mapped to C yet"));
+    }
+    if !record.is_constructible() {
+        anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "records with private fields or `#[non_exhaustive]` cannot be mapped to C yet"));
+    }
+
+    let mut fields = vec![];
+    for field in record.fields() {
+        let ty = c_type(idl, field.ty())?;
+        if let CType::String = ty {
+            // The struct would own the string, which C has no way of freeing.
+            anyhow::bail!(UnsupportedError::new(field.ty(), "strings cannot be record fields in C yet"));
+        }
+        fields.push(CField {
+            c_name: c_ident(field.name().text()),
+            rust_name: field.rust_name().to_string(),
+            ty,
+        });
+    }
+
+    Ok(CRecord {
+        c_name: c_struct_name(qname),
+        rust_path: qname.colon_colon(),
+        fields,
+    })
+}
+
+fn c_type(idl: &Idl, ty: &Ty) -> anyhow::Result<CType> {
+    match ty.kind() {
+        TypeKind::Scalar(Scalar::Char) => {
+            anyhow::bail!(UnsupportedError::new(ty, "`char` cannot be mapped to C yet"))
+        }
+        TypeKind::Scalar(Scalar::I128 | Scalar::U128) => {
+            anyhow::bail!(UnsupportedError::new(ty, "128-bit integers have no portable C type"))
+        }
+        TypeKind::Scalar(scalar) => Ok(CType::Scalar(*scalar)),
+        TypeKind::String { repr: StringRepr::String | StringRepr::StrRef } => Ok(CType::String),
+        TypeKind::UserType { qname } => match idl.definitions().get(qname) {
+            Some(Item::Record(_)) => Ok(CType::Record(qname.clone())),
+            _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to C yet")),
+        },
+        _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to C yet")),
+    }
+}
+
+/// The fixed-width C type of a scalar, other than the `char` and 128-bit integers rejected by [`c_type`][].
+fn c_scalar_ty(scalar: &Scalar) -> &'static str {
+    match scalar {
+        Scalar::Boolean => "bool",
+        Scalar::I8 => "int8_t",
+        Scalar::I16 => "int16_t",
+        Scalar::I32 => "int32_t",
+        Scalar::I64 => "int64_t",
+        Scalar::U8 => "uint8_t",
+        Scalar::U16 => "uint16_t",
+        Scalar::U32 => "uint32_t",
+        Scalar::U64 => "uint64_t",
+        Scalar::F32 => "float",
+        Scalar::F64 => "double",
+        _ => unreachable!("`{scalar}` has no C type"),
+    }
+}
+
+/// Name of the C struct for the record `qname`, like `plain_records_Point` for `plain_records::Point`.
+fn c_struct_name(qname: &QualifiedName) -> String {
+    qname.to_string("_")
+}
+
+/// `name` as a C identifier, with a trailing `_` if it is a C keyword.
+fn c_ident(name: &str) -> String {
+    if C_KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Reserved C keywords that are not Rust keywords (a Rust identifier cannot be one of those).
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "case", "char", "default", "double", "float", "goto", "inline", "int", "long", "register",
+    "restrict", "short", "signed", "sizeof", "switch", "typedef", "union", "unsigned", "void", "volatile",
+];
@@ -0,0 +1,78 @@
+use gluegun_core::codegen::DirBuilder;
+
+use crate::c_api::{c_declaration, CApi};
+
+/// Generates the C header declaring the structs and functions exported by the shim, named after the crate
+/// (e.g., `plain_records.h`).
+pub(crate) struct HeaderGenerator<'api> {
+    api: &'api CApi,
+}
+
+impl<'api> HeaderGenerator<'api> {
+    pub(crate) fn new(api: &'api CApi) -> Self {
+        Self { api }
+    }
+
+    pub(crate) fn generate(self, mut dir: DirBuilder<'_>) -> anyhow::Result<()> {
+        let prefix = &self.api.prefix;
+        let guard = format!("{}_H", prefix.to_uppercase());
+        let mut h = dir.add_file(format!("{prefix}.h"))?;
+
+        write!(h, "#ifndef {guard}")?;
+        write!(h, "#define {guard}")?;
+        write!(h, "")?;
+        write!(h, "#include <stdbool.h>")?;
+        write!(h, "#include <stdint.h>")?;
+        write!(h, "")?;
+        write!(h, "#ifdef __cplusplus")?;
+        write!(h, "extern \"C\" {{")?;
+        h.pop_indent(); // the declarations are not indented within the `extern "C"` block
+        write!(h, "#endif")?;
+
+        for record in &self.api.records {
+            write!(h, "")?;
+            write!(h, "typedef struct {} {{", record.c_name)?;
+            for field in &record.fields {
+                write!(h, "{};", c_declaration(&field.ty.c_output_ty(), &field.c_name))?;
+            }
+            write!(h, "}} {};", record.c_name)?;
+        }
+
+        write!(h, "")?;
+        write!(h, "/*")?;
+        write!(h, " * String parameters must be non-null, NUL-terminated UTF-8 strings.")?;
+        write!(h, " * Returned strings are NUL-terminated UTF-8 strings; a Rust string containing a NUL byte cannot be returned.")?;
+        write!(h, " * Either mistake aborts the process.")?;
+        write!(h, " */")?;
+        for function in &self.api.functions {
+            let params = if function.inputs.is_empty() {
+                "void".to_string()
+            } else {
+                function
+                    .inputs
+                    .iter()
+                    .map(|input| c_declaration(&input.ty.c_input_ty(), &input.c_name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let ret = match &function.output {
+                Some(ty) => ty.c_output_ty(),
+                None => "void".to_string(),
+            };
+            write!(h, "{};", c_declaration(&ret, &format!("{}({params})", function.symbol)))?;
+        }
+
+        write!(h, "")?;
+        write!(h, "/* Frees a string returned by one of the functions above; `s` may be null. */")?;
+        write!(h, "void {}(char *s);", self.api.free_string())?;
+
+        write!(h, "")?;
+        write!(h, "#ifdef __cplusplus")?;
+        h.push_indent();
+        write!(h, "}}")?;
+        write!(h, "#endif")?;
+        write!(h, "")?;
+        write!(h, "#endif /* {guard} */")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+//! The parts of the C plugin that other plugins build on to reach Rust through the C ABI.
+
+pub mod shim;
@@ -0,0 +1,51 @@
+use anyhow::Context;
+use gluegun_core::{
+    cli::{GenerateCx, GlueGunHelper},
+    codegen::LibraryCrate,
+};
+
+mod c_api;
+mod h_gen;
+mod rs_gen;
+
+pub fn main() -> anyhow::Result<()> {
+    gluegun_core::cli::run(GlueGunC)
+}
+
+struct GlueGunC;
+
+impl GlueGunHelper for GlueGunC {
+    type Metadata = ();
+
+    fn name(&self) -> String {
+        "c".to_string()
+    }
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "C".to_string()
+    }
+
+    fn generate(self, cx: &mut GenerateCx, _metadata: &(), output: &mut LibraryCrate) -> anyhow::Result<()> {
+        cx.reject_generics(&self.language())?;
+
+        // Everything is mapped up front, so the header and the shim agree and unsupported items are reported once.
+        let api = c_api::CApi::new(cx.idl())?;
+
+        let include_dir = output
+            .add_dir("include")
+            .context("adding `include` dir")?;
+        h_gen::HeaderGenerator::new(&api)
+            .generate(include_dir)
+            .context("generating C header")?;
+
+        rs_gen::RustCodeGenerator::new(&api)
+            .generate(output)
+            .context("generating Rust sources")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use gluegun_c::shim::Runtime;
+use gluegun_core::codegen::{CodeWriter, LibraryCrate};
+
+use crate::c_api::{CApi, CFunction, CRecord, CType};
+
+/// Generates the C ABI shim: a `#[repr(C)]` mirror of each record, converted to and from the record,
+/// and an `extern "C"` function for each Rust function.
+pub(crate) struct RustCodeGenerator<'api> {
+    api: &'api CApi,
+}
+
+impl<'api> RustCodeGenerator<'api> {
+    pub(crate) fn new(api: &'api CApi) -> Self {
+        Self { api }
+    }
+
+    pub(crate) fn generate(self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
+        let mut lib_rs = lib.add_file("src/lib.rs")?;
+
+        write!(lib_rs, "#![allow(non_camel_case_types)]")?; // mirrors are named like their C structs, e.g. `plain_records_Point`
+
+        Runtime {
+            free_string: self.api.free_string(),
+            free_buffer: None,
+        }
+        .generate(&mut lib_rs)?;
+
+        for record in &self.api.records {
+            self.generate_record(&mut lib_rs, record)?;
+        }
+
+        for function in &self.api.functions {
+            self.generate_function(&mut lib_rs, function)?;
+        }
+
+        Ok(())
+    }
+
+    fn generate_record(&self, lib_rs: &mut CodeWriter<'_>, record: &CRecord) -> anyhow::Result<()> {
+        let CRecord { c_name, rust_path, fields } = record;
+
+        write!(lib_rs, "#[repr(C)]")?;
+        write!(lib_rs, "pub struct {c_name} {{")?;
+        for field in fields {
+            write!(lib_rs, "pub {}: {},", field.rust_name, field.ty.rust_output_ty())?;
+        }
+        write!(lib_rs, "}}")?;
+
+        // Scalar fields are copied as is; record fields are converted to and from their own mirrors.
+        for (from, to) in [(rust_path, c_name), (c_name, rust_path)] {
+            write!(lib_rs, "impl From<{from}> for {to} {{")?;
+            write!(lib_rs, "fn from(value: {from}) -> Self {{")?;
+            write!(lib_rs, "Self {{")?;
+            for field in fields {
+                match field.ty {
+                    CType::Record(_) => write!(lib_rs, "{0}: value.{0}.into(),", field.rust_name)?,
+                    _ => write!(lib_rs, "{0}: value.{0},", field.rust_name)?,
+                }
+            }
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+        }
+        Ok(())
+    }
+
+    /// Generate the `extern "C"` function calling the Rust function,
+    /// converting each argument from its C representation and the result back.
+    fn generate_function(&self, lib_rs: &mut CodeWriter<'_>, function: &CFunction) -> anyhow::Result<()> {
+        let params = function
+            .inputs
+            .iter()
+            .map(|input| format!("{}: {}", input.rust_name, input.ty.rust_input_ty()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(lib_rs, "#[unsafe(no_mangle)]")?;
+        match &function.output {
+            Some(ty) => write!(lib_rs, "pub unsafe extern \"C\" fn {}({params}) -> {} {{", function.symbol, ty.rust_output_ty())?,
+            None => write!(lib_rs, "pub unsafe extern \"C\" fn {}({params}) {{", function.symbol)?,
+        }
+
+        let mut args = vec![];
+        for input in &function.inputs {
+            let name = &input.rust_name;
+            match input.ty {
+                CType::Scalar(_) => {}
+                CType::String => write!(
+                    lib_rs,
+                    "let {name} = unsafe {{ crate::gluegun_runtime::from_c_string({name}, \"{}\") }};",
+                    input.c_name,
+                )?,
+                CType::Record(ref qname) => write!(lib_rs, "let {name}: {} = {name}.into();", qname.colon_colon())?,
+            }
+            args.push(if input.by_ref { format!("&{name}") } else { input.owned_kind.rust_from_value(name) });
+        }
+
+        let (open, close) = function.output_kind.rust_into_value();
+        let call = format!("{open}{}({}){close}", function.rust_path, args.join(", "));
+        match &function.output {
+            None => write!(lib_rs, "{call};")?,
+            Some(CType::Scalar(_)) => write!(lib_rs, "{call}")?,
+            Some(CType::String) => write!(lib_rs, "crate::gluegun_runtime::into_c_string({call})")?,
+            Some(CType::Record(_)) => write!(lib_rs, "{call}.into()")?,
+        }
+
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+//! The runtime of a C ABI shim, shared by the plugins reaching Rust through C (this one and C#).
+
+use gluegun_core::codegen::CodeWriter;
+
+/// The `gluegun_runtime` module of a shim crate: helpers converting strings and buffers to and from their C
+/// representation, called by the shim functions as `crate::gluegun_runtime::*`, and the exported functions
+/// freeing what was handed to the caller.
+///
+/// A shim function panics when the caller breaks one of the preconditions of the C API
+/// (e.g., passes a null or non-UTF-8 string), which aborts the process rather than letting it go on with corrupted data.
+pub struct Runtime {
+    /// Exported name of the function freeing a string returned by the shim.
+    pub free_string: String,
+
+    /// Exported name of the function freeing a buffer returned by the shim, if the shim returns buffers.
+    pub free_buffer: Option<String>,
+}
+
+impl Runtime {
+    pub fn generate(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
+        let Runtime { free_string, free_buffer } = self;
+
+        write!(lib_rs, "mod gluegun_runtime {{")?;
+        write!(lib_rs, "#![allow(dead_code)]")?; // not every crate needs every helper
+        write!(lib_rs, "")?;
+
+        write!(lib_rs, "/// Copy `s` into a C string owned by the caller, who frees it with `{free_string}`.")?;
+        write!(lib_rs, "/// Panics if `s` contains a NUL byte, which a C string cannot hold.")?;
+        write!(lib_rs, "pub(crate) fn into_c_string(s: String) -> *mut std::ffi::c_char {{")?;
+        write!(lib_rs, "match std::ffi::CString::new(s) {{")?;
+        write!(lib_rs, "Ok(s) => s.into_raw(),")?;
+        write!(lib_rs, "Err(err) => panic!(\"cannot return a string with a NUL byte at {{}} to C\", err.nul_position()),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "/// Copy the NUL-terminated UTF-8 string at `ptr`, passed as the parameter `name`, into a `String`.")?;
+        write!(lib_rs, "/// Panics if `ptr` is null or the string is not UTF-8.")?;
+        write!(lib_rs, "pub(crate) unsafe fn from_c_string(ptr: *const std::ffi::c_char, name: &str) -> String {{")?;
+        write!(lib_rs, "assert!(!ptr.is_null(), \"`{{name}}` is null\");")?;
+        write!(lib_rs, "match unsafe {{ std::ffi::CStr::from_ptr(ptr) }}.to_str() {{")?;
+        write!(lib_rs, "Ok(s) => s.to_string(),")?;
+        write!(lib_rs, "Err(err) => panic!(\"`{{name}}` is not UTF-8: {{err}}\"),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "#[unsafe(no_mangle)]")?;
+        write!(lib_rs, "pub unsafe extern \"C\" fn {free_string}(ptr: *mut std::ffi::c_char) {{")?;
+        write!(lib_rs, "if !ptr.is_null() {{ drop(unsafe {{ std::ffi::CString::from_raw(ptr) }}) }}")?;
+        write!(lib_rs, "}}")?;
+
+        if let Some(free_buffer) = free_buffer {
+            write!(lib_rs, "")?;
+            write!(lib_rs, "/// Copy `len` elements starting at `ptr` into a `Vec`; `ptr` may be null if `len` is zero.")?;
+            write!(lib_rs, "pub(crate) unsafe fn from_buffer<T: Copy>(ptr: *const T, len: usize) -> Vec<T> {{")?;
+            write!(lib_rs, "if len == 0 {{ Vec::new() }} else {{ unsafe {{ std::slice::from_raw_parts(ptr, len) }}.to_vec() }}")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "")?;
+            write!(lib_rs, "/// Store the elements of `vec` in a buffer owned by the caller, who frees it with `{free_buffer}`.")?;
+            write!(lib_rs, "/// The buffer is written to `out`; its length is returned.")?;
+            write!(lib_rs, "pub(crate) unsafe fn into_buffer<T>(vec: Vec<T>, out: *mut *mut T) -> usize {{")?;
+            write!(lib_rs, "let len = vec.len();")?;
+            write!(lib_rs, "unsafe {{ *out = Box::into_raw(vec.into_boxed_slice()) as *mut T }};")?;
+            write!(lib_rs, "len")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "")?;
+            write!(lib_rs, "/// Free a buffer of `len` scalars of `size` bytes each, created by `into_buffer`.")?;
+            write!(lib_rs, "/// Scalars of the same size have the same layout, so an unsigned integer of that size stands in for the element type.")?;
+            write!(lib_rs, "#[unsafe(no_mangle)]")?;
+            write!(lib_rs, "pub unsafe extern \"C\" fn {free_buffer}(ptr: *mut u8, len: usize, size: usize) {{")?;
+            write!(lib_rs, "if len == 0 {{ return; }}")?;
+            write!(lib_rs, "unsafe {{")?;
+            write!(lib_rs, "match size {{")?;
+            for (size, ty) in [(1, "u8"), (2, "u16"), (4, "u32"), (8, "u64")] {
+                write!(lib_rs, "{size} => drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr as *mut {ty}, len))),")?;
+            }
+            write!(lib_rs, "_ => unreachable!(\"no scalar has size {{size}}\"),")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+        }
+
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+//! Defined in `gluegun-idl`, so that the (deprecated) casing methods of [`Name`](crate::idl::Name)
+//! and [`QualifiedName`](crate::idl::QualifiedName) can convert names the same way.
+
+pub use gluegun_idl::{Casing, NameStyle};
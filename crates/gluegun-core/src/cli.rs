@@ -2,13 +2,19 @@
 //! some other language. Most GlueGun CLI crates can use the Clap structs defined
 //! in this file.
 
-use std::path::PathBuf;
+use std::collections::BTreeSet;
 
 use accessors_rs::Accessors;
 use anyhow::Context;
-use serde::{de::DeserializeOwned, Deserialize};
+use serde::de::DeserializeOwned;
 
-use crate::{codegen::LibraryCrate, idl::Idl};
+use crate::{
+    codegen::{incremental, LibraryCrate},
+    idl::{Idl, Item, Method, OwnedKind, QualifiedName, Signature, TypeKind},
+    protocol::{self, PluginInfo, PluginInput},
+};
+
+pub use crate::protocol::{MetadataKey, SourceDependency, UnsupportedError, PROTOCOL_VERSION};
 
 /// Trait implemented by gluegun helper applications.
 /// Your `main` function should invoke [`run`][].
@@ -25,6 +31,19 @@ pub trait GlueGunHelper {
     /// Returns the helper name that users provide to invoke this, e.g., for `gluegun-java`, returns `"java"`.
     fn name(&self) -> String;
 
+    /// Returns the version of the helper, normally `env!("CARGO_PKG_VERSION")`.
+    fn version(&self) -> String;
+
+    /// Returns the name of the language the helper generates bindings for, e.g., `"Java"`.
+    fn language(&self) -> String;
+
+    /// Returns the keys of [`Self::Metadata`][] (e.g., `"async-runtime"`), so that `cargo gluegun` can report
+    /// a missing required key, or a key the helper does not know about, before running it.
+    /// By default, no keys are declared and the metadata is not checked.
+    fn metadata_keys(&self) -> Vec<MetadataKey> {
+        vec![]
+    }
+
     /// Generate a helper crate `output` from the given `idl` and `metadata`
     /// 
     /// # Parameters
@@ -54,22 +73,57 @@ where
     let Some(arg1) = args.next() else {
         anyhow::bail!("expected to give given an argument");
     };
+    if arg1 == protocol::INFO_FLAG {
+        let info = PluginInfo {
+            name: helper.name(),
+            version: helper.version(),
+            language: helper.language(),
+            metadata_keys: helper.metadata_keys(),
+        };
+        println!("{}", serde_json::to_string(&info)?);
+        return Ok(());
+    }
     if arg1 != format!("gg-{}", helper.name()) {
         anyhow::bail!("expected to be invoked by `cargo gluegun`");
     }
 
     // Parse the input from stdin
     let stdin = std::io::stdin();
-    let input: GlueGunInput<G::Metadata> = serde_json::from_reader(stdin.lock())?;
+    let input: serde_json::Value = serde_json::from_reader(stdin.lock())?;
+
+    // Announce our protocol version before checking theirs, so that cargo-gluegun can report a mismatch either way.
+    println!("{}", protocol::handshake(PROTOCOL_VERSION));
+    let protocol_version = input.get("protocol_version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if protocol_version != u64::from(PROTOCOL_VERSION) {
+        anyhow::bail!(
+            "cargo-gluegun uses plugin protocol version {protocol_version}, \
+            but gluegun-{name} uses version {PROTOCOL_VERSION}",
+            name = helper.name(),
+        );
+    }
+    let plugin_args: Vec<String> = args.collect();
+    let settings_hash = settings_hash(&helper, &input, &plugin_args)?;
+    let input: PluginInput<G::Metadata> = serde_json::from_value(input)?;
 
     // Create `output` and add user lib as a dependency
-    let mut output = LibraryCrate::from_args(&input.dest_crate);
+    let mut output = LibraryCrate::from_args(&input.dest_crate, settings_hash, &input.idl, &helper.name())?;
     if G::INCLUDE_USER_LIB_DEPENDENCY {
         output.add_dependency(input.idl.crate_name().text()).path(input.idl.crate_path());
     }
 
     // Invoke the user's code
-    helper.generate(&mut GenerateCx { idl: input.idl }, &input.metadata, &mut output)?;
+    let mut cx = GenerateCx {
+        idl: input.idl,
+        dependencies: input.dependencies,
+        args: plugin_args,
+    };
+    if let Err(err) = helper.generate(&mut cx, &input.metadata, &mut output) {
+        // Report the unsupported constructs to cargo-gluegun, which prints them along with those of other plugins.
+        for error in unsupported_errors(&err) {
+            println!("{}", protocol::unsupported_line(error));
+        }
+        return Err(err);
+    }
 
     Ok(output.generate().with_context(|| {
         format!(
@@ -80,13 +134,67 @@ where
     })?)
 }
 
-/// These are the subcommands executed by our system.
-/// Your extension should be able to respond to them.
-#[derive(Deserialize)]
-struct GlueGunInput<M> {
-    idl: Idl,
-    metadata: M,
-    dest_crate: GlueGunDestinationCrate,
+/// The constructs that a plugin does not support, reported all at once by [`for_each_supported`][].
+#[derive(thiserror::Error, Debug)]
+#[error("{count} item{s} could not be bound", count = .0.len(), s = if .0.len() == 1 { "" } else { "s" })]
+pub struct UnsupportedErrors(pub Vec<UnsupportedError>);
+
+/// Call `generate` for each of `items`, carrying on past the items it fails to generate with an [`UnsupportedError`][]
+/// so that every unsupported construct is reported, not just the first; they are returned together as [`UnsupportedErrors`][].
+/// Any other error is returned right away.
+pub fn for_each_supported<T>(
+    items: impl IntoIterator<Item = T>,
+    mut generate: impl FnMut(T) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut unsupported = vec![];
+    for item in items {
+        if let Err(err) = generate(item) {
+            let errors = unsupported_errors(&err);
+            if errors.is_empty() {
+                return Err(err);
+            }
+            unsupported.extend(errors.into_iter().cloned());
+        }
+    }
+
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(UnsupportedErrors(unsupported).into())
+    }
+}
+
+/// The unsupported constructs that caused `err`, if any.
+fn unsupported_errors(err: &anyhow::Error) -> Vec<&UnsupportedError> {
+    for cause in err.chain() {
+        if let Some(error) = cause.downcast_ref::<UnsupportedError>() {
+            return vec![error];
+        }
+        if let Some(UnsupportedErrors(errors)) = cause.downcast_ref::<UnsupportedErrors>() {
+            return errors.iter().collect();
+        }
+    }
+    vec![]
+}
+
+/// Hash everything in `input` apart from the IDL, along with the helper itself and the arguments it was given,
+/// since a change to any of them may affect every generated file.
+/// The helper executable's modification time stands in for its version, so rebuilding it forces a full regeneration.
+fn settings_hash(helper: &impl GlueGunHelper, input: &serde_json::Value, args: &[String]) -> anyhow::Result<String> {
+    let mut settings = input.clone();
+    if let Some(settings) = settings.as_object_mut() {
+        settings.remove("idl");
+    }
+
+    let executable_modified = std::env::current_exe()
+        .and_then(std::fs::metadata)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    Ok(incremental::stable_hash(format!(
+        "{}\n{executable_modified:?}\n{args:?}\n{settings}",
+        helper.name()
+    )))
 }
 
 /// Context provided to the [`GlueGunHelper::generate`][] implementation.
@@ -95,16 +203,93 @@ struct GlueGunInput<M> {
 pub struct GenerateCx {
     /// The IDL from the source crate
     idl: Idl,
+
+    /// The direct dependencies of the source crate, if requested with the `gluegun.include-dependencies` setting.
+    /// These come from cargo's resolved dependency graph, so they give the versions
+    /// actually selected (as in `Cargo.lock`), not the requirements written in `Cargo.toml`.
+    dependencies: Option<Vec<SourceDependency>>,
+
+    /// The arguments given to `cargo gluegun` after `--` (e.g., `["--trace"]` for `cargo gluegun java -- --trace`),
+    /// for one-off options that are not worth a metadata setting. Helpers may ignore those they do not recognize, or fail.
+    args: Vec<String>,
 }
 
-/// The arguments that identify where the crate should be generated.
-/// You don't normally need to inspect the fields of this struct,
-/// instead just invoke [`LibraryCrate::from_args`](`crate::codegen::LibraryCrate::from_args`).
-#[derive(Deserialize, Debug)]
-pub(crate) struct GlueGunDestinationCrate {
-    /// Path at which to create the crate
-    pub path: PathBuf,
+impl GenerateCx {
+    /// Fails with an [`UnsupportedError`][] for each item of the IDL that has generic type parameters (or whose methods do),
+    /// for helpers that cannot map them to `language`.
+    pub fn reject_generics(&self, language: &str) -> anyhow::Result<()> {
+        for_each_supported(self.idl.definitions(), |(qname, item)| {
+            if item.is_generic() {
+                anyhow::bail!(UnsupportedError::new(
+                    qname.colon_colon(),
+                    format!("generic type parameters cannot be mapped to {language}"),
+                ));
+            }
+            Ok(())
+        })
+    }
+
+    /// Fails with an [`UnsupportedError`][] for each record or variant of the IDL whose values contain values of the same type
+    /// (see [`Idl::is_recursive`][]) and are passed to or returned from a function or method (see [`Idl::passed_types`][]),
+    /// for helpers that copy values across the boundary and so cannot map such types to `language`.
+    ///
+    /// The other recursive types are never copied, so they are left out of the IDL instead,
+    /// along with the types that refer to them.
+    pub fn reject_recursive_types(&mut self, language: &str) -> anyhow::Result<()> {
+        let passed = self.idl.passed_types();
+        let (rejected, mut left_out): (BTreeSet<_>, BTreeSet<_>) =
+            self.idl.recursive_types().iter().cloned().partition(|qname| passed.contains(qname));
+
+        for_each_supported(&rejected, |qname| {
+            anyhow::bail!(UnsupportedError::new(
+                qname.colon_colon(),
+                format!("types that contain themselves cannot be passed to or from {language} yet"),
+            ))
+        })?;
+
+        // Items referring to a left out type cannot be passed either (or it would be), and their glue would need it.
+        while let Some(qname) = self.idl.definitions().iter().find_map(|(qname, item)| {
+            (!left_out.contains(qname) && item.user_types().iter().any(|ty| left_out.contains(ty))).then(|| qname.clone())
+        }) {
+            left_out.insert(qname);
+        }
+        self.idl.select(|qname| !left_out.contains(qname));
+        Ok(())
+    }
+
+    /// Fails with an [`UnsupportedError`][] for each function or method of the IDL returning a resource in an `Rc` or `Arc`
+    /// (e.g., `-> Arc<Connection>`), for helpers that give `language` the resource itself:
+    /// the resource would have to be cloned out of the pointer if it is shared, and resources need not be `Clone`.
+    pub fn reject_shared_resources(&self, language: &str) -> anyhow::Result<()> {
+        for_each_supported(self.idl.definitions(), |(qname, item)| {
+            let signatures: Vec<(String, &Signature)> = match item {
+                Item::Function(f) => vec![(qname.colon_colon(), f.signature())],
+                Item::Resource(r) => method_signatures(qname, r.methods()),
+                Item::Record(r) => method_signatures(qname, r.methods()),
+                Item::Variant(v) => method_signatures(qname, v.methods()),
+                Item::Enum(e) => method_signatures(qname, e.methods()),
+                _ => vec![],
+            };
+            for_each_supported(signatures, |(subject, signature)| {
+                let main_ty = signature.output_ty().main_ty();
+                let shared = matches!(main_ty.owned_kind(), Some(OwnedKind::Rc(_) | OwnedKind::Arc(_)));
+                let resource = matches!(
+                    main_ty.ty().kind(),
+                    TypeKind::UserType { qname } if matches!(self.idl.definitions().get(qname), Some(Item::Resource(_)))
+                );
+                if shared && resource {
+                    anyhow::bail!(UnsupportedError::new(
+                        subject,
+                        format!("a resource in an `Rc` or `Arc` cannot be returned to {language}, as it may have to be cloned"),
+                    ));
+                }
+                Ok(())
+            })
+        })
+    }
+}
 
-    /// Name to give the crate; if `None`, then just let `cargo` pick a name.
-    pub crate_name: String,
+/// The name and signature of each of `methods` of the type `qname`.
+fn method_signatures<'m>(qname: &QualifiedName, methods: &'m [Method]) -> Vec<(String, &'m Signature)> {
+    methods.iter().map(|method| (format!("{}::{}", qname.colon_colon(), method.name()), method.signature())).collect()
 }
@@ -4,6 +4,11 @@ pub use code_writer::CodeWriter;
 mod library_crate;
 pub use library_crate::*;
 
+pub(crate) mod incremental;
+
+mod workspace_lock;
+pub use workspace_lock::WorkspaceLock;
+
 mod helper_command;
 pub use helper_command::*;
 
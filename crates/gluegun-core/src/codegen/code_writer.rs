@@ -18,13 +18,19 @@ use std::io::Write;
 ///     this will be indented
 /// }
 /// ```
+///
+/// Lines ending in an open brace, paren, or bracket indent the lines that follow,
+/// and lines starting with the matching close undo it.
+/// For other structure, [`push_indent`](`Self::push_indent`) and [`pop_indent`](`Self::pop_indent`)
+/// adjust the indentation explicitly.
+/// Every line of a multi-line string is indented.
 pub struct CodeWriter<'w> {
     writer: Box<dyn Write + 'w>,
     indent: usize,
 }
 
 impl<'w> CodeWriter<'w> {
-    pub(crate) fn new(writer: impl Write + 'w) -> Self {
+    pub fn new(writer: impl Write + 'w) -> Self {
         Self {
             writer: Box::new(writer),
             indent: 0,
@@ -39,13 +45,13 @@ impl<'w> CodeWriter<'w> {
             self.indent -= 1;
         }
 
-        write!(
-            self.writer,
-            "{:indent$}{}\n",
-            "",
-            string,
-            indent = self.indent * 4
-        )?;
+        for line in string.split('\n') {
+            if line.is_empty() {
+                writeln!(self.writer)?;
+            } else {
+                writeln!(self.writer, "{:indent$}{}", "", line, indent = self.indent * 4)?;
+            }
+        }
 
         if string.ends_with("{") || string.ends_with("(") || string.ends_with("[") {
             self.indent += 1;
@@ -53,4 +59,14 @@ impl<'w> CodeWriter<'w> {
 
         Ok(())
     }
+
+    /// Indent the lines written after this by one more level.
+    pub fn push_indent(&mut self) {
+        self.indent += 1;
+    }
+
+    /// Undo a previous [`push_indent`](`Self::push_indent`).
+    pub fn pop_indent(&mut self) {
+        self.indent -= 1;
+    }
 }
@@ -0,0 +1,46 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// Name of the file, at the root of a generated crate, recording what it was generated from.
+pub(crate) const STATE_FILE_NAME: &str = ".gluegun-state.json";
+
+/// What a generated crate was generated from, persisted as [`STATE_FILE_NAME`][]
+/// so that the next run can tell which files are out of date.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct GenerationState {
+    /// Hash of every input other than the IDL (plugin, metadata, crate settings, dependencies).
+    /// If this changes, everything is regenerated from scratch.
+    pub(crate) settings_hash: String,
+
+    /// Every file written, relative to the root of the crate, with the hash of its contents.
+    pub(crate) files: BTreeMap<PathBuf, String>,
+}
+
+impl GenerationState {
+    /// Load the state recorded in the crate at `crate_path`, if any.
+    /// A missing or unreadable state file is treated as absent, forcing a full regeneration.
+    pub(crate) fn load(crate_path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(crate_path.join(STATE_FILE_NAME)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub(crate) fn save(&self, crate_path: &Path) -> anyhow::Result<()> {
+        let path = crate_path.join(STATE_FILE_NAME);
+        std::fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("writing to file at `{}`", path.display()))
+    }
+}
+
+/// A hash of `data` that, unlike `std`'s `DefaultHasher`, is the same across Rust releases,
+/// so that it can be persisted (64-bit FNV-1a).
+pub(crate) fn stable_hash(data: impl AsRef<[u8]>) -> String {
+    let hash = data.as_ref().iter().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
@@ -1,15 +1,31 @@
-use super::{CodeWriter, HelperCommand, HelperCommandGuard};
-use crate::cli::GlueGunDestinationCrate;
+use super::{
+    incremental::{self, GenerationState},
+    CodeWriter, HelperCommand, HelperCommandGuard, WorkspaceLock,
+};
+use crate::{
+    casing::Casing,
+    idl::{Idl, QualifiedName},
+    protocol::{GeneratedMarker, GlueGunDestinationCrate},
+};
 use accessors_rs::Accessors;
 use anyhow::Context;
 use serde::Deserialize;
 use std::{
     collections::BTreeMap,
-    path::{Path, PathBuf},
-    process::Command,
+    path::{Component, Path, PathBuf},
+    io::Write,
+    process::{Command, Stdio},
 };
 
 /// Type to create a GlueGun adapter crate.
+///
+/// The crate's `Cargo.toml` is scaffolded by [`Self::generate`][]: it is created by `cargo new`
+/// (see [`Self::set_cargo_new_command`][]) with the `crate_name`, `edition`, and `rust-version` given by `cargo gluegun`,
+/// and the dependencies added with [`Self::add_dependency`][] are then added with `cargo add`.
+/// Plugins therefore never write `Cargo.toml` themselves.
+///
+/// With the `gluegun.merge-into` setting, the files are instead generated into a subdirectory of an existing crate
+/// (see [`Self::merge`][]), and nothing else of that crate is touched.
 #[derive(Accessors)]
 pub struct LibraryCrate {
     /// The Rust name of the crate being generated (may include e.g., `-`)
@@ -20,6 +36,35 @@ pub struct LibraryCrate {
     #[accessors(get)]
     crate_path: PathBuf,
 
+    /// If true, `crate_path` is a subdirectory of an existing crate named `crate_name`, which is not scaffolded:
+    /// the files added are written under `crate_path`, but the dependencies and the `[lib]` configuration
+    /// are not added to the crate's `Cargo.toml`, which is left for its authors to maintain.
+    #[accessors(get)]
+    merge: bool,
+
+    /// If true, plugins should emit each top-level item into its own module
+    /// with [`Self::add_item_module`][] rather than putting everything in `src/lib.rs`.
+    #[accessors(get)]
+    split_modules: bool,
+
+    /// The `edition` of the generated crate.
+    #[accessors(get)]
+    edition: String,
+
+    /// The `rust-version` of the generated crate, if any.
+    #[accessors(get)]
+    rust_version: Option<String>,
+
+    /// If true, the `.rs` files are formatted with `rustfmt` as they are written, see [`find_rustfmt`][].
+    format: bool,
+
+    /// The `rustfmt` executable formatting the `.rs` files, found by [`Self::generate`][] if [`Self::format`][] is set.
+    rustfmt: Option<PathBuf>,
+
+    /// The crate whose interface is bound and the plugin binding it, recorded in the [`GeneratedMarker`][].
+    source_crate: String,
+    plugin: String,
+
     lib_configuration: TargetConfiguration,
 
     helper_commands: BTreeMap<String, HelperCommand>,
@@ -27,6 +72,10 @@ pub struct LibraryCrate {
     dependencies: Vec<Dependency>,
     directories: Vec<PathBuf>,
     files: BTreeMap<PathBuf, Vec<u8>>,
+    item_modules: Vec<String>,
+
+    /// Hash of the inputs other than the IDL, see [`GenerationState::settings_hash`][].
+    settings_hash: String,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -34,15 +83,40 @@ pub enum CrateType {
     CDyLib,
 }
 
+impl CrateType {
+    /// The name cargo uses for this crate type in `crate-type`.
+    fn cargo_name(self) -> &'static str {
+        match self {
+            CrateType::CDyLib => "cdylib",
+        }
+    }
+}
+
 impl LibraryCrate {
-    /// Create an instance from a [`GlueGunDestinationCrate`][].
+    /// Create an instance from a [`GlueGunDestinationCrate`][], for the bindings of `idl` generated by `plugin`.
+    /// `settings_hash` covers every input apart from `idl`, whose bindings are compared file by file instead.
     /// This has no immediate effect.
     /// You can use the various methods on this returned value to configure files that should be present.
-    /// Once everything is ready, you can invoke [`Self::execute`][] to make changes on disk.
-    pub(crate) fn from_args(args: &GlueGunDestinationCrate) -> Self {
-        Self {
+    /// Once everything is ready, you can invoke [`Self::generate`][] to make changes on disk.
+    ///
+    /// Plugins do not normally call this: [`run`](`crate::cli::run`) creates the crate and passes it to them.
+    pub fn from_args(
+        args: &GlueGunDestinationCrate,
+        settings_hash: String,
+        idl: &Idl,
+        plugin: &str,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
             crate_name: args.crate_name.clone(),
             crate_path: args.path.clone(),
+            merge: args.merge,
+            split_modules: args.split_modules,
+            edition: args.edition.clone().unwrap_or_else(|| "2021".to_string()),
+            rust_version: args.rust_version.clone(),
+            format: !args.no_format,
+            rustfmt: None,
+            source_crate: idl.crate_name().text().to_string(),
+            plugin: plugin.to_string(),
             helper_commands: BTreeMap::default(),
             cargo_new_command: Box::new(|this| {
                 let mut cargo_command = std::process::Command::new("cargo");
@@ -61,7 +135,9 @@ impl LibraryCrate {
             directories: Default::default(),
             files: Default::default(),
             dependencies: Default::default(),
-        }
+            item_modules: Default::default(),
+            settings_hash,
+        })
     }
 
     /// Configure the command we use to create the new path.
@@ -85,20 +161,155 @@ impl LibraryCrate {
     }
 
     /// Generate the crate on disk. May fail.
+    ///
+    /// If the crate was previously generated with the same settings, it is updated in place:
+    /// files are only rewritten if their contents changed since they were last generated.
+    /// Otherwise, the crate is deleted and generated from scratch
+    /// (or, for a [merged](`Self::merge`) crate, only the subdirectory it is generated into).
     pub fn generate(mut self) -> anyhow::Result<()> {
-        // FIXME: we shouldn't just delete the old thing
-        if self.crate_path.exists() {
-            std::fs::remove_dir_all(&self.crate_path)
-                .with_context(|| format!("removing {}", self.crate_path.display()))?;
+        if self.format {
+            self.rustfmt = find_rustfmt();
+        }
+
+        let settings_hash = self.full_settings_hash();
+        let previous_state = GenerationState::load(&self.crate_path)
+            .filter(|state| state.settings_hash == settings_hash);
+
+        match previous_state {
+            Some(previous_state) => self
+                .update(&previous_state)
+                .with_context(|| format!("updating crate at path {}", self.crate_path.display()))?,
+            None => {
+                if self.crate_path.exists() {
+                    std::fs::remove_dir_all(&self.crate_path)
+                        .with_context(|| format!("removing {}", self.crate_path.display()))?;
+                }
+
+                self.execute().with_context(|| {
+                    format!("generating crate at path {}", self.crate_path.display())
+                })?
+            }
+        }
+
+        GenerationState {
+            settings_hash,
+            files: self
+                .files
+                .iter()
+                .map(|(path, data)| (path.clone(), incremental::stable_hash(data)))
+                .collect(),
+        }
+        .save(&self.crate_path)
+    }
+
+    /// Extend the settings hash given by `cargo gluegun` with the configuration made by the plugin itself,
+    /// which may depend on the IDL as a whole (e.g., a dependency only needed by some items).
+    fn full_settings_hash(&self) -> String {
+        incremental::stable_hash(format!(
+            "{}\n{:?}\n{:?}\n{:?}",
+            self.settings_hash, self.lib_configuration, self.dependencies, self.directories,
+        ))
+    }
+
+    /// Internal method to update a crate previously generated with the same settings, recorded in `previous_state`.
+    fn update(&mut self, previous_state: &GenerationState) -> anyhow::Result<()> {
+        self.write_generated_marker()?;
+
+        for directory in &self.directories {
+            let crate_directory = self.crate_path.join(directory);
+            std::fs::create_dir_all(&crate_directory).with_context(|| {
+                format!("creating directory at `{}`", crate_directory.display())
+            })?;
+        }
+
+        self.declare_item_modules()?;
+
+        for (path, data) in &self.files {
+            // A file is left alone if the plugin generated the same contents last time.
+            let unchanged = previous_state.files.get(path) == Some(&incremental::stable_hash(data));
+            if unchanged && self.crate_path.join(path).exists() {
+                continue;
+            }
+            self.write_file(path, data)?;
+        }
+
+        // Remove files generated last time but not this time, e.g. the module of a deleted item.
+        for path in previous_state.files.keys() {
+            let file_path = self.crate_path.join(path);
+            if !self.files.contains_key(path) && file_path.exists() {
+                eprintln!("removing {file_path:?}");
+                std::fs::remove_file(&file_path)
+                    .with_context(|| format!("removing file at `{}`", file_path.display()))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the [`GENERATED_MARKER`](`crate::protocol::GENERATED_MARKER`) file,
+    /// by which `cargo gluegun` knows that it may overwrite the crate.
+    fn write_generated_marker(&self) -> anyhow::Result<()> {
+        GeneratedMarker::new(&self.source_crate, &self.plugin).write(&self.crate_path)
+    }
+
+    /// Write `data` to the file at `path`, formatting it first if it is Rust code (see [`Self::format`][]).
+    /// Only the files about to be written are formatted, so that updating a crate does not run `rustfmt` on the others.
+    fn write_file(&self, path: &Path, data: &[u8]) -> anyhow::Result<()> {
+        let file_path = self.crate_path.join(path);
+        eprintln!("writing to {file_path:?}");
+
+        let formatted;
+        let data = match &self.rustfmt {
+            Some(rustfmt) if path.extension().is_some_and(|extension| extension == "rs") => {
+                formatted = rustfmt_source(rustfmt, &self.edition, data)
+                    .with_context(|| format!("formatting `{}` with rustfmt", file_path.display()))?;
+                &formatted[..]
+            }
+            _ => data,
+        };
+
+        if let Some(dir_path) = file_path.parent() {
+            std::fs::create_dir_all(dir_path)
+                .with_context(|| format!("creating directory at `{}`", dir_path.display()))?;
         }
 
-        self.execute()
-            .with_context(|| format!("generating crate at path {}", self.crate_path.display()))
+        std::fs::write(&file_path, data)
+            .with_context(|| format!("writing to file at `{}`", file_path.display()))
     }
 
     /// Internal method to generate code.
     fn execute(&mut self) -> anyhow::Result<()> {
-        self.ensure_workspace()?;
+        if self.merge {
+            self.create_subdirectory()?;
+        } else {
+            self.create_crate()?;
+        }
+
+        for directory in &self.directories {
+            let crate_directory = self.crate_path.join(directory);
+            eprintln!("creating {crate_directory:?}");
+            std::fs::create_dir_all(&crate_directory).with_context(|| {
+                format!("creating directory at `{}`", crate_directory.display())
+            })?;
+        }
+
+        self.declare_item_modules()?;
+
+        for (path, data) in &self.files {
+            self.write_file(path, data)?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the crate with `cargo new` and configure its `Cargo.toml`.
+    ///
+    /// The workspace is locked throughout (see [`WorkspaceLock`][]), as `cargo new` adds the crate to its members
+    /// and `cargo add` updates its `Cargo.lock`.
+    fn create_crate(&mut self) -> anyhow::Result<()> {
+        let workspace_path = self.locate_workspace()?;
+        let _lock = WorkspaceLock::acquire(&workspace_path)?;
+        self.ensure_workspace(&workspace_path)?;
 
         self.install_helper_commands()?;
 
@@ -111,36 +322,93 @@ impl LibraryCrate {
             );
         }
 
+        // Marked right away, so that `cargo gluegun` still lets the crate be regenerated if a later step fails.
+        self.write_generated_marker()?;
+
         let cargo_toml_path = self.crate_path.join("Cargo.toml");
+        self.emit_package_settings(&cargo_toml_path)?;
         self.lib_configuration
             .emit_target(&cargo_toml_path, "[lib]")?;
 
         for dependency in &self.dependencies {
             eprintln!("adding {dependency:?}");
-            dependency.execute_cargo_add(&self.crate_name)?;
+            dependency.execute_cargo_add(&cargo_toml_path)?;
         }
 
-        for directory in &self.directories {
-            let crate_directory = self.crate_path.join(directory);
-            eprintln!("creating {crate_directory:?}");
-            std::fs::create_dir_all(&crate_directory).with_context(|| {
-                format!("creating directory at `{}`", crate_directory.display())
-            })?;
+        Ok(())
+    }
+
+    /// Create the subdirectory of the existing crate that a [merged](`Self::merge`) crate is generated into.
+    /// The crate's `Cargo.toml` is not ours to edit, so the dependencies the generated code needs are only listed.
+    fn create_subdirectory(&mut self) -> anyhow::Result<()> {
+        self.install_helper_commands()?;
+
+        std::fs::create_dir_all(&self.crate_path)
+            .with_context(|| format!("creating directory at `{}`", self.crate_path.display()))?;
+        self.write_generated_marker()?;
+
+        for dependency in &self.dependencies {
+            eprintln!(
+                "note: `{crate_name}` is not modified; the generated code needs the dependency `{dependency}`",
+                crate_name = self.crate_name,
+                dependency = dependency.crate_name,
+            );
         }
 
-        for (path, data) in &self.files {
-            let file_path = self.crate_path.join(path);
-            eprintln!("writing to {file_path:?}");
+        Ok(())
+    }
+
+    /// Set `edition` and `rust-version` in the `[package]` section of the `Cargo.toml` created by `cargo new`,
+    /// replacing whatever edition `cargo new` picked by default.
+    fn emit_package_settings(&self, cargo_toml_path: &Path) -> anyhow::Result<()> {
+        let cargo_toml_text = std::fs::read_to_string(cargo_toml_path)
+            .with_context(|| format!("failed to read `{}`", cargo_toml_path.display()))?;
+
+        let mut lines: Vec<String> = cargo_toml_text
+            .lines()
+            .filter(|line| !line.starts_with("rust-version ="))
+            .map(|line| line.to_string())
+            .collect();
 
-            if let Some(dir_path) = file_path.parent() {
-                std::fs::create_dir_all(dir_path)
-                    .with_context(|| format!("creating directory at `{}`", dir_path.display()))?;
+        let Some(package_index) = lines.iter().position(|line| line.trim() == "[package]") else {
+            anyhow::bail!("no `[package]` section in `{}`", cargo_toml_path.display());
+        };
+
+        let mut settings = vec![format!("edition = {:?}", self.edition)];
+        if let Some(rust_version) = &self.rust_version {
+            settings.push(format!("rust-version = {rust_version:?}"));
+        }
+
+        match lines.iter().position(|line| line.starts_with("edition =")) {
+            Some(edition_index) => {
+                lines.splice(edition_index..=edition_index, settings);
             }
+            None => {
+                lines.splice(package_index + 1..package_index + 1, settings);
+            }
+        }
 
-            std::fs::write(&file_path, data)
-                .with_context(|| format!("writing to file at `{}`", file_path.display()))?;
+        std::fs::write(cargo_toml_path, lines.join("\n") + "\n")
+            .with_context(|| format!("failed to write `{}`", cargo_toml_path.display()))?;
+        Ok(())
+    }
+
+    /// Append a `mod` declaration for each module added with [`Self::add_item_module`][] to `src/lib.rs`.
+    fn declare_item_modules(&mut self) -> anyhow::Result<()> {
+        if self.item_modules.is_empty() {
+            return Ok(());
         }
 
+        let Some(lib_rs) = self.files.get_mut(Path::new("src/lib.rs")) else {
+            anyhow::bail!("item modules were added but no `src/lib.rs` was generated");
+        };
+
+        let mut lib_rs = CodeWriter::new(lib_rs);
+        write!(lib_rs, "mod items {{")?;
+        for module_name in &self.item_modules {
+            write!(lib_rs, "mod {module_name};")?;
+        }
+        write!(lib_rs, "}}")?;
         Ok(())
     }
 
@@ -151,19 +419,17 @@ impl LibraryCrate {
         Ok(())
     }
 
-    /// Identifies the surrounding cargo.toml and ensures that it is setup to act as a workspace.
+    /// Ensures that the surrounding cargo.toml, at `workspace_path` (see [`Self::locate_workspace`][]), is setup to act as a workspace.
     /// This is required for `cargo add` to act properly later on.
-    fn ensure_workspace(&self) -> anyhow::Result<()> {
-        let workspace_path = self.locate_workspace()?;
-
+    fn ensure_workspace(&self, workspace_path: &Path) -> anyhow::Result<()> {
         // Read the contents of the workspace cargo.toml
-        let contents = std::fs::read_to_string(&workspace_path)
+        let contents = std::fs::read_to_string(workspace_path)
             .context("failed to read workspace cargo.toml")?;
 
         // Check if [workspace] section exists
         if !contents.contains("[workspace]") {
             // Append [workspace] section if it doesn't exist
-            std::fs::write(&workspace_path, format!("{contents}\n\n[workspace]\n"))
+            std::fs::write(workspace_path, format!("{contents}\n\n[workspace]\n"))
                 .context("failed to update workspace cargo.toml")?;
         }
 
@@ -220,9 +486,9 @@ impl LibraryCrate {
     ///
     /// # Parameters
     ///
-    /// * `path`, path for source file relative to the root of crate
+    /// * `path`, path for the directory relative to the root of crate, which may not contain `..`
     pub fn add_dir(&mut self, path: impl AsRef<Path>) -> anyhow::Result<DirBuilder<'_>> {
-        let dir_path = path.as_ref().to_path_buf();
+        let dir_path = crate_relative_path(path.as_ref())?;
         self.directories.push(dir_path.clone());
         Ok(DirBuilder {
             dir_path,
@@ -231,25 +497,75 @@ impl LibraryCrate {
     }
 
     /// Return a [`CodeWriter`][] for the contents of a file in the crate.
+    /// The file may be in a subdirectory (e.g., `src/models/mod.rs`), which is created as needed.
     ///
     /// No changes on disk occur until [`Self::generate`][] is called.
     ///
     /// # Parameters
     ///
-    /// * `path`, path for source file relative to the root of crate
+    /// * `path`, path for source file relative to the root of crate, which may not contain `..`
     pub fn add_file(&mut self, path: impl AsRef<Path>) -> anyhow::Result<CodeWriter<'_>> {
-        let path = path.as_ref();
+        let path = crate_relative_path(path.as_ref())?;
 
-        if self.files.contains_key(path) {
+        if self.files.contains_key(&path) {
             anyhow::bail!("duplicate path: `{}`", path.display());
         }
 
         Ok(CodeWriter::new(LibraryFileWriter {
             krate: self,
-            path: path.to_path_buf(),
+            path,
             contents: Default::default(),
         }))
     }
+
+    /// Return a [`CodeWriter`][] for the module containing the top-level item `qname`,
+    /// stored at `src/items/<module_name>.rs` where the module name is `qname` in snake case joined by `_`.
+    /// A matching `mod` declaration is appended to `src/lib.rs` when the crate is generated.
+    ///
+    /// When the crate is regenerated, the module is only rewritten if its contents changed.
+    ///
+    /// No changes on disk occur until [`Self::generate`][] is called.
+    ///
+    /// # Parameters
+    ///
+    /// * `qname`, name of the item
+    pub fn add_item_module(&mut self, qname: &QualifiedName) -> anyhow::Result<CodeWriter<'_>> {
+        let module_name = item_module_name(qname);
+        let path = item_module_path(&module_name);
+        if self.files.contains_key(&path) {
+            anyhow::bail!("duplicate item module: `{module_name}`");
+        }
+        self.item_modules.push(module_name);
+        self.add_file(path)
+    }
+}
+
+/// `path` without its `.` components, if it is a relative path that stays within the crate,
+/// so that plugins cannot write outside of the crate (or name the same file in two ways).
+fn crate_relative_path(path: &Path) -> anyhow::Result<PathBuf> {
+    let mut relative_path = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => relative_path.push(name),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                anyhow::bail!("path `{}` is not within the generated crate", path.display())
+            }
+        }
+    }
+    if relative_path.as_os_str().is_empty() {
+        anyhow::bail!("path `{}` does not name anything within the generated crate", path.display());
+    }
+    Ok(relative_path)
+}
+
+/// Name of the module for the item `qname`, see [`LibraryCrate::add_item_module`][].
+fn item_module_name(qname: &QualifiedName) -> String {
+    qname.to_snake_case().to_string("_")
+}
+
+fn item_module_path(module_name: &str) -> PathBuf {
+    Path::new("src/items").join(format!("{module_name}.rs"))
 }
 
 #[derive(Debug)]
@@ -295,7 +611,7 @@ impl DirBuilder<'_> {
     ///
     /// # Parameters
     ///
-    /// * `path`, path for source file relative to the root of crate
+    /// * `path`, path for source file relative to this directory, which may not contain `..`
     pub fn add_file(&mut self, path: impl AsRef<Path>) -> anyhow::Result<CodeWriter<'_>> {
         let path = self.dir_path.join(path);
         self.krate.add_file(path)
@@ -346,7 +662,9 @@ enum DependencyKind {
 }
 
 impl Dependency {
-    fn execute_cargo_add(&self, to_crate_name: &str) -> anyhow::Result<()> {
+    /// Run `cargo add` on the manifest at `to_manifest_path`.
+    /// (Not `-p`, since the crate may be excluded from the surrounding workspace.)
+    fn execute_cargo_add(&self, to_manifest_path: &Path) -> anyhow::Result<()> {
         let Self {
             crate_name,
             kind,
@@ -360,8 +678,8 @@ impl Dependency {
         let mut command = std::process::Command::new("cargo");
         command.arg("add");
 
-        command.arg("-p");
-        command.arg(to_crate_name);
+        command.arg("--manifest-path");
+        command.arg(to_manifest_path);
 
         if let Some(path) = &path {
             command.arg("--path").arg(path);
@@ -480,19 +798,56 @@ impl TargetConfiguration {
             "crate-type = [{}]",
             self.crate_types
                 .iter()
-                .map(|c| format!("{c:?}"))
+                .map(|c| format!("{:?}", c.cargo_name()))
                 .collect::<Vec<_>>()
                 .join(", ")
         )?;
-        writeln!(
-            cargo_toml_text,
-            "edition = {:?}",
-            match &self.edition {
-                Some(edition) => edition,
-                None => "2021",
-            }
-        )?;
+        // Without an explicit edition, the target uses the package's.
+        if let Some(edition) = &self.edition {
+            writeln!(cargo_toml_text, "edition = {edition:?}")?;
+        }
+
+        std::fs::write(cargo_toml_path, cargo_toml_text)
+            .with_context(|| format!("failed to write `{}`", cargo_toml_path.display()))?;
 
         Ok(())
     }
 }
+
+/// The `rustfmt` executable, so that the generated crate is readable and diffable.
+/// If it is not installed, the files are left as they are.
+fn find_rustfmt() -> Option<PathBuf> {
+    let rustfmt = which::which("rustfmt").ok();
+    if rustfmt.is_none() {
+        eprintln!("warning: `rustfmt` not found, the generated code is left unformatted");
+    }
+    rustfmt
+}
+
+/// The Rust source `data` formatted by `rustfmt` for `edition`.
+fn rustfmt_source(rustfmt: &Path, edition: &str, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut child = Command::new(rustfmt)
+        .arg("--edition")
+        .arg(edition)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("spawning `{}`", rustfmt.display()))?;
+
+    // The source is written from another thread so that rustfmt cannot block on a full stdout pipe meanwhile.
+    let stdin = child.stdin.take();
+    let (written, output) = std::thread::scope(|scope| {
+        let writer = scope.spawn(|| stdin.map_or(Ok(()), |mut stdin| stdin.write_all(data)));
+        (writer.join(), child.wait_with_output())
+    });
+    let output = output?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim_end());
+    }
+    match written {
+        Ok(result) => result?,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+    Ok(output.stdout)
+}
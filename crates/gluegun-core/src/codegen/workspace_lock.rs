@@ -0,0 +1,37 @@
+use std::{fs::File, path::Path};
+
+use anyhow::Context;
+
+use super::incremental;
+
+/// An exclusive lock on a workspace, held while its `Cargo.toml` is edited (directly or by `cargo new`),
+/// so that concurrent runs of `cargo gluegun --jobs N`, each generating a crate in the same workspace, do not lose each other's edits.
+///
+/// The lock is taken on a file of the temporary directory named after the workspace, rather than on the manifest itself,
+/// since on some platforms locking a file prevents other processes from writing to it.
+/// It is released when dropped.
+pub struct WorkspaceLock {
+    _file: File,
+}
+
+impl WorkspaceLock {
+    /// Lock the workspace whose root manifest is at `manifest_path`, waiting until no other process holds the lock.
+    pub fn acquire(manifest_path: &Path) -> anyhow::Result<Self> {
+        let manifest_path = manifest_path
+            .canonicalize()
+            .with_context(|| format!("locating `{}`", manifest_path.display()))?;
+        let lock_path = std::env::temp_dir().join(format!(
+            "gluegun-workspace-{}.lock",
+            incremental::stable_hash(manifest_path.as_os_str().as_encoded_bytes()),
+        ));
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .with_context(|| format!("opening lock file at `{}`", lock_path.display()))?;
+        file.lock()
+            .with_context(|| format!("locking the workspace of `{}`", manifest_path.display()))?;
+        Ok(Self { _file: file })
+    }
+}
@@ -10,5 +10,11 @@ pub use clap as clap;
 /// Utility structs and things for GlueGun CLIs.
 pub mod cli;
 
+/// The data exchanged between `cargo gluegun` and plugins.
+pub mod protocol;
+
 /// Utility structs for generating "vaguely well formatted" code.
 pub mod codegen;
+
+/// Converting names to the case conventions of target languages.
+pub mod casing;
@@ -0,0 +1,248 @@
+//! The data that `cargo gluegun` gives a plugin on its stdin.
+//! `cargo gluegun` serializes a [`PluginInput`][] and [`run`](`crate::cli::run`) deserializes it,
+//! so the two sides cannot drift apart.
+//!
+//! Once it has read its input, a plugin prints a handshake line on its stdout giving the protocol version it speaks
+//! (see [`handshake`][]), so that `cargo gluegun` can report a plugin built against another version
+//! instead of letting it misread its input.
+//! If it fails because the crate uses constructs it does not support, it then prints one line per construct
+//! (see [`unsupported_line`][]), so that `cargo gluegun` can report them all together.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use accessors_rs::Accessors;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::idl::Idl;
+
+/// Version of the protocol described in this module.
+/// Bump it whenever a change to [`PluginInput`][] would be misread by plugins built against the previous version.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Start of the handshake line, see [`handshake`][].
+const HANDSHAKE_PREFIX: &str = "gluegun-protocol ";
+
+/// The handshake line by which a plugin announces that it speaks protocol `version`, e.g. `gluegun-protocol 1`.
+pub fn handshake(version: u32) -> String {
+    format!("{HANDSHAKE_PREFIX}{version}")
+}
+
+/// The protocol version announced by `line`, if it is a handshake line.
+pub fn parse_handshake(line: &str) -> Option<u32> {
+    line.trim_end().strip_prefix(HANDSHAKE_PREFIX)?.parse().ok()
+}
+
+/// Start of the lines by which a plugin reports the constructs it does not support, see [`unsupported_line`][].
+const UNSUPPORTED_PREFIX: &str = "gluegun-unsupported ";
+
+/// The line by which a plugin reports `error`, e.g. `gluegun-unsupported {"subject":"i128","reason":"..."}`.
+pub fn unsupported_line(error: &UnsupportedError) -> String {
+    format!("{UNSUPPORTED_PREFIX}{}", serde_json::to_string(error).expect("serializing strings cannot fail"))
+}
+
+/// The error reported by `line`, if it is a line given by [`unsupported_line`][].
+pub fn parse_unsupported_line(line: &str) -> Option<UnsupportedError> {
+    serde_json::from_str(line.trim_end().strip_prefix(UNSUPPORTED_PREFIX)?).ok()
+}
+
+/// A construct of the source crate that a plugin does not support (yet), as opposed to a failure of the plugin itself.
+/// Plugins fail with this error for each such item, see [`for_each_supported`](`crate::cli::for_each_supported`).
+#[derive(thiserror::Error, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[error("`{subject}`: {reason}")]
+pub struct UnsupportedError {
+    /// The item or type that is not supported, e.g. `my_crate::Widget` or `i128`.
+    pub subject: String,
+
+    /// Why it is not supported, e.g. `128-bit integers have no Java type`.
+    pub reason: String,
+}
+
+impl UnsupportedError {
+    pub fn new(subject: impl std::fmt::Display, reason: impl std::fmt::Display) -> Self {
+        Self { subject: subject.to_string(), reason: reason.to_string() }
+    }
+}
+
+/// Name of the file, at the root of a crate generated by a plugin, marking it as generated.
+/// `cargo gluegun` lets plugins overwrite an existing crate only if it has this file (or with `--force`),
+/// so that it does not clobber a crate that merely happens to be at the destination path.
+/// It holds a [`GeneratedMarker`][] as JSON.
+pub const GENERATED_MARKER: &str = ".gluegun-generated";
+
+/// What the [`GENERATED_MARKER`][] file of a generated crate records about where the crate came from,
+/// so that tools can tell generated crates apart (e.g., to regenerate or delete them).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedMarker {
+    /// Name of the crate whose interface was bound, e.g., `my_crate`.
+    pub source_crate: String,
+
+    /// Plugin that generated the crate, e.g., `java` for `gluegun-java`.
+    pub plugin: String,
+
+    /// Version of `gluegun-core` the plugin was built with.
+    pub gluegun_version: String,
+
+    /// When the crate was last generated, in seconds since the Unix epoch.
+    pub generated_at: u64,
+}
+
+impl GeneratedMarker {
+    /// A marker for a crate generated now from `source_crate` by `plugin`.
+    pub fn new(source_crate: impl Into<String>, plugin: impl Into<String>) -> Self {
+        Self {
+            source_crate: source_crate.into(),
+            plugin: plugin.into(),
+            gluegun_version: env!("CARGO_PKG_VERSION").to_string(),
+            // A clock set before 1970 is not worth failing generation over.
+            generated_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+        }
+    }
+
+    /// Read the marker of the generated crate at `crate_path`.
+    pub fn read(crate_path: &Path) -> anyhow::Result<Self> {
+        let path = crate_path.join(GENERATED_MARKER);
+        let text = std::fs::read_to_string(&path).with_context(|| format!("reading `{}`", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing `{}`", path.display()))
+    }
+
+    /// Parse the contents of a [`GENERATED_MARKER`][] file.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// Write the marker into the generated crate at `crate_path`.
+    pub fn write(&self, crate_path: &Path) -> anyhow::Result<()> {
+        let path = crate_path.join(GENERATED_MARKER);
+        std::fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("writing to file at `{}`", path.display()))
+    }
+}
+
+/// Argument with which `cargo gluegun` runs a plugin (instead of `gg-<plugin>`)
+/// to have it print its [`PluginInfo`][] as JSON on stdout,
+/// for `list-plugins` and to check the plugin's metadata before running it.
+pub const INFO_FLAG: &str = "--gg-info";
+
+/// What a plugin reports about itself when run with [`INFO_FLAG`][].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PluginInfo {
+    /// Name of the plugin, e.g., `java` for `gluegun-java`.
+    pub name: String,
+
+    /// Version of the plugin, e.g., `0.1.0`.
+    pub version: String,
+
+    /// Language the plugin generates bindings for, e.g., `Java`.
+    pub language: String,
+
+    /// The keys of the `gluegun.<plugin>` metadata the plugin reads, which `cargo gluegun` checks before running it.
+    /// Empty if the plugin does not declare them (in which case its metadata is not checked).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metadata_keys: Vec<MetadataKey>,
+}
+
+/// A key of the `gluegun.<plugin>` metadata, see [`PluginInfo::metadata_keys`][].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MetadataKey {
+    /// The key, e.g., `async-runtime`.
+    pub name: String,
+
+    /// Whether the plugin cannot run without it.
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl MetadataKey {
+    /// A key that must be given.
+    pub fn required(name: impl Into<String>) -> Self {
+        Self { name: name.into(), required: true }
+    }
+
+    /// A key that may be left out.
+    pub fn optional(name: impl Into<String>) -> Self {
+        Self { name: name.into(), required: false }
+    }
+}
+
+/// Everything a plugin is given to generate its crate.
+/// The metadata is free-form JSON when serialized; plugins deserialize it as their own `M`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PluginInput<M = serde_json::Value> {
+    /// The [`PROTOCOL_VERSION`][] of `cargo gluegun`; 0 if it predates protocol versions.
+    #[serde(default)]
+    pub protocol_version: u32,
+
+    /// The IDL extracted from the source crate.
+    pub idl: Idl,
+
+    /// The `gluegun.<plugin>` metadata, merged from the workspace and package `Cargo.toml`.
+    pub metadata: M,
+
+    /// The direct dependencies of the source crate, if requested with the `gluegun.include-dependencies` setting.
+    #[serde(default)]
+    pub dependencies: Option<Vec<SourceDependency>>,
+
+    /// Where and how to generate the crate.
+    pub dest_crate: GlueGunDestinationCrate,
+}
+
+/// A direct dependency of the source crate, see [`GenerateCx::dependencies`](`crate::cli::GenerateCx::dependencies`).
+#[derive(Accessors, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[accessors(get)]
+pub struct SourceDependency {
+    /// Name of the dependency's package, e.g. `serde`.
+    name: String,
+
+    /// Version of the package selected by cargo, e.g. `1.0.217`.
+    version: String,
+
+    /// Where the package comes from, e.g. `registry+https://github.com/rust-lang/crates.io-index`;
+    /// `None` for path dependencies.
+    source: Option<String>,
+
+    /// How the source crate depends on the package: any of `normal`, `dev`, and `build`.
+    kinds: Vec<String>,
+}
+
+impl SourceDependency {
+    pub fn new(name: String, version: String, source: Option<String>, kinds: Vec<String>) -> Self {
+        Self { name, version, source, kinds }
+    }
+}
+
+/// The arguments that identify where the crate should be generated.
+/// You don't normally need to inspect the fields of this struct,
+/// instead just invoke [`LibraryCrate::from_args`](`crate::codegen::LibraryCrate::from_args`).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GlueGunDestinationCrate {
+    /// Path at which to create the crate
+    pub path: PathBuf,
+
+    /// Name to give the crate; if `None`, then just let `cargo` pick a name.
+    pub crate_name: String,
+
+    /// If true, the generated code should be split into one module per item
+    /// (from the `gluegun.split-modules` setting).
+    #[serde(default)]
+    pub split_modules: bool,
+
+    /// Edition for the generated crate; if `None`, defaults to 2021.
+    #[serde(default)]
+    pub edition: Option<String>,
+
+    /// Minimum supported Rust version for the generated crate, if any.
+    #[serde(default)]
+    pub rust_version: Option<String>,
+
+    /// If true, the generated Rust files are left as the plugin wrote them rather than formatted with `rustfmt`
+    /// (from `--no-format`).
+    #[serde(default)]
+    pub no_format: bool,
+
+    /// If true, `path` is a subdirectory of an existing crate named `crate_name` (from the `gluegun.merge-into` setting):
+    /// only the files under `path` are generated, and the crate's own files, including its `Cargo.toml`, are left alone.
+    #[serde(default)]
+    pub merge: bool,
+}
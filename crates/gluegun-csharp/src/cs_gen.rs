@@ -0,0 +1,424 @@
+use std::collections::BTreeMap;
+
+use gluegun_core::{
+    casing::Casing,
+    cli::{for_each_supported, UnsupportedError},
+    codegen::{CodeWriter, DirBuilder},
+    idl::{Constant, Function, Idl, Item, Method, MethodCategory, Name, QualifiedName, Record, Resource, Signature},
+};
+
+use crate::{
+    marshal::{self, InputMarshal, Marshaller},
+    util,
+};
+
+/// Generates the C# sources: a class per resource, a struct per record,
+/// a static `Functions` class per module, and the `GlueGunRuntime` helpers they share.
+pub(crate) struct CSharpCodeGenerator<'idl> {
+    idl: &'idl Idl,
+    marshaller: Marshaller<'idl>,
+
+    /// Name of the native library built from the generated crate, as given to `DllImport`.
+    library: String,
+}
+
+/// The members of the `Functions` class of a module: its constants and free functions.
+#[derive(Default)]
+struct ModuleMembers<'idl> {
+    constants: Vec<&'idl Constant>,
+    functions: Vec<&'idl Function>,
+}
+
+/// How the C# method for a shim function is declared.
+enum CsMethodKind {
+    /// A constructor of the resource class.
+    Constructor,
+
+    /// An instance method; the shim's first parameter is the handle.
+    Instance,
+
+    /// A static method (or a free function).
+    Static,
+}
+
+impl<'idl> CSharpCodeGenerator<'idl> {
+    pub(crate) fn new(idl: &'idl Idl, library: String) -> Self {
+        Self {
+            idl,
+            marshaller: Marshaller::new(idl),
+            library,
+        }
+    }
+
+    pub(crate) fn generate(self, mut dir: DirBuilder<'_>) -> anyhow::Result<()> {
+        let root_namespace = util::root_namespace(self.idl.crate_name());
+        self.generate_project(&mut dir, &root_namespace)?;
+        self.generate_runtime(&mut dir, &root_namespace)?;
+
+        let mut modules: BTreeMap<QualifiedName, ModuleMembers<'idl>> = Default::default();
+        for_each_supported(self.idl.definitions(), |(qname, item)| match item {
+            Item::Resource(resource) => self.generate_resource(&mut dir, qname, resource),
+            Item::Record(record) => self.generate_record(&mut dir, qname, record),
+            Item::Function(function) => {
+                // Collect functons, grouped by module. We will generate them later.
+                modules.entry(qname.module_name()).or_default().functions.push(function);
+                Ok(())
+            }
+            Item::Constant(constant) => {
+                // Constants go in the same class as the functions of their module.
+                modules.entry(qname.module_name()).or_default().constants.push(constant);
+                Ok(())
+            }
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to C# yet")),
+        })?;
+
+        for (module_qname, members) in &modules {
+            self.generate_functions(&mut dir, module_qname, members)?;
+        }
+
+        Ok(())
+    }
+
+    /// Generate a project file so the sources can be built with `dotnet build`.
+    /// Unsafe code is needed to copy buffers returned from Rust.
+    fn generate_project(&self, dir: &mut DirBuilder<'_>, root_namespace: &Name) -> anyhow::Result<()> {
+        let mut file = dir.add_file(format!("{root_namespace}.csproj"))?;
+        write!(file, "<Project Sdk=\"Microsoft.NET.Sdk\">")?;
+        write!(file, "  <PropertyGroup>")?;
+        write!(file, "    <TargetFramework>net8.0</TargetFramework>")?;
+        write!(file, "    <Nullable>enable</Nullable>")?;
+        write!(file, "    <AllowUnsafeBlocks>true</AllowUnsafeBlocks>")?;
+        write!(file, "  </PropertyGroup>")?;
+        write!(file, "</Project>")?;
+        Ok(())
+    }
+
+    fn generate_cs_file(
+        &self,
+        dir: &mut DirBuilder<'_>,
+        cs_type: &str,
+        qname: &QualifiedName,
+        base_types: &[&str],
+        body: impl FnOnce(&mut CodeWriter<'_>) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut file = dir.add_file(util::class_file_name(qname))?;
+        let util::CsQName { namespace, class_name } = util::class_namespace_and_name(qname);
+        self.write_preamble(&mut file, &namespace)?;
+        if base_types.is_empty() {
+            write!(file, "public {cs_type} {class_name} {{")?;
+        } else {
+            write!(file, "public {cs_type} {class_name} : {} {{", base_types.join(", "))?;
+        }
+        body(&mut file)?;
+        write!(file, "}}")?;
+        Ok(())
+    }
+
+    fn write_preamble(&self, file: &mut CodeWriter<'_>, namespace: &str) -> anyhow::Result<()> {
+        write!(file, "#nullable enable")?;
+        write!(file, "")?;
+        write!(file, "using System;")?;
+        write!(file, "using System.Collections.Generic;")?;
+        write!(file, "using System.Linq;")?;
+        write!(file, "using System.Runtime.InteropServices;")?;
+        write!(file, "")?;
+        write!(file, "namespace {namespace};")?;
+        write!(file, "")?;
+        Ok(())
+    }
+
+    /// Generate `GlueGunRuntime.cs`, with the exception thrown for Rust errors
+    /// and the helpers that copy (and then free) strings and buffers returned from Rust.
+    fn generate_runtime(&self, dir: &mut DirBuilder<'_>, root_namespace: &Name) -> anyhow::Result<()> {
+        let crate_name = self.idl.crate_name().to_string();
+        let free_string = util::helper_symbol_name(&crate_name, "free_string");
+        let free_buffer = util::helper_symbol_name(&crate_name, "free_buffer");
+
+        let mut file = dir.add_file("GlueGunRuntime.cs")?;
+        self.write_preamble(&mut file, root_namespace.text())?;
+
+        write!(file, "/// <summary>Thrown when a Rust function returns an error; the message is the error's <c>Display</c> output.</summary>")?;
+        write!(file, "public sealed class GlueGunException : Exception {{")?;
+        write!(file, "public GlueGunException(string message) : base(message) {{")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "internal static class GlueGunRuntime {{")?;
+        write!(file, "/// <summary>The native library built from the generated Rust crate.</summary>")?;
+        write!(file, "internal const string Library = \"{}\";", self.library)?;
+        write!(file, "")?;
+        write!(file, "[DllImport(Library, CallingConvention = CallingConvention.Cdecl)]")?;
+        write!(file, "private static extern void {free_string}(IntPtr ptr);")?;
+        write!(file, "")?;
+        write!(file, "[DllImport(Library, CallingConvention = CallingConvention.Cdecl)]")?;
+        write!(file, "private static extern void {free_buffer}(IntPtr ptr, nuint len, nuint size);")?;
+        write!(file, "")?;
+        write!(file, "/// <summary>Copy a string returned from Rust and free the original; null stays null.</summary>")?;
+        write!(file, "internal static string? TakeString(IntPtr ptr) {{")?;
+        write!(file, "if (ptr == IntPtr.Zero) {{")?;
+        write!(file, "return null;")?;
+        write!(file, "}}")?;
+        write!(file, "try {{")?;
+        write!(file, "return Marshal.PtrToStringUTF8(ptr);")?;
+        write!(file, "}} finally {{")?;
+        write!(file, "{free_string}(ptr);")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "/// <summary>Throw a <see cref=\"GlueGunException\"/> if a Rust function reported an error.</summary>")?;
+        write!(file, "internal static void ThrowIfError(IntPtr error) {{")?;
+        write!(file, "if (error != IntPtr.Zero) {{")?;
+        write!(file, "throw new GlueGunException(TakeString(error)!);")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "/// <summary>Return <paramref name=\"result\"/> unless a Rust function reported an error.</summary>")?;
+        write!(file, "internal static T Check<T>(T result, IntPtr error) {{")?;
+        write!(file, "ThrowIfError(error);")?;
+        write!(file, "return result;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "/// <summary>Copy a buffer returned from Rust into an array and free the original.</summary>")?;
+        write!(file, "internal static unsafe T[] TakeArray<T>(IntPtr ptr, nuint len) where T : unmanaged {{")?;
+        write!(file, "try {{")?;
+        write!(file, "return new ReadOnlySpan<T>((void*)ptr, checked((int)len)).ToArray();")?;
+        write!(file, "}} finally {{")?;
+        write!(file, "{free_buffer}(ptr, len, (nuint)sizeof(T));")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "/// <summary>Copy a buffer returned from Rust into a list and free the original.</summary>")?;
+        write!(file, "internal static List<T> TakeList<T>(IntPtr ptr, nuint len) where T : unmanaged {{")?;
+        write!(file, "return new List<T>(TakeArray<T>(ptr, len));")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "/// <summary>Copy the key and value buffers of a map returned from Rust into a dictionary and free the originals.</summary>")?;
+        write!(file, "internal static Dictionary<K, V> TakeDictionary<K, V>(IntPtr keys, IntPtr values, nuint len) where K : unmanaged where V : unmanaged {{")?;
+        write!(file, "List<K> keyList = TakeList<K>(keys, len);")?;
+        write!(file, "List<V> valueList = TakeList<V>(values, len);")?;
+        write!(file, "var result = new Dictionary<K, V>(keyList.Count);")?;
+        write!(file, "for (int i = 0; i < keyList.Count; i++) {{")?;
+        write!(file, "result.Add(keyList[i], valueList[i]);")?;
+        write!(file, "}}")?;
+        write!(file, "return result;")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        Ok(())
+    }
+
+    /// Generate a class owning the Rust value through a `SafeHandle`,
+    /// which frees it when the object is disposed or finalized.
+    /// Constants become `const` members, with the same value.
+    fn generate_resource(&self, dir: &mut DirBuilder<'_>, qname: &QualifiedName, resource: &Resource) -> anyhow::Result<()> {
+        let class_name = util::class_namespace_and_name(qname).class_name;
+        let free = util::helper_symbol_name(&qname.to_string("__"), "free");
+
+        self.generate_cs_file(dir, "sealed class", qname, &["IDisposable"], |file| {
+            self.generate_constants(file, qname, resource.constants())?;
+            write!(file, "internal sealed class Handle : SafeHandle {{")?;
+            write!(file, "public Handle() : base(IntPtr.Zero, true) {{")?;
+            write!(file, "}}")?;
+            write!(file, "")?;
+            write!(file, "public override bool IsInvalid => handle == IntPtr.Zero;")?;
+            write!(file, "")?;
+            write!(file, "protected override bool ReleaseHandle() {{")?;
+            write!(file, "{free}(handle);")?;
+            write!(file, "return true;")?;
+            write!(file, "}}")?;
+            write!(file, "}}")?;
+            write!(file, "")?;
+            write!(file, "[DllImport(GlueGunRuntime.Library, CallingConvention = CallingConvention.Cdecl)]")?;
+            write!(file, "private static extern void {free}(IntPtr handle);")?;
+            write!(file, "")?;
+            write!(file, "internal readonly Handle handle;")?;
+            write!(file, "")?;
+            write!(file, "internal {class_name}(Handle handle) {{")?;
+            write!(file, "this.handle = handle;")?;
+            write!(file, "}}")?;
+            write!(file, "")?;
+            write!(file, "/// <summary>Give up ownership of the Rust value, which is about to be consumed; this object can no longer be used.</summary>")?;
+            write!(file, "internal IntPtr TakeHandle() {{")?;
+            write!(file, "IntPtr raw = handle.DangerousGetHandle();")?;
+            write!(file, "handle.SetHandleAsInvalid();")?;
+            write!(file, "return raw;")?;
+            write!(file, "}}")?;
+            write!(file, "")?;
+            write!(file, "public void Dispose() {{")?;
+            write!(file, "handle.Dispose();")?;
+            write!(file, "}}")?;
+
+            for method in resource.methods() {
+                write!(file, "")?;
+                self.generate_method(file, qname, &class_name, method)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Generate a struct with the same layout as the `#[repr(C)]` mirror of the record in the shim crate.
+    fn generate_record(&self, dir: &mut DirBuilder<'_>, qname: &QualifiedName, record: &Record) -> anyhow::Result<()> {
+        if !record.is_constructible() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "records with private fields or `#[non_exhaustive]` cannot be mapped to C# yet"));
+        }
+
+        let mut fields = vec![];
+        for field in record.fields() {
+            fields.push((self.marshaller.field(field.ty())?, field.name().to_pascal_case()));
+        }
+
+        let mut file = dir.add_file(util::class_file_name(qname))?;
+        let util::CsQName { namespace, class_name } = util::class_namespace_and_name(qname);
+        self.write_preamble(&mut file, &namespace)?;
+        write!(file, "[StructLayout(LayoutKind.Sequential)]")?;
+        write!(file, "public struct {class_name} {{")?;
+        for (cs_ty, name) in fields {
+            write!(file, "public {cs_ty} {name};")?;
+        }
+        write!(file, "}}")?;
+        Ok(())
+    }
+
+    /// Generate a `const` member for each of the `constants` of `qname` (a resource or a module), with the same value.
+    fn generate_constants<'c>(
+        &self,
+        file: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        constants: impl IntoIterator<Item = &'c Constant>,
+    ) -> anyhow::Result<()> {
+        let mut any = false;
+        for constant in constants {
+            let Some((cs_ty, value)) = util::cs_constant(constant.ty(), constant.value()) else {
+                anyhow::bail!(UnsupportedError::new(
+                    qname.join(constant.rust_name()).colon_colon(),
+                    format!("constants of type `{}` cannot be mapped to C# yet", constant.ty()),
+                ));
+            };
+            write!(file, "public const {cs_ty} {name} = {value};", name = constant.name())?;
+            any = true;
+        }
+        if any {
+            write!(file, "")?;
+        }
+        Ok(())
+    }
+
+    fn generate_functions(&self, dir: &mut DirBuilder<'_>, module_qname: &QualifiedName, members: &ModuleMembers<'idl>) -> anyhow::Result<()> {
+        let functions_class = module_qname.join("Functions");
+        self.generate_cs_file(dir, "static class", &functions_class, &[], |file| {
+            self.generate_constants(file, module_qname, members.constants.iter().copied())?;
+            for (index, function) in members.functions.iter().enumerate() {
+                if index > 0 {
+                    write!(file, "")?;
+                }
+                let symbol = util::symbol_name(module_qname, function.name());
+                self.generate_method_and_extern(file, &symbol, function.name(), CsMethodKind::Static, None, function.signature())?;
+            }
+            Ok(())
+        })
+    }
+
+    fn generate_method(&self, file: &mut CodeWriter<'_>, qname: &QualifiedName, class_name: &Name, method: &Method) -> anyhow::Result<()> {
+        let symbol = util::symbol_name(qname, method.name());
+        let (kind, self_input) = match method.category() {
+            MethodCategory::Constructor => (CsMethodKind::Constructor, None),
+            MethodCategory::StaticMethod => (CsMethodKind::Static, None),
+            MethodCategory::BuilderMethod(self_kind) | MethodCategory::InstanceMethod(self_kind) => {
+                (CsMethodKind::Instance, Some(self.marshaller.self_input(qname, self_kind)?))
+            }
+            category => anyhow::bail!("unsupported method category: {category:?}"),
+        };
+        let name = match kind {
+            CsMethodKind::Constructor => class_name,
+            _ => method.name(),
+        };
+        self.generate_method_and_extern(file, &symbol, name, kind, self_input, method.signature())
+    }
+
+    /// Generate the `DllImport` declaration of the shim function `symbol` and the public method `name` calling it.
+    ///
+    /// The declaration is derived from the same [`Marshaller`][] descriptions as the shim itself,
+    /// so the two signatures match parameter for parameter.
+    fn generate_method_and_extern(
+        &self,
+        file: &mut CodeWriter<'_>,
+        symbol: &str,
+        name: &Name,
+        kind: CsMethodKind,
+        self_input: Option<InputMarshal>,
+        signature: &Signature,
+    ) -> anyhow::Result<()> {
+        let mut inputs = vec![];
+        inputs.extend(self_input);
+        let mut cs_params = vec![];
+        for input in signature.inputs() {
+            let marshal = self.marshaller.input(input.name(), input.refd_ty())?;
+            cs_params.push(format!("{} {}", marshal.cs_ty, util::cs_local_name(input.name())));
+            inputs.push(marshal);
+        }
+        let output = signature.output_ty();
+        let output_marshal = self.marshaller.output(output.main_ty())?;
+        let fallible = output.error_ty().is_some();
+
+        // The `extern` declaration mirroring the shim function.
+        let mut extern_params = vec![];
+        let error_param = marshal::error_param();
+        let abi_params = inputs
+            .iter()
+            .flat_map(|input| &input.abi_params)
+            .chain(&output_marshal.out_params)
+            .chain(fallible.then_some(&error_param));
+        for param in abi_params {
+            extern_params.push(format!("{} {}", param.cs_ty, util::cs_extern_param_name(&param.name)));
+        }
+        write!(file, "[DllImport(GlueGunRuntime.Library, CallingConvention = CallingConvention.Cdecl)]")?;
+        write!(file, "private static extern {} {symbol}({});", output_marshal.cs_ret_ty, extern_params.join(", "))?;
+
+        // The arguments passed to it by the public method.
+        let mut args: Vec<String> = inputs.iter().flat_map(|input| input.cs_args.iter().cloned()).collect();
+        args.extend(output_marshal.cs_out_args.iter().cloned());
+        if fallible {
+            args.push("out IntPtr ggError".to_string());
+        }
+        let call = format!("{symbol}({})", args.join(", "));
+
+        write!(file, "")?;
+        let cs_name = name.to_pascal_case();
+        let cs_params = cs_params.join(", ");
+        match kind {
+            CsMethodKind::Constructor => {
+                // The shim returns the handle of the new value, which the constructor adopts.
+                if output_marshal.cs_ret_ty != format!("{}.Handle", output_marshal.cs_ty) {
+                    anyhow::bail!("constructor `{name}` must return `Self`");
+                }
+                if fallible {
+                    write!(file, "public {cs_name}({cs_params}) : this(GlueGunRuntime.Check({call}, ggError)) {{")?;
+                } else {
+                    write!(file, "public {cs_name}({cs_params}) : this({call}) {{")?;
+                }
+                write!(file, "}}")?;
+                return Ok(());
+            }
+            CsMethodKind::Instance => {
+                write!(file, "public {} {cs_name}({cs_params}) {{", output_marshal.cs_ty)?;
+            }
+            CsMethodKind::Static => {
+                write!(file, "public static {} {cs_name}({cs_params}) {{", output_marshal.cs_ty)?;
+            }
+        }
+
+        let void = output_marshal.cs_ty == "void";
+        if void {
+            write!(file, "{call};")?;
+        } else {
+            write!(file, "{} ggResult = {call};", output_marshal.cs_ret_ty)?;
+        }
+        if fallible {
+            write!(file, "GlueGunRuntime.ThrowIfError(ggError);")?;
+        }
+        if !void {
+            write!(file, "return {};", output_marshal.cs_value)?;
+        }
+        write!(file, "}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::Context;
+use gluegun_core::{
+    cli::{GenerateCx, GlueGunHelper},
+    codegen::LibraryCrate,
+};
+
+mod cs_gen;
+mod marshal;
+mod rs_gen;
+mod util;
+
+pub fn main() -> anyhow::Result<()> {
+    gluegun_core::cli::run(GlueGunCSharp)
+}
+
+struct GlueGunCSharp;
+
+impl GlueGunHelper for GlueGunCSharp {
+    type Metadata = ();
+
+    fn name(&self) -> String {
+        "csharp".to_string()
+    }
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "C#".to_string()
+    }
+
+    fn generate(self, cx: &mut GenerateCx, _metadata: &(), output: &mut LibraryCrate) -> anyhow::Result<()> {
+        cx.reject_generics(&self.language())?;
+        cx.reject_shared_resources(&self.language())?;
+
+        // `DllImport` names the library without the platform's prefix and suffix (`lib*.so`, `*.dll`).
+        let library = output.crate_name().replace('-', "_");
+
+        let cs_src_dir = output
+            .add_dir("cs_src")
+            .context("adding `cs_src` dir")?;
+        cs_gen::CSharpCodeGenerator::new(cx.idl(), library)
+            .generate(cs_src_dir)
+            .context("generating C# sources")?;
+
+        rs_gen::RustCodeGenerator::new(cx.idl())
+            .generate(output)
+            .context("generating Rust sources")?;
+
+        Ok(())
+    }
+}
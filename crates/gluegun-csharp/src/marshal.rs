@@ -0,0 +1,483 @@
+//! How values cross the C ABI between the Rust shim and the C# wrappers.
+//!
+//! Both code generators derive the shim signatures from the descriptions here,
+//! so the Rust `extern "C"` functions and the C# `DllImport` declarations always agree.
+
+use gluegun_core::idl::{Idl, Item, Name, QualifiedName, RefdTy, Scalar, SelfKind, Ty, TypeKind};
+
+use crate::util;
+
+/// One parameter of a shim function, as declared on both sides of the C ABI.
+pub(crate) struct AbiParam {
+    /// Name of the parameter in the Rust function (and, escaped if needed, in the C# declaration).
+    pub(crate) name: String,
+
+    /// Type in the Rust `extern "C"` function.
+    pub(crate) rust_ty: String,
+
+    /// Type in the C# `extern` declaration, including any marshalling attribute or `out` modifier.
+    pub(crate) cs_ty: String,
+}
+
+impl AbiParam {
+    fn new(name: impl ToString, rust_ty: impl ToString, cs_ty: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            rust_ty: rust_ty.to_string(),
+            cs_ty: cs_ty.to_string(),
+        }
+    }
+}
+
+/// How a function input crosses the C ABI.
+pub(crate) struct InputMarshal {
+    /// Name of the Rust parameter; the rebuilt value is bound to a local of this name.
+    pub(crate) name: String,
+
+    /// Type of the parameter in the public C# method.
+    pub(crate) cs_ty: String,
+
+    /// Parameters of the shim function that carry the value.
+    pub(crate) abi_params: Vec<AbiParam>,
+
+    /// C# expressions passed for `abi_params`, in the same order.
+    pub(crate) cs_args: Vec<String>,
+
+    /// Rust expression rebuilding the value from `abi_params`.
+    pub(crate) rust_value: String,
+
+    /// Rust expression passing the rebuilt value to the function.
+    pub(crate) rust_arg: String,
+}
+
+/// How a function result crosses the C ABI.
+pub(crate) struct OutputMarshal {
+    /// Return type of the public C# method.
+    pub(crate) cs_ty: String,
+
+    /// Return type of the shim function; `None` if it returns nothing.
+    pub(crate) rust_ret_ty: Option<String>,
+
+    /// Return type of the C# `extern` declaration.
+    pub(crate) cs_ret_ty: String,
+
+    /// Trailing out-parameters of the shim function carrying the rest of the value.
+    pub(crate) out_params: Vec<AbiParam>,
+
+    /// C# arguments passed for `out_params`, declaring the locals read by `cs_value`.
+    pub(crate) cs_out_args: Vec<String>,
+
+    /// Rust expression converting the result (bound to `value`) into the shim's return value,
+    /// writing any `out_params` along the way.
+    pub(crate) rust_return: String,
+
+    /// Rust expression returned by the shim function when the Rust function fails.
+    pub(crate) rust_default: String,
+
+    /// C# expression building the public result from the shim's return value (bound to `ggResult`) and the out locals.
+    pub(crate) cs_value: String,
+}
+
+/// The out-parameter through which fallible shim functions report errors:
+/// a string to be freed by the caller, or null on success.
+pub(crate) fn error_param() -> AbiParam {
+    AbiParam::new("error", "*mut *mut std::ffi::c_char", "out IntPtr")
+}
+
+pub(crate) struct Marshaller<'idl> {
+    idl: &'idl Idl,
+}
+
+impl<'idl> Marshaller<'idl> {
+    pub(crate) fn new(idl: &'idl Idl) -> Self {
+        Self { idl }
+    }
+
+    /// Describe how the `self` parameter of a method on the resource `qname` is passed.
+    ///
+    /// Borrowed handles are passed as the resource's `SafeHandle`, which keeps them alive for the call.
+    /// Methods consuming `self` take ownership of the pointer and invalidate the C# object.
+    pub(crate) fn self_input(&self, qname: &QualifiedName, self_kind: &SelfKind) -> anyhow::Result<InputMarshal> {
+        let rust_ty = qname.colon_colon();
+        let class = util::class_dot_name(qname);
+        let (abi_param, cs_arg, rust_value) = match self_kind {
+            SelfKind::ByRef => (
+                AbiParam::new("handle", format!("*const {rust_ty}"), format!("{class}.Handle")),
+                "this.handle",
+                "unsafe { &*handle }",
+            ),
+            SelfKind::ByRefMut => (
+                AbiParam::new("handle", format!("*mut {rust_ty}"), format!("{class}.Handle")),
+                "this.handle",
+                "unsafe { &mut *handle }",
+            ),
+            SelfKind::ByValue => (
+                AbiParam::new("handle", format!("*mut {rust_ty}"), "IntPtr"),
+                "this.TakeHandle()",
+                "*unsafe { Box::from_raw(handle) }",
+            ),
+            _ => anyhow::bail!("unsupported self kind: {self_kind:?}"),
+        };
+        Ok(InputMarshal {
+            name: "handle".to_string(),
+            cs_ty: class,
+            abi_params: vec![abi_param],
+            cs_args: vec![cs_arg.to_string()],
+            rust_value: rust_value.to_string(),
+            rust_arg: "handle".to_string(),
+        })
+    }
+
+    /// Describe how the parameter `name` of type `refd_ty` is passed.
+    pub(crate) fn input(&self, name: &Name, refd_ty: &RefdTy) -> anyhow::Result<InputMarshal> {
+        let ty = refd_ty.ty();
+        let cs_name = util::cs_local_name(name);
+        let rust_arg = match refd_ty {
+            RefdTy::Owned(kind, _) => kind.rust_from_value(name),
+            RefdTy::Ref(kind, ty) if kind.is_mut() => {
+                anyhow::bail!("mutable references cannot be passed from C# yet: {ty}")
+            }
+            RefdTy::Ref(..) => format!("&{name}"),
+        };
+
+        let marshal = match ty.kind() {
+            TypeKind::Scalar(scalar) => {
+                let cs_ty = self.scalar(scalar)?;
+                InputMarshal {
+                    name: name.to_string(),
+                    cs_ty: cs_ty.to_string(),
+                    abi_params: vec![AbiParam::new(name, scalar, cs_ty)],
+                    cs_args: vec![cs_name],
+                    rust_value: name.to_string(),
+                    rust_arg,
+                }
+            }
+            TypeKind::String { .. } | TypeKind::Path { .. } => InputMarshal {
+                name: name.to_string(),
+                cs_ty: "string".to_string(),
+                abi_params: vec![AbiParam::new(name, "*const std::ffi::c_char", "[MarshalAs(UnmanagedType.LPUTF8Str)] string")],
+                cs_args: vec![cs_name.clone()],
+                rust_value: owned_string(ty, &format!("unsafe {{ crate::gluegun_runtime::from_c_string({name}, \"{cs_name}\") }}")),
+                rust_arg,
+            },
+            TypeKind::Option { element, repr: _ } => match element.kind() {
+                // Null stands for `None`.
+                TypeKind::String { .. } | TypeKind::Path { .. } => InputMarshal {
+                    name: name.to_string(),
+                    cs_ty: "string?".to_string(),
+                    abi_params: vec![AbiParam::new(name, "*const std::ffi::c_char", "[MarshalAs(UnmanagedType.LPUTF8Str)] string?")],
+                    cs_args: vec![cs_name.clone()],
+                    rust_value: format!(
+                        "(!{name}.is_null()).then(|| {})",
+                        owned_string(element, &format!("unsafe {{ crate::gluegun_runtime::from_c_string({name}, \"{cs_name}\") }}")),
+                    ),
+                    rust_arg,
+                },
+                // A flag says whether the value is present.
+                TypeKind::Scalar(scalar) => {
+                    let cs_ty = self.scalar(scalar)?;
+                    InputMarshal {
+                        name: name.to_string(),
+                        cs_ty: format!("{cs_ty}?"),
+                        abi_params: vec![
+                            AbiParam::new(format!("{name}_has_value"), "u8", "byte"),
+                            AbiParam::new(name, scalar, cs_ty),
+                        ],
+                        cs_args: vec![
+                            format!("{cs_name}.HasValue ? (byte)1 : (byte)0"),
+                            format!("{cs_name}.GetValueOrDefault()"),
+                        ],
+                        rust_value: format!("({name}_has_value != 0).then_some({name})"),
+                        rust_arg,
+                    }
+                }
+                _ => anyhow::bail!("unsupported optional type for C#: {ty}"),
+            },
+            // Collections are copied out of C# arrays, passed as a pointer and a length.
+            TypeKind::Vec { element, repr: _ } => {
+                let (element, cs_element) = self.collection_element(element)?;
+                InputMarshal {
+                    name: name.to_string(),
+                    cs_ty: format!("List<{cs_element}>"),
+                    abi_params: vec![
+                        AbiParam::new(name, format!("*const {element}"), format!("{cs_element}[]")),
+                        AbiParam::new(format!("{name}_len"), "usize", "nuint"),
+                    ],
+                    cs_args: vec![format!("{cs_name}.ToArray()"), format!("(nuint){cs_name}.Count")],
+                    rust_value: format!("unsafe {{ crate::gluegun_runtime::from_buffer({name}, {name}_len) }}"),
+                    rust_arg,
+                }
+            }
+            // Bytes are copied out of a C# `byte[]` in the same way, but without going through a list.
+            TypeKind::Bytes { repr: _ } => InputMarshal {
+                name: name.to_string(),
+                cs_ty: "byte[]".to_string(),
+                abi_params: vec![
+                    AbiParam::new(name, "*const u8", "byte[]"),
+                    AbiParam::new(format!("{name}_len"), "usize", "nuint"),
+                ],
+                cs_args: vec![cs_name.clone(), format!("(nuint){cs_name}.Length")],
+                rust_value: format!("unsafe {{ crate::gluegun_runtime::from_buffer({name}, {name}_len) }}"),
+                rust_arg,
+            },
+            TypeKind::Map { key, value, repr: _ } => {
+                let (key, cs_key) = self.collection_element(key)?;
+                let (value, cs_value) = self.collection_element(value)?;
+                InputMarshal {
+                    name: name.to_string(),
+                    cs_ty: format!("Dictionary<{cs_key}, {cs_value}>"),
+                    abi_params: vec![
+                        AbiParam::new(format!("{name}_keys"), format!("*const {key}"), format!("{cs_key}[]")),
+                        AbiParam::new(format!("{name}_values"), format!("*const {value}"), format!("{cs_value}[]")),
+                        AbiParam::new(format!("{name}_len"), "usize", "nuint"),
+                    ],
+                    // `Keys` and `Values` enumerate the entries in the same order.
+                    cs_args: vec![
+                        format!("{cs_name}.Keys.ToArray()"),
+                        format!("{cs_name}.Values.ToArray()"),
+                        format!("(nuint){cs_name}.Count"),
+                    ],
+                    rust_value: format!(
+                        "unsafe {{ crate::gluegun_runtime::from_buffer({name}_keys, {name}_len) }}.into_iter().zip(unsafe {{ crate::gluegun_runtime::from_buffer({name}_values, {name}_len) }}).collect()"
+                    ),
+                    rust_arg,
+                }
+            }
+            TypeKind::UserType { qname } => match self.user_item(qname)? {
+                Item::Resource(_) => {
+                    let rust_ty = qname.colon_colon();
+                    let class = util::class_dot_name(qname);
+                    match refd_ty {
+                        // The resource is moved into Rust; the C# object is no longer usable.
+                        RefdTy::Owned(..) => InputMarshal {
+                            name: name.to_string(),
+                            cs_ty: class,
+                            abi_params: vec![AbiParam::new(name, format!("*mut {rust_ty}"), "IntPtr")],
+                            cs_args: vec![format!("{cs_name}.TakeHandle()")],
+                            rust_value: format!("*unsafe {{ Box::from_raw({name}) }}"),
+                            rust_arg,
+                        },
+                        RefdTy::Ref(..) => InputMarshal {
+                            name: name.to_string(),
+                            abi_params: vec![AbiParam::new(name, format!("*const {rust_ty}"), format!("{class}.Handle"))],
+                            cs_ty: class,
+                            cs_args: vec![format!("{cs_name}.handle")],
+                            rust_value: format!("unsafe {{ &*{name} }}"),
+                            rust_arg: name.to_string(),
+                        },
+                    }
+                }
+                Item::Record(_) => {
+                    let class = util::class_dot_name(qname);
+                    InputMarshal {
+                        name: name.to_string(),
+                        abi_params: vec![AbiParam::new(name, format!("crate::{}", util::record_repr_name(qname)), &class)],
+                        cs_ty: class,
+                        cs_args: vec![cs_name],
+                        rust_value: format!("{}::from({name})", qname.colon_colon()),
+                        rust_arg,
+                    }
+                }
+                _ => anyhow::bail!("unsupported type for C#: {ty}"),
+            },
+            _ => anyhow::bail!("unsupported type for C#: {ty}"),
+        };
+        Ok(marshal)
+    }
+
+    /// Describe how a result of type `refd_ty` is returned.
+    pub(crate) fn output(&self, refd_ty: &RefdTy) -> anyhow::Result<OutputMarshal> {
+        let ty = refd_ty.ty();
+
+        let marshal = match ty.kind() {
+            _ if ty.is_unit() || ty.is_never() => OutputMarshal {
+                cs_ty: "void".to_string(),
+                rust_ret_ty: None,
+                cs_ret_ty: "void".to_string(),
+                out_params: vec![],
+                cs_out_args: vec![],
+                rust_return: String::new(),
+                rust_default: String::new(),
+                cs_value: String::new(),
+            },
+            TypeKind::Scalar(scalar) => {
+                let cs_ty = self.scalar(scalar)?;
+                OutputMarshal {
+                    cs_ty: cs_ty.to_string(),
+                    rust_ret_ty: Some(scalar.to_string()),
+                    cs_ret_ty: cs_ty.to_string(),
+                    out_params: vec![],
+                    cs_out_args: vec![],
+                    rust_return: "value".to_string(),
+                    rust_default: "Default::default()".to_string(),
+                    cs_value: "ggResult".to_string(),
+                }
+            }
+            // Strings are returned as C strings allocated by Rust, which C# copies and then frees.
+            TypeKind::String { .. } | TypeKind::Path { .. } => OutputMarshal {
+                cs_ty: "string".to_string(),
+                rust_ret_ty: Some("*mut std::ffi::c_char".to_string()),
+                cs_ret_ty: "IntPtr".to_string(),
+                out_params: vec![],
+                cs_out_args: vec![],
+                rust_return: format!("crate::gluegun_runtime::into_c_string({})", string_of(ty, "value")),
+                rust_default: "std::ptr::null_mut()".to_string(),
+                cs_value: "GlueGunRuntime.TakeString(ggResult)!".to_string(),
+            },
+            TypeKind::Option { element, repr: _ } => match element.kind() {
+                TypeKind::String { .. } | TypeKind::Path { .. } => OutputMarshal {
+                    cs_ty: "string?".to_string(),
+                    rust_ret_ty: Some("*mut std::ffi::c_char".to_string()),
+                    cs_ret_ty: "IntPtr".to_string(),
+                    out_params: vec![],
+                    cs_out_args: vec![],
+                    rust_return: format!(
+                        "value.map_or(std::ptr::null_mut(), |value| crate::gluegun_runtime::into_c_string({}))",
+                        string_of(element, "value"),
+                    ),
+                    rust_default: "std::ptr::null_mut()".to_string(),
+                    cs_value: "GlueGunRuntime.TakeString(ggResult)".to_string(),
+                },
+                TypeKind::Scalar(scalar) => {
+                    let cs_ty = self.scalar(scalar)?;
+                    OutputMarshal {
+                        cs_ty: format!("{cs_ty}?"),
+                        rust_ret_ty: Some("u8".to_string()),
+                        cs_ret_ty: "byte".to_string(),
+                        out_params: vec![AbiParam::new("out_value", format!("*mut {scalar}"), format!("out {cs_ty}"))],
+                        cs_out_args: vec![format!("out {cs_ty} ggValue")],
+                        rust_return: "match value { Some(value) => { unsafe { *out_value = value }; 1 } None => 0 }".to_string(),
+                        rust_default: "0".to_string(),
+                        cs_value: format!("ggResult != 0 ? ggValue : ({cs_ty}?)null"),
+                    }
+                }
+                _ => anyhow::bail!("unsupported optional type for C#: {ty}"),
+            },
+            // Collections are returned as buffers allocated by Rust, which C# copies and then frees.
+            TypeKind::Vec { element, repr: _ } => {
+                let (element, cs_element) = self.collection_element(element)?;
+                OutputMarshal {
+                    cs_ty: format!("List<{cs_element}>"),
+                    rust_ret_ty: Some("usize".to_string()),
+                    cs_ret_ty: "nuint".to_string(),
+                    out_params: vec![AbiParam::new("out_ptr", format!("*mut *mut {element}"), "out IntPtr")],
+                    cs_out_args: vec!["out IntPtr ggPtr".to_string()],
+                    rust_return: "unsafe { crate::gluegun_runtime::into_buffer(value.to_vec(), out_ptr) }".to_string(),
+                    rust_default: "0".to_string(),
+                    cs_value: format!("GlueGunRuntime.TakeList<{cs_element}>(ggPtr, ggResult)"),
+                }
+            }
+            TypeKind::Bytes { repr: _ } => OutputMarshal {
+                cs_ty: "byte[]".to_string(),
+                rust_ret_ty: Some("usize".to_string()),
+                cs_ret_ty: "nuint".to_string(),
+                out_params: vec![AbiParam::new("out_ptr", "*mut *mut u8", "out IntPtr")],
+                cs_out_args: vec!["out IntPtr ggPtr".to_string()],
+                rust_return: "unsafe { crate::gluegun_runtime::into_buffer(value.to_vec(), out_ptr) }".to_string(),
+                rust_default: "0".to_string(),
+                cs_value: "GlueGunRuntime.TakeArray<byte>(ggPtr, ggResult)".to_string(),
+            },
+            TypeKind::Map { key, value, repr: _ } => {
+                let (key, cs_key) = self.collection_element(key)?;
+                let (value, cs_value) = self.collection_element(value)?;
+                OutputMarshal {
+                    cs_ty: format!("Dictionary<{cs_key}, {cs_value}>"),
+                    rust_ret_ty: Some("usize".to_string()),
+                    cs_ret_ty: "nuint".to_string(),
+                    out_params: vec![
+                        AbiParam::new("out_keys", format!("*mut *mut {key}"), "out IntPtr"),
+                        AbiParam::new("out_values", format!("*mut *mut {value}"), "out IntPtr"),
+                    ],
+                    cs_out_args: vec!["out IntPtr ggKeys".to_string(), "out IntPtr ggValues".to_string()],
+                    rust_return: "{ let (keys, values): (Vec<_>, Vec<_>) = value.into_iter().map(|(key, value)| (key.clone(), value.clone())).unzip(); unsafe { crate::gluegun_runtime::into_buffer(values, out_values); crate::gluegun_runtime::into_buffer(keys, out_keys) } }".to_string(),
+                    rust_default: "0".to_string(),
+                    cs_value: format!("GlueGunRuntime.TakeDictionary<{cs_key}, {cs_value}>(ggKeys, ggValues, ggResult)"),
+                }
+            }
+            TypeKind::UserType { qname } => match (self.user_item(qname)?, refd_ty) {
+                // Ownership of the boxed resource passes to the C# object.
+                (Item::Resource(_), RefdTy::Owned(..)) => {
+                    let class = util::class_dot_name(qname);
+                    OutputMarshal {
+                        cs_ret_ty: format!("{class}.Handle"),
+                        cs_value: format!("new {class}(ggResult)"),
+                        cs_ty: class,
+                        rust_ret_ty: Some(format!("*mut {}", qname.colon_colon())),
+                        out_params: vec![],
+                        cs_out_args: vec![],
+                        rust_return: "Box::into_raw(Box::new(value))".to_string(),
+                        rust_default: "std::ptr::null_mut()".to_string(),
+                    }
+                }
+                (Item::Record(_), _) => {
+                    let class = util::class_dot_name(qname);
+                    let repr = util::record_repr_name(qname);
+                    // The mirror is built from a reference, so records returned by reference work too.
+                    let value = match refd_ty {
+                        RefdTy::Owned(..) => "&value",
+                        RefdTy::Ref(..) => "value",
+                    };
+                    OutputMarshal {
+                        cs_ret_ty: class.clone(),
+                        cs_ty: class,
+                        rust_ret_ty: Some(format!("crate::{repr}")),
+                        out_params: vec![],
+                        cs_out_args: vec![],
+                        rust_return: format!("crate::{repr}::from({value})"),
+                        rust_default: "Default::default()".to_string(),
+                        cs_value: "ggResult".to_string(),
+                    }
+                }
+                _ => anyhow::bail!("unsupported return type for C#: {refd_ty}"),
+            },
+            _ => anyhow::bail!("unsupported return type for C#: {refd_ty}"),
+        };
+        Ok(marshal)
+    }
+
+    /// The C# type for a field of a record; records are passed by value, so only scalars are supported.
+    pub(crate) fn field(&self, ty: &Ty) -> anyhow::Result<&'static str> {
+        match ty.kind() {
+            TypeKind::Scalar(scalar) => self.scalar(scalar),
+            _ => anyhow::bail!("unsupported record field type for C#: {ty}"),
+        }
+    }
+
+    fn scalar(&self, scalar: &Scalar) -> anyhow::Result<&'static str> {
+        match util::cs_scalar_ty(scalar) {
+            Some(cs_ty) => Ok(cs_ty),
+            None => anyhow::bail!("unsupported scalar type for C#: {scalar}"),
+        }
+    }
+
+    /// The Rust and C# types of a collection element; only scalars can be copied across as a buffer.
+    fn collection_element(&self, ty: &Ty) -> anyhow::Result<(Scalar, &'static str)> {
+        match ty.kind() {
+            TypeKind::Scalar(scalar) => Ok((*scalar, self.scalar(scalar)?)),
+            _ => anyhow::bail!("unsupported collection element type for C#: {ty}"),
+        }
+    }
+
+    fn user_item(&self, qname: &QualifiedName) -> anyhow::Result<&'idl Item> {
+        match self.idl.definitions().get(qname) {
+            Some(item) => Ok(item),
+            None => anyhow::bail!("no definition for `{}`", qname.colon_colon()),
+        }
+    }
+}
+
+/// Rust expression converting the `String` expression `expr` into the owned form of `ty` (a string or path).
+fn owned_string(ty: &Ty, expr: &str) -> String {
+    match ty.kind() {
+        TypeKind::Path { .. } => format!("std::path::PathBuf::from({expr})"),
+        _ => expr.to_string(),
+    }
+}
+
+/// Rust expression converting `expr`, a value of `ty` (a string or path), into a `String`.
+fn string_of(ty: &Ty, expr: &str) -> String {
+    match ty.kind() {
+        TypeKind::Path { .. } => format!("{expr}.to_string_lossy().into_owned()"),
+        _ => format!("{expr}.to_string()"),
+    }
+}
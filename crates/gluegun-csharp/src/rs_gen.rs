@@ -0,0 +1,230 @@
+use gluegun_c::shim::Runtime;
+use gluegun_core::{
+    cli::{for_each_supported, UnsupportedError},
+    codegen::{CodeWriter, LibraryCrate},
+    idl::{Idl, IsAsync, Item, Method, MethodCategory, OwnedKind, QualifiedName, Record, Resource, Signature},
+};
+
+use crate::{
+    marshal::{self, InputMarshal, Marshaller},
+    util,
+};
+
+/// Generates the C ABI shim: one `extern "C"` function per Rust function or method,
+/// plus the helpers C# uses to free memory allocated on the Rust side.
+pub(crate) struct RustCodeGenerator<'idl> {
+    idl: &'idl Idl,
+    marshaller: Marshaller<'idl>,
+}
+
+impl<'idl> RustCodeGenerator<'idl> {
+    pub(crate) fn new(idl: &'idl Idl) -> Self {
+        Self {
+            idl,
+            marshaller: Marshaller::new(idl),
+        }
+    }
+
+    pub(crate) fn generate(self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
+        // When splitting, each item goes into its own module; these are generated first
+        // since the `lib.rs` writer borrows `lib` until it is dropped.
+        // Record mirrors are types used by shim functions in every module, so they always go in `lib.rs`.
+        let split_modules = *lib.split_modules();
+        if split_modules {
+            for_each_supported(self.idl.definitions(), |(qname, item)| {
+                if let Item::Record(_) = item {
+                    return Ok(());
+                }
+                let mut item_rs = lib.add_item_module(qname)?;
+                self.generate_item(&mut item_rs, qname, item)
+            })?;
+        }
+
+        let mut lib_rs = lib.add_file("src/lib.rs")?;
+
+        write!(lib_rs, "#![allow(non_snake_case)]")?; // symbols keep the Rust type names, like `bank__Account__new`
+
+        let crate_name = self.idl.crate_name().to_string();
+        Runtime {
+            free_string: util::helper_symbol_name(&crate_name, "free_string"),
+            free_buffer: Some(util::helper_symbol_name(&crate_name, "free_buffer")),
+        }
+        .generate(&mut lib_rs)?;
+
+        for_each_supported(self.idl.definitions(), |(qname, item)| {
+            if !split_modules || matches!(item, Item::Record(_)) {
+                self.generate_item(&mut lib_rs, qname, item)?;
+            }
+            Ok(())
+        })?;
+
+        Ok(())
+    }
+
+    fn generate_item(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, item: &Item) -> anyhow::Result<()> {
+        match item {
+            Item::Resource(resource) => self.generate_resource(lib_rs, qname, resource),
+            Item::Record(record) => self.generate_record(lib_rs, qname, record),
+            Item::Function(function) => {
+                let symbol = util::symbol_name(&qname.module_name(), function.name());
+                self.generate_shim_function(lib_rs, &symbol, &qname.colon_colon(), None, function.signature())
+            }
+            // The value is written into the C# class, so no shim is needed.
+            Item::Constant(_) => Ok(()),
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to C# yet")),
+        }
+    }
+
+    /// Generate the function that frees a resource once its C# `SafeHandle` is released, and the resource's methods.
+    fn generate_resource(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, resource: &Resource) -> anyhow::Result<()> {
+        let free = util::helper_symbol_name(&qname.to_string("__"), "free");
+        write!(lib_rs, "#[unsafe(no_mangle)]")?;
+        write!(lib_rs, "pub unsafe extern \"C\" fn {free}(handle: *mut {}) {{", qname.colon_colon())?;
+        write!(lib_rs, "drop(unsafe {{ Box::from_raw(handle) }})")?;
+        write!(lib_rs, "}}")?;
+
+        for method in resource.methods() {
+            self.generate_method(lib_rs, qname, method)?;
+        }
+        Ok(())
+    }
+
+    /// Generate the `#[repr(C)]` mirror of a record, which is passed by value, and conversions to and from the record.
+    fn generate_record(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, record: &Record) -> anyhow::Result<()> {
+        if !record.methods().is_empty() {
+            anyhow::bail!("methods on records are not supported for C#: {}", qname.colon_colon());
+        }
+
+        let repr = util::record_repr_name(qname);
+        let rust_ty = qname.colon_colon();
+
+        write!(lib_rs, "#[repr(C)]")?;
+        write!(lib_rs, "#[derive(Default)]")?;
+        write!(lib_rs, "pub struct {repr} {{")?;
+        for field in record.fields() {
+            self.marshaller.field(field.ty())?;
+            write!(lib_rs, "pub {}: {},", field.name(), field.ty())?;
+        }
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl From<&{rust_ty}> for {repr} {{")?;
+        write!(lib_rs, "fn from(value: &{rust_ty}) -> Self {{")?;
+        write!(lib_rs, "Self {{")?;
+        for field in record.fields() {
+            write!(lib_rs, "{}: value.{},", field.name(), field.rust_name())?;
+        }
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl From<{repr}> for {rust_ty} {{")?;
+        write!(lib_rs, "fn from(value: {repr}) -> Self {{")?;
+        write!(lib_rs, "Self {{")?;
+        for field in record.fields() {
+            write!(lib_rs, "{}: value.{},", field.rust_name(), field.name())?;
+        }
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    fn generate_method(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, method: &Method) -> anyhow::Result<()> {
+        let self_input = match method.category() {
+            MethodCategory::Constructor | MethodCategory::StaticMethod => None,
+            MethodCategory::BuilderMethod(self_kind) | MethodCategory::InstanceMethod(self_kind) => {
+                Some(self.marshaller.self_input(qname, self_kind)?)
+            }
+            category => anyhow::bail!("unsupported method category: {category:?}"),
+        };
+        let symbol = util::symbol_name(qname, method.name());
+        let path = match method.trait_name() {
+            // A method from a trait impl is only found through the type if the trait is in scope.
+            Some(trait_name) => format!("<{} as {}>::{}", qname.colon_colon(), trait_name.colon_colon(), method.rust_name()),
+            None => format!("{}::{}", qname.colon_colon(), method.rust_name()),
+        };
+        self.generate_shim_function(lib_rs, &symbol, &path, self_input, method.signature())
+    }
+
+    /// Generate the `extern "C"` function `symbol` calling the Rust function at `path`.
+    ///
+    /// Arguments are rebuilt from their C representation (see [`Marshaller::input`][]) and the result is converted back
+    /// (see [`Marshaller::output`][]). Errors are reported through a trailing `error` out-parameter.
+    fn generate_shim_function(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        symbol: &str,
+        path: &str,
+        self_input: Option<InputMarshal>,
+        signature: &Signature,
+    ) -> anyhow::Result<()> {
+        if *signature.is_async() == IsAsync::Yes {
+            anyhow::bail!("async functions are not supported for C#: {path}");
+        }
+
+        let mut inputs = vec![];
+        inputs.extend(self_input);
+        for input in signature.inputs() {
+            inputs.push(self.marshaller.input(input.name(), input.refd_ty())?);
+        }
+        let output = signature.output_ty();
+        let output_marshal = self.marshaller.output(output.main_ty())?;
+        let fallible = output.error_ty().is_some();
+
+        write!(lib_rs, "#[unsafe(no_mangle)]")?;
+        write!(lib_rs, "pub unsafe extern \"C\" fn {symbol}(")?;
+        for param in inputs.iter().flat_map(|input| &input.abi_params) {
+            write!(lib_rs, "{}: {},", param.name, param.rust_ty)?;
+        }
+        for param in &output_marshal.out_params {
+            write!(lib_rs, "{}: {},", param.name, param.rust_ty)?;
+        }
+        if fallible {
+            let param = marshal::error_param();
+            write!(lib_rs, "{}: {},", param.name, param.rust_ty)?;
+        }
+        match &output_marshal.rust_ret_ty {
+            Some(ty) => write!(lib_rs, ") -> {ty} {{")?,
+            None => write!(lib_rs, ") {{")?,
+        }
+
+        for input in inputs.iter().filter(|input| input.rust_value != input.name) {
+            write!(lib_rs, "let {} = {};", input.name, input.rust_value)?;
+        }
+
+        let args = inputs.iter().map(|input| input.rust_arg.as_str()).collect::<Vec<_>>().join(", ");
+        // A value returned behind a smart pointer is taken out of it first.
+        let (open, close) = output.main_ty().owned_kind().map_or(("", ""), OwnedKind::rust_into_value);
+        let call = if fallible && !open.is_empty() {
+            format!("{path}({args}).map(|value| {open}value{close})")
+        } else {
+            format!("{open}{path}({args}){close}")
+        };
+        let void = output_marshal.rust_ret_ty.is_none();
+
+        if fallible {
+            write!(lib_rs, "unsafe {{ *error = std::ptr::null_mut() }};")?;
+            write!(lib_rs, "match {call} {{")?;
+            if void {
+                write!(lib_rs, "Ok(value) => value,")?;
+            } else {
+                write!(lib_rs, "Ok(value) => {},", output_marshal.rust_return)?;
+            }
+            write!(lib_rs, "Err(err) => {{")?;
+            write!(lib_rs, "unsafe {{ *error = crate::gluegun_runtime::into_c_string(err.to_string()) }};")?;
+            if !void {
+                write!(lib_rs, "{}", output_marshal.rust_default)?;
+            }
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+        } else if void {
+            write!(lib_rs, "{call}")?;
+        } else {
+            write!(lib_rs, "let value = {call};")?;
+            write!(lib_rs, "{}", output_marshal.rust_return)?;
+        }
+
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::PathBuf;
+
+use gluegun_core::{
+    casing::Casing,
+    idl::{Literal, Name, QualifiedName, Scalar, Ty, TypeKind},
+};
+
+/// A qualified name following C# conventions.
+pub(crate) struct CsQName {
+    /// like `Bank.Accounts`
+    pub(crate) namespace: String,
+
+    /// like `Account`
+    pub(crate) class_name: Name,
+}
+
+/// Convert a qualified name from Rust to C# conventions and break apart the namespace/class name.
+pub(crate) fn class_namespace_and_name(qname: &QualifiedName) -> CsQName {
+    let (module_name, type_name) = qname.to_pascal_case().split_module_name();
+    CsQName {
+        namespace: module_name.dotted(),
+        class_name: type_name,
+    }
+}
+
+/// Return a string like `global::Bank.Accounts.Account`.
+/// The `global::` prefix keeps a class from shadowing a namespace of the same name (e.g. `Account.Account`).
+pub(crate) fn class_dot_name(qname: &QualifiedName) -> String {
+    format!("global::{}", qname.to_pascal_case().dotted())
+}
+
+/// Return a path like `Bank/Accounts/Account.cs`
+pub(crate) fn class_file_name(qname: &QualifiedName) -> PathBuf {
+    let mut path: PathBuf = qname.to_pascal_case().names().iter().map(|name| name.text()).collect();
+    path.set_extension("cs");
+    path
+}
+
+/// The root C# namespace for the crate, like `Bank` for the crate `bank`.
+pub(crate) fn root_namespace(crate_name: &Name) -> Name {
+    crate_name.to_pascal_case()
+}
+
+/// The exported name of the shim function for `fn_name` defined in `qname`
+/// (the resource for methods, the module for free functions), like `bank__Account__withdraw`.
+pub(crate) fn symbol_name(qname: &QualifiedName, fn_name: &Name) -> String {
+    format!("{}__{fn_name}", qname.to_string("__"))
+}
+
+/// The exported name of a shim function generated by gluegun itself rather than for a Rust function,
+/// like `bank__Account__gluegun__free` for the prefix `bank__Account`.
+/// The `gluegun` segment keeps these from colliding with user methods.
+pub(crate) fn helper_symbol_name(prefix: &str, helper: &str) -> String {
+    format!("{prefix}__gluegun__{helper}")
+}
+
+/// Name of the `#[repr(C)]` mirror of a record in the shim crate, like `GeometryPointRepr` for `geometry::Point`.
+pub(crate) fn record_repr_name(qname: &QualifiedName) -> String {
+    format!("{}Repr", qname.to_pascal_case().to_string(""))
+}
+
+/// The C# name for the parameter or local `name`, escaped if it is a C# keyword.
+pub(crate) fn cs_local_name(name: &Name) -> String {
+    escape_keyword(name.to_camel_case().text())
+}
+
+/// The C# name for a parameter of an `extern` declaration: the Rust name, escaped if it is a C# keyword.
+pub(crate) fn cs_extern_param_name(name: &str) -> String {
+    escape_keyword(name)
+}
+
+fn escape_keyword(name: &str) -> String {
+    if CS_KEYWORDS.contains(&name) {
+        format!("@{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Reserved C# keywords, which need an `@` prefix when used as identifiers.
+const CS_KEYWORDS: &[&str] = &[
+    "abstract", "as", "base", "bool", "break", "byte", "case", "catch", "char", "checked", "class", "const",
+    "continue", "decimal", "default", "delegate", "do", "double", "else", "enum", "event", "explicit", "extern",
+    "false", "finally", "fixed", "float", "for", "foreach", "goto", "if", "implicit", "in", "int", "interface",
+    "internal", "is", "lock", "long", "namespace", "new", "null", "object", "operator", "out", "override",
+    "params", "private", "protected", "public", "readonly", "ref", "return", "sbyte", "sealed", "short",
+    "sizeof", "stackalloc", "static", "string", "struct", "switch", "this", "throw", "true", "try", "typeof",
+    "uint", "ulong", "unchecked", "unsafe", "ushort", "using", "virtual", "void", "volatile", "while",
+];
+
+/// The C# type with the same representation as the Rust scalar, if any.
+///
+/// `bool` and `char` have no single blittable C# counterpart, so they are not supported yet.
+pub(crate) fn cs_scalar_ty(scalar: &Scalar) -> Option<&'static str> {
+    match scalar {
+        Scalar::I8 => Some("sbyte"),
+        Scalar::U8 => Some("byte"),
+        Scalar::I16 => Some("short"),
+        Scalar::U16 => Some("ushort"),
+        Scalar::I32 => Some("int"),
+        Scalar::U32 => Some("uint"),
+        Scalar::I64 => Some("long"),
+        Scalar::U64 => Some("ulong"),
+        Scalar::F32 => Some("float"),
+        Scalar::F64 => Some("double"),
+        _ => None,
+    }
+}
+
+/// The C# type and value of a constant whose Rust type is `ty` and value `value`,
+/// if `ty` is a string or a scalar with a C# type (see [`cs_scalar_ty`][]).
+/// Integers need no suffix, as C# gives a constant integer whichever of its types the value fits in.
+pub(crate) fn cs_constant(ty: &Ty, value: &Literal) -> Option<(&'static str, String)> {
+    match (value, ty.kind()) {
+        (Literal::String(value), TypeKind::String { repr: _ }) => {
+            let mut literal = String::from("\"");
+            for unit in value.encode_utf16() {
+                match char::from_u32(unit.into()) {
+                    Some('"') => literal.push_str("\\\""),
+                    Some('\\') => literal.push_str("\\\\"),
+                    Some(c) if c.is_ascii() && !c.is_ascii_control() => literal.push(c),
+                    _ => literal.push_str(&format!("\\u{unit:04x}")),
+                }
+            }
+            literal.push('"');
+            Some(("string", literal))
+        }
+        (Literal::Float(digits), TypeKind::Scalar(Scalar::F32)) => Some(("float", format!("{digits}f"))),
+        (Literal::Integer(digits) | Literal::Float(digits), TypeKind::Scalar(scalar)) => {
+            Some((cs_scalar_ty(scalar)?, digits.clone()))
+        }
+        _ => None,
+    }
+}
@@ -13,6 +13,14 @@ impl GlueGunHelper for GlueGunDummy {
         "dummy".to_string()
     }
 
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "none".to_string()
+    }
+
     fn generate(self, cx: &mut GenerateCx, metadata: &Self::Metadata, output: &mut LibraryCrate) -> anyhow::Result<()> {
         let mut f = output.add_file("README.md")?;
         write!(f, "# Dummy GlueGun crate generator")?;
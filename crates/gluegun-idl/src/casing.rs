@@ -0,0 +1,142 @@
+use crate::{Name, QualifiedName};
+
+/// A case convention for the names of a target language.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NameStyle {
+    /// `snake_case`, e.g., Rust functions and Python methods.
+    Snake,
+
+    /// `camelCase`, e.g., Java methods and fields.
+    Camel,
+
+    /// `PascalCase` (also called `UpperCamelCase`), e.g., Java and C# classes.
+    Pascal,
+
+    /// `kebab-case`, e.g., package names.
+    Kebab,
+}
+
+impl NameStyle {
+    /// Convert `text` to this style.
+    ///
+    /// The text is split into words at `_` and `-`, before an uppercase letter that follows a lowercase letter or a digit
+    /// (`fooBar`, `Http2Client`), and before the last letter of a run of uppercase letters followed by a lowercase letter
+    /// (`HTTPClient` is `HTTP` and `Client`). Digits belong to the word they follow (`utf8`, `Vec2D` is `Vec2` and `D`).
+    /// Each word is then cased as the style requires, so acronyms are cased like any other word
+    /// (`HTTPClient` becomes `http_client`, `httpClient`, or `HttpClient`).
+    ///
+    /// Leading and trailing underscores are kept as they are (`_unused` becomes `_Unused` in `PascalCase`),
+    /// and in `camelCase` and `PascalCase`, a `_` is kept between two digits (`x86_64` stays `x86_64`),
+    /// since dropping it would run the numbers together.
+    pub fn apply(self, text: &str) -> String {
+        let body = text.trim_matches('_');
+        if body.is_empty() {
+            return text.to_string();
+        }
+        let prefix = &text[..text.len() - text.trim_start_matches('_').len()];
+        let suffix = &text[text.trim_end_matches('_').len()..];
+
+        let mut result = prefix.to_string();
+        let mut previous: Option<&str> = None;
+        for (index, word) in words(body).into_iter().enumerate() {
+            match self {
+                NameStyle::Snake | NameStyle::Kebab => {
+                    if index > 0 {
+                        result.push(if self == NameStyle::Snake { '_' } else { '-' });
+                    }
+                    result.push_str(&word.to_lowercase());
+                }
+                NameStyle::Camel | NameStyle::Pascal => {
+                    let digits_meet = previous.is_some_and(|p| p.ends_with(|c: char| c.is_ascii_digit()))
+                        && word.starts_with(|c: char| c.is_ascii_digit());
+                    if digits_meet {
+                        result.push('_');
+                    }
+                    if index == 0 && self == NameStyle::Camel {
+                        result.push_str(&word.to_lowercase());
+                    } else {
+                        result.push_str(&capitalize(word));
+                    }
+                }
+            }
+            previous = Some(word);
+        }
+        result.push_str(suffix);
+        result
+    }
+}
+
+/// Split `text` into words, see [`NameStyle::apply`][].
+fn words(text: &str) -> Vec<&str> {
+    let mut words = vec![];
+    for part in text.split(['_', '-']).filter(|part| !part.is_empty()) {
+        let chars: Vec<(usize, char)> = part.char_indices().collect();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (offset, c) = chars[i];
+            let before = chars[i - 1].1;
+            let after = chars.get(i + 1).map(|&(_, c)| c);
+            let boundary = c.is_uppercase()
+                && (before.is_lowercase()
+                    || before.is_ascii_digit()
+                    || (before.is_uppercase() && after.is_some_and(|a| a.is_lowercase())));
+            if boundary {
+                words.push(&part[start..offset]);
+                start = offset;
+            }
+        }
+        words.push(&part[start..]);
+    }
+    words
+}
+
+/// `word` with its first letter uppercase and the others lowercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.as_str().to_lowercase().chars()).collect(),
+        None => String::new(),
+    }
+}
+
+/// Conversion of names to the case conventions of target languages, see [`NameStyle`][].
+///
+/// Plugins should convert names through this trait rather than by hand,
+/// so that the same Rust name is converted the same way by every plugin.
+pub trait Casing: Sized {
+    /// Convert to `style`.
+    fn to_style(&self, style: NameStyle) -> Self;
+
+    /// Convert to `snake_case`.
+    fn to_snake_case(&self) -> Self {
+        self.to_style(NameStyle::Snake)
+    }
+
+    /// Convert to `camelCase`.
+    fn to_camel_case(&self) -> Self {
+        self.to_style(NameStyle::Camel)
+    }
+
+    /// Convert to `PascalCase`.
+    fn to_pascal_case(&self) -> Self {
+        self.to_style(NameStyle::Pascal)
+    }
+
+    /// Convert to `kebab-case`.
+    fn to_kebab_case(&self) -> Self {
+        self.to_style(NameStyle::Kebab)
+    }
+}
+
+impl Casing for Name {
+    fn to_style(&self, style: NameStyle) -> Self {
+        Name::from(style.apply(self.text()))
+    }
+}
+
+/// Each name of the path is converted on its own (e.g., `my_crate::http_client` becomes `myCrate.httpClient` in `camelCase`).
+impl Casing for QualifiedName {
+    fn to_style(&self, style: NameStyle) -> Self {
+        self.names().iter().map(|name| name.to_style(style)).collect()
+    }
+}
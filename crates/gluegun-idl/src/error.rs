@@ -1,5 +1,6 @@
 use std::{ffi::OsString, path::PathBuf};
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{Name, RefKind, Span};
@@ -10,8 +11,8 @@ pub enum Error {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
-    #[error("parse error: {0}")]
-    Parse(String),
+    #[error("{0}: parse error: {1}")]
+    Parse(Span, String),
 
     #[error("{0}: generics not permitted")]
     GenericsNotPermitted(Span),
@@ -22,9 +23,6 @@ pub enum Error {
     #[error("{0}: unexpected associated type binding")]
     BindingNotExpected(Span),
 
-    #[error("{0}: fields must either be all public or all crate-private")]
-    MixedPublicPrivateFields(Span),
-
     #[error("{0}: unrecognized Rust item")]
     UnrecognizedItem(Span),
 
@@ -69,12 +67,108 @@ pub enum Error {
 
     #[error("{0}: only owned types are permitted here, not `{1}`-types")]
     ReferenceType(Span, RefKind),
+
+    #[error("{0}: invalid `#[gluegun]` attribute: {1}")]
+    InvalidAttribute(Span, String),
+
+    #[error("{0}: no parameter named `{1}`")]
+    UnknownParameter(Span, Name),
+
+    #[error("{0}: the default value of `{1}` is not a literal of type `{2}`")]
+    MismatchedDefault(Span, Name, String),
+
+    #[error("{0}: the value of the constant `{1}` is not a literal of type `{2}`")]
+    MismatchedConstant(Span, Name, String),
+
+    #[error("{0}: cannot find the file for module `{1}` (looked for `{1}.rs` and `{1}/mod.rs`)")]
+    ModuleNotFound(Span, Name),
+
+    #[error("{0}: cannot find the module file `{1}` named by `#[path]`")]
+    ModulePathNotFound(Span, PathBuf),
+
+    #[error("{0}: `impl {1}` is not supported (recognized traits are `AsRef<T>`, `Into<String>`, `ToString`, `Future`, and `Iterator`)")]
+    UnsupportedImplTrait(Span, String),
+
+    #[error("{0}: `Box`, `Rc`, and `Arc` are only supported as the type of a parameter or a return value")]
+    SmartPointerNotPermitted(Span),
+
+    #[error("{0}: `{1}` cannot be mapped, since its value can only be reached by locking or borrowing it in Rust")]
+    InteriorMutability(Span, String),
 }
 
-impl From<syn::Error> for Error {
-    fn from(value: syn::Error) -> Self {
-        Error::Parse(value.to_string())
+impl Error {
+    /// The location in the Rust source that the error refers to, if any.
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Error::GenericsNotPermitted(span)
+            | Error::BindingNotFound(span, _)
+            | Error::BindingNotExpected(span)
+            | Error::UnrecognizedItem(span)
+            | Error::UnsupportedNumberOfArguments(span, _, _)
+            | Error::UnsupportedItem(span)
+            | Error::ExplicitSelfNotSupported(span)
+            | Error::MacroNotSupported(span)
+            | Error::UnsupportedType(span)
+            | Error::UnsupportedUseOfType(span)
+            | Error::UnresolvedName(span)
+            | Error::NotType(span)
+            | Error::AnonymousField(span)
+            | Error::UnsupportedInputPattern(span)
+            | Error::DoubleAsync(span)
+            | Error::ReferenceType(span, _)
+            | Error::InvalidAttribute(span, _)
+            | Error::UnknownParameter(span, _)
+            | Error::MismatchedDefault(span, _, _)
+            | Error::MismatchedConstant(span, _, _)
+            | Error::ModuleNotFound(span, _)
+            | Error::ModulePathNotFound(span, _)
+            | Error::UnsupportedImplTrait(span, _)
+            | Error::SmartPointerNotPermitted(span)
+            | Error::InteriorMutability(span, _)
+            | Error::Parse(span, _) => Some(span),
+
+            Error::Io(_) | Error::InvalidPath(_) | Error::NotUtf8(_) => None,
+        }
     }
+
+    /// The error as a [`ParseError`][], if it refers to a location in the Rust source.
+    pub fn to_parse_error(&self) -> Option<ParseError> {
+        let span = self.span()?;
+        let text = self.to_string();
+        let message = text.strip_prefix(&format!("{span}: ")).unwrap_or(&text);
+        Some(ParseError {
+            file: span.path.clone(),
+            line: span.start.line,
+            column: span.start.column,
+            end_line: span.end.line,
+            end_column: span.end.column,
+            message: message.to_string(),
+        })
+    }
+}
+
+/// An [`Error`][] located in the Rust source, with the location as separate fields
+/// for tools that show errors next to the offending construct (e.g., editors).
+#[derive(Error, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[error("{}:{line}:{column}: {message}", file.display())]
+pub struct ParseError {
+    /// The file holding the offending construct.
+    pub file: PathBuf,
+
+    /// Line where the construct starts (1-indexed).
+    pub line: usize,
+
+    /// Column where the construct starts (1-indexed in utf-8 characters).
+    pub column: usize,
+
+    /// Line where the construct ends (1-indexed).
+    pub end_line: usize,
+
+    /// Column just past the end of the construct (1-indexed in utf-8 characters).
+    pub end_column: usize,
+
+    /// What is wrong with the construct, without its location.
+    pub message: String,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,14 +1,13 @@
 use accessors_rs::Accessors;
-use convert_case::{Case, Casing};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     ffi::{OsStr, OsString}, path::PathBuf,
 };
 
-use crate::{Error, RefdTy, Span, Ty};
+use crate::{Casing, Error, RefdTy, Span, Ty};
 
 #[serde_as]
 #[derive(Accessors, Clone, Debug, Serialize, Deserialize)]
@@ -23,6 +22,110 @@ pub struct Idl {
     /// A list of definitions to be exported. Each of them will be located within the crate in question.
     #[serde_as(as = "Vec<(_, _)>")]
     pub(crate) definitions: BTreeMap<QualifiedName, Item>,
+
+    /// The public type aliases of the crate (e.g., `pub type UserId = u64;`) and the types they stand for.
+    /// References to an alias in the definitions are already replaced by the aliased type;
+    /// this is for plugins that want to emit a typedef too.
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub(crate) type_aliases: BTreeMap<QualifiedName, Ty>,
+
+    /// The records and variants whose values contain values of the same type, directly or not
+    /// (e.g., `struct Node { children: Vec<Node> }`), see [`Idl::is_recursive`][].
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) recursive_types: BTreeSet<QualifiedName>,
+}
+
+impl Idl {
+    /// True if values of the type `qname` can contain values of the same type,
+    /// through its fields or the fields of its arms (possibly inside collections, options, or other records and variants).
+    /// Such types are legal in Rust, but they cannot be mapped to a type that inlines its fields.
+    pub fn is_recursive(&self, qname: &QualifiedName) -> bool {
+        self.recursive_types.contains(qname)
+    }
+
+    /// The records and variants whose values are passed to or returned from a function or method
+    /// (as a parameter, a result, an error, or `self`), along with the user types contained in those values.
+    /// A plugin that copies values across the boundary only copies these; e.g., a recursive type (see [`Idl::is_recursive`][])
+    /// that is not among them never has to be copied.
+    pub fn passed_types(&self) -> BTreeSet<QualifiedName> {
+        let mut pending = vec![];
+        for (qname, item) in &self.definitions {
+            let methods = match item {
+                Item::Resource(r) => &r.methods,
+                Item::Interface(i) => &i.methods,
+                Item::Record(r) => &r.methods,
+                Item::Variant(v) => &v.methods,
+                Item::Enum(e) => &e.methods,
+                Item::Function(f) => {
+                    pending.extend(f.signature.tys().flat_map(|ty| ty.user_types()));
+                    continue;
+                }
+                Item::Constant(_) => continue,
+            };
+            for method in methods {
+                if let MethodCategory::BuilderMethod(_) | MethodCategory::InstanceMethod(_) = method.category {
+                    pending.push(qname.clone());
+                }
+                pending.extend(method.signature.tys().flat_map(|ty| ty.user_types()));
+            }
+        }
+
+        let mut passed = BTreeSet::new();
+        while let Some(qname) = pending.pop() {
+            if let Some(item) = self.definitions.get(&qname) {
+                if passed.insert(qname) {
+                    pending.extend(item.contained_types());
+                }
+            }
+        }
+        passed
+    }
+
+    /// Keep only the definitions for which `selected` returns true, along with the user types they refer to,
+    /// directly or not (e.g., the records in the signature of a selected function, and the types of their fields),
+    /// so that code generated for what remains still compiles.
+    /// Type aliases are kept if the types they stand for are.
+    pub fn select(&mut self, mut selected: impl FnMut(&QualifiedName) -> bool) {
+        let mut kept = BTreeSet::new();
+        let mut pending: Vec<QualifiedName> = self.definitions.keys().filter(|qname| selected(qname)).cloned().collect();
+        while let Some(qname) = pending.pop() {
+            if let Some(item) = self.definitions.get(&qname) {
+                if kept.insert(qname) {
+                    pending.extend(item.user_types());
+                }
+            }
+        }
+
+        self.definitions.retain(|qname, _| kept.contains(qname));
+        self.type_aliases.retain(|_, ty| ty.user_types().iter().all(|qname| kept.contains(qname)));
+        self.recursive_types.retain(|qname| kept.contains(qname));
+    }
+}
+
+/// The items of `definitions` that can contain themselves, see [`Idl::is_recursive`][].
+/// An item is recursive if it can be reached from its own [`Item::contained_types`][].
+pub(crate) fn recursive_types(definitions: &BTreeMap<QualifiedName, Item>) -> BTreeSet<QualifiedName> {
+    let contained_types = |qname: &QualifiedName| match definitions.get(qname) {
+        Some(item) => item.contained_types(),
+        None => vec![],
+    };
+    definitions
+        .keys()
+        .filter(|&qname| {
+            let mut visited = BTreeSet::new();
+            let mut pending = contained_types(qname);
+            while let Some(next) = pending.pop() {
+                if next == *qname {
+                    return true;
+                }
+                if visited.insert(next.clone()) {
+                    pending.extend(contained_types(&next));
+                }
+            }
+            false
+        })
+        .cloned()
+        .collect()
 }
 
 #[derive(Accessors, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -52,23 +155,21 @@ impl QualifiedName {
     }
 
     /// Convert all names to "camelCase".
+    #[deprecated = "use `gluegun_core::casing::Casing::to_camel_case`"]
     pub fn camel_case(&self) -> QualifiedName {
-        let names = self
-            .names
-            .iter()
-            .map(|name| Name::from(name.text.to_case(Case::Camel)))
-            .collect();
-        QualifiedName { names }
+        self.to_camel_case()
     }
 
     /// Convert all names to "UpperCamelCase".
+    #[deprecated = "use `gluegun_core::casing::Casing::to_pascal_case`"]
     pub fn upper_camel_case(&self) -> QualifiedName {
-        let names = self
-            .names
-            .iter()
-            .map(|name| Name::from(name.text.to_case(Case::UpperCamel)))
-            .collect();
-        QualifiedName { names }
+        self.to_pascal_case()
+    }
+
+    /// Convert all names to "snake_case".
+    #[deprecated = "use `gluegun_core::casing::Casing::to_snake_case`"]
+    pub fn snake_case(&self) -> QualifiedName {
+        self.to_snake_case()
     }
 
     /// Create a qualified name from a vector
@@ -102,14 +203,6 @@ impl QualifiedName {
         }
     }
 
-    /// Set the name to whatever the module is that contains `name` (removes the last item;
-    /// errors if `name` is empty).
-    pub(crate) fn set_to_module_of(&mut self, name: &QualifiedName) {
-        assert!(!name.names.is_empty());
-        let len = name.names.len();
-        self.names.extend(name.names.iter().take(len - 1).cloned());
-    }
-
     /// Clear to an empty list
     pub(crate) fn clear(&mut self) {
         self.names.clear();
@@ -126,6 +219,12 @@ impl From<&Name> for QualifiedName {
     }
 }
 
+impl FromIterator<Name> for QualifiedName {
+    fn from_iter<I: IntoIterator<Item = Name>>(names: I) -> Self {
+        QualifiedName::new(names.into_iter().collect())
+    }
+}
+
 impl<N> From<&[N]> for QualifiedName
 where 
 N: Into<Name> + Copy
@@ -149,17 +248,21 @@ impl Name {
     }
 
     /// Convert name to "camelCase".
+    #[deprecated = "use `gluegun_core::casing::Casing::to_camel_case`"]
     pub fn camel_case(&self) -> Name {
-        Name {
-            text: self.text.to_case(Case::Camel),
-        }
+        self.to_camel_case()
     }
 
     /// Convert name to "UpperCamelCase".
+    #[deprecated = "use `gluegun_core::casing::Casing::to_pascal_case`"]
     pub fn upper_camel_case(&self) -> Name {
-        Name {
-            text: self.text.to_case(Case::UpperCamel),
-        }
+        self.to_pascal_case()
+    }
+
+    /// Convert name to "snake_case".
+    #[deprecated = "use `gluegun_core::casing::Casing::to_snake_case`"]
+    pub fn snake_case(&self) -> Name {
+        self.to_snake_case()
     }
 
     pub fn output() -> Self {
@@ -235,6 +338,10 @@ pub enum Item {
     /// It typically maps to a class or something like it.
     Resource(Resource),
 
+    /// An *Interface* is a set of methods to be implemented in the target language,
+    /// e.g., a callback. It typically maps to an interface or abstract class.
+    Interface(Interface),
+
     /// A *Record* is a structure with a known (and fixed) set of fields and types.
     /// It should map to a value type if that is available.
     Record(Record),
@@ -250,18 +357,113 @@ pub enum Item {
     /// A *Function* is a standalone function that can be called.
     /// Note that each of the various types can also have attached methods.
     Function(Function),
+
+    /// A *Constant* is a `pub const` or `pub static` item of a module, like `pub const VERSION: &str = "1.0";`.
+    /// Its value is a literal, so target languages can declare a constant with the same value.
+    Constant(Constant),
 }
 
 impl Item {
+    /// The name exported to other languages, see [`Function::name`][].
     pub fn name(&self) -> &Name {
         match self {
             Item::Resource(r) => &r.name,
+            Item::Interface(i) => &i.name,
             Item::Record(r) => &r.name,
             Item::Variant(v) => &v.name,
             Item::Enum(e) => &e.name,
             Item::Function(f) => &f.name,
+            Item::Constant(c) => &c.name,
         }
     }
+
+    /// The name of the item in Rust, see [`Function::rust_name`][].
+    pub fn rust_name(&self) -> &Name {
+        match self {
+            Item::Resource(r) => &r.rust_name,
+            Item::Interface(i) => &i.rust_name,
+            Item::Record(r) => &r.rust_name,
+            Item::Variant(v) => &v.rust_name,
+            Item::Enum(e) => &e.rust_name,
+            Item::Function(f) => &f.rust_name,
+            Item::Constant(c) => &c.rust_name,
+        }
+    }
+
+    /// The doc comment on the item, if any (see [`Function::docs`][]).
+    pub fn docs(&self) -> &Option<String> {
+        match self {
+            Item::Resource(r) => &r.docs,
+            Item::Interface(i) => &i.docs,
+            Item::Record(r) => &r.docs,
+            Item::Variant(v) => &v.docs,
+            Item::Enum(e) => &e.docs,
+            Item::Function(f) => &f.docs,
+            Item::Constant(c) => &c.docs,
+        }
+    }
+
+    /// The generic type parameters of the item, see [`Function::generics`][].
+    /// C-like enums cannot have any.
+    pub fn generics(&self) -> &[Name] {
+        match self {
+            Item::Resource(r) => &r.generics,
+            Item::Interface(i) => &i.generics,
+            Item::Record(r) => &r.generics,
+            Item::Variant(v) => &v.generics,
+            Item::Enum(_) | Item::Constant(_) => &[],
+            Item::Function(f) => &f.generics,
+        }
+    }
+
+    /// The user-defined types the item refers to: in the types of its fields and in the signatures of its methods
+    /// (or of the function), possibly with duplicates.
+    pub fn user_types(&self) -> Vec<QualifiedName> {
+        let mut tys: Vec<&Ty> = vec![];
+        let methods = match self {
+            Item::Resource(r) => &r.methods,
+            Item::Interface(i) => &i.methods,
+            Item::Record(r) => {
+                tys.extend(r.fields.iter().map(|field| &field.ty));
+                &r.methods
+            }
+            Item::Variant(v) => {
+                tys.extend(v.arms.iter().flat_map(|arm| &arm.fields).map(|field| &field.ty));
+                &v.methods
+            }
+            Item::Enum(e) => &e.methods,
+            Item::Function(f) => return f.signature.tys().flat_map(|ty| ty.user_types()).collect(),
+            Item::Constant(c) => return c.ty.user_types(),
+        };
+        tys.extend(methods.iter().flat_map(|method| method.signature.tys()));
+        tys.iter().flat_map(|ty| ty.user_types()).collect()
+    }
+
+    /// The user-defined types whose values are part of a value of the item, possibly with duplicates:
+    /// those in the types of the fields of a record or of the arms of a variant.
+    /// Unlike [`Self::user_types`][], the signatures of methods are not included.
+    pub fn contained_types(&self) -> Vec<QualifiedName> {
+        let fields: Vec<&Field> = match self {
+            Item::Record(r) => r.fields.iter().collect(),
+            Item::Variant(v) => v.arms.iter().flat_map(|arm| &arm.fields).collect(),
+            Item::Resource(_) | Item::Interface(_) | Item::Enum(_) | Item::Function(_) | Item::Constant(_) => vec![],
+        };
+        fields.iter().flat_map(|field| field.ty.user_types()).collect()
+    }
+
+    /// True if the item or any of its methods has generic type parameters.
+    /// Plugins that cannot map generics use this to report them up front.
+    pub fn is_generic(&self) -> bool {
+        let methods = match self {
+            Item::Resource(r) => &r.methods,
+            Item::Interface(i) => &i.methods,
+            Item::Record(r) => &r.methods,
+            Item::Variant(v) => &v.methods,
+            Item::Enum(e) => &e.methods,
+            Item::Function(_) | Item::Constant(_) => return !self.generics().is_empty(),
+        };
+        !self.generics().is_empty() || methods.iter().any(|m| !m.generics.is_empty())
+    }
 }
 
 #[derive(Accessors, Clone, Debug, Serialize, Deserialize)]
@@ -269,8 +471,21 @@ impl Item {
 pub struct Function {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
-    /// Name in Rust syntax, like `crate::foo::bar`, relative
+    /// Name exported to other languages: the Rust name, unless overridden with `#[gluegun(name = "...")]`.
     pub(crate) name: Name,
+
+    /// Name of the item in Rust, which generated Rust code uses to refer to it.
+    /// Differs from [`Function::name`][] only if the item was renamed.
+    pub(crate) rust_name: Name,
+
+    /// Text of the doc comment (`///` or `#[doc = "..."]`), with one line per line of the comment
+    /// and the common leading indentation removed; `None` if there is no doc comment.
+    pub(crate) docs: Option<String>,
+
+    /// Names of the generic type parameters, like `T` in `fn identity<T>(x: T) -> T`.
+    /// Bounds (`T: Clone`) are accepted but not recorded.
+    pub(crate) generics: Vec<Name>,
+
     pub(crate) signature: Signature,
 }
 
@@ -281,7 +496,72 @@ pub struct Function {
 pub struct Resource {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
+
+    /// Exported name, see [`Function::name`][].
     pub(crate) name: Name,
+
+    /// Name of the item in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// Generic type parameters, see [`Function::generics`][].
+    pub(crate) generics: Vec<Name>,
+
+    /// Public constants declared in inherent impls, like `pub const MAX: u32 = 100;`.
+    pub(crate) constants: Vec<Constant>,
+
+    pub(crate) methods: Vec<Method>,
+}
+
+/// A constant associated with a type, like `MAX` in `impl Foo { pub const MAX: u32 = 100; }`,
+/// or a constant of a module (see [`Item::Constant`][]).
+/// Only constants whose value is a literal are supported, so target languages can declare them with the same value.
+#[derive(Accessors, Clone, Debug, Serialize, Deserialize)]
+#[accessors(get)]
+pub struct Constant {
+    /// Span identifying this item in Rust source (currently its name).
+    pub(crate) span: Span,
+
+    /// Exported name, see [`Function::name`][].
+    pub(crate) name: Name,
+
+    /// Name of the item in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// Type of the constant; a `&'static str` constant has the string type.
+    pub(crate) ty: Ty,
+
+    /// Value of the constant, checked to suit `ty`.
+    pub(crate) value: Literal,
+}
+
+/// An *Interface* is a set of methods to be implemented in the target language,
+/// e.g., a callback. It typically maps to an interface or abstract class.
+/// In Rust, it is a public trait.
+#[derive(Accessors, Clone, Debug, Serialize, Deserialize)]
+#[accessors(get)]
+pub struct Interface {
+    /// Span identifying this item in Rust source (currently its name).
+    pub(crate) span: Span,
+
+    /// Exported name, see [`Function::name`][].
+    pub(crate) name: Name,
+
+    /// Name of the item in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// Generic type parameters, see [`Function::generics`][].
+    pub(crate) generics: Vec<Name>,
+
+    /// The methods declared by the trait; associated functions without `self` are static methods.
     pub(crate) methods: Vec<Method>,
 }
 
@@ -292,7 +572,23 @@ pub struct Resource {
 pub struct Variant {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
+
+    /// Exported name, see [`Function::name`][].
     pub(crate) name: Name,
+
+    /// Name of the item in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// Generic type parameters, see [`Function::generics`][].
+    pub(crate) generics: Vec<Name>,
+
+    /// True if the enum is declared `#[non_exhaustive]`, so arms may be added later:
+    /// a value coming from Rust may have an arm not listed in `arms`.
+    pub(crate) non_exhaustive: bool,
+
     pub(crate) arms: Vec<VariantArm>,
     pub(crate) methods: Vec<Method>,
 }
@@ -303,9 +599,27 @@ pub struct VariantArm {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
     pub(crate) name: Name,
+
+    /// How the fields are written in Rust, needed to construct or match the arm.
+    pub(crate) fields_kind: FieldsKind,
+
+    /// Fields of the arm; positional fields are named `f0`, `f1`, etc.
     pub(crate) fields: Vec<Field>,
 }
 
+/// How the fields of a [`VariantArm`][] are written in Rust.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum FieldsKind {
+    /// Like `Circle { radius: f64 }`.
+    Named,
+
+    /// Like `Rectangle(f64, f64)`.
+    Tuple,
+
+    /// Like `Empty`.
+    Unit,
+}
+
 /// An *Enum* is corresponds to a C-like Rust enum.
 /// It should map to a value type if that is available.
 #[derive(Accessors, Clone, Debug, Serialize, Deserialize)]
@@ -313,7 +627,19 @@ pub struct VariantArm {
 pub struct Enum {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
+
+    /// Exported name, see [`Function::name`][].
     pub(crate) name: Name,
+
+    /// Name of the item in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// True if the enum is declared `#[non_exhaustive]`, see [`Variant::non_exhaustive`][].
+    pub(crate) non_exhaustive: bool,
+
     pub(crate) arms: Vec<EnumArm>,
     pub(crate) methods: Vec<Method>,
 }
@@ -336,11 +662,25 @@ pub struct Method {
     /// Method category
     pub(crate) category: MethodCategory,
 
-    /// Name of the method.
+    /// Exported name of the method, see [`Function::name`][].
     pub(crate) name: Name,
 
+    /// Name of the method in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// Generic type parameters, see [`Function::generics`][].
+    pub(crate) generics: Vec<Name>,
+
     /// Method signature.
     pub(crate) signature: Signature,
+
+    /// Public name of the exported trait whose impl provides the method, or `None` for an inherent method.
+    /// Bindings calling the method as `Type::method` have to bring the trait into scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) trait_name: Option<QualifiedName>,
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
@@ -355,6 +695,11 @@ pub enum MethodCategory {
     /// `fn method(self, ...) -> Self`. They can be treated as ordinary methods
     /// but in some cases you may wish to map types that have builder methods
     /// in some other way.
+    ///
+    /// A method is a builder method if it takes `self` or `&mut self` and returns
+    /// `Self` (or the type itself, by name); `&self` methods are not, since they cannot
+    /// update the value being built. This can be overridden with `#[gluegun(builder)]`
+    /// or `#[gluegun(not_builder)]` on the method.
     BuilderMethod(SelfKind),
 
     /// Some kind of method that takes `self`, `&self`, or `&mut self`.
@@ -387,16 +732,49 @@ pub struct Record {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
 
-    /// Name of the record.
+    /// Exported name of the record, see [`Function::name`][].
     pub(crate) name: Name,
 
-    /// List of fields and their types.
+    /// Name of the item in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
+    /// Generic type parameters, see [`Function::generics`][].
+    pub(crate) generics: Vec<Name>,
+
+    /// List of fields and their types, including private ones (see [`Field::public`][]).
     pub(crate) fields: Vec<Field>,
 
+    /// True if the struct is declared `#[non_exhaustive]`, so fields may be added later:
+    /// like a struct with private fields, it cannot be built outside of its crate.
+    pub(crate) non_exhaustive: bool,
+
     /// Methods attached to this record.
     pub(crate) methods: Vec<Method>,
 }
 
+impl Record {
+    /// The public fields of the record, the only ones that should be visible in the target language.
+    pub fn public_fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields.iter().filter(|field| field.public)
+    }
+
+    /// True if some fields are private, in which case the record cannot be built from its public fields:
+    /// a value can only come from Rust (e.g., from a function returning the record).
+    pub fn has_private_fields(&self) -> bool {
+        self.fields.iter().any(|field| !field.public)
+    }
+
+    /// True if the record can be built from its public fields outside of its crate,
+    /// i.e., it has no private fields and is not `#[non_exhaustive]`.
+    /// Otherwise a value can only come from Rust.
+    pub fn is_constructible(&self) -> bool {
+        !self.non_exhaustive && !self.has_private_fields()
+    }
+}
+
 /// A field in a record.
 #[derive(Accessors, Clone, Debug, Serialize, Deserialize)]
 #[accessors(get)]
@@ -404,11 +782,26 @@ pub struct Field {
     /// Span identifying this item in Rust source (currently its name).
     pub(crate) span: Span,
 
-    /// Name of the field.
+    /// Exported name of the field, see [`Function::name`][].
     pub(crate) name: Name,
 
+    /// Name of the field in Rust, see [`Function::rust_name`][].
+    pub(crate) rust_name: Name,
+
+    /// Doc comment, see [`Function::docs`][].
+    pub(crate) docs: Option<String>,
+
     /// Type of the field.
     pub(crate) ty: Ty,
+
+    /// True if the field is declared `pub` (always the case for the fields of an enum variant).
+    /// Private fields cannot be read or set outside of the Rust crate, so they should not cross the FFI boundary.
+    pub(crate) public: bool,
+
+    /// True if the field is marked `#[gluegun(readonly)]`: it is given a value when the record is built,
+    /// but should not be settable afterwards (e.g., no setter should be generated for it).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) readonly: bool,
 }
 
 /// Signature to a function or method.
@@ -424,6 +817,21 @@ pub struct Signature {
 
     /// Function return type.
     pub(crate) output_ty: FunctionOutput,
+
+    /// True if the result should not be ignored by callers: either the function is
+    /// annotated with `#[must_use]` or it returns a type that is.
+    pub(crate) must_use: bool,
+}
+
+impl Signature {
+    /// The types of the inputs and outputs.
+    fn tys(&self) -> impl Iterator<Item = &Ty> {
+        self.inputs
+            .iter()
+            .map(|input| input.refd_ty.ty())
+            .chain([self.output_ty.main_ty.ty()])
+            .chain(&self.output_ty.error_ty)
+    }
 }
 
 /// Indicates if this is an async method or not.
@@ -443,8 +851,50 @@ pub struct FunctionInput {
     /// Name of the function parameter.
     pub(crate) name: Name,
 
+    /// Doc comment, see [`Function::docs`][].
+    /// Rust does not accept `///` on parameters, so this can only come from a `#[doc]` attribute
+    /// that some macro strips before compilation.
+    pub(crate) docs: Option<String>,
+
     /// Type of the function parameter.
     pub(crate) refd_ty: RefdTy,
+
+    /// Value to use when the caller omits this parameter, from `#[gluegun(default(name = literal))]`.
+    /// Backends for languages with default arguments can emit it, those that support overloading
+    /// can generate an additional overload without the parameter; others ignore it.
+    pub(crate) default_value: Option<Literal>,
+}
+
+/// A literal value, such as the default value of a [`FunctionInput`][].
+/// It has been checked to suit the type it is given for.
+///
+/// Numbers are kept as their decimal digits (without `_` separators or a type suffix),
+/// so that no precision is lost whatever the width of the type.
+///
+/// The `Display` impl gives the Rust syntax of the literal.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Literal {
+    Boolean(bool),
+    Char(char),
+
+    /// An integer, possibly negative (e.g., `-1`).
+    Integer(String),
+
+    /// A floating-point number, possibly negative (e.g., `-0.5` or `1e3`).
+    Float(String),
+
+    String(String),
+}
+
+impl std::fmt::Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Literal::Boolean(value) => write!(f, "{value}"),
+            Literal::Char(value) => write!(f, "{value:?}"),
+            Literal::Integer(digits) | Literal::Float(digits) => write!(f, "{digits}"),
+            Literal::String(value) => write!(f, "{value:?}"),
+        }
+    }
 }
 
 /// Function return type. This includes a "main" return type
@@ -3,7 +3,7 @@ use std::{borrow::Cow, sync::Arc};
 use accessors_rs::Accessors;
 use serde::{Deserialize, Serialize};
 
-use crate::{QualifiedName, Span};
+use crate::{Name, QualifiedName, Span};
 
 #[derive(Accessors, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
 pub struct Ty {
@@ -51,6 +51,21 @@ impl Ty {
         )
     }
 
+    /// Returns the never type `!`, used for functions that never return.
+    pub(crate) fn never(span: Span) -> Self {
+        Self::new(span, TypeKind::Never)
+    }
+
+    /// True if this is the unit type `()`.
+    pub fn is_unit(&self) -> bool {
+        matches!(&*self.kind, TypeKind::Tuple { elements, repr: _ } if elements.is_empty())
+    }
+
+    /// True if this is the never type `!`.
+    pub fn is_never(&self) -> bool {
+        matches!(&*self.kind, TypeKind::Never)
+    }
+
     /// Create a [`RefdTy`][] with reference kind `kind`.
     pub fn refd(self, kind: RefKind) -> RefdTy {
         RefdTy::Ref(kind, self)
@@ -60,6 +75,46 @@ impl Ty {
     pub fn owned(self) -> RefdTy {
         RefdTy::Owned(OwnedKind::Owned, self)
     }
+
+    /// The user-defined types this type refers to, like `Point` in `Vec<Option<Point>>`.
+    pub fn user_types(&self) -> Vec<QualifiedName> {
+        let mut qnames = vec![];
+        self.push_user_types(&mut qnames);
+        qnames
+    }
+
+    fn push_user_types(&self, qnames: &mut Vec<QualifiedName>) {
+        match &*self.kind {
+            TypeKind::Map { key, value, repr: _ } => {
+                key.push_user_types(qnames);
+                value.push_user_types(qnames);
+            }
+            TypeKind::Vec { element, repr: _ }
+            | TypeKind::Array { element, len: _ }
+            | TypeKind::Set { element, repr: _ }
+            | TypeKind::Option { element, repr: _ } => element.push_user_types(qnames),
+            TypeKind::Result { ok, err, repr: _ } => {
+                ok.push_user_types(qnames);
+                err.push_user_types(qnames);
+            }
+            TypeKind::Tuple { elements, repr: _ } => {
+                for element in elements {
+                    element.push_user_types(qnames);
+                }
+            }
+            TypeKind::Future { output, repr: _ } => output.push_user_types(qnames),
+            TypeKind::Iterator { item, repr: _ } => item.push_user_types(qnames),
+            TypeKind::UserType { qname } => qnames.push(qname.clone()),
+            TypeKind::Bytes { .. }
+            | TypeKind::Path { .. }
+            | TypeKind::Duration { .. }
+            | TypeKind::String { .. }
+            | TypeKind::Scalar(_)
+            | TypeKind::Error { .. }
+            | TypeKind::Never
+            | TypeKind::TypeParameter { .. } => {}
+        }
+    }
 }
 
 impl std::fmt::Display for Ty {
@@ -67,8 +122,11 @@ impl std::fmt::Display for Ty {
         match &*self.kind {
             TypeKind::Map { key, value, repr: _ } => write!(f, "Map<{}, {}>", key, value),
             TypeKind::Vec { element, repr: _ } => write!(f, "Vec<{}>", element),
+            TypeKind::Array { element, len } => write!(f, "[{}; {}]", element, len),
+            TypeKind::Bytes { repr: _ } => write!(f, "Bytes"),
             TypeKind::Set { element , repr: _} => write!(f, "Set<{}>", element),
             TypeKind::Path { repr: _ } => write!(f, "Path"),
+            TypeKind::Duration { repr: _ } => write!(f, "Duration"),
             TypeKind::String { repr: _ } => write!(f, "String"),
             TypeKind::Option { element, repr: _ } => write!(f, "Option<{}>", element),
             TypeKind::Result { ok, err, repr: _ } => write!(f, "Result<{}, {}>", ok, err),
@@ -86,8 +144,11 @@ impl std::fmt::Display for Ty {
             }
             TypeKind::Scalar(s) => write!(f, "{}", s),
             TypeKind::Future { output, repr: _ } => write!(f, "impl Future<Output = {}>", output),
+            TypeKind::Iterator { item, repr: _ } => write!(f, "impl Iterator<Item = {}>", item),
             TypeKind::Error { repr: _ } => write!(f, "Error"),
+            TypeKind::Never => write!(f, "!"),
             TypeKind::UserType { qname  } => write!(f, "{}", qname.to_string("::")),
+            TypeKind::TypeParameter { name } => write!(f, "{name}"),
         }
     }
 }
@@ -104,6 +165,16 @@ pub enum TypeKind {
         element: Ty,
         repr: VecRepr,
     },
+    /// A fixed-size array `[T; N]`; plugins may map it to a fixed-length type or treat it as a list.
+    Array {
+        element: Ty,
+        len: usize,
+    },
+    /// Binary data: a `Vec<u8>` or `&[u8]`, unless marked `#[gluegun(no_bytes)]` (then it is a [`TypeKind::Vec`][]).
+    /// Plugins should map it to their native byte array (e.g., `byte[]` or `bytes`) rather than a list of numbers.
+    Bytes {
+        repr: BytesRepr,
+    },
     Set {
         element: Ty,
         repr: MapSetRepr,
@@ -111,6 +182,11 @@ pub enum TypeKind {
     Path {
         repr: PathRepr,
     },
+    /// A span of time: a `std::time::Duration`, unless marked `#[gluegun(no_duration)]` (then `Duration` is looked up among the user's types).
+    /// Plugins should map it to their native duration type (e.g., `java.time.Duration` or `datetime.timedelta`).
+    Duration {
+        repr: DurationRepr,
+    },
     String {
         repr: StringRepr,
     },
@@ -135,15 +211,30 @@ pub enum TypeKind {
         repr: FutureRepr,
     },
 
+    Iterator {
+        item: Ty,
+        repr: IteratorRepr,
+    },
+
     // Represents a generic exception/error type.
     Error {
         repr: ErrorRepr,
     },
 
+    /// The never type `!`, only permitted as the return type of a function that never returns.
+    /// Distinct from the unit type `()`, which is an empty [`TypeKind::Tuple`][].
+    Never,
+
     /// Type defined by the user
     UserType {
         qname: QualifiedName,
     },
+
+    /// A generic type parameter (like `T`) of the enclosing item or function,
+    /// see [`Function::generics`](`crate::Function::generics`).
+    TypeParameter {
+        name: Name,
+    },
 }
 
 impl TypeKind {
@@ -163,8 +254,11 @@ impl std::fmt::Display for TypeKind {
         match self {
             TypeKind::Map { key, value, repr: _ } => write!(f, "Map<{}, {}>", key, value)?,
             TypeKind::Vec { element, repr: _ } => write!(f, "Vec<{}>", element)?,
+            TypeKind::Array { element, len } => write!(f, "[{}; {}]", element, len)?,
+            TypeKind::Bytes { repr: _ } => write!(f, "Bytes")?,
             TypeKind::Set { element, repr: _ } => write!(f, "Set<{}>", element)?,
             TypeKind::Path { repr: _ } => write!(f, "Path")?,
+            TypeKind::Duration { repr: _ } => write!(f, "Duration")?,
             TypeKind::String { repr: _ } => write!(f, "String")?, 
             TypeKind::Option { element, repr: _ } => write!(f, "Option<{}>", element)?,
             TypeKind::Result { ok, err, repr: _ } => write!(f, "Result<{}, {}>", ok, err)?,
@@ -182,8 +276,11 @@ impl std::fmt::Display for TypeKind {
             },
             TypeKind::Scalar(scalar) => write!(f, "{}", scalar)?,
             TypeKind::Future { output, repr: _ } => write!(f, "impl Future<Output = {}>", output)?,
+            TypeKind::Iterator { item, repr: _ } => write!(f, "impl Iterator<Item = {}>", item)?,
             TypeKind::Error { repr: _ } => write!(f, "Error")?,
+            TypeKind::Never => write!(f, "!")?,
             TypeKind::UserType { qname } => write!(f, "{}", qname.to_string("::"))?,
+            TypeKind::TypeParameter { name } => write!(f, "{name}")?,
         }
         Ok(())
     }
@@ -201,6 +298,9 @@ pub enum StringRepr {
     
     /// impl ToString
     ImplToString,
+
+    /// impl Into<String>
+    ImplIntoString,
 }
 
 /// Different patterns that we recognize as being a "Vec" in Rust code.
@@ -214,15 +314,39 @@ pub enum VecRepr {
     SliceRef,
 }
 
-/// Different patterns that we recognize as being a "Map" in Rust code.
+/// Different patterns that we recognize as being "Bytes" in Rust code.
+#[non_exhaustive]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum BytesRepr {
+    /// `Vec<u8>`
+    Vec,
+
+    /// `&[u8]` (of some kind)
+    SliceRef,
+}
+
+/// Different patterns that we recognize as being a "Map" or "Set" in Rust code.
 #[non_exhaustive]
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
 pub enum MapSetRepr {
+    /// `HashMap` or `HashSet`, iterated in no particular order
     Hash,
+
+    /// `BTreeMap` or `BTreeSet`, iterated in sorted order
     BTree,
+
+    /// `IndexMap` or `IndexSet`, iterated in insertion order
     Index,
 }
 
+impl MapSetRepr {
+    /// True if the map or set is iterated in sorted order (by key, for a map),
+    /// which the target language may rely on (e.g., by choosing a `TreeMap` rather than a `HashMap` in Java).
+    pub fn is_ordered(&self) -> bool {
+        matches!(self, MapSetRepr::BTree)
+    }
+}
+
 /// Different patterns that we recognize as being a "Path" in Rust code.
 #[non_exhaustive]
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
@@ -234,6 +358,14 @@ pub enum PathRepr {
     PathBuf,
 }
 
+/// Different patterns that we recognize as being a "Duration" in Rust code.
+#[non_exhaustive]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum DurationRepr {
+    /// `std::time::Duration` (or `core::time::Duration`), which is never negative and counts nanoseconds in a `u64` of seconds and a `u32` of nanoseconds
+    Duration,
+}
+
 /// Different patterns that we recognize as being a "Option" in Rust code.
 #[non_exhaustive]
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
@@ -269,6 +401,14 @@ pub enum FutureRepr {
     PinBoxDynFuture(AutoTraits),
 }
 
+/// Different patterns that we recognize as being an "Iterator" in Rust code.
+#[non_exhaustive]
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum IteratorRepr {
+    /// `impl Iterator<Item = T>`
+    ImplIterator(AutoTraits),
+}
+
 /// Different patterns that we recognize as being an "Error" in Rust code.
 #[non_exhaustive]
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
@@ -289,6 +429,12 @@ pub struct AutoTraits {
     unpin: bool,
 }
 
+impl AutoTraits {
+    pub(crate) fn new(send: bool, sync: bool, unpin: bool) -> Self {
+        AutoTraits { send, sync, unpin }
+    }
+}
+
 /// Recognized scalar types.
 ///
 /// The `Display` impl gives their Rust names.
@@ -301,10 +447,12 @@ pub enum Scalar {
     I16,
     I32,
     I64,
+    I128,
     U8,
     U16,
     U32,
     U64,
+    U128,
     F32,
     F64,
 }
@@ -318,10 +466,12 @@ impl Scalar {
             Scalar::I16 => "i16",
             Scalar::I32 => "i32",
             Scalar::I64 => "i64",
+            Scalar::I128 => "i128",
             Scalar::U8 => "u8",
             Scalar::U16 => "u16",
             Scalar::U32 => "u32",
             Scalar::U64 => "u64",
+            Scalar::U128 => "u128",
             Scalar::F32 => "f32",
             Scalar::F64 => "f64",
         })
@@ -347,7 +497,7 @@ pub enum RefdTy {
 impl RefdTy {
     pub fn ty(&self) -> &Ty {
         match self {
-            RefdTy::Owned(OwnedKind::Owned, ty) => ty,
+            RefdTy::Owned(_, ty) => ty,
             RefdTy::Ref(_, ty) => ty,
         }
     }
@@ -359,11 +509,20 @@ impl RefdTy {
         }
     }
 
+    /// If this is an owned type, how it is owned (e.g., through a `Box`).
+    pub fn owned_kind(&self) -> Option<&OwnedKind> {
+        match self {
+            RefdTy::Owned(kind, _) => Some(kind),
+            RefdTy::Ref(..) => None,
+        }
+    }
+
     /// If this is an owned type, return it, else return an error.
     /// Used when backends do not support reference types in a particular position.
     pub fn owned_or_err(&self) -> crate::Result<&Ty> {
         match self {
             RefdTy::Owned(OwnedKind::Owned, ty) => Ok(ty),
+            RefdTy::Owned(_, ty) => Err(crate::Error::SmartPointerNotPermitted(ty.span().clone())),
             RefdTy::Ref(ref_kind, ty) => {
                 Err(crate::Error::ReferenceType(
                     ty.span().clone(),
@@ -378,19 +537,98 @@ impl std::fmt::Display for RefdTy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RefdTy::Owned(OwnedKind::Owned, ty) => write!(f, "{}", ty),
+            RefdTy::Owned(kind, ty) => write!(f, "{kind}<{}>", kind.pointee_display(ty)),
             RefdTy::Ref(RefKind::AnonRef, ty) => write!(f, "&{}", ty),
+            RefdTy::Ref(RefKind::AnonRefMut, ty) => write!(f, "&mut {}", ty),
             RefdTy::Ref(RefKind::ImplAsRef, ty) => write!(f, "impl AsRef<{}>", ty),
         }
     }
 }
 
-/// Indicates the style of ownership. For now this is always just `T`
-/// but we may expand in the future to include `Box<T>`, for example.
+/// Indicates the style of ownership: `T` on its own, or behind a smart pointer.
+///
+/// Smart pointers are transparent: the type is that of the value they point to
+/// (`String` for `str`, and `Vec<T>` for `[T]`), so target languages see `T`.
+/// Only the Rust glue calling the function needs to know about the pointer,
+/// see [`OwnedKind::rust_from_value`][] and [`OwnedKind::rust_into_value`][].
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum OwnedKind {
     /// `T` on its own
     Owned,
+
+    /// `Box<T>`
+    Box(Pointee),
+
+    /// `std::rc::Rc<T>`
+    Rc(Pointee),
+
+    /// `std::sync::Arc<T>`
+    Arc(Pointee),
+}
+
+/// What a smart pointer (see [`OwnedKind`][]) points to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Pointee {
+    /// A value of the type, like `Box<Point>`
+    Sized,
+
+    /// `str`, like `Box<str>`; the type is `String`
+    Str,
+
+    /// `[T]`, like `Arc<[T]>`; the type is `Vec<T>`
+    Slice,
+}
+
+impl OwnedKind {
+    /// Rust expression turning `expr`, a value of the type (e.g., a `String` for a `Box<str>`), into this kind of value.
+    pub fn rust_from_value(&self, expr: impl std::fmt::Display) -> String {
+        match self {
+            OwnedKind::Owned => expr.to_string(),
+            // `From` converts `T`, `String`, and `Vec<T>` into all the smart pointers.
+            OwnedKind::Box(_) | OwnedKind::Rc(_) | OwnedKind::Arc(_) => format!("{expr}.into()"),
+        }
+    }
+
+    /// Rust code to put before and after an expression of this kind to get a value of the type (e.g., a `String` for a `Box<str>`).
+    /// The value is moved out of a `Box`, and cloned out of an `Rc` or `Arc` unless it is the last reference,
+    /// so a sized type behind an `Rc` or `Arc` must implement `Clone`.
+    pub fn rust_into_value(&self) -> (&'static str, &'static str) {
+        match self {
+            OwnedKind::Owned => ("", ""),
+            OwnedKind::Box(Pointee::Sized) => ("(*", ")"),
+            OwnedKind::Box(Pointee::Str) => ("String::from(", ")"),
+            OwnedKind::Box(Pointee::Slice) => ("Vec::from(", ")"),
+            OwnedKind::Rc(Pointee::Sized) => ("std::rc::Rc::unwrap_or_clone(", ")"),
+            OwnedKind::Arc(Pointee::Sized) => ("std::sync::Arc::unwrap_or_clone(", ")"),
+            OwnedKind::Rc(Pointee::Str) | OwnedKind::Arc(Pointee::Str) => ("String::from(&*", ")"),
+            OwnedKind::Rc(Pointee::Slice) | OwnedKind::Arc(Pointee::Slice) => ("Vec::from(&*", ")"),
+        }
+    }
+
+    /// The Rust type pointed to, given the type `ty` of the value.
+    fn pointee_display(&self, ty: &Ty) -> String {
+        match self {
+            OwnedKind::Box(Pointee::Str) | OwnedKind::Rc(Pointee::Str) | OwnedKind::Arc(Pointee::Str) => "str".to_string(),
+            OwnedKind::Box(Pointee::Slice) | OwnedKind::Rc(Pointee::Slice) | OwnedKind::Arc(Pointee::Slice) => match ty.kind() {
+                TypeKind::Vec { element, repr: _ } => format!("[{element}]"),
+                _ => "[u8]".to_string(), // bytes
+            },
+            _ => ty.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for OwnedKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OwnedKind::Owned => Ok(()),
+            OwnedKind::Box(_) => write!(f, "Box"),
+            OwnedKind::Rc(_) => write!(f, "Rc"),
+            OwnedKind::Arc(_) => write!(f, "Arc"),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -399,14 +637,26 @@ pub enum RefKind {
     /// `&T` with no specified lifetime
     AnonRef,
 
+    /// `&mut T` with no specified lifetime.
+    /// The callee may change the value, so backends must lend it mutably rather than share it.
+    AnonRefMut,
+
     /// `impl AsRef<T>`
     ImplAsRef,
 }
 
+impl RefKind {
+    /// True for a mutable borrow (`&mut T`).
+    pub fn is_mut(&self) -> bool {
+        matches!(self, RefKind::AnonRefMut)
+    }
+}
+
 impl std::fmt::Display for RefKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RefKind::AnonRef => write!(f, "&"),
+            RefKind::AnonRefMut => write!(f, "&mut"),
             RefKind::ImplAsRef => write!(f, "impl AsRef"),
         }
     }
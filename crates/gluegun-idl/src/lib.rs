@@ -1,9 +1,11 @@
+mod casing;
 mod error;
 mod ir_items;
 mod ir_types;
 mod parse;
 mod span;
 
+pub use casing::*;
 pub use error::*;
 pub use ir_items::*;
 pub use ir_types::*;
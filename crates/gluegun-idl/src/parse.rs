@@ -1,17 +1,34 @@
-use std::path::{Path, PathBuf};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use crate::{Error, Idl, Name, QualifiedName, SourcePath};
 
 pub struct Parser {
+    /// The Rust source files read so far, see [`Self::source_files`][].
+    source_files: Vec<PathBuf>,
 }
 
 impl Parser {
     pub fn new() -> Self {
         Self {
+            source_files: vec![],
         }
     }
 
+    /// The Rust source files looked for while parsing crates so far, in order, including any that did not exist
+    /// (e.g., the module file of a private module that could not be found).
+    /// The IDL of a crate only depends on these files, so it need not be parsed again while they are unchanged.
+    pub fn source_files(&self) -> &[PathBuf] {
+        &self.source_files
+    }
+
     /// Parse the crate with the given name and the path to its `lib.rs`.
+    /// Public modules are followed, whether inline or declared with `mod foo;`
+    /// (in which case the module is read from `foo.rs` or `foo/mod.rs`, or the file named by a `#[path]` attribute,
+    /// as `rustc` would).
     /// 
     /// * `crate_name`, name of the crate in Rust code
     /// * `cargo_path`, path to include in output as the path to the crate, normally the manifest dir
@@ -25,15 +42,32 @@ impl Parser {
         let crate_name: Name = crate_name.into();
         let rs_path: &Path = rs_path.as_ref();
         let arena = AstArena::default();
+        let result = self.parse_with_arena(&arena, crate_name, crate_path.into(), rs_path);
+        self.source_files.extend(arena.paths.into_inner());
+        result
+    }
+
+    fn parse_with_arena(
+        &self,
+        arena: &AstArena,
+        crate_name: Name,
+        crate_path: PathBuf,
+        rs_path: &Path,
+    ) -> crate::Result<Idl> {
         let ast = arena.parse_file(rs_path)?;
         let crate_qname = QualifiedName::from(&crate_name);
         let source = SourcePath::new(rs_path);
-        let recognized = pass1::Recognizer::new(&source, crate_qname, ast).into_recognized()?;
+        let module_dir = rs_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let recognized =
+            pass1::Recognizer::new(arena, &source, crate_qname, &ast.items, module_dir.clone(), module_dir)
+                .into_recognized()?;
         let elaborated = pass2::Elaborator::new(recognized).into_elaborated_items()?;
         Ok(Idl {
             crate_name,
-            crate_path: crate_path.into(),
-            definitions: elaborated,
+            crate_path,
+            recursive_types: crate::ir_items::recursive_types(&elaborated.items),
+            definitions: elaborated.items,
+            type_aliases: elaborated.type_aliases,
         })
     }
 
@@ -90,35 +124,79 @@ fn extract_crate_name(rs_path: &Path) -> crate::Result<Name> {
 #[derive(Default)]
 struct AstArena {
     files: typed_arena::Arena<syn::File>,
+
+    /// The path of each file that was looked for, even if it could not be read or parsed.
+    paths: RefCell<Vec<PathBuf>>,
 }
 
 impl AstArena {
     fn parse_file(&self, path: &Path) -> crate::Result<&syn::File> {
+        self.paths.borrow_mut().push(path.to_path_buf());
         let contents = std::fs::read_to_string(path)?;
-        let file = syn::parse_file(&contents)?;
+        let file = syn::parse_file(&contents)
+            .map_err(|err| Error::Parse(SourcePath::new(path).span(err.span()), err.to_string()))?;
         Ok(self.files.alloc(file))
     }
 }
 
 /// Internal intermediate structure representing some kind of public user-visible definition.
+#[derive(Clone)]
 struct Definition<'p> {
-    /// The syn module from which this was parsed.
-    module: &'p syn::File,
+    /// The name of the module in which this was defined; names in the definition are resolved relative to it.
+    module_name: QualifiedName,
+
+    /// The name under which the definition is visible outside the crate: where it is defined, if that is public,
+    /// or else where it is re-exported with `pub use` (if it is). `None` if it is not part of the public interface.
+    public_name: Option<QualifiedName>,
 
     /// The path which the definition was parsed from.
     source: SourcePath,
 
     /// The kind of definition.
     kind: DefinitionKind<'p>,
+
+    /// The `impl` blocks providing the methods of the definition, wherever they are in the crate:
+    /// its inherent impls, then its impls of exported traits. Empty for a re-export.
+    impls: Vec<ImplBlock<'p>>,
+}
+
+impl<'p> Definition<'p> {
+    /// The definition that this one re-exports, or `self` if it is not a re-export.
+    fn resolve<'d>(&'d self, recognized: &'d BTreeMap<QualifiedName, Definition<'p>>) -> &'d Definition<'p> {
+        match &self.kind {
+            DefinitionKind::ReExport(target) => &recognized[target],
+            _ => self,
+        }
+    }
+}
+
+/// An `impl` block of a definition, which may be in another module (and file) than the definition itself.
+#[derive(Clone)]
+struct ImplBlock<'p> {
+    /// The name of the module containing the block; names in its methods are resolved relative to it.
+    module_name: QualifiedName,
+
+    /// The path which the block was parsed from.
+    source: SourcePath,
+
+    /// The public name of the trait the block implements, or `None` for an inherent impl.
+    trait_name: Option<QualifiedName>,
+
+    item: &'p syn::ItemImpl,
 }
 
 /// Internal intermediate structure representing kind of some public user-visible definition.
 /// The names reference [WIT](https://component-model.bytecodealliance.org/design/wit.html).
+#[derive(Clone)]
 enum DefinitionKind<'p> {
     /// *Resources* are "class-like" structures defined by their methods.
     /// In Rust, they are represented by a struct with private fields or a `#[non_exhaustive]` attribute.
     Resource(&'p syn::ItemStruct),
 
+    /// *Interfaces* are sets of methods meant to be implemented in the target language (e.g., callbacks).
+    /// In Rust, they are represented by a public trait.
+    Interface(&'p syn::ItemTrait),
+
     /// *Records* are "struct-like" structures defined by their fields.
     /// In Rust, they are represented by a struct with public fields and no `#[non_exhaustive]` attribute.
     Record(&'p syn::ItemStruct),
@@ -134,10 +212,24 @@ enum DefinitionKind<'p> {
     /// *Functions* are top-level, callable functions (!).
     Function(&'p syn::ItemFn),
 
-    /// *Modules* are public Rust modules; unlike the other variants, these are not mapped to output items,
-    /// but they are used in name resolution.
-    #[expect(dead_code)]
-    FileModule,
+    /// *Constants* are `pub const` items whose value is a literal.
+    Constant(&'p syn::ItemConst),
+
+    /// *Statics* (`pub static`, but not `pub static mut`) are mapped to constants like `pub const` items.
+    Static(&'p syn::ItemStatic),
+
+    /// *Type aliases* (`pub type Foo = Bar;`) are not mapped to output items either;
+    /// references to them are replaced by the aliased type. Private aliases are recognized too, without a public name.
+    TypeAlias(&'p syn::ItemType),
+
+    /// *Re-exports* (`pub use foo::Bar;`) make the definition with the given name available under another name
+    /// (which is never itself a re-export); they are not mapped to output items either.
+    ReExport(QualifiedName),
+
+    /// *Modules* are Rust modules, either inline (`mod foo { ... }`) or in their own file (`mod foo;`);
+    /// unlike the other variants, these are not mapped to output items, but they are used in name resolution.
+    /// Private modules are included too, since their items may be re-exported.
+    Module,
 }
 
 /// Pass 1: Recognize types, imports, and things. Don't fill out the details (fields, methods).
@@ -150,4 +242,7 @@ mod known_rust;
 
 mod util;
 
+/// Parsing of `#[gluegun(...)]` attributes.
+mod attributes;
+
 mod modifier;
\ No newline at end of file
@@ -0,0 +1,152 @@
+use syn::punctuated::Punctuated;
+
+use crate::{Error, Literal, Name};
+
+use super::{util, SourcePath};
+
+/// Options supplied through `#[gluegun(...)]` attributes on an item.
+#[derive(Default, Debug)]
+pub(super) struct GluegunAttributes {
+    /// Default values for function parameters, from `#[gluegun(default(name = literal, ...))]`.
+    /// They are checked against the parameter types once those are known.
+    pub(super) defaults: Vec<(syn::Ident, Literal)>,
+
+    /// Whether a method is a builder method, from `#[gluegun(builder)]` or `#[gluegun(not_builder)]`;
+    /// if `None`, this is decided from its signature.
+    pub(super) builder: Option<bool>,
+
+    /// If true, `Vec<u8>` and `&[u8]` are lists of numbers rather than bytes, from `#[gluegun(no_bytes)]`.
+    pub(super) no_bytes: bool,
+
+    /// If true, `Duration` is not `std::time::Duration` but a type of the user's, from `#[gluegun(no_duration)]`.
+    pub(super) no_duration: bool,
+
+    /// Name under which the item is exported, from `#[gluegun(name = "...")]`.
+    pub(super) name: Option<Name>,
+
+    /// If true, a record field can only be set when the record is built, from `#[gluegun(readonly)]`.
+    pub(super) readonly: bool,
+}
+
+impl GluegunAttributes {
+    /// Returns the default value given for the parameter `name`, if any.
+    pub(super) fn default_value(&self, name: &Name) -> Option<Literal> {
+        self.defaults
+            .iter()
+            .find(|(ident, _)| util::recognize_name(ident) == *name)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// The name under which to export an item called `rust_name` in Rust.
+    pub(super) fn exported_name(&self, rust_name: &Name) -> Name {
+        self.name.clone().unwrap_or_else(|| rust_name.clone())
+    }
+}
+
+/// True if `attr` is a `#[gluegun(...)]` attribute (possibly written `#[gluegun::gluegun(...)]`).
+fn is_gluegun_attribute(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "gluegun")
+}
+
+/// True if `attrs` include `#[gluegun(skip)]`, possibly among other options.
+/// Malformed attributes are not reported here, but by [`parse_gluegun_attributes`][] if the item is not skipped.
+pub(super) fn is_skipped(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().filter(|attr| is_gluegun_attribute(attr)).any(|attr| {
+        attr.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
+            .is_ok_and(|options| options.iter().any(|option| option.path().is_ident("skip")))
+    })
+}
+
+/// Parse all `#[gluegun(...)]` attributes from `attrs`.
+pub(super) fn parse_gluegun_attributes(
+    source: &SourcePath,
+    attrs: &[syn::Attribute],
+) -> crate::Result<GluegunAttributes> {
+    let mut result = GluegunAttributes::default();
+
+    for attr in attrs.iter().filter(|attr| is_gluegun_attribute(attr)) {
+        // A bare `#[gluegun]` has no options; it lets the fields of a struct or enum have some (see the `gluegun` macro).
+        if let syn::Meta::Path(_) = attr.meta {
+            continue;
+        }
+
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                meta.parse_nested_meta(|meta| {
+                    let Some(ident) = meta.path.get_ident() else {
+                        return Err(meta.error("expected a parameter name"));
+                    };
+                    let value: syn::Expr = meta.value()?.parse()?;
+                    result.defaults.push((ident.clone(), parse_literal(&value)?));
+                    Ok(())
+                })
+            } else if meta.path.is_ident("skip") {
+                // Skipped items are ignored before their attributes are parsed, see `is_skipped`.
+                Ok(())
+            } else if meta.path.is_ident("name") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                if !is_identifier(&value.value()) {
+                    return Err(syn::Error::new(value.span(), "expected an identifier"));
+                }
+                result.name = Some(Name::from(value.value()));
+                Ok(())
+            } else if meta.path.is_ident("builder") || meta.path.is_ident("not_builder") {
+                let builder = meta.path.is_ident("builder");
+                if result.builder == Some(!builder) {
+                    return Err(meta.error("`builder` and `not_builder` cannot be used together"));
+                }
+                result.builder = Some(builder);
+                Ok(())
+            } else if meta.path.is_ident("no_bytes") {
+                result.no_bytes = true;
+                Ok(())
+            } else if meta.path.is_ident("no_duration") {
+                result.no_duration = true;
+                Ok(())
+            } else if meta.path.is_ident("readonly") {
+                result.readonly = true;
+                Ok(())
+            } else {
+                Err(meta.error("unrecognized gluegun option"))
+            }
+        })
+        .map_err(|err| Error::InvalidAttribute(source.span(attr), err.to_string()))?;
+    }
+
+    Ok(result)
+}
+
+/// Parse `expr`, which must be a literal, possibly a negated number (e.g., `-1`).
+pub(super) fn parse_literal(expr: &syn::Expr) -> syn::Result<Literal> {
+    let literal = match expr {
+        syn::Expr::Lit(syn::ExprLit { lit, attrs: _ }) => match lit {
+            syn::Lit::Bool(value) => Some(Literal::Boolean(value.value)),
+            syn::Lit::Char(value) => Some(Literal::Char(value.value())),
+            syn::Lit::Int(value) => Some(Literal::Integer(value.base10_digits().to_string())),
+            syn::Lit::Float(value) => Some(Literal::Float(value.base10_digits().to_string())),
+            syn::Lit::Str(value) => Some(Literal::String(value.value())),
+            _ => None,
+        },
+        syn::Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr: operand, attrs: _ })
+            if matches!(**operand, syn::Expr::Lit(_)) =>
+        {
+            match parse_literal(operand)? {
+                Literal::Integer(digits) => Some(Literal::Integer(format!("-{digits}"))),
+                Literal::Float(digits) => Some(Literal::Float(format!("-{digits}"))),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    literal.ok_or_else(|| syn::Error::new_spanned(expr, "expected a literal"))
+}
+
+/// True if `text` can be used as a name in the target languages: ASCII letters, digits, and `_`, not starting with a digit.
+/// Keywords are accepted, since a keyword in Rust need not be one in the target language.
+fn is_identifier(text: &str) -> bool {
+    text.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use crate::{AutoTraits, Error, Name, RefdTy, Scalar, Span, StringRepr, Ty, TypeKind};
+use crate::{AutoTraits, Error, Name, OwnedKind, Pointee, RefdTy, Scalar, Span, StringRepr, Ty, TypeKind};
 
 use super::modifier::Modifier;
 
@@ -111,20 +111,30 @@ pub(super) const KNOWN_RUST_TYPES: &[KnownRustType] = known_rust_types! {
     [Modifier::Ref(r)] str[][] @ span => TypeKind::String { repr: StringRepr::StrRef }.refd(span, r),
 
     [] std::vec::Vec[element][] @ span => TypeKind::Vec { element, repr: crate::VecRepr::Vec, }.not_refd(span),
-    [] std::collections::HashMap[key, value][] @ span =>TypeKind::Map { key, value, repr: crate::MapSetRepr::BTree }.not_refd(span),
+    [] std::collections::HashMap[key, value][] @ span => TypeKind::Map { key, value, repr: crate::MapSetRepr::Hash }.not_refd(span),
+    [] std::collections::hash_map::HashMap[key, value][] @ span => TypeKind::Map { key, value, repr: crate::MapSetRepr::Hash }.not_refd(span),
     [] std::collections::BTreeMap[key, value][] @ span => TypeKind::Map { key, value, repr: crate::MapSetRepr::BTree }.not_refd(span),
-    [] std::collections::HashSet[element][] @ span =>TypeKind::Set { element, repr: crate::MapSetRepr::BTree }.not_refd(span),
+    [] std::collections::btree_map::BTreeMap[key, value][] @ span => TypeKind::Map { key, value, repr: crate::MapSetRepr::BTree }.not_refd(span),
+    [] std::collections::HashSet[element][] @ span => TypeKind::Set { element, repr: crate::MapSetRepr::Hash }.not_refd(span),
+    [] std::collections::hash_set::HashSet[element][] @ span => TypeKind::Set { element, repr: crate::MapSetRepr::Hash }.not_refd(span),
     [] std::collections::BTreeSet[element][] @ span => TypeKind::Set { element, repr: crate::MapSetRepr::BTree }.not_refd(span),
+    [] std::collections::btree_set::BTreeSet[element][] @ span => TypeKind::Set { element, repr: crate::MapSetRepr::BTree }.not_refd(span),
     [Modifier::Ref(r)] std::path::Path[][] @ span => TypeKind::Path { repr: crate::PathRepr::PathRef }.refd(span, r),
     [] std::path::PathBuf[][] @ span => TypeKind::Path { repr: crate::PathRepr::PathBuf }.not_refd(span),
+    [] std::time::Duration[][] @ span => TypeKind::Duration { repr: crate::DurationRepr::Duration }.not_refd(span),
+    [] core::time::Duration[][] @ span => TypeKind::Duration { repr: crate::DurationRepr::Duration }.not_refd(span),
 
+    [] char[][] @ span => TypeKind::Scalar(Scalar::Char).not_refd(span),
+    [] u8[][] @ span => TypeKind::Scalar(Scalar::U8).not_refd(span),
     [] u16[][] @ span => TypeKind::Scalar(Scalar::U16).not_refd(span),
     [] u32[][] @ span => TypeKind::Scalar(Scalar::U32).not_refd(span),
     [] u64[][] @ span => TypeKind::Scalar(Scalar::U64).not_refd(span),
+    [] u128[][] @ span => TypeKind::Scalar(Scalar::U128).not_refd(span),
     [] i8[][] @ span => TypeKind::Scalar(Scalar::I8).not_refd(span),
     [] i16[][] @ span => TypeKind::Scalar(Scalar::I16).not_refd(span),
     [] i32[][] @ span => TypeKind::Scalar(Scalar::I32).not_refd(span),
     [] i64[][] @ span => TypeKind::Scalar(Scalar::I64).not_refd(span),
+    [] i128[][] @ span => TypeKind::Scalar(Scalar::I128).not_refd(span),
     [] f32[][] @ span => TypeKind::Scalar(Scalar::F32).not_refd(span),
     [] f64[][] @ span => TypeKind::Scalar(Scalar::F64).not_refd(span),
 
@@ -135,9 +145,38 @@ pub(super) const KNOWN_RUST_TYPES: &[KnownRustType] = known_rust_types! {
 /// Known Rust types that we recognize from the std library or elsewhere.
 pub(super) const KNOWN_RUST_IMPL_TRAIT_TYPES: &[KnownRustType] = known_rust_types! {
     [] std::string::ToString[][] @ span => TypeKind::String { repr: StringRepr::ImplToString }.not_refd(span),
+    [] std::convert::Into[target][] @ span => match target.kind() {
+        TypeKind::String { repr: StringRepr::String } => TypeKind::String { repr: StringRepr::ImplIntoString }.not_refd(span),
+        _ => return Err(Error::UnsupportedUseOfType(span)),
+    },
     [] std::task::Future[][Output = output] @ span => TypeKind::Future { output, repr: crate::FutureRepr::ImplFuture(AutoTraits::default()) }.not_refd(span),
+    [] std::iter::Iterator[][Item = item] @ span => TypeKind::Iterator { item, repr: crate::IteratorRepr::ImplIterator(AutoTraits::default()) }.not_refd(span),
 
     ---
     
     std::convert::AsRef => Modifier::Ref(crate::RefKind::ImplAsRef),
 };
+
+/// Defines a smart pointer that is transparent in the IDL (see [`crate::OwnedKind`][]).
+pub(super) struct KnownSmartPointer {
+    /// A path, beginning with the crate name.
+    pub(super) name: &'static [&'static str],
+
+    /// The kind recording the pointer, given what it points to.
+    pub(super) owned_kind: fn(Pointee) -> OwnedKind,
+}
+
+/// Smart pointers that we unwrap to the type they point to.
+pub(super) const KNOWN_SMART_POINTERS: &[KnownSmartPointer] = &[
+    KnownSmartPointer { name: &["std", "boxed", "Box"], owned_kind: OwnedKind::Box },
+    KnownSmartPointer { name: &["std", "rc", "Rc"], owned_kind: OwnedKind::Rc },
+    KnownSmartPointer { name: &["std", "sync", "Arc"], owned_kind: OwnedKind::Arc },
+];
+
+/// Types whose value can only be reached by locking or borrowing them, so a smart pointer to one cannot be unwrapped.
+pub(super) const KNOWN_INTERIOR_MUTABILITY_TYPES: &[&[&str]] = &[
+    &["std", "sync", "Mutex"],
+    &["std", "sync", "RwLock"],
+    &["std", "cell", "RefCell"],
+    &["std", "cell", "Cell"],
+];
@@ -1,37 +1,102 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, path::PathBuf, rc::Rc};
 
 use syn::spanned::Spanned;
 
 use crate::{Error, Span, QualifiedName, SourcePath};
 
-use super::{util, Definition, DefinitionKind};
+use super::{util, AstArena, Definition, DefinitionKind, ImplBlock};
 
 pub(super) struct Recognizer<'ast> {
+    arena: &'ast AstArena,
     source: SourcePath,
     module_name: QualifiedName,
-    ast: &'ast syn::File,
+    items: &'ast [syn::Item],
+
+    /// True if this module is part of the public interface (it and all the modules containing it are public).
+    exported: bool,
+
+    /// Directory in which the files of submodules declared with `mod foo;` are found.
+    module_dir: PathBuf,
+
+    /// Directory that `#[path = "..."]` attributes on submodules are relative to:
+    /// the directory of the current file, or [`Self::module_dir`][] within an inline module.
+    path_dir: PathBuf,
+
     recognized: BTreeMap<QualifiedName, Definition<'ast>>,
+
+    /// The `pub use` items found so far, resolved once all definitions are recognized.
+    reexports: Vec<ReExport<'ast>>,
+
+    /// The `impl` blocks found so far, attached to the definitions they are for once those are all recognized.
+    impls: Vec<Impl<'ast>>,
+}
+
+/// A name (or glob) brought in by a `pub use` item.
+struct ReExport<'ast> {
+    /// The module containing the `pub use` item.
+    module_name: QualifiedName,
+
+    /// True if that module is part of the public interface.
+    exported: bool,
+
+    /// The path that module was parsed from.
+    source: SourcePath,
+
+    /// The path to the module containing the re-exported item(s), as written.
+    path: Vec<&'ast syn::Ident>,
+
+    /// The re-exported item and the name under which it is re-exported, or `None` for a glob (`pub use foo::*;`).
+    leaf: Option<(&'ast syn::Ident, &'ast syn::Ident)>,
+
+    /// The `pub use` item, for error reporting.
+    item: &'ast syn::ItemUse,
+}
+
+/// An `impl` block, see [`resolve_impls`][].
+struct Impl<'ast> {
+    /// The module containing the block.
+    module_name: QualifiedName,
+
+    /// The items of that module (whose `use` items may name the type or trait) and the path they were parsed from.
+    module: &'ast [syn::Item],
+    source: SourcePath,
+
+    item: &'ast syn::ItemImpl,
 }
 
+/// What [`Recognizer::recognize_module`][] found in a module and its submodules.
+type Recognized<'ast> = (BTreeMap<QualifiedName, Definition<'ast>>, Vec<ReExport<'ast>>, Vec<Impl<'ast>>);
+
 impl<'ast> Recognizer<'ast> {
     pub(super) fn new(
+        arena: &'ast AstArena,
         source: &SourcePath,
         module_name: QualifiedName,
-        ast: &'ast syn::File,
+        items: &'ast [syn::Item],
+        module_dir: PathBuf,
+        path_dir: PathBuf,
     ) -> Self {
         Self {
+            arena,
             source: source.clone(),
             module_name,
-            ast,
+            items,
+            module_dir,
+            path_dir,
+            exported: true,
             recognized: BTreeMap::new(),
+            reexports: vec![],
+            impls: vec![],
         }
     }
 
-    fn definition(&self, kind: DefinitionKind<'ast>) -> Definition<'ast> {
+    fn definition(&self, qname: &QualifiedName, kind: DefinitionKind<'ast>) -> Definition<'ast> {
         Definition {
             kind,
             source: self.source.clone(),
-            module: self.ast,
+            module_name: self.module_name.clone(),
+            public_name: self.exported.then(|| qname.clone()),
+            impls: vec![],
         }
     }
 
@@ -39,11 +104,26 @@ impl<'ast> Recognizer<'ast> {
         variant(self.source.span(spanned))
     }
 
-    pub(super) fn into_recognized(mut self) -> crate::Result<Arc<BTreeMap<QualifiedName, Definition<'ast>>>> {
-        for item in &self.ast.items {
-            self.recognize_item(item)?;
+    pub(super) fn into_recognized(self) -> crate::Result<Rc<BTreeMap<QualifiedName, Definition<'ast>>>> {
+        let (mut recognized, reexports, impls) = self.recognize_module()?;
+        resolve_reexports(&mut recognized, reexports)?;
+        resolve_impls(&mut recognized, impls);
+        Ok(Rc::new(recognized))
+    }
+
+    /// Recognize the items of this module and, recursively, of its submodules,
+    /// returning the definitions and the (unresolved) `pub use` items and `impl` blocks found.
+    ///
+    /// Items that cannot be recognized are an error only if they are part of the public interface;
+    /// elsewhere they are skipped (and so cannot be re-exported).
+    fn recognize_module(mut self) -> crate::Result<Recognized<'ast>> {
+        for item in self.items {
+            match self.recognize_item(item) {
+                Err(_) if !self.exported => {}
+                result => result?,
+            }
         }
-        Ok(Arc::new(self.recognized))
+        Ok((self.recognized, self.reexports, self.impls))
     }
 
     fn recognize_item(&mut self, item: &'ast syn::Item) -> crate::Result<()> {
@@ -54,6 +134,10 @@ impl<'ast> Recognizer<'ast> {
 
             syn::Item::Fn(item) => self.recognize_fn(item),
 
+            syn::Item::Const(item) => self.recognize_const(item),
+
+            syn::Item::Static(item) => self.recognize_static(item),
+
             syn::Item::Mod(item) => self.recognize_mod(item),
 
             syn::Item::Trait(item) => self.recognize_trait(item),
@@ -62,8 +146,7 @@ impl<'ast> Recognizer<'ast> {
 
             syn::Item::Use(item) => self.recognize_use(item),
 
-            // Ignore impls
-            syn::Item::Impl(_item) => Ok(()),
+            syn::Item::Impl(item) => self.recognize_impl(item),
 
             _ => Err(self.error(crate::Error::UnsupportedItem, item)),
         }
@@ -77,37 +160,34 @@ impl<'ast> Recognizer<'ast> {
         let name = util::recognize_name(&item.ident);
         let qname = self.module_name.join(&name);
 
-        if item.generics.params.len() > 0 {
-            return Err(self.error(Error::GenericsNotPermitted, &item.generics));
-        }
-
-        let public_fields = item
+        // Fields marked `#[gluegun(skip)]` are left out of the record, so they don't count.
+        let fields = item
             .fields
+            .iter()
+            .filter(|field| !util::ignore_from_attrs(&field.attrs))
+            .collect::<Vec<_>>();
+        let public_fields = fields
             .iter()
             .filter(|field| util::is_public(&field.vis))
             .count();
 
-        if public_fields > 0 && public_fields == item.fields.len() {
-            // All public fields: this is a struct.
+        if public_fields > 0 {
+            // Some public fields: this is a struct.
+            // Any private fields are hidden, so that a value can only be built in Rust (see `Record::has_private_fields`).
             //
             // It can have methods, but they have to be `&self` or `self`.
             self.recognized.insert(
-                qname,
-                self.definition(DefinitionKind::Record(item)),
+                qname.clone(),
+                self.definition(&qname, DefinitionKind::Record(item)),
             );
-            Ok(())
-        } else if public_fields == 0 {
+        } else {
             // All private fields, this is a class
             self.recognized.insert(
-                qname,
-                self.definition(DefinitionKind::Resource(item)),
+                qname.clone(),
+                self.definition(&qname, DefinitionKind::Resource(item)),
             );
-            Ok(())
-        } else {
-            // Some public, some private fields -- error.
-
-            Err(self.error(Error::MixedPublicPrivateFields, item))
         }
+        Ok(())
     }
 
     fn recognize_enum(&mut self, item: &'ast syn::ItemEnum) -> crate::Result<()> {
@@ -115,10 +195,6 @@ impl<'ast> Recognizer<'ast> {
             return Ok(());
         }
 
-        if item.generics.params.len() > 0 {
-            return Err(self.error(Error::GenericsNotPermitted, &item.generics));
-        }
-
         let unignored_variants = item
             .variants
             .iter()
@@ -135,14 +211,17 @@ impl<'ast> Recognizer<'ast> {
 
         if variants_have_args {
             self.recognized.insert(
-                qname,
-                self.definition(DefinitionKind::Variant(item, unignored_variants)),
+                qname.clone(),
+                self.definition(&qname, DefinitionKind::Variant(item, unignored_variants)),
             );
             Ok(())
+        } else if !item.generics.params.is_empty() {
+            // Only enums with data-carrying variants can make use of type parameters.
+            Err(self.error(Error::GenericsNotPermitted, &item.generics))
         } else {
             self.recognized.insert(
-                qname,
-                self.definition(DefinitionKind::Enum(item, unignored_variants)),
+                qname.clone(),
+                self.definition(&qname, DefinitionKind::Enum(item, unignored_variants)),
             );
             Ok(())
         }
@@ -156,46 +235,481 @@ impl<'ast> Recognizer<'ast> {
         let name = util::recognize_name(&item.sig.ident);
         let qname = self.module_name.join(&name);
 
-        if item.sig.generics.params.len() > 0 {
-            return Err(self.error(Error::GenericsNotPermitted, &item.sig.generics));
+        self.recognized.insert(
+            qname.clone(),
+            self.definition(&qname, DefinitionKind::Function(item)),
+        );
+        Ok(())
+    }
+
+    fn recognize_const(&mut self, item: &'ast syn::ItemConst) -> crate::Result<()> {
+        if util::ignore(&item.vis, &item.attrs) {
+            return Ok(());
         }
 
+        if !item.generics.params.is_empty() {
+            return Err(self.error(Error::GenericsNotPermitted, &item.generics));
+        }
+
+        let qname = self.module_name.join(util::recognize_name(&item.ident));
         self.recognized.insert(
-            qname,
-            self.definition(DefinitionKind::Function(item)),
+            qname.clone(),
+            self.definition(&qname, DefinitionKind::Constant(item)),
         );
         Ok(())
     }
 
-    fn recognize_mod(&self, item: &syn::ItemMod) -> Result<(), Error> {
+    fn recognize_static(&mut self, item: &'ast syn::ItemStatic) -> crate::Result<()> {
         if util::ignore(&item.vis, &item.attrs) {
             return Ok(());
         }
 
-        Err(self.error(crate::Error::UnsupportedItem, item))
+        // The value of a `static mut` may change, so it cannot be copied into the target language.
+        if let syn::StaticMutability::Mut(_) = item.mutability {
+            return Err(self.error(Error::UnsupportedItem, item));
+        }
+
+        let qname = self.module_name.join(util::recognize_name(&item.ident));
+        self.recognized.insert(
+            qname.clone(),
+            self.definition(&qname, DefinitionKind::Static(item)),
+        );
+        Ok(())
     }
 
-    fn recognize_trait(&self, item: &syn::ItemTrait) -> Result<(), Error> {
-        if util::ignore(&item.vis, &item.attrs) {
+    /// Recognize a submodule; private modules are followed too, since their items may be re-exported.
+    fn recognize_mod(&mut self, item: &'ast syn::ItemMod) -> Result<(), Error> {
+        if util::ignore_from_attrs(&item.attrs) {
             return Ok(());
         }
 
-        Err(self.error(crate::Error::UnsupportedItem, item))
+        let name = util::recognize_name(&item.ident);
+        let qname = self.module_name.join(&name);
+        let exported = self.exported && util::is_public(&item.vis);
+
+        let mut submodule = match self.load_submodule(item, &qname) {
+            Ok(submodule) => submodule,
+            // Private modules only matter for what they re-export, so one that cannot be loaded is skipped,
+            // just like the items in them that we do not understand.
+            Err(_) if !exported => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        submodule.exported = exported;
+
+        self.recognized.insert(qname.clone(), self.definition(&qname, DefinitionKind::Module));
+        let (recognized, reexports, impls) = submodule.recognize_module()?;
+        self.recognized.extend(recognized);
+        self.reexports.extend(reexports);
+        self.impls.extend(impls);
+        Ok(())
     }
 
-    fn recognize_type(&self, item: &syn::ItemType) -> Result<(), Error> {
+    /// Create the recognizer for the items of the module `item`, named `qname`, reading its file if it is not inline.
+    fn load_submodule(&self, item: &'ast syn::ItemMod, qname: &QualifiedName) -> Result<Recognizer<'ast>, Error> {
+        let name = util::recognize_name(&item.ident);
+        let child_dir = self.module_dir.join(name.text());
+
+        Ok(match (&item.content, util::path_attribute(&item.attrs)) {
+            // `mod foo { ... }`: submodules of `foo` are looked up in `foo/`, as if `foo` were in `foo.rs`.
+            (Some((_, items)), _) => Recognizer::new(
+                self.arena,
+                &self.source,
+                qname.clone(),
+                items,
+                child_dir.clone(),
+                child_dir,
+            ),
+
+            // `#[path = "..."] mod foo;`: as with `mod.rs`, submodules are looked up next to the named file.
+            (None, Some(relative_path)) => {
+                let path = self.path_dir.join(relative_path);
+                if !path.is_file() {
+                    return Err(Error::ModulePathNotFound(self.source.span(item), path));
+                }
+                let dir = path.parent().unwrap_or(&self.path_dir).to_path_buf();
+                let ast = self.arena.parse_file(&path)?;
+                Recognizer::new(self.arena, &SourcePath::new(&path), qname.clone(), &ast.items, dir.clone(), dir)
+            }
+
+            // `mod foo;`: the module is in `foo.rs` (with submodules in `foo/`) or `foo/mod.rs`.
+            (None, None) => {
+                let file_path = self.module_dir.join(format!("{name}.rs"));
+                let mod_rs_path = child_dir.join("mod.rs");
+                let path = if file_path.is_file() {
+                    file_path
+                } else if mod_rs_path.is_file() {
+                    mod_rs_path
+                } else {
+                    return Err(Error::ModuleNotFound(self.source.span(&item.ident), name));
+                };
+                let file_dir = path.parent().unwrap_or(&self.module_dir).to_path_buf();
+                let ast = self.arena.parse_file(&path)?;
+                Recognizer::new(self.arena, &SourcePath::new(&path), qname.clone(), &ast.items, child_dir, file_dir)
+            }
+        })
+    }
+
+    fn recognize_trait(&mut self, item: &'ast syn::ItemTrait) -> Result<(), Error> {
         if util::ignore(&item.vis, &item.attrs) {
             return Ok(());
         }
 
-        Err(self.error(crate::Error::UnsupportedItem, item))
+        let name = util::recognize_name(&item.ident);
+        let qname = self.module_name.join(&name);
+
+        self.recognized.insert(
+            qname.clone(),
+            self.definition(&qname, DefinitionKind::Interface(item)),
+        );
+        Ok(())
+    }
+
+    /// Record a type alias. Private aliases are recorded too, since public signatures can use them,
+    /// but they get no public name and so are not part of the interface.
+    fn recognize_type(&mut self, item: &'ast syn::ItemType) -> Result<(), Error> {
+        if util::ignore_from_attrs(&item.attrs) {
+            return Ok(());
+        }
+
+        // References to the alias are replaced by the aliased type, which leaves nowhere to put type arguments.
+        // A private generic alias is left out, and so cannot be used in a public signature.
+        if !item.generics.params.is_empty() {
+            if !util::is_public(&item.vis) {
+                return Ok(());
+            }
+            return Err(self.error(Error::GenericsNotPermitted, &item.generics));
+        }
+
+        let name = util::recognize_name(&item.ident);
+        let qname = self.module_name.join(&name);
+
+        let mut definition = self.definition(&qname, DefinitionKind::TypeAlias(item));
+        if !util::is_public(&item.vis) {
+            definition.public_name = None;
+        }
+        self.recognized.insert(qname, definition);
+        Ok(())
+    }
+
+    /// Record an `impl` block, whatever its type, see [`resolve_impls`][].
+    fn recognize_impl(&mut self, item: &'ast syn::ItemImpl) -> Result<(), Error> {
+        if util::ignore_from_attrs(&item.attrs) {
+            return Ok(());
+        }
+
+        self.impls.push(Impl {
+            module_name: self.module_name.clone(),
+            module: self.items,
+            source: self.source.clone(),
+            item,
+        });
+        Ok(())
     }
 
-    fn recognize_use(&self, item: &syn::ItemUse) -> Result<(), Error> {
+    /// Record the names re-exported by a `pub use` item, see [`resolve_reexports`][].
+    fn recognize_use(&mut self, item: &'ast syn::ItemUse) -> Result<(), Error> {
         if util::ignore(&item.vis, &item.attrs) {
             return Ok(());
         }
 
-        Err(self.error(crate::Error::UnsupportedItem, item))
+        self.recognize_use_tree(item, vec![], &item.tree)
+    }
+
+    fn recognize_use_tree(
+        &mut self,
+        item: &'ast syn::ItemUse,
+        mut path: Vec<&'ast syn::Ident>,
+        tree: &'ast syn::UseTree,
+    ) -> Result<(), Error> {
+        let leaf = match tree {
+            syn::UseTree::Path(tree) => {
+                path.push(&tree.ident);
+                return self.recognize_use_tree(item, path, &tree.tree);
+            }
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.recognize_use_tree(item, path.clone(), tree)?;
+                }
+                return Ok(());
+            }
+            syn::UseTree::Name(name) => Some((&name.ident, &name.ident)),
+            syn::UseTree::Rename(rename) => Some((&rename.ident, &rename.rename)),
+            syn::UseTree::Glob(_) => None,
+        };
+
+        self.reexports.push(ReExport {
+            module_name: self.module_name.clone(),
+            exported: self.exported,
+            source: self.source.clone(),
+            path,
+            leaf,
+            item,
+        });
+        Ok(())
+    }
+}
+
+/// Add the names brought in by `reexports` to `recognized`, as [`DefinitionKind::ReExport`][] definitions,
+/// and give each re-exported definition the first public name it gets, if it had none.
+///
+/// Re-exports can refer to other re-exports (including those brought in by other globs),
+/// so single names and globs are resolved in turn until no more names can be added,
+/// whatever the order in which they appear in the source.
+/// Public re-exports of items from outside the crate cannot be resolved and are an error.
+fn resolve_reexports<'ast>(
+    recognized: &mut BTreeMap<QualifiedName, Definition<'ast>>,
+    reexports: Vec<ReExport<'ast>>,
+) -> crate::Result<()> {
+    let (globs, mut names): (Vec<_>, Vec<_>) = reexports.into_iter().partition(|reexport| reexport.leaf.is_none());
+
+    loop {
+        names = resolve_reexported_names(recognized, names)?;
+
+        let mut added = false;
+        for glob in &globs {
+            let Some(module_name) = reexported_module(recognized, glob) else {
+                if !glob.exported {
+                    continue;
+                }
+                return Err(Error::UnsupportedItem(glob.source.span(glob.item)));
+            };
+            let globbed = recognized
+                .iter()
+                .filter(|(qname, definition)| {
+                    qname.module_name() == module_name
+                        && !matches!(definition.kind, DefinitionKind::Module)
+                        && !matches!(definition.kind, DefinitionKind::TypeAlias(item) if !util::is_public(&item.vis))
+                })
+                .map(|(qname, _)| qname.clone())
+                .collect::<Vec<_>>();
+            for target in globbed {
+                // Names defined in the module itself, or re-exported by name from it (even if not resolved yet),
+                // shadow those brought in by the glob, as in Rust.
+                let qname = glob.module_name.join(target.tail_name());
+                let shadowed = recognized.contains_key(&qname)
+                    || names.iter().any(|name| {
+                        name.module_name == glob.module_name
+                            && util::recognize_name(name.leaf.unwrap().1) == qname.tail_name()
+                    });
+                if !shadowed {
+                    add_reexport(recognized, glob, qname, &target);
+                    added = true;
+                }
+            }
+        }
+
+        if !added {
+            break;
+        }
+    }
+
+    // Re-exports in private modules are only followed to find public names, so unresolved ones do not matter.
+    match names.iter().find(|reexport| reexport.exported) {
+        None => Ok(()),
+        Some(unresolved) => Err(Error::UnsupportedItem(unresolved.source.span(unresolved.item))),
+    }
+}
+
+/// Resolve the re-exported single names that can be, returning the others.
+fn resolve_reexported_names<'ast>(
+    recognized: &mut BTreeMap<QualifiedName, Definition<'ast>>,
+    mut names: Vec<ReExport<'ast>>,
+) -> crate::Result<Vec<ReExport<'ast>>> {
+    loop {
+        let count = names.len();
+        let mut unresolved = vec![];
+        for reexport in names {
+            let (ident, rename) = reexport.leaf.unwrap();
+            let Some(target) = reexported_module(recognized, &reexport)
+                .map(|module_name| module_name.join(util::recognize_name(ident)))
+                .filter(|target| recognized.contains_key(target))
+            else {
+                unresolved.push(reexport);
+                continue;
+            };
+            // Only items can be re-exported, the items of a module have to be re-exported one by one (or with a glob).
+            if let DefinitionKind::Module = recognized[&target].kind {
+                return Err(Error::UnsupportedItem(reexport.source.span(reexport.item)));
+            }
+            let qname = reexport.module_name.join(util::recognize_name(rename));
+            add_reexport(recognized, &reexport, qname, &target);
+        }
+        if unresolved.len() == count {
+            return Ok(unresolved);
+        }
+        names = unresolved;
+    }
+}
+
+/// The module of the crate named by the path of `reexport` (relative to the module containing it,
+/// unless it starts with `crate`), or `None` if there is none.
+fn reexported_module(
+    recognized: &BTreeMap<QualifiedName, Definition<'_>>,
+    reexport: &ReExport<'_>,
+) -> Option<QualifiedName> {
+    resolve_module_path(recognized, &reexport.module_name, &reexport.path)
+}
+
+/// The module of the crate named by `path`, relative to the module `module_name` unless it starts with `crate`,
+/// or `None` if there is none.
+fn resolve_module_path(
+    recognized: &BTreeMap<QualifiedName, Definition<'_>>,
+    module_name: &QualifiedName,
+    path: &[&syn::Ident],
+) -> Option<QualifiedName> {
+    let mut module_name = module_name.clone();
+    for (index, &ident) in path.iter().enumerate() {
+        if index == 0 && ident == "crate" {
+            module_name = module_name.just_crate();
+        } else if index == 0 && ident == "self" {
+            continue;
+        } else if ident == "super" {
+            if module_name.names.len() == 1 {
+                return None;
+            }
+            module_name = module_name.module_name();
+        } else {
+            module_name = module_name.join(util::recognize_name(ident));
+            if !matches!(recognized.get(&module_name), Some(Definition { kind: DefinitionKind::Module, .. })) {
+                return None;
+            }
+        }
+    }
+    Some(module_name)
+}
+
+/// Add `qname` as a re-export of `target` (or of what `target` re-exports, if it is a re-export itself).
+fn add_reexport<'ast>(
+    recognized: &mut BTreeMap<QualifiedName, Definition<'ast>>,
+    reexport: &ReExport<'ast>,
+    qname: QualifiedName,
+    target: &QualifiedName,
+) {
+    let target = match &recognized[target].kind {
+        DefinitionKind::ReExport(target) => target.clone(),
+        _ => target.clone(),
+    };
+
+    if reexport.exported {
+        let definition = recognized.get_mut(&target).unwrap();
+        if definition.public_name.is_none() {
+            definition.public_name = Some(qname.clone());
+        }
+    }
+
+    recognized.insert(
+        qname,
+        Definition {
+            source: reexport.source.clone(),
+            module_name: reexport.module_name.clone(),
+            public_name: None,
+            kind: DefinitionKind::ReExport(target),
+            impls: vec![],
+        },
+    );
+}
+
+/// Attach each of `impls` to the definition of its type, if that is recognized: inherent impls, and impls of
+/// traits that are part of the public interface (other trait impls, like `impl Display for Foo`, are dropped).
+/// The blocks may be in any module of the crate, as long as they name the type (and trait) by a path
+/// that resolves from there, possibly through a `use` item of their module.
+/// Inherent impls come first, so that their methods take precedence over trait methods of the same name.
+fn resolve_impls<'ast>(recognized: &mut BTreeMap<QualifiedName, Definition<'ast>>, impls: Vec<Impl<'ast>>) {
+    let (inherent, trait_impls): (Vec<_>, Vec<_>) = impls.into_iter().partition(|block| block.item.trait_.is_none());
+
+    for block in inherent.into_iter().chain(trait_impls) {
+        let syn::Type::Path(self_ty) = &*block.item.self_ty else {
+            continue;
+        };
+        if self_ty.qself.is_some() {
+            continue;
+        }
+        let Some(target) = resolve_impl_path(recognized, &block, &self_ty.path) else {
+            continue;
+        };
+
+        let trait_name = match &block.item.trait_ {
+            None => None,
+            Some((None, trait_path, _)) => {
+                let Some(definition) = resolve_impl_path(recognized, &block, trait_path).map(|name| &recognized[&name])
+                else {
+                    continue;
+                };
+                match (&definition.kind, &definition.public_name) {
+                    (DefinitionKind::Interface(_), Some(public_name)) => Some(public_name.clone()),
+                    _ => continue,
+                }
+            }
+            // `impl !Trait for Foo`
+            Some((Some(_), _, _)) => continue,
+        };
+
+        let definition = recognized.get_mut(&target).unwrap();
+        if let DefinitionKind::Record(_)
+        | DefinitionKind::Resource(_)
+        | DefinitionKind::Variant(..)
+        | DefinitionKind::Enum(..) = definition.kind
+        {
+            definition.impls.push(ImplBlock {
+                module_name: block.module_name,
+                source: block.source,
+                trait_name,
+                item: block.item,
+            });
+        }
+    }
+}
+
+/// The definition named by `path` (a type or trait in the header of `block`), following re-exports,
+/// or `None` if it is not a recognized definition. A single name is looked up in the module of `block`,
+/// then among the names brought in by its `use` items, public or not.
+fn resolve_impl_path(
+    recognized: &BTreeMap<QualifiedName, Definition<'_>>,
+    block: &Impl<'_>,
+    path: &syn::Path,
+) -> Option<QualifiedName> {
+    if path.leading_colon.is_some() {
+        return None;
+    }
+
+    let idents = path.segments.iter().map(|segment| &segment.ident).collect::<Vec<_>>();
+    let (&ident, module_path) = idents.split_last()?;
+    let qname = match module_path {
+        [] => {
+            let qname = block.module_name.join(util::recognize_name(ident));
+            if recognized.contains_key(&qname) {
+                qname
+            } else {
+                let (use_path, ident) = block.module.iter().find_map(|item| match item {
+                    syn::Item::Use(item) => imported_path(&item.tree, ident, vec![]),
+                    _ => None,
+                })?;
+                resolve_module_path(recognized, &block.module_name, &use_path)?.join(util::recognize_name(ident))
+            }
+        }
+        _ => resolve_module_path(recognized, &block.module_name, module_path)?.join(util::recognize_name(ident)),
+    };
+
+    match &recognized.get(&qname)?.kind {
+        DefinitionKind::ReExport(target) => Some(target.clone()),
+        _ => Some(qname),
+    }
+}
+
+/// If `tree` brings `name` into scope, the path of the module it comes from and its name there.
+fn imported_path<'ast>(
+    tree: &'ast syn::UseTree,
+    name: &syn::Ident,
+    mut path: Vec<&'ast syn::Ident>,
+) -> Option<(Vec<&'ast syn::Ident>, &'ast syn::Ident)> {
+    match tree {
+        syn::UseTree::Path(tree) => {
+            path.push(&tree.ident);
+            imported_path(&tree.tree, name, path)
+        }
+        syn::UseTree::Group(group) => group.items.iter().find_map(|tree| imported_path(tree, name, path.clone())),
+        syn::UseTree::Name(tree) if tree.ident == *name => Some((path, &tree.ident)),
+        syn::UseTree::Rename(tree) if tree.rename == *name => Some((path, &tree.ident)),
+        syn::UseTree::Name(_) | syn::UseTree::Rename(_) | syn::UseTree::Glob(_) => None,
     }
 }
@@ -1,15 +1,17 @@
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, rc::Rc};
 
 use syn::spanned::Spanned;
 
 use crate::{
-    Enum, Error, Field, Function, FunctionInput, FunctionOutput, IsAsync, Item, Method, MethodCategory, Name, OwnedKind, QualifiedName, Record, RefdTy, Resource, SelfKind, Signature, Span, Ty, TypeKind, Variant, VariantArm
+    BytesRepr, Constant, Enum, Error, Field, FieldsKind, Function, FunctionInput, FunctionOutput, Interface, IsAsync, Item, Literal, Method, MethodCategory, Name, OwnedKind, Pointee, QualifiedName, Record, RefdTy, Resource, Scalar, SelfKind, Signature, Span, Ty, TypeKind, Variant, VariantArm
 };
 
 use super::{
     known_rust::{
-        KnownRustFn, KnownRustType, RustPath, KNOWN_RUST_IMPL_TRAIT_TYPES, KNOWN_RUST_TYPES,
+        KnownRustFn, KnownRustType, RustPath, KNOWN_INTERIOR_MUTABILITY_TYPES, KNOWN_RUST_IMPL_TRAIT_TYPES,
+        KNOWN_RUST_TYPES, KNOWN_SMART_POINTERS,
     },
+    attributes,
     modifier::Modifier,
     util, Definition, DefinitionKind, SourcePath,
 };
@@ -17,17 +19,39 @@ use super::{
 pub(super) struct Elaborator<'arena> {
     source: Option<SourcePath>,
     module_qname: QualifiedName,
-    recognized: Arc<BTreeMap<QualifiedName, Definition<'arena>>>,
+    recognized: Rc<BTreeMap<QualifiedName, Definition<'arena>>>,
     out_items: BTreeMap<QualifiedName, Item>,
+    out_type_aliases: BTreeMap<QualifiedName, Ty>,
+
+    /// The generic type parameters in scope: those of the current definition, of the current impl, and of the current function.
+    generics: Vec<Name>,
+
+    /// If true, `Vec<u8>` and `&[u8]` are elaborated as lists rather than bytes,
+    /// because the current definition or function is marked `#[gluegun(no_bytes)]`.
+    no_bytes: bool,
+
+    /// If true, `Duration` is elaborated as a user type rather than [`TypeKind::Duration`][],
+    /// because the current definition or function is marked `#[gluegun(no_duration)]`.
+    no_duration: bool,
+}
+
+/// The result of pass 2, see the fields of [`crate::Idl`][] by the same names.
+pub(super) struct Elaborated {
+    pub(super) items: BTreeMap<QualifiedName, Item>,
+    pub(super) type_aliases: BTreeMap<QualifiedName, Ty>,
 }
 
 impl<'arena> Elaborator<'arena> {
-    pub(super) fn new(recognized: Arc<BTreeMap<QualifiedName, Definition<'arena>>>) -> Self {
+    pub(super) fn new(recognized: Rc<BTreeMap<QualifiedName, Definition<'arena>>>) -> Self {
         Self {
             recognized,
             source: None,
             module_qname: QualifiedName::new(vec![]),
             out_items: BTreeMap::new(),
+            out_type_aliases: BTreeMap::new(),
+            generics: vec![],
+            no_bytes: false,
+            no_duration: false,
         }
     }
 
@@ -41,11 +65,18 @@ impl<'arena> Elaborator<'arena> {
         variant(self.source().span(spanned))
     }
 
-    pub(super) fn into_elaborated_items(mut self) -> crate::Result<BTreeMap<QualifiedName, Item>> {
+    pub(super) fn into_elaborated_items(mut self) -> crate::Result<Elaborated> {
         let recognized = self.recognized.clone();
         for (qname, definition) in recognized.iter() {
+            // Definitions are elaborated under their public name, which is where they are re-exported, if they are;
+            // those that are not part of the public interface are skipped.
+            let definition = definition.resolve(&recognized);
+            if definition.public_name.as_ref() != Some(qname) {
+                continue;
+            }
+
             self.source = Some(definition.source.clone());
-            self.module_qname.set_to_module_of(qname);
+            self.module_qname = definition.module_name.clone();
 
             // Convert the input definition and produce the output definition.
             if let Some(item) = self.elaborate_definition(qname, definition)? {
@@ -54,8 +85,14 @@ impl<'arena> Elaborator<'arena> {
 
             self.source = None;
             self.module_qname.clear();
+            self.generics.clear();
+            self.no_bytes = false;
+            self.no_duration = false;
         }
-        Ok(self.out_items)
+        Ok(Elaborated {
+            items: self.out_items,
+            type_aliases: self.out_type_aliases,
+        })
     }
 
     fn elaborate_definition(
@@ -70,6 +107,9 @@ impl<'arena> Elaborator<'arena> {
             DefinitionKind::Resource(item) => Ok(Some(Item::Resource(
                 self.elaborate_resource(qname, definition, item)?,
             ))),
+            DefinitionKind::Interface(item) => Ok(Some(Item::Interface(
+                self.elaborate_interface(qname, item)?,
+            ))),
             DefinitionKind::Variant(item, variants) => Ok(Some(Item::Variant(
                 self.elaborate_variant(qname, definition, item, variants)?,
             ))),
@@ -79,31 +119,63 @@ impl<'arena> Elaborator<'arena> {
             DefinitionKind::Function(item_fn) => Ok(Some(Item::Function(
                 self.elaborate_function(qname, definition, item_fn)?,
             ))),
-            DefinitionKind::FileModule => {
+            DefinitionKind::Constant(item) => Ok(Some(Item::Constant(
+                self.elaborate_constant(None, &item.attrs, &item.ident, &item.ty, &item.expr)?,
+            ))),
+            DefinitionKind::Static(item) => Ok(Some(Item::Constant(
+                self.elaborate_constant(None, &item.attrs, &item.ident, &item.ty, &item.expr)?,
+            ))),
+            DefinitionKind::TypeAlias(item) => {
+                let ty = self.elaborate_aliased_ty(definition, item)?;
+                self.out_type_aliases.insert(qname.clone(), ty);
+                Ok(None)
+            }
+            DefinitionKind::Module => {
                 // We don't do model modules explicitly in the output, they are inferred by the set of public definitions.
                 Ok(None)
             }
+            DefinitionKind::ReExport(_) => unreachable!("re-exports are resolved by `into_elaborated_items`"),
         }
     }
 
+    /// Elaborate the type that the alias `definition` stands for.
+    /// This is done in the module defining the alias, whatever module the alias is used in.
+    fn elaborate_aliased_ty(
+        &self,
+        definition: &Definition<'arena>,
+        item: &syn::ItemType,
+    ) -> crate::Result<Ty> {
+        let mut elaborator = Elaborator::new(self.recognized.clone());
+        elaborator.source = Some(definition.source.clone());
+        elaborator.module_qname = definition.module_name.clone();
+        let attributes = attributes::parse_gluegun_attributes(elaborator.source(), &item.attrs)?;
+        elaborator.no_bytes = attributes.no_bytes;
+        elaborator.no_duration = attributes.no_duration;
+        elaborator.elaborate_owned_ty(None, &mut vec![], &item.ty)
+    }
+
     fn elaborate_record(
         &mut self,
         qname: &QualifiedName,
         definition: &Definition<'arena>,
         item: &syn::ItemStruct,
     ) -> crate::Result<Record> {
-        if item.generics.params.len() > 0 {
-            return Err(self.error(Error::GenericsNotPermitted, &item.generics));
-        }
-
+        let generics = self.push_generics(&item.generics)?;
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &item.attrs)?;
+        self.no_bytes = attributes.no_bytes;
+        self.no_duration = attributes.no_duration;
         let span = self.source().span(&item.ident);
         let self_ty = Ty::user(span.clone(), qname);
-        let methods = self.elaborate_methods(definition.module, &self_ty, &item.ident)?;
+        let methods = self.elaborate_methods(definition, &self_ty, None)?;
 
         Ok(Record {
             span,
-            name: qname.tail_name(),
+            name: attributes.exported_name(&qname.tail_name()),
+            rust_name: qname.tail_name(),
+            docs: util::docs(&item.attrs),
+            generics,
             fields: self.elaborate_record_fields(&self_ty, item)?,
+            non_exhaustive: util::is_non_exhaustive(&item.attrs),
             methods,
         })
     }
@@ -117,26 +189,38 @@ impl<'arena> Elaborator<'arena> {
         item.fields
             .iter()
             .zip(0..)
-            .map(|(field, index)| self.elaborate_record_field(self_ty, index, field))
+            .filter(|(field, _)| !util::ignore_from_attrs(&field.attrs))
+            .map(|(field, index)| self.elaborate_record_field(self_ty, index, field, util::is_public(&field.vis)))
             .collect()
     }
 
+    /// Elaborate a field of a record or variant arm, which is `public` if declared `pub` (or in a variant arm).
     fn elaborate_record_field(
         &mut self,
         self_ty: &Ty,
         index: usize,
         field: &syn::Field,
+        public: bool,
     ) -> crate::Result<Field> {
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &field.attrs)?;
         match &field.ident {
             Some(name) => Ok(Field {
                 span: self.source().span(name),
-                name: util::recognize_name(name),
+                name: attributes.exported_name(&util::recognize_name(name)),
+                rust_name: util::recognize_name(name),
+                docs: util::docs(&field.attrs),
                 ty: self.elaborate_owned_ty(Some(self_ty), &mut vec![], &field.ty)?,
+                public,
+                readonly: attributes.readonly,
             }),
             None => Ok(Field {
                 span: self.source().span(field),
-                name: Name::from(format!("f{index}")),
+                name: attributes.exported_name(&Name::from(format!("f{index}"))),
+                rust_name: Name::from(format!("f{index}")),
+                docs: util::docs(&field.attrs),
                 ty: self.elaborate_owned_ty(Some(self_ty), &mut vec![], &field.ty)?,
+                public,
+                readonly: attributes.readonly,
             }),
         }
     }
@@ -148,13 +232,88 @@ impl<'arena> Elaborator<'arena> {
         definition: &Definition<'arena>,
         item: &syn::ItemStruct,
     ) -> crate::Result<Resource> {
-        let span = || self.source().span(&item.ident);
-        let self_ty = Ty::user(span(), qname);
-        let methods = self.elaborate_methods(definition.module, &self_ty, &item.ident)?;
+        let generics = self.push_generics(&item.generics)?;
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &item.attrs)?;
+        self.no_bytes = attributes.no_bytes;
+        self.no_duration = attributes.no_duration;
+        let span = self.source().span(&item.ident);
+        let self_ty = Ty::user(span.clone(), qname);
+        let mut constants = vec![];
+        let methods = self.elaborate_methods(definition, &self_ty, Some(&mut constants))?;
 
         Ok(Resource {
-            span: span(),
-            name: qname.tail_name(),
+            span,
+            name: attributes.exported_name(&qname.tail_name()),
+            rust_name: qname.tail_name(),
+            docs: util::docs(&item.attrs),
+            generics,
+            constants,
+            methods,
+        })
+    }
+
+    /// An "interface" is a trait, to be implemented in the target language.
+    /// Its methods are the methods declared in the trait (whether or not they have a default body).
+    fn elaborate_interface(
+        &mut self,
+        qname: &QualifiedName,
+        item: &syn::ItemTrait,
+    ) -> crate::Result<Interface> {
+        let generics = self.push_generics(&item.generics)?;
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &item.attrs)?;
+        self.no_bytes = attributes.no_bytes;
+        self.no_duration = attributes.no_duration;
+        let span = self.source().span(&item.ident);
+        let self_ty = Ty::user(span.clone(), qname);
+
+        let mut methods = vec![];
+        for item_in_trait in &item.items {
+            match item_in_trait {
+                syn::TraitItem::Fn(fn_item) => {
+                    if util::ignore_from_attrs(&fn_item.attrs) {
+                        continue;
+                    }
+
+                    let mut method = self.elaborate_fn_sig(Some(&self_ty), &fn_item.attrs, &fn_item.sig)?;
+
+                    // An interface cannot be constructed from the target language,
+                    // so an associated function returning `Self` is just a static method.
+                    if let MethodCategory::Constructor = method.category {
+                        method.category = MethodCategory::StaticMethod;
+                    }
+                    methods.push(method);
+                }
+
+                syn::TraitItem::Const(item_in_trait) => {
+                    if !util::ignore_from_attrs(&item_in_trait.attrs) {
+                        return Err(self.error(Error::UnsupportedItem, item_in_trait));
+                    }
+                }
+                syn::TraitItem::Type(item_in_trait) => {
+                    if !util::ignore_from_attrs(&item_in_trait.attrs) {
+                        return Err(self.error(Error::UnsupportedItem, item_in_trait));
+                    }
+                }
+                syn::TraitItem::Macro(item_in_trait) => {
+                    if !util::ignore_from_attrs(&item_in_trait.attrs) {
+                        return Err(self.error(Error::UnsupportedItem, item_in_trait));
+                    }
+                }
+
+                syn::TraitItem::Verbatim(item_in_trait) => {
+                    return Err(self.error(Error::UnsupportedItem, item_in_trait));
+                }
+
+                _ => return Err(self.error(Error::UnrecognizedItem, item_in_trait)),
+            }
+        }
+
+        Ok(Interface {
+            span,
+            name: attributes.exported_name(&qname.tail_name()),
+            rust_name: qname.tail_name(),
+            docs: util::docs(&item.attrs),
+            generics,
             methods,
         })
     }
@@ -166,16 +325,24 @@ impl<'arena> Elaborator<'arena> {
         item: &syn::ItemEnum,
         variants: &[&syn::Variant],
     ) -> crate::Result<Variant> {
+        let generics = self.push_generics(&item.generics)?;
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &item.attrs)?;
+        self.no_bytes = attributes.no_bytes;
+        self.no_duration = attributes.no_duration;
         let span = self.source().span(&item.ident);
         let self_ty = Ty::user(span.clone(), qname);
         let arms = variants
             .iter()
             .map(|&v| self.elaborate_variant_arm(&self_ty, v))
             .collect::<crate::Result<Vec<_>>>()?;
-        let methods = self.elaborate_methods(definition.module, &self_ty, &item.ident)?;
+        let methods = self.elaborate_methods(definition, &self_ty, None)?;
         Ok(Variant {
             span,
-            name: util::recognize_name(&item.ident),
+            name: attributes.exported_name(&util::recognize_name(&item.ident)),
+            rust_name: util::recognize_name(&item.ident),
+            docs: util::docs(&item.attrs),
+            generics,
+            non_exhaustive: util::is_non_exhaustive(&item.attrs),
             arms,
             methods,
         })
@@ -191,26 +358,29 @@ impl<'arena> Elaborator<'arena> {
             syn::Fields::Named(fields) => Ok(VariantArm {
                 span: self.source().span(&variant.ident),
                 name,
+                fields_kind: FieldsKind::Named,
                 fields: fields
                     .named
                     .iter()
                     .zip(0..)
-                    .map(|(field, index)| self.elaborate_record_field(self_ty, index, field))
+                    .map(|(field, index)| self.elaborate_record_field(self_ty, index, field, true))
                     .collect::<crate::Result<Vec<_>>>()?,
             }),
             syn::Fields::Unnamed(fields) => Ok(VariantArm {
                 span: self.source().span(&variant.ident),
                 name,
+                fields_kind: FieldsKind::Tuple,
                 fields: fields
                     .unnamed
                     .iter()
                     .zip(0..)
-                    .map(|(field, index)| self.elaborate_record_field(self_ty, index, field))
+                    .map(|(field, index)| self.elaborate_record_field(self_ty, index, field, true))
                     .collect::<crate::Result<Vec<_>>>()?,
             }),
             syn::Fields::Unit => Ok(VariantArm {
                 span: self.source().span(&variant.ident),
                 name,
+                fields_kind: FieldsKind::Unit,
                 fields: Default::default(),
             }),
         }
@@ -233,41 +403,82 @@ impl<'arena> Elaborator<'arena> {
                 }
             })
             .collect::<Vec<_>>();
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &item.attrs)?;
+        self.no_bytes = attributes.no_bytes;
+        self.no_duration = attributes.no_duration;
         let span = self.source().span(&item.ident);
         let self_ty = Ty::user(span.clone(), qname);
-        let methods = self.elaborate_methods(definition.module, &self_ty, &item.ident)?;
+        let methods = self.elaborate_methods(definition, &self_ty, None)?;
         Ok(Enum {
             span,
-            name: util::recognize_name(&item.ident),
+            name: attributes.exported_name(&util::recognize_name(&item.ident)),
+            rust_name: util::recognize_name(&item.ident),
+            docs: util::docs(&item.attrs),
+            non_exhaustive: util::is_non_exhaustive(&item.attrs),
             arms,
             methods,
         })
     }
 
+    /// Elaborate the methods in the impls of `definition` (see [`Definition::impls`][]).
+    /// Public constants in those impls are pushed onto `constants`; they are an error if it is `None`.
+    ///
+    /// Each impl is elaborated in its own module, which may not be that of the definition.
+    /// A method may be provided by more than one impl (e.g., inherently and by a trait);
+    /// only the first is kept, so an inherent method takes precedence, as in Rust.
     fn elaborate_methods(
-        &self,
-        module: &syn::File,
+        &mut self,
+        definition: &Definition<'arena>,
         self_ty: &Ty,
-        ident: &syn::Ident,
+        mut constants: Option<&mut Vec<Constant>>,
     ) -> crate::Result<Vec<Method>> {
-        let mut methods = vec![];
+        let mut methods: Vec<Method> = vec![];
+
+        let outer_source = self.source.clone();
+        let outer_module_qname = self.module_qname.clone();
 
-        let inherent_impls = self.find_inherent_impls(module, ident);
+        for block in &definition.impls {
+            self.source = Some(block.source.clone());
+            self.module_qname = block.module_name.clone();
 
-        for impl_item in inherent_impls {
-            for item_in_impl in &impl_item.items {
+            // The items of a trait impl are as public as the trait; those of an inherent impl need `pub`.
+            let ignore = |vis: &syn::Visibility, attrs: &[syn::Attribute]| match block.trait_name {
+                Some(_) => util::ignore_from_attrs(attrs),
+                None => util::ignore(vis, attrs),
+            };
+
+            // The impl's own type parameters are in scope for its methods, e.g. `T` in `impl<T> Wrapper<T>`.
+            let outer_generics = self.generics.len();
+            self.push_generics(&block.item.generics)?;
+
+            for item_in_impl in &block.item.items {
                 match item_in_impl {
                     syn::ImplItem::Fn(fn_item) => {
-                        self.parse_method(&mut methods, self_ty, impl_item, fn_item)?;
+                        if !ignore(&fn_item.vis, &fn_item.attrs) {
+                            let mut method = self.elaborate_fn_sig(Some(self_ty), &fn_item.attrs, &fn_item.sig)?;
+                            if !methods.iter().any(|m| m.name == method.name) {
+                                method.trait_name = block.trait_name.clone();
+                                methods.push(method);
+                            }
+                        }
                     }
 
                     syn::ImplItem::Const(item_in_impl) => {
-                        if !util::ignore(&item_in_impl.vis, &item_in_impl.attrs) {
-                            return Err(self.error(Error::UnsupportedItem, &item_in_impl));
+                        if !ignore(&item_in_impl.vis, &item_in_impl.attrs) {
+                            let Some(constants) = &mut constants else {
+                                return Err(self.error(Error::UnsupportedItem, item_in_impl));
+                            };
+                            constants.push(self.elaborate_constant(
+                                Some(self_ty),
+                                &item_in_impl.attrs,
+                                &item_in_impl.ident,
+                                &item_in_impl.ty,
+                                &item_in_impl.expr,
+                            )?);
                         }
                     }
                     syn::ImplItem::Type(item_in_impl) => {
-                        if !util::ignore(&item_in_impl.vis, &item_in_impl.attrs) {
+                        if !ignore(&item_in_impl.vis, &item_in_impl.attrs) {
                             return Err(self.error(Error::UnsupportedItem, &item_in_impl));
                         }
                     }
@@ -284,29 +495,84 @@ impl<'arena> Elaborator<'arena> {
                     _ => return Err(self.error(Error::UnrecognizedItem, &item_in_impl)),
                 }
             }
+
+            self.generics.truncate(outer_generics);
         }
 
+        self.source = outer_source;
+        self.module_qname = outer_module_qname;
+
         Ok(methods)
     }
 
-    fn parse_method(
-        &self,
-        methods: &mut Vec<Method>,
-        self_ty: &Ty,
-        impl_item: &syn::ItemImpl,
-        fn_item: &syn::ImplItemFn,
-    ) -> crate::Result<()> {
-        if util::ignore(&fn_item.vis, &fn_item.attrs) {
-            return Ok(());
+    /// A constant, whose value must be a literal of its type: either an associated constant of the resource `self_ty`,
+    /// or a `const` or `static` item of a module (with no `self_ty`).
+    fn elaborate_constant(
+        &mut self,
+        self_ty: Option<&Ty>,
+        attrs: &[syn::Attribute],
+        ident: &syn::Ident,
+        ty: &syn::Type,
+        expr: &syn::Expr,
+    ) -> crate::Result<Constant> {
+        let attributes = attributes::parse_gluegun_attributes(self.source(), attrs)?;
+        let rust_name = util::recognize_name(ident);
+
+        // A string constant is a `&'static str`, which is a string like any other in the target language.
+        // Other references do not accept a named lifetime, but a constant can only hold `'static` ones anyway.
+        let mut rust_ty = ty.clone();
+        if let syn::Type::Reference(reference) = &mut rust_ty {
+            if reference.lifetime.as_ref().is_some_and(|lifetime| lifetime.ident == "static") {
+                reference.lifetime = None;
+            }
         }
+        let ty = self.elaborate_ty(self_ty, &mut vec![], &rust_ty)?.ty().clone();
+        let value = match attributes::parse_literal(expr) {
+            Ok(value) if literal_suits(&value, &ty) => value,
+            _ => return Err(Error::MismatchedConstant(self.source().span(expr), rust_name, ty.to_string())),
+        };
+
+        Ok(Constant {
+            span: self.source().span(ident),
+            name: attributes.exported_name(&rust_name),
+            rust_name,
+            docs: util::docs(attrs),
+            ty,
+            value,
+        })
+    }
 
-        if !impl_item.generics.params.is_empty() {
-            return Err(self.error(Error::GenericsNotPermitted, &impl_item.generics));
+    /// Bring the type parameters declared by `generics` into scope, returning their names.
+    /// Bounds and `where` clauses are accepted but ignored; lifetime and const parameters are not supported.
+    fn push_generics(&mut self, generics: &syn::Generics) -> crate::Result<Vec<Name>> {
+        let mut names = vec![];
+        for param in &generics.params {
+            match param {
+                syn::GenericParam::Type(param) => {
+                    if let Some(default) = &param.default {
+                        return Err(self.error(Error::GenericsNotPermitted, default));
+                    }
+                    names.push(util::recognize_name(&param.ident));
+                }
+                syn::GenericParam::Lifetime(_) | syn::GenericParam::Const(_) => {
+                    return Err(self.error(Error::GenericsNotPermitted, param));
+                }
+            }
         }
+        self.generics.extend(names.iter().cloned());
+        Ok(names)
+    }
 
-        let method = self.elaborate_fn_sig(Some(self_ty), &fn_item.sig)?;
-        methods.push(method);
-        Ok(())
+    /// If `rust_path` is just the name of a type parameter in scope (like `T`), returns that name.
+    fn type_parameter(&self, rust_path: &RustPath<'_>) -> Option<Name> {
+        let [ident] = &rust_path.idents[..] else {
+            return None;
+        };
+        if !rust_path.tys.is_empty() || !rust_path.bindings.is_empty() {
+            return None;
+        }
+        let name = util::recognize_name(ident);
+        self.generics.contains(&name).then_some(name)
     }
 
     fn function_input_name(&self, input: &syn::PatType) -> crate::Result<Name> {
@@ -325,6 +591,7 @@ impl<'arena> Elaborator<'arena> {
     ) -> crate::Result<Ty> {
         match self.elaborate_ty(self_ty, modifiers, ty)? {
             RefdTy::Owned(OwnedKind::Owned, ty) => Ok(ty),
+            RefdTy::Owned(..) => Err(self.error(Error::SmartPointerNotPermitted, ty)),
             RefdTy::Ref(..) => Err(self.error(Error::UnsupportedType, ty)),
         }
     }
@@ -362,6 +629,9 @@ impl<'arena> Elaborator<'arena> {
 
             syn::Type::Paren(ty) => return self.elaborate_returned_ty(is_async, self_ty, &ty.elem),
 
+            // `-> !` is only meaningful in return position, so we don't accept it in `elaborate_ty`.
+            syn::Type::Never(_) => Ok(FunctionOutput { main_ty: Ty::never(span).owned(), error_ty: None }),
+
             syn::Type::Path(type_path) => {
                 let rust_path = self.elaborate_type_path(self_ty, type_path)?;
                 if let Some((main_ty, err_ty)) = self.match_type_path(self_ty, &mut vec![], ty, &rust_path, &["std", "result", "Result"])? {
@@ -411,20 +681,21 @@ impl<'arena> Elaborator<'arena> {
             syn::Type::Reference(ty) => {
                 // Treat `&T` the same as `T`
 
-                if let Some(m) = &ty.mutability {
-                    // Do not permit `&mut`
-                    return Err(self.error(Error::UnsupportedType, &m));
-                }
-
                 if let Some(m) = &ty.lifetime {
                     // Do not permit named lifetimes for now (do they do any harm though?)
                     return Err(self.error(Error::UnsupportedType, &m));
                 }
 
-                // `&T` is the same from an abstract point of view, only the Rust representation is affected.
+                // `&T` and `&mut T` are the same from an abstract point of view, only the Rust representation is affected;
+                // backends still need to know whether to lend the value mutably.
+                let ref_kind = if ty.mutability.is_some() {
+                    crate::RefKind::AnonRefMut
+                } else {
+                    crate::RefKind::AnonRef
+                };
                 Self::with_modifier(
                     modifiers,
-                    Modifier::Ref(crate::RefKind::AnonRef),
+                    Modifier::Ref(ref_kind),
                     |modifiers| self.elaborate_ty(self_ty, modifiers, &ty.elem),
                 )
             }
@@ -435,6 +706,9 @@ impl<'arena> Elaborator<'arena> {
                 let span = self.source().span(ty);
                 if let [Modifier::Ref(r)] = &**modifiers {
                     let elem = self.elaborate_owned_ty(self_ty, &mut vec![], &ty.elem)?;
+                    if self.is_byte(&elem) {
+                        return Ok(TypeKind::Bytes { repr: BytesRepr::SliceRef }.refd(span, r.clone()));
+                    }
                     Ok(TypeKind::Vec {
                         element: elem.clone(),
                         repr: crate::VecRepr::SliceRef,
@@ -445,6 +719,20 @@ impl<'arena> Elaborator<'arena> {
                 }
             }
 
+            syn::Type::Array(ty) => {
+                // `[T; N]` requires `N` to be an integer literal
+
+                let span = self.source().span(ty);
+                let syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(len), .. }) = &ty.len else {
+                    return Err(self.error(Error::UnsupportedType, &ty.len));
+                };
+                let len = len
+                    .base10_parse::<usize>()
+                    .map_err(|_| self.error(Error::UnsupportedType, len))?;
+                let element = self.elaborate_owned_ty(self_ty, &mut vec![], &ty.elem)?;
+                self.maybe_referenced(modifiers, ty, Ty::new(span, TypeKind::Array { element, len }))
+            }
+
             syn::Type::Tuple(ty) => {
                 // Tuples are first-class in our IR
 
@@ -486,11 +774,23 @@ impl<'arena> Elaborator<'arena> {
             } else {
                 Err(self.error(Error::UnresolvedName, &ty))
             }
+        } else if let Some(name) = self.type_parameter(&rust_path) {
+            // Found a generic type parameter; these shadow all other types.
+            let span = self.source().span(ty);
+            self.maybe_referenced(modifiers, ty, Ty::new(span, TypeKind::TypeParameter { name }))
+        } else if let Some(error_ty) = self.elaborate_error_ty(ty, &rust_path)? {
+            // Found `Box<dyn Error>` or `anyhow::Error`.
+            self.maybe_referenced(modifiers, ty, error_ty)
+        } else if let Some(pointer) =
+            KNOWN_SMART_POINTERS.iter().find(|pointer| self.type_path_matches(&rust_path, pointer.name))
+        {
+            // Found `Box<T>`, `Rc<T>`, or `Arc<T>`, which stand for `T`.
+            self.elaborate_smart_pointer(self_ty, modifiers, ty, &rust_path, pointer.owned_kind)
         } else if let Some(rust_ty) =
             self.elaborate_rust_type(self_ty, modifiers, ty, &rust_path, &KNOWN_RUST_TYPES)?
         {
             // Found a well-known Rust type.
-            Ok(rust_ty)
+            Ok(self.vec_to_bytes(rust_ty))
         } else if let Some(user_ty) =
             self.elaborate_user_type(ty, &rust_path.idents, &rust_path.tys)?
         {
@@ -508,6 +808,107 @@ impl<'arena> Elaborator<'arena> {
         }
     }
 
+    /// Match the path that appears in `ty` against the error types we recognize, `anyhow::Error` and `Box<dyn Error>`.
+    /// The trait object may also be bounded by auto traits (e.g., `Box<dyn Error + Send + Sync + 'static>`), which are recorded.
+    /// `anyhow::Error` must be written out in full, so that a type of the user's named `Error` is not mistaken for it.
+    ///
+    /// Returns `Ok(None)` if `ty` is not one of them, or an error if it is a `Box<dyn ...>` we do not support.
+    fn elaborate_error_ty(&self, ty: &syn::Type, path: &RustPath<'_>) -> crate::Result<Option<Ty>> {
+        let span = self.source().span(ty);
+        if path.idents.len() == 2 && path.idents[0] == "anyhow" && path.idents[1] == "Error" && path.tys.is_empty() {
+            return Ok(Some(Ty::anyhow_error(span)));
+        }
+
+        if !self.type_path_matches(path, &["std", "boxed", "Box"]) {
+            return Ok(None);
+        }
+        let [syn::Type::TraitObject(trait_object)] = &path.tys[..] else {
+            return Ok(None);
+        };
+
+        let (mut error, mut send, mut sync, mut unpin) = (false, false, false, false);
+        for bound in &trait_object.bounds {
+            match bound {
+                syn::TypeParamBound::Trait(bound)
+                    if bound.lifetimes.is_none() && matches!(bound.modifier, syn::TraitBoundModifier::None) =>
+                {
+                    let bound_path = self.elaborate_path(None, &bound.path)?;
+                    let is = |known_rust_paths: &[&[&str]]| {
+                        known_rust_paths.iter().any(|known_rust_path| self.type_path_matches(&bound_path, known_rust_path))
+                    };
+                    if is(&[&["std", "error", "Error"], &["core", "error", "Error"]]) {
+                        error = true;
+                    } else if is(&[&["std", "marker", "Send"]]) {
+                        send = true;
+                    } else if is(&[&["std", "marker", "Sync"]]) {
+                        sync = true;
+                    } else if is(&[&["std", "marker", "Unpin"]]) {
+                        unpin = true;
+                    } else {
+                        return Err(self.error(Error::UnsupportedType, bound));
+                    }
+                }
+                syn::TypeParamBound::Lifetime(lifetime) if lifetime.ident == "static" => {}
+                _ => return Err(self.error(Error::UnsupportedType, bound)),
+            }
+        }
+        if !error {
+            return Err(self.error(Error::UnsupportedType, trait_object));
+        }
+
+        let repr = crate::ErrorRepr::BoxDynError(crate::AutoTraits::new(send, sync, unpin));
+        Ok(Some(Ty::new(span, TypeKind::Error { repr })))
+    }
+
+    /// Elaborate the smart pointer `ty` (see [`KNOWN_SMART_POINTERS`][]), whose path is `path`, to the type of the value it points to.
+    /// `Box<str>` stands for `String`, and `Box<[T]>` for `Vec<T>` (or bytes); `owned_kind` records which it was for the Rust glue.
+    fn elaborate_smart_pointer(
+        &self,
+        self_ty: Option<&Ty>,
+        modifiers: &mut Vec<Modifier>,
+        ty: &syn::Type,
+        path: &RustPath<'_>,
+        owned_kind: fn(Pointee) -> OwnedKind,
+    ) -> crate::Result<RefdTy> {
+        // A borrowed pointer (e.g., `&Arc<T>`) would have to be lent by the glue, which is not supported yet.
+        if !modifiers.is_empty() {
+            return Err(self.error(Error::UnsupportedUseOfType, ty));
+        }
+        if !path.bindings.is_empty() {
+            return Err(self.error(Error::BindingNotExpected, ty));
+        }
+        let [pointee] = &path.tys[..] else {
+            return Err(self.error(Error::UnsupportedUseOfType, ty));
+        };
+
+        let span = self.source().span(ty);
+        match pointee {
+            syn::Type::Path(type_path) if type_path.qself.is_none() && type_path.path.is_ident("str") => {
+                let string = Ty::new(span, TypeKind::String { repr: crate::StringRepr::String });
+                Ok(RefdTy::Owned(owned_kind(Pointee::Str), string))
+            }
+            syn::Type::Slice(slice) => {
+                let element = self.elaborate_owned_ty(self_ty, modifiers, &slice.elem)?;
+                let vec = Ty::new(span, TypeKind::Vec { element, repr: crate::VecRepr::Vec });
+                Ok(self.vec_to_bytes(RefdTy::Owned(owned_kind(Pointee::Slice), vec)))
+            }
+            _ => {
+                if let syn::Type::Path(type_path) = pointee {
+                    let pointee_path = self.elaborate_type_path(self_ty, type_path)?;
+                    if let Some(name) = KNOWN_INTERIOR_MUTABILITY_TYPES
+                        .iter()
+                        .find(|name| self.type_path_matches(&pointee_path, name))
+                    {
+                        let name = name.last().unwrap().to_string();
+                        return Err(Error::InteriorMutability(self.source().span(pointee), name));
+                    }
+                }
+                let value = self.elaborate_owned_ty(self_ty, modifiers, pointee)?;
+                Ok(RefdTy::Owned(owned_kind(Pointee::Sized), value))
+            }
+        }
+    }
+
     /// Match the path, deconstructed into `idents` and `tys`, that appears in `ty` against the list `krts` of known Rust types.
     /// Returns `Ok(Some(ty))` if the match is successful or `Ok(None)` if there is no match.
     /// Returns an error if there is a match for the name but the arity is wrong or some other similar situation.
@@ -533,6 +934,10 @@ impl<'arena> Elaborator<'arena> {
                     .map(|ty| self.elaborate_owned_ty(self_ty, modifiers, ty))
                     .collect::<crate::Result<Vec<Ty>>>()?;
                 let ty = f(span.clone(), &modifiers, &tys, &path.bindings)?;
+                if self.no_duration && matches!(ty.ty().kind(), TypeKind::Duration { .. }) {
+                    // `#[gluegun(no_duration)]`: leave `Duration` to the user's types.
+                    return Ok(None);
+                }
                 Ok(Some(ty))
             }
             KnownRustFn::Modifier(modifier) => {
@@ -547,6 +952,24 @@ impl<'arena> Elaborator<'arena> {
         }
     }
 
+    /// True if `ty` is `u8`, so that a list of it is [`TypeKind::Bytes`][] (unless `#[gluegun(no_bytes)]` is in effect).
+    fn is_byte(&self, ty: &Ty) -> bool {
+        !self.no_bytes && matches!(ty.kind(), TypeKind::Scalar(Scalar::U8))
+    }
+
+    /// Converts an owned `Vec<u8>` into [`TypeKind::Bytes`][]; other types are returned unchanged.
+    fn vec_to_bytes(&self, refd_ty: RefdTy) -> RefdTy {
+        if let RefdTy::Owned(kind, ty) = &refd_ty {
+            if let TypeKind::Vec { element, repr: crate::VecRepr::Vec } = ty.kind() {
+                if self.is_byte(element) {
+                    let bytes = Ty::new(ty.span().clone(), TypeKind::Bytes { repr: BytesRepr::Vec });
+                    return RefdTy::Owned(kind.clone(), bytes);
+                }
+            }
+        }
+        refd_ty
+    }
+
     /// Returns true if the user-provided `path` matches against the known Rust path (e.g., `std::option::Option`) we are looking for.
     fn type_path_matches(&self, path: &RustPath<'_>, known_rust_path: &[&str]) -> bool {
         if path.idents.len() == 1 {
@@ -603,7 +1026,8 @@ impl<'arena> Elaborator<'arena> {
                     )? {
                         return Ok(ty);
                     } else {
-                        return Err(self.error(Error::UnsupportedType, &bound));
+                        let trait_name = bound.path.segments.iter().map(|s| s.ident.to_string()).collect::<Vec<_>>().join("::");
+                        return Err(Error::UnsupportedImplTrait(self.source().span(bound), trait_name));
                     }
                 }
 
@@ -688,8 +1112,8 @@ impl<'arena> Elaborator<'arena> {
         match self.recognized.get(&path) {
             None => Ok(None),
 
-            Some(definition) => match &definition.kind {
-                DefinitionKind::FileModule => {
+            Some(definition) => match &definition.resolve(&self.recognized).kind {
+                DefinitionKind::Module => {
                     match self.elaborate_user_ty_in_module_relative_to(
                         ty,
                         &path,
@@ -707,10 +1131,28 @@ impl<'arena> Elaborator<'arena> {
                     if !tys.is_empty() {
                         Err(self.error(Error::GenericsNotPermitted, &ty))
                     } else {
-                        Ok(Some(Ty::user(self.source().span(ident0), &path)))
+                        // The type is referred to by its public name, wherever it is defined.
+                        match &definition.resolve(&self.recognized).public_name {
+                            Some(public_name) => Ok(Some(Ty::user(self.source().span(ident0), public_name))),
+                            None => Err(self.error(Error::UnresolvedName, ty)),
+                        }
+                    }
+                }
+                // Aliases are replaced by the type they stand for.
+                DefinitionKind::TypeAlias(item) => {
+                    if !tys.is_empty() {
+                        Err(self.error(Error::GenericsNotPermitted, ty))
+                    } else {
+                        Ok(Some(self.elaborate_aliased_ty(definition.resolve(&self.recognized), item)?))
                     }
                 }
-                DefinitionKind::Function(_) => Err(self.error(Error::NotType, &ty)),
+                DefinitionKind::Function(_)
+                | DefinitionKind::Interface(_)
+                | DefinitionKind::Constant(_)
+                | DefinitionKind::Static(_) => {
+                    Err(self.error(Error::NotType, ty))
+                }
+                DefinitionKind::ReExport(_) => unreachable!("re-exports never refer to re-exports"),
             },
         }
     }
@@ -804,62 +1246,54 @@ impl<'arena> Elaborator<'arena> {
         }
     }
 
-    // Given a struct name like `Foo`,
-    fn find_inherent_impls(
-        &self,
-        module: &'arena syn::File,
-        ident: &syn::Ident,
-    ) -> Vec<&'arena syn::ItemImpl> {
-        module
-            .items
-            .iter()
-            .filter_map(|item| {
-                if let syn::Item::Impl(item_impl) = item {
-                    Some(item_impl)
-                } else {
-                    None
-                }
-            })
-            .filter(|item_impl| item_impl.trait_.is_none())
-            .filter(|item_impl| {
-                if let syn::Type::Path(path) = &*item_impl.self_ty {
-                    path.path.is_ident(ident)
-                } else {
-                    false
-                }
-            })
-            .collect()
-    }
-
     fn elaborate_function(
-        &self,
-        _qname: &QualifiedName,
+        &mut self,
+        qname: &QualifiedName,
         _definition: &Definition<'arena>,
         item_fn: &&syn::ItemFn,
     ) -> crate::Result<Function> {
         let Method {
             span,
             category: _,
-            name,
+            name: _,
+            rust_name: _,
+            docs,
+            generics,
             signature,
-        } = self.elaborate_fn_sig(None, &item_fn.sig)?;
+            trait_name: _,
+        } = self.elaborate_fn_sig(None, &item_fn.attrs, &item_fn.sig)?;
+
+        // Like other items, functions are named after their public name, which `pub use ... as` may have changed.
+        let attributes = attributes::parse_gluegun_attributes(self.source(), &item_fn.attrs)?;
+        let name = attributes.exported_name(&qname.tail_name());
+        let rust_name = qname.tail_name();
+
         Ok(Function {
             span,
             name,
+            rust_name,
+            docs,
+            generics,
             signature,
         })
     }
 
     fn elaborate_fn_sig(
-        &self,
+        &mut self,
         self_ty: Option<&Ty>,
+        attrs: &[syn::Attribute],
         sig: &syn::Signature,
     ) -> crate::Result<Method> {
-        if !sig.generics.params.is_empty() {
-            return Err(self.error(Error::GenericsNotPermitted, &sig.generics));
-        }
+        let outer_generics = self.generics.len();
+        let generics = self.push_generics(&sig.generics)?;
 
-        let name = util::recognize_name(&sig.ident);
+        let rust_name = util::recognize_name(&sig.ident);
+
+        let attributes = attributes::parse_gluegun_attributes(self.source(), attrs)?;
+        let outer_no_bytes = self.no_bytes;
+        self.no_bytes |= attributes.no_bytes;
+        let outer_no_duration = self.no_duration;
+        self.no_duration |= attributes.no_duration;
 
         // Check for `&self` and friends
         let self_kind = if let Some(syn::FnArg::Receiver(receiver)) = sig.inputs.first() {
@@ -889,15 +1323,30 @@ impl<'arena> Elaborator<'arena> {
 
                 syn::FnArg::Typed(input) => {
                     let ty = self.elaborate_ty(self_ty, &mut vec![], &input.ty)?;
+                    let name = self.function_input_name(input)?;
                     inputs.push(FunctionInput {
                         span: self.source().span(&input.pat),
-                        name: self.function_input_name(input)?,
+                        default_value: attributes.default_value(&name),
+                        docs: util::docs(&input.attrs),
+                        name,
                         refd_ty: ty,
                     })
                 }
             }
         }
 
+        // Every default must name one of the parameters, and suit its type.
+        for (ident, literal) in &attributes.defaults {
+            let default_name = util::recognize_name(ident);
+            let Some(input) = inputs.iter().find(|input| input.name == default_name) else {
+                return Err(Error::UnknownParameter(self.source().span(ident), default_name));
+            };
+            let ty = input.refd_ty.ty();
+            if !literal_suits(literal, ty) {
+                return Err(Error::MismatchedDefault(self.source().span(ident), default_name, ty.to_string()));
+            }
+        }
+
         let mut is_async = if sig.asyncness.is_some() {
             IsAsync::Yes
         } else {
@@ -906,36 +1355,97 @@ impl<'arena> Elaborator<'arena> {
 
         let output_ty = self.elaborate_return_ty(&mut is_async, self_ty, &sig.output)?;
 
+        // Returning a `#[must_use]` type makes the function itself `#[must_use]`, as in Rust.
+        let must_use = util::is_must_use(attrs) || self.is_must_use_ty(&output_ty.main_ty);
+
         // Detect if the return type is an owned copy of the self type;
         // this will be used to decide whether to categorize this as a builder
-        // method.
+        // method. The kinds are compared, since `Self` and the type's name have different spans.
         let output_is_self = if let Some(self_ty) = self_ty {
-            output_ty.main_ty.owned_ty() == Some(self_ty)
+            output_ty.main_ty.owned_ty().map(Ty::kind) == Some(self_ty.kind())
         } else {
             false
         };
 
-        // Categorize the function
+        // Categorize the function; see `MethodCategory::BuilderMethod` for the rule for builder methods.
         let category = match self_kind {
+            None if attributes.builder == Some(true) => {
+                return Err(Error::InvalidAttribute(
+                    self.source().span(&sig.ident),
+                    "a builder method must take `self` or `&mut self`".to_string(),
+                ));
+            }
             None if sig.ident == "new" && output_is_self => MethodCategory::Constructor,
             None => MethodCategory::StaticMethod,
-            Some(SelfKind::ByValue) if output_is_self => {
-                MethodCategory::BuilderMethod(self_kind.unwrap())
+            Some(self_kind) => {
+                let is_builder = attributes.builder.unwrap_or(
+                    output_is_self && matches!(self_kind, SelfKind::ByValue | SelfKind::ByRefMut),
+                );
+                if is_builder {
+                    MethodCategory::BuilderMethod(self_kind)
+                } else {
+                    MethodCategory::InstanceMethod(self_kind)
+                }
             }
-            Some(self_kind) => MethodCategory::InstanceMethod(self_kind),
         };
 
+        self.generics.truncate(outer_generics);
+        self.no_bytes = outer_no_bytes;
+        self.no_duration = outer_no_duration;
+
         Ok(Method {
             span: self.source().span(&sig.ident),
             category,
-            name,
+            name: attributes.exported_name(&rust_name),
+            rust_name,
+            docs: util::docs(attrs),
+            generics,
             signature: Signature {
                 is_async,
                 inputs,
                 output_ty,
+                must_use,
             },
+            trait_name: None,
         })
     }
+
+    /// True if `refd_ty` is an owned user-defined type declared `#[must_use]`.
+    fn is_must_use_ty(&self, refd_ty: &RefdTy) -> bool {
+        let Some(TypeKind::UserType { qname }) = refd_ty.owned_ty().map(|ty| ty.kind()) else {
+            return false;
+        };
+        match self.recognized.get(qname).map(|definition| &definition.resolve(&self.recognized).kind) {
+            Some(DefinitionKind::Record(item) | DefinitionKind::Resource(item)) => util::is_must_use(&item.attrs),
+            Some(DefinitionKind::Variant(item, _) | DefinitionKind::Enum(item, _)) => util::is_must_use(&item.attrs),
+            Some(
+                DefinitionKind::Interface(_)
+                | DefinitionKind::Function(_)
+                | DefinitionKind::Constant(_)
+                | DefinitionKind::Static(_)
+                | DefinitionKind::TypeAlias(_)
+                | DefinitionKind::ReExport(_)
+                | DefinitionKind::Module,
+            )
+            | None => false,
+        }
+    }
+}
+
+/// True if `literal` can be a value of type `ty`: there is no conversion, except that a string can be a path.
+fn literal_suits(literal: &Literal, ty: &Ty) -> bool {
+    match (literal, ty.kind()) {
+        (Literal::Boolean(_), TypeKind::Scalar(Scalar::Boolean)) => true,
+        (Literal::Char(_), TypeKind::Scalar(Scalar::Char)) => true,
+        (Literal::Integer(digits), TypeKind::Scalar(scalar)) => match scalar {
+            Scalar::I8 | Scalar::I16 | Scalar::I32 | Scalar::I64 | Scalar::I128 => true,
+            Scalar::U8 | Scalar::U16 | Scalar::U32 | Scalar::U64 | Scalar::U128 => !digits.starts_with('-'),
+            Scalar::Boolean | Scalar::Char | Scalar::F32 | Scalar::F64 => false,
+        },
+        (Literal::Float(_), TypeKind::Scalar(Scalar::F32 | Scalar::F64)) => true,
+        (Literal::String(_), TypeKind::String { repr: _ } | TypeKind::Path { repr: _ }) => true,
+        _ => false,
+    }
 }
 
 pub trait MatchArity: Sized {
@@ -1,5 +1,7 @@
 use crate::Name;
 
+use super::attributes;
+
 /// If true, ignore this item.
 pub(super) fn ignore(vis: &syn::Visibility, attrs: &[syn::Attribute]) -> bool {
     // Only look at public things
@@ -23,7 +25,91 @@ pub(super) fn ignore_from_attrs(attrs: &[syn::Attribute]) -> bool {
         return true;
     }
 
-    false
+    // Ignore things tagged with `#[gluegun(skip)]`
+    attributes::is_skipped(attrs)
+}
+
+/// True if `attrs` include `#[must_use]` (with or without a reason).
+pub(super) fn is_must_use(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("must_use"))
+}
+
+/// True if `attrs` include `#[non_exhaustive]`.
+pub(super) fn is_non_exhaustive(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("non_exhaustive"))
+}
+
+/// The doc comment in `attrs`, from `///` comments, `//!` comments, and `#[doc = "..."]` attributes.
+/// Lines are joined with newlines and the indentation common to all of them (usually the space after `///`) is removed,
+/// as is the ` * ` decoration at the start of each line of a `/** ... */` comment.
+pub(super) fn docs(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("doc")) {
+        let syn::Meta::NameValue(syn::MetaNameValue {
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(text),
+                    ..
+                }),
+            ..
+        }) = &attr.meta
+        else {
+            continue;
+        };
+        lines.extend(strip_block_decoration(&text.value()));
+    }
+
+    let lines = unindent(lines.iter().map(|line| line.as_str()).collect())?;
+    Some(lines.join("\n").trim_matches('\n').to_string())
+}
+
+/// A `/** ... */` comment is a single doc attribute spanning several lines, often each starting with ` * `;
+/// remove that decoration. (A single `///` line starting with `*` is a list item and is left alone.)
+fn strip_block_decoration(text: &str) -> Vec<String> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    if lines.len() > 1 {
+        if let Some(unindented) = unindent(lines.clone()) {
+            if unindented.iter().all(|line| line.is_empty() || line.starts_with('*')) {
+                return unindented
+                    .iter()
+                    .map(|line| line.strip_prefix('*').unwrap_or(line).to_string())
+                    .collect();
+            }
+        }
+    }
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+/// Remove the indentation common to all non-blank `lines`, and any trailing whitespace.
+/// Returns `None` if all lines are blank.
+fn unindent(lines: Vec<&str>) -> Option<Vec<&str>> {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()?;
+    Some(
+        lines
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or("").trim_end())
+            .collect(),
+    )
+}
+
+/// The value of a `#[path = "..."]` attribute in `attrs`, if any.
+pub(super) fn path_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            value:
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(text),
+                    ..
+                }),
+            ..
+        }) if path.is_ident("path") => Some(text.value()),
+        _ => None,
+    })
 }
 
 /// Returns true if this is fully public.
@@ -1,11 +1,11 @@
 use std::{
-    path::{Path, PathBuf},
+    path::Path,
     process::Command,
 };
 
 use anyhow::Context;
 
-use crate::util::make_java_class_files_directory;
+use crate::util::{java_files, make_java_class_files_directory};
 
 /// build-rs helper: compile all `java` files in `java_src` and
 /// store into `$OUT_DIR/java_class_files`.
@@ -16,8 +16,9 @@ use crate::util::make_java_class_files_directory;
 pub fn build_rs_main() -> anyhow::Result<()> {
     let java_class_files = make_java_class_files_directory()?;
     let new_classpath = init_classpath(&java_class_files);
-    for java_path in java_files("java_src".as_ref()) {
-        compile_java(&java_path, &java_class_files, &new_classpath)?;
+    let java_src: &Path = "java_src".as_ref();
+    for java_path in java_files(java_src) {
+        compile_java(&java_path, java_src, &java_class_files, &new_classpath)?;
     }
     Ok(())
 }
@@ -30,22 +31,11 @@ fn init_classpath(java_class_files: &Path) -> String {
     new_classpath
 }
 
-fn java_files(java_src: &Path) -> impl Iterator<Item = PathBuf> {
-    walkdir::WalkDir::new(java_src)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| {
-            e.path()
-                .extension()
-                .map(|ext| ext == "java")
-                .unwrap_or(false)
-        })
-        .map(|e| e.into_path())
-}
-
+/// Compile `java_path`; the classes it refers to are compiled along with it from `java_src` if needed,
+/// since the files are compiled in no particular order.
 fn compile_java(
     java_path: &Path,
+    java_src: &Path,
     java_class_files: &Path,
     new_classpath: &str,
 ) -> anyhow::Result<()> {
@@ -56,6 +46,8 @@ fn compile_java(
         .arg(&java_class_files)
         .arg("-cp")
         .arg(&new_classpath)
+        .arg("-sourcepath")
+        .arg(java_src)
         .arg(&java_path)
         .output()
         .with_context(|| format!("invoking `javac` on `{}`", java_path.display()))?;
@@ -0,0 +1,47 @@
+//! Support code for Rust iterators handed to Java.
+//!
+//! The iterator is boxed and Java holds on to it as an opaque `long` handle,
+//! calling back into the generated native functions (which use the functions here) to advance it.
+//! Java is responsible for calling [`drop_handle`][] exactly once, even if it stops iterating early.
+
+use std::iter::Peekable;
+
+/// What a handle points at. Peekable so that Java's `hasNext` can look ahead without consuming an item.
+type Handle<T> = Peekable<Box<dyn Iterator<Item = T> + Send>>;
+
+/// Box `iter` and return the handle that Java will store.
+/// The iterator must be `Send` because Java may advance or release it from any thread.
+pub fn into_handle<T: 'static>(iter: impl Iterator<Item = T> + Send + 'static) -> i64 {
+    let iter: Box<dyn Iterator<Item = T> + Send> = Box::new(iter);
+    Box::into_raw(Box::new(iter.peekable())) as i64
+}
+
+/// True if the iterator behind `handle` has another item.
+///
+/// # Safety
+///
+/// `handle` must have been returned by [`into_handle`][] for the same `T`, must not yet have been dropped,
+/// and must not be in use by another thread.
+pub unsafe fn has_next<T>(handle: i64) -> bool {
+    let iter = unsafe { &mut *(handle as *mut Handle<T>) };
+    iter.peek().is_some()
+}
+
+/// Advance the iterator behind `handle`.
+///
+/// # Safety
+///
+/// As for [`has_next`][].
+pub unsafe fn next<T>(handle: i64) -> Option<T> {
+    let iter = unsafe { &mut *(handle as *mut Handle<T>) };
+    iter.next()
+}
+
+/// Drop the iterator behind `handle`; the handle may not be used afterwards.
+///
+/// # Safety
+///
+/// As for [`has_next`][].
+pub unsafe fn drop_handle<T>(handle: i64) {
+    drop(unsafe { Box::from_raw(handle as *mut Handle<T>) });
+}
@@ -0,0 +1,11 @@
+/// The attribute of the jar manifest recording the Java version the classes were compiled for (`--target-version`).
+pub const TARGET_VERSION_ATTRIBUTE: &str = "X-GlueGun-Target-Version";
+
+/// The contents of the manifest (`META-INF/MANIFEST.MF`) of the jar, recording `target_version` if given.
+pub fn manifest(target_version: Option<u32>) -> String {
+    let mut manifest = String::from("Manifest-Version: 1.0\nCreated-By: gluegun-java-util\n");
+    if let Some(target_version) = target_version {
+        manifest.push_str(&format!("{TARGET_VERSION_ATTRIBUTE}: {target_version}\n"));
+    }
+    manifest
+}
@@ -0,0 +1,82 @@
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::Context;
+
+use crate::util::java_files;
+
+/// The oldest Java version that can be given to `javac --release`; older versions are not supported by current JDKs.
+pub const MIN_TARGET_VERSION: u32 = 8;
+
+/// The Java compiler, used to compile the Java sources generated by gluegun-java.
+#[derive(Debug)]
+pub struct Javac {
+    path: PathBuf,
+}
+
+impl Javac {
+    /// Find `javac` in the `bin` directory of `java_home` (the value of `JAVA_HOME`) if given, else on the `PATH`.
+    pub fn locate(java_home: Option<&OsStr>) -> anyhow::Result<Self> {
+        if let Some(java_home) = java_home {
+            let path = Path::new(java_home)
+                .join("bin")
+                .join(format!("javac{}", std::env::consts::EXE_SUFFIX));
+            if !path.is_file() {
+                anyhow::bail!(
+                    "`JAVA_HOME` is set to `{}`, but there is no `javac` at `{}`; \
+                    point `JAVA_HOME` at a JDK (not a JRE), or unset it to use the `javac` on your `PATH`",
+                    Path::new(java_home).display(),
+                    path.display(),
+                );
+            }
+            return Ok(Self { path });
+        }
+
+        match which::which("javac") {
+            Ok(path) => Ok(Self { path }),
+            Err(err) => anyhow::bail!(
+                "could not find `javac` ({err}); \
+                install a JDK and add its `bin` directory to your `PATH`, or set `JAVA_HOME` to its location"
+            ),
+        }
+    }
+
+    /// Path to the `javac` executable.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Compile all the `.java` files under `java_src` into class files under `java_class_files`,
+    /// for Java version `target_version` if given (`javac --release`).
+    /// Diagnostics from `javac` go to stderr.
+    pub fn compile(&self, java_src: &Path, java_class_files: &Path, target_version: Option<u32>) -> anyhow::Result<()> {
+        let status = self
+            .command(java_src, java_class_files, target_version)?
+            .status()
+            .with_context(|| format!("invoking `{}`", self.path.display()))?;
+
+        if !status.success() {
+            anyhow::bail!("`javac` failed with {status} compiling the sources in `{}`", java_src.display());
+        }
+        Ok(())
+    }
+
+    /// The `javac` command run by [`Javac::compile`][].
+    pub fn command(&self, java_src: &Path, java_class_files: &Path, target_version: Option<u32>) -> anyhow::Result<Command> {
+        let sources: Vec<PathBuf> = java_files(java_src).collect();
+        if sources.is_empty() {
+            anyhow::bail!("no Java sources found in `{}`", java_src.display());
+        }
+
+        let mut command = Command::new(&self.path);
+        command.arg("-d").arg(java_class_files);
+        if let Some(target_version) = target_version {
+            command.arg("--release").arg(target_version.to_string());
+        }
+        command.args(&sources);
+        Ok(command)
+    }
+}
@@ -1,5 +1,10 @@
 mod build_rs;
+pub mod iterator;
+pub mod jar;
+pub mod javac;
 mod main_rs;
+pub mod record;
+pub mod resource;
 mod util;
 
 pub use build_rs::*;
@@ -1,29 +1,62 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
 use clap::Parser;
 
-use crate::util;
+use crate::{
+    jar,
+    javac::{self, Javac},
+    util,
+};
 
 #[derive(clap::Parser)]
 struct Cli {
+    /// Java version to compile for (`javac --release`), also recorded in the jar manifest;
+    /// defaults to the version of `javac`.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        alias = "release",
+        value_parser = clap::value_parser!(u32).range(i64::from(javac::MIN_TARGET_VERSION)..),
+    )]
+    target_version: Option<u32>,
+
     #[command(subcommand)]
     command: CliCommand,
 }
 
 #[derive(clap::Subcommand)]
 enum CliCommand {
+    /// Compile the generated Java sources into class files.
+    Compile {
+        /// Directory containing the Java sources.
+        #[arg(long, value_name = "DIR", default_value = "java_src")]
+        java_src: PathBuf,
+    },
+
     Jar,
 }
 
 /// Main function from the binary
 pub fn bin_main() -> anyhow::Result<()> {
-    let _java_class_files = util::make_java_class_files_directory()?;
+    let java_class_files = util::make_java_class_files_directory()?;
     let cli = Cli::try_parse()?;
     match cli.command {
+        CliCommand::Compile { java_src } => {
+            // `JAVA_HOME`, if set, selects the JDK to use.
+            let javac = Javac::locate(std::env::var_os("JAVA_HOME").as_deref())?;
+            javac.compile(&java_src, &java_class_files, cli.target_version)?;
+        }
         CliCommand::Jar => {
             // To start, build the artifact by running `cargo build`
 
+            // Then write the manifest, which records the Java version the classes were compiled for
+            let manifest_path = util::out_dir()?.join("MANIFEST.MF");
+            std::fs::write(&manifest_path, jar::manifest(cli.target_version))
+                .with_context(|| format!("writing `{}`", manifest_path.display()))?;
 
-            // Then run `jar cf`
-            
+            // Then run `jar cfm` with it
         }
     }
     Ok(())
@@ -0,0 +1,41 @@
+//! Support code for records (and variants) passed between Rust and Java.
+//!
+//! A record crosses the boundary as an opaque `long` handle to a boxed Rust value.
+//! Java builds one from its fields with the generated `native_record_new` function
+//! (for a variant, the `native_variant_new_*` function of the arm)
+//! and passes it to a native method, which takes ownership with [`from_handle`][].
+//! A record returned from Rust is read back field by field through the generated getters,
+//! after which Java is responsible for calling [`drop_handle`][] exactly once.
+
+/// Box `value` and return the handle that Java will pass around.
+pub fn into_handle<T: 'static>(value: T) -> i64 {
+    Box::into_raw(Box::new(value)) as i64
+}
+
+/// Borrow the record behind `handle`.
+///
+/// # Safety
+///
+/// `handle` must have been returned by [`into_handle`][] for the same `T`, must not yet have been released
+/// (by [`from_handle`][] or [`drop_handle`][]), and must not be released while the reference is in use.
+pub unsafe fn get<'a, T>(handle: i64) -> &'a T {
+    unsafe { &*(handle as *const T) }
+}
+
+/// Take back ownership of the record behind `handle`; the handle may not be used afterwards.
+///
+/// # Safety
+///
+/// As for [`get`][].
+pub unsafe fn from_handle<T>(handle: i64) -> T {
+    *unsafe { Box::from_raw(handle as *mut T) }
+}
+
+/// Drop the record behind `handle`; the handle may not be used afterwards.
+///
+/// # Safety
+///
+/// As for [`get`][].
+pub unsafe fn drop_handle<T>(handle: i64) {
+    drop(unsafe { from_handle::<T>(handle) });
+}
@@ -0,0 +1,19 @@
+//! Support code for resources, Rust values owned by a Java object.
+//!
+//! Like a record, a resource is boxed and Java holds on to it as an opaque `long` handle
+//! (created with [`into_handle`][] by the constructor or any other function returning the resource).
+//! Unlike a record, the handle lives as long as the Java object: each method borrows the value with [`get`][]
+//! or [`get_mut`][], a method taking `self` by value (or a function taking the resource as an argument)
+//! takes it back with [`from_handle`][],
+//! and otherwise Java calls [`drop_handle`][] exactly once when the object is closed or collected.
+
+pub use crate::record::{drop_handle, from_handle, get, into_handle};
+
+/// Mutably borrow the resource behind `handle`.
+///
+/// # Safety
+///
+/// As for [`get`][]; in addition, the handle must not be in use by another thread.
+pub unsafe fn get_mut<'a, T>(handle: i64) -> &'a mut T {
+    unsafe { &mut *(handle as *mut T) }
+}
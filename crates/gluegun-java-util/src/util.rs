@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 
@@ -13,6 +13,21 @@ pub(crate) fn make_java_class_files_directory() -> Result<PathBuf, anyhow::Error
     Ok(java_class_files)
 }
 
+/// All the `.java` files under `java_src`.
+pub(crate) fn java_files(java_src: &Path) -> impl Iterator<Item = PathBuf> {
+    walkdir::WalkDir::new(java_src)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .map(|ext| ext == "java")
+                .unwrap_or(false)
+        })
+        .map(|e| e.into_path())
+}
+
 pub(crate) fn out_dir() -> anyhow::Result<PathBuf> {
     Ok(PathBuf::from(
         std::env::var("OUT_DIR").map_err(|_| anyhow::anyhow!("OUT_DIR not set"))?,
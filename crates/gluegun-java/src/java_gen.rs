@@ -1,33 +1,58 @@
 use std::collections::BTreeMap;
 
 use gluegun_core::{
+    casing::Casing,
+    cli::{for_each_supported, UnsupportedError},
     codegen::{CodeWriter, DirBuilder, Separator},
     idl::{
-        Enum, Field, Function, FunctionInput, Idl, Item, Method, MethodCategory, Name,
+        Constant, Enum, Field, Function, FunctionInput, Idl, Item, Method, MethodCategory, Name,
         QualifiedName, Record, Resource, Scalar, SelfKind, Signature, Ty, TypeKind, Variant,
     },
 };
 
-use crate::util;
+use crate::{
+    metadata::{AsyncStyle, JavaMetadata, OptionStyle},
+    util::{self, JavaQName},
+};
+
+/// The members of the `Functions` class of a module: its constants and free functions.
+#[derive(Default)]
+struct ModuleMembers<'idl> {
+    constants: Vec<&'idl Constant>,
+    functions: Vec<&'idl Function>,
+}
 
 pub(crate) struct JavaCodeGenerator<'idl> {
     idl: &'idl Idl,
+    metadata: &'idl JavaMetadata,
 }
 
 impl<'idl> JavaCodeGenerator<'idl> {
-    pub(crate) fn new(idl: &'idl Idl) -> Self {
-        Self { idl }
+    pub(crate) fn new(idl: &'idl Idl, metadata: &'idl JavaMetadata) -> Self {
+        Self { idl, metadata }
     }
 
     pub(crate) fn generate(mut self, mut dir: DirBuilder<'_>) -> anyhow::Result<()> {
-        let mut functions: BTreeMap<QualifiedName, Vec<&'idl Function>> = Default::default();
+        let mut modules: BTreeMap<QualifiedName, ModuleMembers<'idl>> = Default::default();
 
-        for (qname, item) in self.idl.definitions() {
-            self.generate_item(&mut dir, qname, item, &mut functions)?;
+        for_each_supported(self.idl.definitions(), |(qname, item)| {
+            self.generate_item(&mut dir, qname, item, &mut modules)
+        })?;
+
+        for_each_supported(&modules, |(module_qname, members)| {
+            self.generate_functions(&mut dir, module_qname, members)
+        })?;
+
+        for qname in util::thrown_qnames(self.idl) {
+            self.generate_exception_class(&mut dir, qname)?;
+        }
+
+        if util::throws_rust_exception(self.idl) {
+            self.generate_rust_exception_class(&mut dir)?;
         }
 
-        for (module_qname, functions) in &functions {
-            self.generate_functions(&mut dir, module_qname, functions)?;
+        for (qname, ty) in util::tuple_types(self.idl) {
+            self.generate_tuple_class(&mut dir, &qname, ty)?;
         }
 
         Ok(())
@@ -38,14 +63,18 @@ impl<'idl> JavaCodeGenerator<'idl> {
         dir: &mut DirBuilder<'_>,
         java_type: &str,
         qname: &QualifiedName,
+        implements: Option<&str>,
         body: impl FnOnce(&mut Self, &mut CodeWriter<'_>) -> anyhow::Result<()>,
     ) -> anyhow::Result<()> {
-        let mut file = dir.add_file(util::class_file_name(qname))?;
-        let (package, name) = qname.split_module_name();
-        let package = package.camel_case().dotted();
+        let mut file = dir.add_file(util::class_file_name(self.idl, qname))?;
+        let JavaQName { package, class_name: name } = util::class_package_and_name(self.idl, qname);
+        let package = package.dotted();
         write!(file, "package {package};")?;
         write!(file, "")?;
-        write!(file, "public {java_type} {name} {{",)?;
+        match implements {
+            Some(interface) => write!(file, "public {java_type} {name} implements {interface} {{",)?,
+            None => write!(file, "public {java_type} {name} {{",)?,
+        }
 
         body(self, &mut file)?;
 
@@ -59,7 +88,7 @@ impl<'idl> JavaCodeGenerator<'idl> {
         dir: &mut DirBuilder<'_>,
         qname: &QualifiedName,
         item: &'idl Item,
-        functions: &mut BTreeMap<QualifiedName, Vec<&'idl Function>>,
+        modules: &mut BTreeMap<QualifiedName, ModuleMembers<'idl>>,
     ) -> anyhow::Result<()> {
         match item {
             Item::Resource(resource) => self.generate_resource(dir, qname, resource),
@@ -68,13 +97,15 @@ impl<'idl> JavaCodeGenerator<'idl> {
             Item::Enum(an_enum) => self.generate_enum(dir, qname, an_enum),
             Item::Function(function) => {
                 // Collect functons, grouped by module. We will generate them later.
-                functions
-                    .entry(qname.module_name())
-                    .or_insert(Default::default())
-                    .push(function);
+                modules.entry(qname.module_name()).or_default().functions.push(function);
                 Ok(())
             }
-            _ => anyhow::bail!("unsupported item: "),
+            Item::Constant(constant) => {
+                // Constants go in the same class as the functions of their module.
+                modules.entry(qname.module_name()).or_default().constants.push(constant);
+                Ok(())
+            }
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to Java yet")),
         }
     }
 
@@ -82,13 +113,138 @@ impl<'idl> JavaCodeGenerator<'idl> {
         &mut self,
         dir: &mut DirBuilder<'_>,
         module_qname: &QualifiedName,
-        functions: &[&Function],
+        members: &ModuleMembers<'idl>,
     ) -> anyhow::Result<()> {
         let functions_class = module_qname.join("Functions");
-        self.generate_java_file(dir, "class", &functions_class, |this, file| {
-            for function in functions {
-                this.generate_regular_method(file, None, function.name(), function.signature())?;
+        self.generate_java_file(dir, "class", &functions_class, None, |this, file| {
+            this.generate_constants(file, members.constants.iter().copied())?;
+            this.generate_async_executor(file, members.functions.iter().map(|function| function.signature()))?;
+            for_each_supported(&members.functions, |function| {
+                this.generate_regular_method(file, None, function.name(), function.signature())
+            })
+        })
+    }
+
+    /// Generate the exception thrown when a Rust function fails with an error of the type `qname`,
+    /// which carries the error value in its `error` field.
+    ///
+    /// Rust constructs the exception from the error as passed to native methods (a handle, or the name of an enum variant),
+    /// see `RustCodeGenerator::generate_exception_conversion`.
+    fn generate_exception_class(&mut self, dir: &mut DirBuilder<'_>, qname: &QualifiedName) -> anyhow::Result<()> {
+        let java_qname = util::exception_package_and_name(self.idl, qname);
+        let mut file = dir.add_file(util::java_file_name(&java_qname))?;
+        let JavaQName { package, class_name } = java_qname;
+        let error_class = util::class_dot_name(self.idl, qname);
+        let (native_ty, from_native) = if let Some(Item::Enum(_)) = self.idl.definitions().get(qname) {
+            ("String", "valueOf")
+        } else {
+            ("long", "fromNativeHandle")
+        };
+
+        write!(file, "package {};", package.dotted())?;
+        write!(file, "")?;
+        write!(file, "public class {class_name} extends RuntimeException {{")?;
+        write!(file, "public final {error_class} error;")?;
+        write!(file, "")?;
+        write!(file, "public {class_name}({error_class} error) {{")?;
+        write!(file, "super(String.valueOf(error));")?;
+        write!(file, "this.error = error;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public {class_name}({native_ty} error) {{")?;
+        write!(file, "this({error_class}.{from_native}(error));")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        Ok(())
+    }
+
+    /// Generate the exception thrown when a Rust function fails with an `anyhow::Error` or `Box<dyn Error>`,
+    /// whose message is the error's `Display` string (see `RustCodeGenerator::generate_rust_exception_conversion`).
+    fn generate_rust_exception_class(&mut self, dir: &mut DirBuilder<'_>) -> anyhow::Result<()> {
+        let java_qname = util::rust_exception_package_and_name(self.idl);
+        let mut file = dir.add_file(util::java_file_name(&java_qname))?;
+        let JavaQName { package, class_name } = java_qname;
+
+        write!(file, "package {};", package.dotted())?;
+        write!(file, "")?;
+        write!(file, "public class {class_name} extends RuntimeException {{")?;
+        write!(file, "public {class_name}(String message) {{")?;
+        write!(file, "super(message);")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        Ok(())
+    }
+
+    /// Generate the class standing for tuples of the type `ty`, which has a field for each element (`f0`, `f1`, etc.)
+    /// and a constructor taking them all.
+    /// Like a record, a tuple is passed between Java and Rust as a handle to the Rust value
+    /// (see `RustCodeGenerator::generate_tuple_natives`).
+    fn generate_tuple_class(&mut self, dir: &mut DirBuilder<'_>, qname: &QualifiedName, ty: &Ty) -> anyhow::Result<()> {
+        let TypeKind::Tuple { elements, repr: _ } = ty.kind() else {
+            anyhow::bail!("`{ty}` is not a tuple");
+        };
+        let JavaQName { package: _, class_name } = util::class_package_and_name(self.idl, qname);
+        let dot_name = util::class_dot_name(self.idl, qname);
+        let fields: Vec<(Name, &Ty)> = elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (Name::from(format!("f{index}")), element))
+            .collect();
+
+        self.generate_java_file(dir, "final class", qname, None, |this, file| {
+            for (name, ty) in &fields {
+                this.generate_field(file, "public", name, ty)?;
+            }
+
+            write!(file, "")?;
+            write!(file, "public {class_name}(")?;
+            for ((name, ty), sep) in fields.iter().comma_separated() {
+                write!(file, "{ty} {name}{sep}", ty = this.write_public_ty(ty)?)?;
+            }
+            write!(file, ") {{")?;
+            for (name, _) in &fields {
+                write!(file, "this.{name} = {name};")?;
+            }
+            write!(file, "}}")?;
+
+            let new = util::record_native_name("new");
+            let drop = util::record_native_name("drop");
+
+            write!(file, "")?;
+            write!(file, "public static native long {new}(")?;
+            for ((name, ty), sep) in fields.iter().comma_separated() {
+                write!(file, "{param}{sep}", param = this.write_native_param(ty, name)?)?;
+            }
+            write!(file, ");")?;
+            for (name, ty) in &fields {
+                let get = util::record_native_name(&format!("get_{name}"));
+                write!(file, "public static native {ty} {get}(long handle);", ty = this.write_native_ty(ty)?)?;
+            }
+            write!(file, "public static native void {drop}(long handle);")?;
+
+            write!(file, "")?;
+            write!(file, "public long toNativeHandle() {{")?;
+            write!(file, "return {new}(")?;
+            for ((name, ty), sep) in fields.iter().comma_separated() {
+                write!(file, "{}{sep}", this.public_to_native(ty, &format!("this.{name}")))?;
             }
+            write!(file, ");")?;
+            write!(file, "}}")?;
+
+            write!(file, "")?;
+            write!(file, "public static {dot_name} fromNativeHandle(long handle) {{")?;
+            write!(file, "try {{")?;
+            write!(file, "return new {dot_name}(")?;
+            for ((name, ty), sep) in fields.iter().comma_separated() {
+                let get = util::record_native_name(&format!("get_{name}"));
+                let (open, close) = this.native_to_public(ty);
+                write!(file, "{open}{get}(handle){close}{sep}")?;
+            }
+            write!(file, ");")?;
+            write!(file, "}} finally {{")?;
+            write!(file, "{drop}(handle);")?;
+            write!(file, "}}")?;
+            write!(file, "}}")?;
             Ok(())
         })
     }
@@ -99,25 +255,142 @@ impl<'idl> JavaCodeGenerator<'idl> {
         qname: &QualifiedName,
         resource: &Resource,
     ) -> anyhow::Result<()> {
-        self.generate_java_file(dir, "class", qname, |this, file| {
-            write!(file, "private long pointer;")?;
-            this.generate_methods(file, resource.methods())?;
+        self.generate_java_file(dir, "class", qname, Some("AutoCloseable"), |this, file| {
+            this.generate_constants(file, resource.constants())?;
+            this.generate_resource_state(file, qname)?;
+            this.generate_methods(file, qname, resource.methods())?;
             Ok(())
         })
     }
 
+    /// Generate a `static final` field for each constant, initialized with its value so that Java need not call into Rust.
+    fn generate_constants<'c>(
+        &self,
+        file: &mut CodeWriter<'_>,
+        constants: impl IntoIterator<Item = &'c Constant>,
+    ) -> anyhow::Result<()> {
+        let mut any = false;
+        for constant in constants {
+            let ty = self.write_ty(constant.ty())?;
+            let value = util::java_literal(constant.ty(), constant.value())?;
+            write!(file, "public static final {ty} {name} = {value};", name = constant.name())?;
+            any = true;
+        }
+        if any {
+            write!(file, "")?;
+        }
+        Ok(())
+    }
+
+    /// Generate the members through which an object of a resource class owns the Rust value,
+    /// as an opaque handle (see `gluegun_java_util::resource`).
+    ///
+    /// As for iterators (see [`Self::generate_iterator_class`][]), the Rust value is released when `close` is called
+    /// or, failing that, by a `Cleaner` when the object is collected.
+    /// Methods using the handle are `synchronized` so that it is never used by two threads at once.
+    fn generate_resource_state(&self, file: &mut CodeWriter<'_>, qname: &QualifiedName) -> anyhow::Result<()> {
+        let class_name = util::class_package_and_name(self.idl, qname).class_name;
+        let drop = util::resource_native_name("drop");
+
+        write!(file, "private static final java.lang.ref.Cleaner CLEANER = java.lang.ref.Cleaner.create();")?;
+        write!(file, "")?;
+        write!(file, "public static native void {drop}(long handle);")?;
+        write!(file, "")?;
+        write!(file, "// Kept separate from the object itself so that the cleaner does not keep it reachable.")?;
+        write!(file, "private static final class State implements Runnable {{")?;
+        write!(file, "private long handle;")?;
+        write!(file, "")?;
+        write!(file, "State(long handle) {{")?;
+        write!(file, "this.handle = handle;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public void run() {{")?;
+        write!(file, "if (handle != 0) {{")?;
+        write!(file, "{drop}(handle);")?;
+        write!(file, "handle = 0;")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "private final State state;")?;
+        write!(file, "private final java.lang.ref.Cleaner.Cleanable cleanable;")?;
+        write!(file, "")?;
+        write!(file, "private {class_name}(State state) {{")?;
+        write!(file, "this.state = state;")?;
+        write!(file, "this.cleanable = CLEANER.register(this, state);")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public static {class_name} fromNativeHandle(long handle) {{")?;
+        write!(file, "return new {class_name}(new State(handle));")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "// The handle for a method borrowing the Rust value.")?;
+        write!(file, "private long handle() {{")?;
+        write!(file, "if (state.handle == 0) {{")?;
+        write!(file, "throw new IllegalStateException(\"`{class_name}` has been closed\");")?;
+        write!(file, "}}")?;
+        write!(file, "return state.handle;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "// The handle for a method taking the Rust value by value; the object is closed afterwards.")?;
+        write!(file, "private long takeHandle() {{")?;
+        write!(file, "long handle = handle();")?;
+        write!(file, "state.handle = 0;")?;
+        write!(file, "cleanable.clean();")?;
+        write!(file, "return handle;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "// The handle for a Rust function taking the value as an argument; the object is closed afterwards.")?;
+        write!(file, "public synchronized long intoNativeHandle() {{")?;
+        write!(file, "return takeHandle();")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public synchronized void close() {{")?;
+        write!(file, "cleanable.clean();")?;
+        write!(file, "}}")?;
+        Ok(())
+    }
+
     fn generate_record(
         &mut self,
         dir: &mut DirBuilder<'_>,
         qname: &QualifiedName,
         record: &Record,
     ) -> anyhow::Result<()> {
-        self.generate_java_file(dir, "class", qname, |this, file| {
-            this.generate_fields(file, record.fields())?;
+        self.generate_java_file(dir, "class", qname, None, |this, file| {
+            // Readonly fields are private, so that they can only be set by the constructor below (or from Rust).
+            for field in record.public_fields() {
+                let visibility = if *field.readonly() { "private" } else { "public" };
+                this.generate_field(file, visibility, &field.name().to_camel_case(), field.ty())?;
+            }
+            this.generate_record_conversions(file, qname, record)?;
 
-            // FIXME: make a constructor?
+            // A record with private fields or `#[non_exhaustive]` cannot be built from Java, only returned from Rust.
+            // One with readonly fields is built with a constructor taking all of its fields.
+            let class_name = util::class_package_and_name(this.idl, qname).class_name;
+            if !record.is_constructible() {
+                write!(file, "")?;
+                write!(file, "private {class_name}() {{}}")?;
+            } else if record.fields().iter().any(|field| *field.readonly()) {
+                write!(file, "")?;
+                write!(file, "public {class_name}(")?;
+                for (field, sep) in record.fields().iter().comma_separated() {
+                    let name = field.name().to_camel_case();
+                    write!(file, "{annotation}{ty} {name}{sep}", annotation = this.nullable_annotation(field.ty()), ty = this.write_public_ty(field.ty())?)?;
+                }
+                write!(file, ") {{")?;
+                for field in record.fields() {
+                    write!(file, "this.{name} = {name};", name = field.name().to_camel_case())?;
+                }
+                write!(file, "}}")?;
 
-            this.generate_methods(file, record.methods())?;
+                // Used by `fromNativeHandle`.
+                write!(file, "")?;
+                write!(file, "private {class_name}() {{}}")?;
+            }
+
+            this.generate_accessors(file, record.public_fields())?;
+            this.generate_methods(file, qname, record.methods())?;
             Ok(())
         })
     }
@@ -128,17 +401,93 @@ impl<'idl> JavaCodeGenerator<'idl> {
         qname: &QualifiedName,
         variant: &Variant,
     ) -> anyhow::Result<()> {
-        self.generate_java_file(dir, "abstract class", qname, |this, file| {
-            this.generate_methods(file, variant.methods())?;
+        self.generate_java_file(dir, "abstract sealed class", qname, None, |this, file| {
+            this.generate_variant_conversions(file, qname, variant)?;
+            this.generate_methods(file, qname, variant.methods())?;
             Ok(())
-        })?;
+        })
+    }
 
-        for variant_arm in variant.arms() {
-            let variant_qname = qname.module_name().join(variant_arm.name());
-            self.generate_java_file(dir, "abstract class", &variant_qname, |this, file| {
-                this.generate_fields(file, variant_arm.fields())?;
-                Ok(())
-            })?;
+    /// Generate the natives backing a variant (see `RustCodeGenerator::generate_variant_natives`),
+    /// a nested subclass for each arm holding its fields,
+    /// and the methods converting a value to and from a handle to the Rust value.
+    ///
+    /// Each subclass converts itself to a handle using the native constructor for its arm.
+    /// Converting back asks Rust which arm the handle holds and reads the fields of that arm;
+    /// as for records (see [`Self::generate_record_conversions`][]), the handle is then released.
+    fn generate_variant_conversions(
+        &self,
+        file: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        variant: &Variant,
+    ) -> anyhow::Result<()> {
+        let class_name = util::class_dot_name(self.idl, qname);
+        let arm_native = util::variant_native_name("arm");
+        let drop = util::variant_native_name("drop");
+
+        for arm in variant.arms() {
+            let new = util::variant_native_name(&format!("new_{}", arm.name().to_snake_case()));
+            write!(file, "public static native long {new}(")?;
+            for (field, sep) in arm.fields().iter().comma_separated() {
+                write!(file, "{param}{sep}", param = self.write_native_param(field.ty(), field.name())?)?;
+            }
+            write!(file, ");")?;
+        }
+        write!(file, "public static native String {arm_native}(long handle);")?;
+        for arm in variant.arms() {
+            for field in arm.fields() {
+                let get = util::variant_native_name(&format!("get_{}_{}", arm.name().to_snake_case(), field.name()));
+                write!(file, "public static native {ty} {get}(long handle);", ty = self.write_native_ty(field.ty())?)?;
+            }
+        }
+        write!(file, "public static native void {drop}(long handle);")?;
+
+        write!(file, "")?;
+        write!(file, "public abstract long toNativeHandle();")?;
+
+        write!(file, "")?;
+        write!(file, "public static {class_name} fromNativeHandle(long handle) {{")?;
+        write!(file, "try {{")?;
+        write!(file, "switch ({arm_native}(handle)) {{")?;
+        for arm in variant.arms() {
+            let arm_class = arm.name().to_pascal_case();
+            write!(file, "case \"{arm_class}\": {{")?;
+            write!(file, "{arm_class} arm = new {arm_class}();")?;
+            for field in arm.fields() {
+                let get = util::variant_native_name(&format!("get_{}_{}", arm.name().to_snake_case(), field.name()));
+                let (open, close) = self.native_to_public(field.ty());
+                write!(file, "arm.{name} = {open}{get}(handle){close};", name = field.name().to_camel_case())?;
+            }
+            write!(file, "return arm;")?;
+            write!(file, "}}")?;
+        }
+        write!(file, "default: {{")?;
+        write!(file, "throw new IllegalStateException(\"unknown arm of {class_name}\");")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "}} finally {{")?;
+        write!(file, "{drop}(handle);")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+
+        for arm in variant.arms() {
+            let arm_class = arm.name().to_pascal_case();
+            let new = util::variant_native_name(&format!("new_{}", arm.name().to_snake_case()));
+            write!(file, "")?;
+            write!(file, "public static final class {arm_class} extends {class_name} {{")?;
+            if !arm.fields().is_empty() {
+                self.generate_fields(file, arm.fields())?;
+                write!(file, "")?;
+            }
+            write!(file, "public long toNativeHandle() {{")?;
+            write!(file, "return {new}(")?;
+            for (field, sep) in arm.fields().iter().comma_separated() {
+                let value = format!("this.{}", field.name().to_camel_case());
+                write!(file, "{}{sep}", self.public_to_native(field.ty(), &value))?;
+            }
+            write!(file, ");")?;
+            write!(file, "}}")?;
+            write!(file, "}}")?;
         }
 
         Ok(())
@@ -150,48 +499,185 @@ impl<'idl> JavaCodeGenerator<'idl> {
         qname: &QualifiedName,
         an_enum: &Enum,
     ) -> anyhow::Result<()> {
-        self.generate_java_file(dir, "enum", qname, |this, file| {
+        self.generate_java_file(dir, "enum", qname, None, |this, file| {
             for (arm, sep) in an_enum.arms().iter().comma_separated() {
-                write!(file, "{}{sep}", arm.name().upper_camel_case())?;
+                write!(file, "{}{sep}", arm.name().to_pascal_case())?;
             }
-            this.generate_methods(file, an_enum.methods())?;
+            this.generate_methods(file, qname, an_enum.methods())?;
             Ok(())
         })
     }
 
-    fn generate_fields(&self, file: &mut CodeWriter<'_>, fields: &[Field]) -> anyhow::Result<()> {
+    fn generate_fields<'f>(
+        &self,
+        file: &mut CodeWriter<'_>,
+        fields: impl IntoIterator<Item = &'f Field>,
+    ) -> anyhow::Result<()> {
         for field in fields {
-            write!(
-                file,
-                "public {ty} {name};",
-                ty = self.write_ty(field.ty())?,
-                name = field.name().camel_case()
-            )?;
+            self.generate_field(file, "public", &field.name().to_camel_case(), field.ty())?;
         }
         Ok(())
     }
 
+    fn generate_field(&self, file: &mut CodeWriter<'_>, visibility: &str, name: &Name, ty: &Ty) -> anyhow::Result<()> {
+        // An `Optional` field starts out empty rather than `null`.
+        let init = if self.is_optional(ty) {
+            " = java.util.Optional.empty()"
+        } else {
+            ""
+        };
+        let annotation = self.nullable_annotation(ty);
+        write!(file, "{annotation}{visibility} {ty} {name}{init};", ty = self.write_public_ty(ty)?)?;
+        Ok(())
+    }
+
+    /// Generate a getter for each of `fields` (e.g., `getName()` for `name`),
+    /// and a setter for each that is not marked `#[gluegun(readonly)]` (e.g., `setName(name)`).
+    fn generate_accessors<'f>(
+        &self,
+        file: &mut CodeWriter<'_>,
+        fields: impl IntoIterator<Item = &'f Field>,
+    ) -> anyhow::Result<()> {
+        for field in fields {
+            let name = field.name().to_camel_case();
+            let accessor = field.name().to_pascal_case();
+            let annotation = self.nullable_annotation(field.ty());
+            let ty = self.write_public_ty(field.ty())?;
+
+            write!(file, "")?;
+            write!(file, "{annotation}public {ty} get{accessor}() {{")?;
+            write!(file, "return this.{name};")?;
+            write!(file, "}}")?;
+
+            if !field.readonly() {
+                write!(file, "")?;
+                write!(file, "public void set{accessor}({annotation}{ty} {name}) {{")?;
+                write!(file, "this.{name} = {name};")?;
+                write!(file, "}}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The annotation marking a value of type `ty` as nullable (with a trailing space),
+    /// if it is nullable and `nullable-annotation` is set.
+    fn nullable_annotation(&self, ty: &Ty) -> String {
+        match &self.metadata.nullable_annotation {
+            Some(annotation) if self.is_nullable(ty) => format!("@{annotation} "),
+            _ => String::new(),
+        }
+    }
+
+    /// Generate the natives backing a record (see `RustCodeGenerator::generate_record_natives`)
+    /// and the methods converting it to and from a handle to the Rust value.
+    ///
+    /// The handle passed to Rust is consumed by the native method receiving it;
+    /// the handle of a record returned from Rust is released once its fields have been read.
+    /// Only public fields are copied, so a record with private fields can only be converted from a handle,
+    /// as can a `#[non_exhaustive]` one.
+    fn generate_record_conversions(
+        &self,
+        file: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        record: &Record,
+    ) -> anyhow::Result<()> {
+        let class_name = util::class_dot_name(self.idl, qname);
+        let new = util::record_native_name("new");
+        let drop = util::record_native_name("drop");
+
+        write!(file, "")?;
+        if record.is_constructible() {
+            write!(file, "public static native long {new}(")?;
+            for (field, sep) in record.fields().iter().comma_separated() {
+                write!(file, "{param}{sep}", param = self.write_native_param(field.ty(), field.name())?)?;
+            }
+            write!(file, ");")?;
+        }
+        for field in record.public_fields() {
+            let get = util::record_native_name(&format!("get_{}", field.name()));
+            write!(file, "public static native {ty} {get}(long handle);", ty = self.write_native_ty(field.ty())?)?;
+        }
+        write!(file, "public static native void {drop}(long handle);")?;
+
+        if record.is_constructible() {
+            write!(file, "")?;
+            write!(file, "public long toNativeHandle() {{")?;
+            write!(file, "return {new}(")?;
+            for (field, sep) in record.fields().iter().comma_separated() {
+                let value = format!("this.{}", field.name().to_camel_case());
+                write!(file, "{}{sep}", self.public_to_native(field.ty(), &value))?;
+            }
+            write!(file, ");")?;
+            write!(file, "}}")?;
+        }
+
+        write!(file, "")?;
+        write!(file, "public static {class_name} fromNativeHandle(long handle) {{")?;
+        write!(file, "try {{")?;
+        write!(file, "{class_name} record = new {class_name}();")?;
+        for field in record.public_fields() {
+            let get = util::record_native_name(&format!("get_{}", field.name()));
+            let (open, close) = self.native_to_public(field.ty());
+            write!(file, "record.{name} = {open}{get}(handle){close};", name = field.name().to_camel_case())?;
+        }
+        write!(file, "return record;")?;
+        write!(file, "}} finally {{")?;
+        write!(file, "{drop}(handle);")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+
+        Ok(())
+    }
+
     fn generate_methods(
         &self,
         file: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
         methods: &[Method],
     ) -> anyhow::Result<()> {
+        self.generate_async_executor(file, methods.iter().map(|method| method.signature()))?;
         for method in methods {
-            self.generate_method(file, method)?;
+            self.generate_method(file, qname, method)?;
+        }
+        Ok(())
+    }
+
+    /// True if the function with `signature` returns a `CompletableFuture` (see the `async-style` metadata).
+    fn is_completable(&self, signature: &Signature) -> bool {
+        util::is_async(signature) && self.metadata.async_style == AsyncStyle::CompletableFuture
+    }
+
+    /// Generate the executor on which the methods of a class returning a `CompletableFuture` make their native call,
+    /// if any of `signatures` needs it.
+    ///
+    /// The native call blocks until the Rust runtime has driven the future to completion,
+    /// so it is kept off the common `ForkJoinPool`. Its threads are daemons so that they do not keep the JVM alive.
+    fn generate_async_executor<'s>(
+        &self,
+        file: &mut CodeWriter<'_>,
+        mut signatures: impl Iterator<Item = &'s Signature>,
+    ) -> anyhow::Result<()> {
+        if signatures.any(|signature| self.is_completable(signature)) {
+            write!(file, "")?;
+            write!(file, "private static final java.util.concurrent.Executor ASYNC_EXECUTOR = java.util.concurrent.Executors.newCachedThreadPool(runnable -> {{")?;
+            write!(file, "Thread thread = new Thread(runnable);")?;
+            write!(file, "thread.setDaemon(true);")?;
+            write!(file, "return thread;")?;
+            write!(file, "}});")?;
         }
         Ok(())
     }
 
-    fn generate_method(&self, file: &mut CodeWriter<'_>, method: &Method) -> anyhow::Result<()> {
+    fn generate_method(&self, file: &mut CodeWriter<'_>, qname: &QualifiedName, method: &Method) -> anyhow::Result<()> {
         write!(file, "")?;
 
         match method.category() {
-            MethodCategory::Constructor => todo!(),
+            MethodCategory::Constructor => self.generate_constructor(file, qname, method.signature()),
 
             MethodCategory::InstanceMethod(self_kind)
             | MethodCategory::BuilderMethod(self_kind) => self.generate_regular_method(
                 file,
-                Some(self_kind),
+                Some(&self.receiver(qname, self_kind)?),
                 method.name(),
                 method.signature(),
             ),
@@ -204,48 +690,432 @@ impl<'idl> JavaCodeGenerator<'idl> {
         }
     }
 
+    /// How an instance method of the type `qname` passes `self` to its native counterpart.
+    ///
+    /// An object of a resource class lends the handle it owns (or gives it up, for a method taking `self` by value).
+    /// A record or variant is copied into a new handle for each call, and an enum is passed as the name of its variant.
+    fn receiver(&self, qname: &QualifiedName, self_kind: &SelfKind) -> anyhow::Result<Receiver> {
+        match self.idl.definitions().get(qname) {
+            Some(Item::Resource(_)) => Ok(Receiver {
+                native_param: "long self_handle",
+                value: match self_kind {
+                    SelfKind::ByValue => "takeHandle()",
+                    _ => "handle()",
+                },
+                synchronized: true,
+            }),
+            Some(Item::Record(_) | Item::Variant(_)) => Ok(Receiver {
+                native_param: "long self_handle",
+                value: "toNativeHandle()",
+                synchronized: false,
+            }),
+            Some(Item::Enum(_)) => Ok(Receiver {
+                native_param: "String self_name",
+                value: "name()",
+                synchronized: false,
+            }),
+            _ => anyhow::bail!("unsupported receiver type: `{}`", qname.colon_colon()),
+        }
+    }
+
+    /// Generate the constructor of a resource class (the Rust `new` function), along with its native counterpart,
+    /// which returns the handle the object will own (see [`Self::generate_resource_state`][]).
+    fn generate_constructor(
+        &self,
+        file: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        signature: &Signature,
+    ) -> anyhow::Result<()> {
+        let Some(Item::Resource(_)) = self.idl.definitions().get(qname) else {
+            anyhow::bail!("`{}::new`: only resources can have constructors", qname.colon_colon());
+        };
+        let class_name = util::class_package_and_name(self.idl, qname).class_name;
+        let name = Name::from("new");
+
+        let mut overloads = vec![vec![]];
+        overloads.extend(util::default_overloads(signature));
+        for omitted in overloads {
+            let inputs = util::remaining_inputs(signature, &omitted);
+            let native_name = self.generate_native_counterpart(file, None, &name, signature, &omitted)?;
+
+            write!(file, "")?;
+            write!(file, "public {class_name}(")?;
+            self.generate_public_inputs(file, &inputs)?;
+            if signature.output_ty().error_ty().is_some() {
+                write!(file, ") throws RuntimeException {{")?;
+            } else {
+                write!(file, ") {{")?;
+            }
+            self.generate_range_checks(file, &inputs)?;
+            write!(file, "this.state = new State({native_name}(")?;
+            self.generate_native_arguments(file, None, &inputs)?;
+            write!(file, "));")?;
+            write!(file, "this.cleanable = CLEANER.register(this, state);")?;
+            write!(file, "}}")?;
+        }
+
+        Ok(())
+    }
+
     fn generate_regular_method(
         &self,
         file: &mut CodeWriter<'_>,
-        self_kind: Option<&SelfKind>,
+        receiver: Option<&Receiver>,
         name: &Name,
         signature: &Signature,
     ) -> anyhow::Result<()> {
-        let native_name = self.generate_native_counterpart(file, self_kind, name, signature)?;
+        self.generate_overload(file, receiver, name, signature, &[])?;
+
+        // Parameters with default values get overloads that omit them; the defaults are supplied on the Rust side.
+        for omitted in util::default_overloads(signature) {
+            self.generate_overload(file, receiver, name, signature, &omitted)?;
+        }
+
+        if let Some(item_ty) = util::iterator_item(signature.output_ty().main_ty().ty()) {
+            self.generate_iterator_class(file, name, item_ty)?;
+        }
+
+        Ok(())
+    }
+
+    /// Generate the nested class returned by the method `name` to hold a Rust iterator over `item_ty`.
+    ///
+    /// The class holds an opaque handle to the Rust iterator and implements `java.util.Iterator`.
+    /// The Rust iterator is released once it is exhausted or when `close` is called;
+    /// if the caller stops early and never calls `close`, a `Cleaner` releases it when the object is collected.
+    /// Methods are `synchronized` so that the handle is never used by two threads at once.
+    fn generate_iterator_class(
+        &self,
+        file: &mut CodeWriter<'_>,
+        name: &Name,
+        item_ty: &Ty,
+    ) -> anyhow::Result<()> {
+        let class_name = util::iterator_class_name(name);
+        let has_next = util::iterator_native_name(name, "has_next");
+        let next = util::iterator_native_name(name, "next");
+        let drop = util::iterator_native_name(name, "drop");
+        let item = self.write_objectified_ty(item_ty)?;
 
         write!(file, "")?;
+        write!(file, "public static native byte {has_next}(long handle);")?;
+        write!(file, "public static native {ty} {next}(long handle);", ty = self.write_ty(item_ty)?)?;
+        write!(file, "public static native void {drop}(long handle);")?;
 
-        let static_kw = if self_kind.is_none() { "static" } else { "" };
+        write!(file, "")?;
+        write!(file, "public static final class {class_name} implements java.util.Iterator<{item}>, AutoCloseable {{")?;
+        write!(file, "private static final java.lang.ref.Cleaner CLEANER = java.lang.ref.Cleaner.create();")?;
+        write!(file, "")?;
+        write!(file, "// Kept separate from the iterator itself so that the cleaner does not keep it reachable.")?;
+        write!(file, "private static final class State implements Runnable {{")?;
+        write!(file, "private long handle;")?;
+        write!(file, "")?;
+        write!(file, "State(long handle) {{")?;
+        write!(file, "this.handle = handle;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public void run() {{")?;
+        write!(file, "if (handle != 0) {{")?;
+        write!(file, "{drop}(handle);")?;
+        write!(file, "handle = 0;")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "private final State state;")?;
+        write!(file, "private final java.lang.ref.Cleaner.Cleanable cleanable;")?;
+        write!(file, "")?;
+        write!(file, "{class_name}(long handle) {{")?;
+        write!(file, "this.state = new State(handle);")?;
+        write!(file, "this.cleanable = CLEANER.register(this, state);")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public synchronized boolean hasNext() {{")?;
+        write!(file, "if (state.handle == 0) {{")?;
+        write!(file, "return false;")?;
+        write!(file, "}}")?;
+        write!(file, "if ({has_next}(state.handle) != 0) {{")?;
+        write!(file, "return true;")?;
+        write!(file, "}}")?;
+        write!(file, "close();")?;
+        write!(file, "return false;")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public synchronized {item} next() {{")?;
+        write!(file, "if (!hasNext()) {{")?;
+        write!(file, "throw new java.util.NoSuchElementException();")?;
+        write!(file, "}}")?;
+        write!(file, "return {next}(state.handle);")?;
+        write!(file, "}}")?;
+        write!(file, "")?;
+        write!(file, "public synchronized void close() {{")?;
+        write!(file, "cleanable.clean();")?;
+        write!(file, "}}")?;
+        write!(file, "}}")?;
+
+        Ok(())
+    }
 
-        let return_ty = signature.output_ty().main_ty();
-        write!(
-            file,
-            "public {static_kw} {ret} {name}(",
-            ret = self.write_ty(return_ty.ty())?,
-            name = name
-        )?;
-        self.generate_function_inputs(file, signature.inputs())?;
-        write!(file, ") {{")?;
-        write!(file, "return {native_name}(")?;
-        for (input, sep) in signature.inputs().iter().comma_separated() {
-            write!(file, "{input_name}{sep}", input_name = input.name())?;
+    /// Generate a public method along with the native method backing it.
+    ///
+    /// # Parameters
+    ///
+    /// * `omitted`, parameters left out of this overload (empty for the method with the full signature)
+    fn generate_overload(
+        &self,
+        file: &mut CodeWriter<'_>,
+        receiver: Option<&Receiver>,
+        name: &Name,
+        signature: &Signature,
+        omitted: &[&FunctionInput],
+    ) -> anyhow::Result<()> {
+        let inputs = util::remaining_inputs(signature, omitted);
+        let native_name = self.generate_native_counterpart(file, receiver, name, signature, omitted)?;
+
+        write!(file, "")?;
+
+        let static_kw = match receiver {
+            None => "static",
+            Some(Receiver { synchronized: true, .. }) => "synchronized",
+            Some(_) => "",
+        };
+
+        let return_ty = util::awaited_ty(signature.output_ty().main_ty().ty());
+        let iterator_class = util::iterator_item(return_ty).map(|_| util::iterator_class_name(name));
+        let completable = self.is_completable(signature);
+        let ret = match &iterator_class {
+            Some(class_name) => class_name.clone(),
+            None if self.is_optional(return_ty) => self.write_public_ty(return_ty)?,
+            None if completable && util::is_void(return_ty) => "Void".to_string(),
+            None if completable => self.write_objectified_ty(return_ty)?,
+            None => self.write_return_ty(return_ty)?,
+        };
+        let ret = if completable {
+            format!("java.util.concurrent.CompletableFuture<{ret}>")
+        } else {
+            ret
+        };
+
+        // A future is never `null`, even if the value it completes with may be.
+        if self.is_nullable(return_ty) && !completable {
+            if let Some(annotation) = &self.metadata.nullable_annotation {
+                write!(file, "@{annotation}")?;
+            }
         }
-        write!(file, ");")?;
+
+        // `#[must_use]` maps to the configured check-return annotation; it is meaningless on a `void` method.
+        if let Some(annotation) = &self.metadata.check_return_annotation {
+            if *signature.must_use() && !util::is_void(return_ty) {
+                write!(file, "@{annotation}")?;
+            }
+        }
+        write!(file, "public {static_kw} {ret} {name}(")?;
+        self.generate_public_inputs(file, &inputs)?;
+
+        // Errors from Rust are thrown as an exception carrying the error, if it has a Java class,
+        // as the crate's `RustException` if it is an `anyhow::Error` or `Box<dyn Error>`,
+        // and otherwise as a `RuntimeException` (or, for a future, complete it exceptionally).
+        match signature.output_ty().error_ty() {
+            Some(error_ty) if !completable => match util::thrown_qname(self.idl, error_ty) {
+                Some(qname) => write!(file, ") throws {} {{", util::exception_dot_name(self.idl, qname))?,
+                None if util::is_rust_error(error_ty) => write!(file, ") throws {} {{", util::rust_exception_dot_name(self.idl))?,
+                None => write!(file, ") throws RuntimeException {{")?,
+            },
+            _ => write!(file, ") {{")?,
+        }
+
+        self.generate_range_checks(file, &inputs)?;
+
+        // A resource argument gives up its handle, which must not be the one the receiver lends.
+        if receiver.is_some() {
+            for input in &inputs {
+                if util::resource_qname(self.idl, input.refd_ty().ty()).is_some() {
+                    let input_name = input.name();
+                    write!(file, "if ((Object) {input_name} == this) {{")?;
+                    write!(file, "throw new IllegalArgumentException(\"`{input_name}` cannot be the object itself\");")?;
+                    write!(file, "}}")?;
+                }
+            }
+        }
+
+        // Iterators come back from Rust as a handle, which is wrapped in the class holding it.
+        let (open, close) = match &iterator_class {
+            Some(class_name) => (format!("new {class_name}("), ")"),
+            None => self.native_to_public(return_ty),
+        };
+
+        if completable {
+            self.generate_completable_call(file, receiver, &native_name, return_ty, (&open, close), &inputs)?;
+            write!(file, "}}")?;
+            return Ok(());
+        }
+
+        self.generate_native_call(file, receiver, &native_name, return_ty, (&open, close), &inputs)?;
+
+        // Java has no way to declare that a method never returns,
+        // so the best we can do is make sure control never falls out the bottom.
+        if return_ty.is_never() {
+            write!(
+                file,
+                "throw new AssertionError(\"`{name}` is declared as never returning\");"
+            )?;
+        }
+
         write!(file, "}}")?;
 
         Ok(())
     }
 
+    /// Generate the body of a public method returning a `CompletableFuture`, which makes the native call
+    /// on the class's executor (see [`Self::generate_async_executor`][]).
+    ///
+    /// For a resource, the call holds the object's lock, as a `synchronized` method would,
+    /// so that the handle cannot be released while the Rust value is in use.
+    fn generate_completable_call(
+        &self,
+        file: &mut CodeWriter<'_>,
+        receiver: Option<&Receiver>,
+        native_name: &str,
+        return_ty: &Ty,
+        (open, close): (&str, &str),
+        inputs: &[&FunctionInput],
+    ) -> anyhow::Result<()> {
+        let run = if util::is_void(return_ty) {
+            "runAsync"
+        } else {
+            "supplyAsync"
+        };
+        let synchronized = receiver.is_some_and(|receiver| receiver.synchronized);
+
+        write!(file, "return java.util.concurrent.CompletableFuture.{run}(() -> {{")?;
+        if synchronized {
+            write!(file, "synchronized (this) {{")?;
+        }
+        self.generate_native_call(file, receiver, native_name, return_ty, (open, close), inputs)?;
+        if synchronized {
+            write!(file, "}}")?;
+        }
+        write!(file, "}}, ASYNC_EXECUTOR);")?;
+        Ok(())
+    }
+
+    /// Generate the statement(s) calling the native method `native_name` and returning its result,
+    /// converted to the public type by wrapping it in `open` and `close`.
+    ///
+    /// An `Option` that cannot be `null` on the native side comes back as an array holding zero or one element
+    /// (see [`util::primitive_option_element`][]), which is unpacked here.
+    fn generate_native_call(
+        &self,
+        file: &mut CodeWriter<'_>,
+        receiver: Option<&Receiver>,
+        native_name: &str,
+        return_ty: &Ty,
+        (open, close): (&str, &str),
+        inputs: &[&FunctionInput],
+    ) -> anyhow::Result<()> {
+        if let Some(element) = util::primitive_option_element(self.idl, return_ty) {
+            write!(file, "{ty}[] nativeResult = {native_name}(", ty = self.write_native_ty(element)?)?;
+            self.generate_native_arguments(file, receiver, inputs)?;
+            write!(file, ");")?;
+            let value = match element.kind() {
+                // `Boolean` is the public type of an optional `bool`, but the native side passes a `byte`.
+                TypeKind::Scalar(Scalar::Boolean) => "nativeResult[0] != 0".to_string(),
+                _ => {
+                    let (open, close) = self.native_to_public(element);
+                    format!("{open}nativeResult[0]{close}")
+                }
+            };
+            if self.is_optional(return_ty) {
+                write!(
+                    file,
+                    "return nativeResult.length == 0 ? java.util.Optional.empty() : java.util.Optional.of({value});"
+                )?;
+            } else {
+                write!(file, "return nativeResult.length == 0 ? null : {value};")?;
+            }
+            return Ok(());
+        }
+
+        // Unit and never both map to `void`, so there is nothing to return.
+        let return_kw = if util::is_void(return_ty) {
+            ""
+        } else {
+            "return "
+        };
+        write!(file, "{return_kw}{open}{native_name}(")?;
+        self.generate_native_arguments(file, receiver, inputs)?;
+        write!(file, "){close};")?;
+        Ok(())
+    }
+
+    /// Reject integers that would otherwise wrap when narrowed to an unsigned Rust type.
+    fn generate_range_checks(&self, file: &mut CodeWriter<'_>, inputs: &[&FunctionInput]) -> anyhow::Result<()> {
+        if self.metadata.checked_conversions {
+            for input in inputs {
+                let input_name = input.name();
+                let ty = input.refd_ty().ty();
+                if let Some(condition) = util::out_of_range_condition(input_name, ty) {
+                    write!(file, "if ({condition}) {{")?;
+                    write!(
+                        file,
+                        "throw new IllegalArgumentException(\"`{input_name}` is out of range for `{ty}`: \" + {input_name});"
+                    )?;
+                    write!(file, "}}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the arguments with which a public method calls its native counterpart.
+    fn generate_native_arguments(
+        &self,
+        file: &mut CodeWriter<'_>,
+        receiver: Option<&Receiver>,
+        inputs: &[&FunctionInput],
+    ) -> anyhow::Result<()> {
+        if let Some(receiver) = receiver {
+            let sep = if inputs.is_empty() { "" } else { "," };
+            write!(file, "{}{sep}", receiver.value)?;
+        }
+        for (input, sep) in inputs.iter().comma_separated() {
+            let input_ty = input.refd_ty().ty();
+            write!(file, "{}{sep}", self.public_to_native(input_ty, &input.name().to_string()))?;
+        }
+        Ok(())
+    }
+
     fn generate_function_inputs(
         &self,
         file: &mut CodeWriter<'_>,
-        inputs: &[FunctionInput],
+        inputs: &[&FunctionInput],
     ) -> anyhow::Result<()> {
         for (input, sep) in inputs.iter().comma_separated() {
             write!(
                 file,
-                "{ty} {name}{sep}",
-                ty = self.write_ty(input.refd_ty().ty())?,
+                "{param}{sep}",
+                param = self.write_native_param(input.refd_ty().ty(), input.name())?
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::generate_function_inputs`][] but for the public method,
+    /// where `Option` parameters are represented according to the `option-style` metadata.
+    fn generate_public_inputs(
+        &self,
+        file: &mut CodeWriter<'_>,
+        inputs: &[&FunctionInput],
+    ) -> anyhow::Result<()> {
+        for (input, sep) in inputs.iter().comma_separated() {
+            let ty = input.refd_ty().ty();
+            let annotation = match &self.metadata.nullable_annotation {
+                Some(annotation) if self.is_nullable(ty) => format!("@{annotation} "),
+                _ => String::new(),
+            };
+            write!(
+                file,
+                "{annotation}{ty} {name}{sep}",
+                ty = self.write_public_ty(ty)?,
                 name = input.name()
             )?;
         }
@@ -255,40 +1125,192 @@ impl<'idl> JavaCodeGenerator<'idl> {
     fn generate_native_counterpart(
         &self,
         file: &mut CodeWriter<'_>,
-        self_kind: Option<&SelfKind>,
+        receiver: Option<&Receiver>,
         name: &Name,
         signature: &Signature,
+        omitted: &[&FunctionInput],
     ) -> anyhow::Result<String> {
-        let native_name = format!("native${name}");
+        let native_name = util::native_method_name(name, omitted);
+        let inputs = util::remaining_inputs(signature, omitted);
 
         write!(file, "")?;
 
-        let static_kw = if self_kind.is_none() { "static" } else { "" };
-
-        let return_ty = signature.output_ty().main_ty();
-        write!(
-            file,
-            "public {static_kw} native {ret} {native_name}(",
-            ret = self.write_ty(return_ty.ty())?,
-        )?;
-        self.generate_function_inputs(file, signature.inputs())?;
+        // See `util::is_void` for why `void` methods have a `byte` native counterpart.
+        let return_ty = signature.output_ty().main_ty().ty();
+        let ret = if util::is_void(return_ty) {
+            "byte".to_string()
+        } else if util::iterator_item(return_ty).is_some() {
+            "long".to_string() // handle, see `generate_iterator_class`
+        } else if let Some(element) = util::primitive_option_element(self.idl, util::awaited_ty(return_ty)) {
+            format!("{}[]", self.write_native_ty(element)?) // see `generate_native_call`
+        } else {
+            self.write_native_ty(util::awaited_ty(return_ty))?
+        };
+        // Natives are always static, the receiver (if any) is passed explicitly.
+        write!(file, "public static native {ret} {native_name}(")?;
+        if let Some(receiver) = receiver {
+            let sep = if inputs.is_empty() { "" } else { "," };
+            write!(file, "{}{sep}", receiver.native_param)?;
+        }
+        self.generate_function_inputs(file, &inputs)?;
         write!(file, ");")?;
 
         Ok(native_name)
     }
 
+    /// Like [`Self::write_ty`][] but for the return type of a method,
+    /// where both `()` and `!` are represented as `void`.
+    fn write_return_ty(&self, ty: &Ty) -> anyhow::Result<String> {
+        if util::is_void(ty) {
+            Ok("void".to_string())
+        } else {
+            self.write_ty(util::awaited_ty(ty))
+        }
+    }
+
+    /// Like [`Self::write_ty`][] but for native methods,
+    /// where a record or variant is passed as a handle and an enum as the name of its variant.
+    fn write_native_ty(&self, ty: &Ty) -> anyhow::Result<String> {
+        if util::handle_qname(self.idl, ty).is_some() || util::resource_qname(self.idl, ty).is_some() {
+            Ok("long".to_string())
+        } else if util::enum_qname(self.idl, ty).is_some() {
+            Ok("String".to_string())
+        } else if let TypeKind::Set { element, repr: _ } = ty.kind() {
+            Ok(format!("java.util.List<{E}>", E = self.write_objectified_ty(element)?))
+        } else {
+            self.write_ty(ty)
+        }
+    }
+
+    /// Declaration of the native method parameter(s) receiving the value `name` of type `ty`.
+    ///
+    /// A map is passed as a list of its keys and a list of its values, in the same order,
+    /// since the Rust side cannot iterate over a `java.util.Map`.
+    ///
+    /// An `Option` is passed as a `byte` that is non-zero if the value is present, followed by the value
+    /// (or a placeholder if it is absent), since the Rust side cannot receive a `null` reference.
+    fn write_native_param(&self, ty: &Ty, name: &Name) -> anyhow::Result<String> {
+        if let TypeKind::Option { element, repr: _ } = ty.kind() {
+            if let TypeKind::Option { .. } | TypeKind::Map { .. } = element.kind() {
+                anyhow::bail!(UnsupportedError::new(ty, format!("`{name}` cannot be passed from Java yet")));
+            }
+            Ok(format!("byte {name}_is_some, {param}", param = self.write_native_param(element, name)?))
+        } else if let TypeKind::Map { key, value, repr: _ } = ty.kind() {
+            Ok(format!(
+                "java.util.List<{K}> {name}_keys, java.util.List<{V}> {name}_values",
+                K = self.write_objectified_ty(key)?,
+                V = self.write_objectified_ty(value)?,
+            ))
+        } else {
+            Ok(format!("{ty} {name}", ty = self.write_native_ty(ty)?))
+        }
+    }
+
+    /// Java expression converting `value`, of the public type for `ty`, to the type passed to a native method
+    /// (see [`Self::write_native_ty`][]).
+    fn public_to_native(&self, ty: &Ty, value: &str) -> String {
+        if let TypeKind::Option { element, repr: _ } = ty.kind() {
+            // See `write_native_param`.
+            let (is_some, present) = if self.is_optional(ty) {
+                (format!("{value}.isPresent()"), format!("{value}.get()"))
+            } else {
+                (format!("{value} != null"), value.to_string())
+            };
+            let present = match element.kind() {
+                TypeKind::Scalar(Scalar::Boolean) => format!("({present} ? (byte) 1 : (byte) 0)"),
+                _ => self.public_to_native(element, &present),
+            };
+            format!(
+                "{is_some} ? (byte) 1 : (byte) 0, {is_some} ? {present} : {absent}",
+                absent = self.absent_native_value(element),
+            )
+        } else if util::handle_qname(self.idl, ty).is_some() {
+            format!("{value}.toNativeHandle()")
+        } else if util::resource_qname(self.idl, ty).is_some() {
+            format!("{value}.intoNativeHandle()")
+        } else if util::enum_qname(self.idl, ty).is_some() {
+            format!("{value}.name()")
+        } else if let TypeKind::Set { .. } = ty.kind() {
+            format!("new java.util.ArrayList<>({value})")
+        } else if let TypeKind::Map { .. } = ty.kind() {
+            // See `write_native_param`: iterating the same map twice visits the entries in the same order.
+            format!("new java.util.ArrayList<>({value}.keySet()), new java.util.ArrayList<>({value}.values())")
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// Placeholder passed to a native method for an absent `Option<ty>`, see [`Self::write_native_param`][].
+    fn absent_native_value(&self, ty: &Ty) -> String {
+        match ty.kind() {
+            TypeKind::Scalar(_) => match self.write_ty(ty) {
+                Ok(native_ty) => format!("({native_ty}) 0"),
+                Err(_) => "0".to_string(),
+            },
+            _ if util::handle_qname(self.idl, ty).is_some() || util::resource_qname(self.idl, ty).is_some() => {
+                "0L".to_string()
+            }
+            _ if util::enum_qname(self.idl, ty).is_some() => "\"\"".to_string(),
+            TypeKind::String { .. } | TypeKind::Path { .. } => "\"\"".to_string(),
+            TypeKind::Vec { .. } | TypeKind::Array { .. } | TypeKind::Set { .. } => "java.util.List.of()".to_string(),
+            _ => "null".to_string(),
+        }
+    }
+
+    /// Text to wrap around a value returned by a native method to convert it to the public type for `ty`;
+    /// the reverse of [`Self::public_to_native`][].
+    ///
+    /// For an `Option`, the native method returns a nullable reference, which is wrapped if `Option` maps to `Optional`
+    /// (unless it returns an array, see [`Self::generate_native_call`][]).
+    /// A record or variant comes back as a handle to the Rust value, which is read into a new Java object.
+    fn native_to_public(&self, ty: &Ty) -> (String, &'static str) {
+        if self.is_optional(ty) {
+            ("java.util.Optional.ofNullable(".to_string(), ")")
+        } else if let Some(qname) = util::handle_qname(self.idl, ty).or(util::resource_qname(self.idl, ty).cloned()) {
+            (format!("{}.fromNativeHandle(", util::class_dot_name(self.idl, &qname)), ")")
+        } else if let Some(qname) = util::enum_qname(self.idl, ty) {
+            (format!("{}.valueOf(", util::class_dot_name(self.idl, qname)), ")")
+        } else {
+            (String::new(), "")
+        }
+    }
+
+    /// True if `ty` is an `Option` represented as a nullable reference in the public API.
+    fn is_nullable(&self, ty: &Ty) -> bool {
+        matches!(ty.kind(), TypeKind::Option { .. }) && self.metadata.option_style == OptionStyle::Nullable
+    }
+
+    /// True if `ty` is an `Option` represented as a `java.util.Optional` in the public API.
+    /// Native methods still pass a nullable reference, which the public method converts.
+    fn is_optional(&self, ty: &Ty) -> bool {
+        matches!(ty.kind(), TypeKind::Option { .. }) && self.metadata.option_style == OptionStyle::Optional
+    }
+
+    /// Like [`Self::write_ty`][] but for parameters, return types, and fields of the public API,
+    /// where an `Option` may map to `java.util.Optional` (see the `option-style` metadata).
+    fn write_public_ty(&self, ty: &Ty) -> anyhow::Result<String> {
+        match ty.kind() {
+            TypeKind::Option { element, repr: _ } if self.is_optional(ty) => Ok(format!(
+                "java.util.Optional<{E}>",
+                E = self.write_objectified_ty(element)?,
+            )),
+            _ => self.write_ty(ty),
+        }
+    }
+
     fn write_ty(&self, ty: &Ty) -> anyhow::Result<String> {
         match ty.kind() {
             TypeKind::Scalar(scalar) => match scalar {
                 Scalar::Char => Ok("int".to_string()),
-                Scalar::Boolean => Ok("boolean".to_string()),
-                Scalar::I8 | Scalar::U8 => Ok("byte".to_string()),
-                Scalar::I16 | Scalar::U16 => Ok("short".to_string()),
-                Scalar::I32 | Scalar::U32 => Ok("int".to_string()),
-                Scalar::I64 | Scalar::U64 => Ok("long".to_string()),
+                Scalar::Boolean => Ok("byte".to_string()),
+                // Unsigned integers use the next wider Java type, so that every value fits (except for `u64`).
+                Scalar::I8 => Ok("byte".to_string()),
+                Scalar::I16 | Scalar::U8 => Ok("short".to_string()),
+                Scalar::I32 | Scalar::U16 => Ok("int".to_string()),
+                Scalar::I64 | Scalar::U32 | Scalar::U64 => Ok("long".to_string()),
                 Scalar::F32 => Ok("float".to_string()),
                 Scalar::F64 => Ok("double".to_string()),
-                _ => anyhow::bail!("unsupported scalar type: `{scalar}`"),
+                _ => anyhow::bail!(UnsupportedError::new(scalar, "this scalar type has no Java type")),
             },
             _ => self.write_objectified_ty(ty),
         }
@@ -301,7 +1323,8 @@ impl<'idl> JavaCodeGenerator<'idl> {
                 K = self.write_objectified_ty(key)?,
                 V = self.write_objectified_ty(value)?,
             )),
-            TypeKind::Vec { element, repr: _ } => Ok(format!(
+            // Java has no fixed-length collection type, so fixed-size arrays become lists too.
+            TypeKind::Vec { element, repr: _ } | TypeKind::Array { element, len: _ } => Ok(format!(
                 "java.util.List<{E}>",
                 E = self.write_objectified_ty(element)?,
             )),
@@ -318,28 +1341,48 @@ impl<'idl> JavaCodeGenerator<'idl> {
             // are specially handled.
             TypeKind::Result { ok: _, err: _, repr: _ } => Ok("Object".to_string()),
 
-            // FIXME: I think tuple arguments should be flattened,
-            // since Java has no native concept of them.
-            TypeKind::Tuple { elements: _, repr: _ } => Ok("Object[]".to_string()),
+            // Java has no tuples, so a class is generated for each shape of tuple, see `generate_tuple_class`.
+            TypeKind::Tuple { elements: _, repr: _ } => match util::tuple_qname(self.idl, ty) {
+                Some(qname) => Ok(util::class_dot_name(self.idl, &qname)),
+                None => anyhow::bail!("`()` has no Java type, except as a return type"),
+            },
 
             TypeKind::Scalar(scalar) => match scalar {
                 Scalar::Char => Ok("Integer".to_string()),
                 Scalar::Boolean => Ok("Boolean".to_string()),
-                Scalar::I8 | Scalar::U8 => Ok("Byte".to_string()),
-                Scalar::I16 | Scalar::U16 => Ok("Short".to_string()),
-                Scalar::I32 | Scalar::U32 => Ok("Integer".to_string()),
-                Scalar::I64 | Scalar::U64 => Ok("Long".to_string()),
+                Scalar::I8 => Ok("Byte".to_string()),
+                Scalar::I16 | Scalar::U8 => Ok("Short".to_string()),
+                Scalar::I32 | Scalar::U16 => Ok("Integer".to_string()),
+                Scalar::I64 | Scalar::U32 | Scalar::U64 => Ok("Long".to_string()),
                 Scalar::F32 => Ok("Float".to_string()),
                 Scalar::F64 => Ok("Double".to_string()),
-                _ => anyhow::bail!("unsupported scalar type: `{scalar}`"),
+                _ => anyhow::bail!(UnsupportedError::new(scalar, "this scalar type has no Java type")),
             },
             TypeKind::Future { output, repr: _ } => Ok(format!(
                 "java.util.concurrent.Future<{V}>",
                 V = self.write_objectified_ty(output)?
             )),
-            TypeKind::Error { repr: _} => todo!(),
-            TypeKind::UserType { qname } => Ok(util::class_dot_name(qname)),
-            _ => anyhow::bail!("unsupported type: `{ty}`"),
+            TypeKind::Iterator { item, repr: _ } => Ok(format!(
+                "java.util.Iterator<{E}>",
+                E = self.write_objectified_ty(item)?
+            )),
+            TypeKind::Error { repr: _ } => {
+                anyhow::bail!(UnsupportedError::new(ty, "errors have no Java type, except as the error of a `Result`"))
+            }
+            TypeKind::UserType { qname } => Ok(util::class_dot_name(self.idl, qname)),
+            _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to Java yet")),
         }
     }
 }
+
+/// How an instance method passes `self` to its native counterpart, see [`JavaCodeGenerator::receiver`][].
+struct Receiver {
+    /// Declaration of the parameter of the native method receiving `self`.
+    native_param: &'static str,
+
+    /// Java expression passed for `self` by the public method.
+    value: &'static str,
+
+    /// True if the public method must be `synchronized`, because it uses a handle owned by the object.
+    synchronized: bool,
+}
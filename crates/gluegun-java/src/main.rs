@@ -1,11 +1,13 @@
 use anyhow::Context;
 use camino::Utf8PathBuf;
 use gluegun_core::{
-    cli::{GenerateCx, GlueGunHelper},
+    cli::{GenerateCx, GlueGunHelper, MetadataKey},
     codegen::{AddDependency, LibraryCrate},
 };
+use metadata::JavaMetadata;
 
 mod java_gen;
+mod metadata;
 mod rs_gen;
 mod util;
 
@@ -16,13 +18,35 @@ pub fn main() -> anyhow::Result<()> {
 struct GlueGunJava;
 
 impl GlueGunHelper for GlueGunJava {
-    type Metadata = ();
+    /// `None` if the user did not supply any `gluegun.java` metadata.
+    type Metadata = Option<JavaMetadata>;
 
     fn name(&self) -> String {
         "java".to_string()
     }
 
-    fn generate(self, cx: &mut GenerateCx, &(): &(), output: &mut LibraryCrate) -> anyhow::Result<()> {
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "Java".to_string()
+    }
+
+    fn metadata_keys(&self) -> Vec<MetadataKey> {
+        JavaMetadata::KEYS.iter().copied().map(MetadataKey::optional).collect()
+    }
+
+    fn generate(self, cx: &mut GenerateCx, metadata: &Option<JavaMetadata>, output: &mut LibraryCrate) -> anyhow::Result<()> {
+        cx.reject_generics(&self.language())?;
+        cx.reject_shared_resources(&self.language())?;
+
+        // A type can only contain itself through a collection of records or variants, which cannot be copied yet.
+        cx.reject_recursive_types(&self.language())?;
+
+        let default_metadata = JavaMetadata::default();
+        let metadata = metadata.as_ref().unwrap_or(&default_metadata);
+
         // libary dependencies
         output.add_dependency("duchess").version("0.3");
 
@@ -37,11 +61,11 @@ impl GlueGunHelper for GlueGunJava {
         let java_src_dir = output
             .add_dir("java_src")
             .with_context(|| format!("adding `java_src` dir"))?;
-        java_gen::JavaCodeGenerator::new(cx.idl())
+        java_gen::JavaCodeGenerator::new(cx.idl(), metadata)
             .generate(java_src_dir)
             .with_context(|| format!("generaring Java sources"))?;
 
-        rs_gen::RustCodeGenerator::new(cx.idl())
+        rs_gen::RustCodeGenerator::new(cx.idl(), metadata)
             .generate(output)
             .with_context(|| format!("generaring Rust sources"))?;
 
@@ -0,0 +1,96 @@
+use serde::Deserialize;
+
+/// Configuration for the Java plugin, read from `gluegun.java` in the workspace or package `Cargo.toml` metadata.
+#[derive(Deserialize, Debug)]
+#[serde(default, rename_all = "kebab-case")]
+pub(crate) struct JavaMetadata {
+    /// The runtime used to drive `async` functions, see [`AsyncRuntime`][].
+    pub(crate) async_runtime: AsyncRuntime,
+
+    /// How `async` functions are represented in Java, see [`AsyncStyle`][].
+    pub(crate) async_style: AsyncStyle,
+
+    /// Fully qualified name of an annotation, such as `com.google.errorprone.annotations.CheckReturnValue`,
+    /// placed on methods whose Rust counterpart is `#[must_use]`. No annotation is emitted if unset.
+    pub(crate) check_return_annotation: Option<String>,
+
+    /// If true (the default), Java methods throw `IllegalArgumentException` when an integer argument
+    /// does not fit the unsigned Rust parameter it is passed to; otherwise the value silently wraps.
+    pub(crate) checked_conversions: bool,
+
+    /// How `Option<T>` is represented in Java, see [`OptionStyle`][].
+    pub(crate) option_style: OptionStyle,
+
+    /// Fully qualified name of an annotation, such as `javax.annotation.Nullable`,
+    /// placed on parameters, return types, and fields whose Rust type is `Option<T>`
+    /// when `option-style` is `nullable`. No annotation is emitted if unset.
+    pub(crate) nullable_annotation: Option<String>,
+}
+
+impl JavaMetadata {
+    /// The keys of the metadata, as written in `Cargo.toml`; none of them is required.
+    pub(crate) const KEYS: &'static [&'static str] = &[
+        "async-runtime",
+        "async-style",
+        "check-return-annotation",
+        "checked-conversions",
+        "option-style",
+        "nullable-annotation",
+    ];
+}
+
+impl Default for JavaMetadata {
+    fn default() -> Self {
+        Self {
+            async_runtime: AsyncRuntime::default(),
+            async_style: AsyncStyle::default(),
+            check_return_annotation: None,
+            checked_conversions: true,
+            option_style: OptionStyle::default(),
+            nullable_annotation: None,
+        }
+    }
+}
+
+/// Runtime used to drive the futures produced by `async` functions.
+/// A single runtime is created lazily on first use and shared by every native function in the crate.
+#[derive(Deserialize, Default, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum AsyncRuntime {
+    /// A multi-threaded tokio runtime.
+    #[default]
+    Tokio,
+
+    /// A single-threaded tokio runtime; futures run on the thread that invoked the Java method.
+    CurrentThread,
+
+    /// The global `async-std` executor.
+    AsyncStd,
+}
+
+/// Java representation of an `async` function (or one returning `impl Future`).
+/// Either way, the future itself is driven to completion by the runtime selected with `async-runtime`.
+#[derive(Deserialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum AsyncStyle {
+    /// A method that blocks the calling thread until the future completes.
+    #[default]
+    Blocking,
+
+    /// A method returning a `java.util.concurrent.CompletableFuture<T>` right away;
+    /// the native call blocks a thread of an executor owned by the class instead.
+    CompletableFuture,
+}
+
+/// Java representation of an `Option<T>` parameter, return type, or field.
+/// Options nested inside other types (e.g., `Vec<Option<T>>`) are always nullable.
+#[derive(Deserialize, Default, Copy, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum OptionStyle {
+    /// A `T` that may be `null`.
+    #[default]
+    Nullable,
+
+    /// A `java.util.Optional<T>`.
+    Optional,
+}
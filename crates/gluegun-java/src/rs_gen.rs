@@ -1,21 +1,27 @@
 use std::collections::BTreeMap;
 
 use gluegun_core::{
+    casing::Casing,
+    cli::{for_each_supported, UnsupportedError},
     codegen::{CodeWriter, LibraryCrate},
     idl::{
-        Enum, FunctionInput, FunctionOutput, Idl, Item, Method, MethodCategory, Name, QualifiedName, Record, RefdTy, Resource, Signature, TypeKind, Variant
+        Enum, FieldsKind, FunctionInput, FunctionOutput, Idl, Item, Literal, Method, MethodCategory, Name, OwnedKind, QualifiedName, Record, RefdTy, Resource, Scalar, SelfKind, Signature, Ty, TypeKind, Variant, VariantArm
     },
 };
 
-use crate::util::{self, AsTy, JavaQName};
+use crate::{
+    metadata::{AsyncRuntime, JavaMetadata},
+    util::{self, AsTy, JavaQName},
+};
 
 pub(crate) struct RustCodeGenerator<'idl> {
     idl: &'idl Idl,
+    metadata: &'idl JavaMetadata,
 }
 
 impl<'idl> RustCodeGenerator<'idl> {
-    pub(crate) fn new(idl: &'idl Idl) -> Self {
-        Self { idl }
+    pub(crate) fn new(idl: &'idl Idl, metadata: &'idl JavaMetadata) -> Self {
+        Self { idl, metadata }
     }
 
     pub(crate) fn generate(mut self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
@@ -26,19 +32,177 @@ impl<'idl> RustCodeGenerator<'idl> {
     }
 
     fn generate_lib_rs(&mut self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
+        // When splitting, each item goes into its own module; these are generated first
+        // since the `lib.rs` writer borrows `lib` until it is dropped.
+        let split_modules = *lib.split_modules();
+        if split_modules {
+            for_each_supported(self.idl.definitions(), |(qname, item)| {
+                let mut item_rs = lib.add_item_module(qname)?;
+                write!(item_rs, "use crate::*;")?; // the Java packages are declared in `lib.rs`
+                self.generate_item(&mut item_rs, qname, item)
+            })?;
+        }
+
         let mut lib_rs = lib.add_file("src/lib.rs")?;
 
         write!(lib_rs, "#![allow(non_snake_case)]")?; // FIXME: bug in duchess
 
         self.generate_java_classes(&mut lib_rs)?;
 
-        for (qname, item) in self.idl.definitions() {
-            self.generate_item(&mut lib_rs, qname, item)?;
+        if self.idl.definitions().values().any(|item| matches!(item, Item::Enum(_))) {
+            self.generate_java_enum_trait(&mut lib_rs)?;
+        }
+
+        for qname in util::thrown_qnames(self.idl) {
+            self.generate_exception_conversion(&mut lib_rs, qname)?;
+        }
+
+        if util::throws_rust_exception(self.idl) {
+            self.generate_rust_exception_conversion(&mut lib_rs)?;
+        }
+
+        for (qname, ty) in util::tuple_types(self.idl) {
+            self.generate_tuple_natives(&mut lib_rs, &qname, ty)?;
+        }
+
+        if !split_modules {
+            for_each_supported(self.idl.definitions(), |(qname, item)| self.generate_item(&mut lib_rs, qname, item))?;
+        }
+
+        let uses_async = self.uses_async();
+        if uses_async {
+            self.generate_async_runtime(&mut lib_rs)?;
+        }
+
+        drop(lib_rs);
+        if uses_async {
+            match self.metadata.async_runtime {
+                AsyncRuntime::Tokio => {
+                    lib.add_dependency("tokio").version("1").feature("rt-multi-thread");
+                }
+                AsyncRuntime::CurrentThread => {
+                    lib.add_dependency("tokio").version("1").feature("rt");
+                }
+                AsyncRuntime::AsyncStd => {
+                    lib.add_dependency("async-std").version("1");
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// True if any function or method in the IDL must be driven by an async runtime.
+    fn uses_async(&self) -> bool {
+        self.idl.definitions().values().any(|item| {
+            let methods = match item {
+                Item::Resource(r) => r.methods(),
+                Item::Record(r) => r.methods(),
+                Item::Variant(v) => v.methods(),
+                Item::Enum(e) => e.methods(),
+                Item::Function(f) => return util::is_async(f.signature()),
+                _ => return false,
+            };
+            methods.iter().any(|m| util::is_async(m.signature()))
+        })
+    }
+
+    /// Generate the `gluegun_runtime` module that lazily creates the runtime used to drive futures.
+    /// The runtime is created on first use and then shared by every native function.
+    ///
+    /// The runtime is deliberately never dropped: other Java threads may still be blocked on it
+    /// while the JVM is shutting down, and its worker threads do not keep the process alive.
+    fn generate_async_runtime(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
+        write!(lib_rs, "mod gluegun_runtime {{")?;
+        match self.metadata.async_runtime {
+            AsyncRuntime::Tokio | AsyncRuntime::CurrentThread => {
+                let builder = match self.metadata.async_runtime {
+                    AsyncRuntime::CurrentThread => "new_current_thread",
+                    _ => "new_multi_thread",
+                };
+                write!(lib_rs, "static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();")?;
+                write!(lib_rs, "")?;
+                write!(lib_rs, "pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {{")?;
+                write!(lib_rs, "let runtime = RUNTIME.get_or_init(|| {{")?;
+                write!(lib_rs, "tokio::runtime::Builder::{builder}()")?;
+                write!(lib_rs, ".enable_all()")?;
+                write!(lib_rs, ".build()")?;
+                write!(lib_rs, ".expect(\"failed to create tokio runtime\")")?;
+                write!(lib_rs, "}});")?;
+                write!(lib_rs, "runtime.block_on(future)")?;
+                write!(lib_rs, "}}")?;
+            }
+            AsyncRuntime::AsyncStd => {
+                // async-std manages its own global executor, which is also created lazily.
+                write!(lib_rs, "pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {{")?;
+                write!(lib_rs, "async_std::task::block_on(future)")?;
+                write!(lib_rs, "}}")?;
+            }
+        }
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    /// Generate the `JavaEnum` trait, implemented for each fieldless enum by `generate_enum`.
+    ///
+    /// It is declared in the generated crate, rather than in `gluegun_java_util`,
+    /// so that it can be implemented for the enums of the crate being bound.
+    fn generate_java_enum_trait(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
+        write!(lib_rs, "trait JavaEnum: Sized {{")?;
+        write!(lib_rs, "fn from_java_name(name: &str) -> Option<Self>;")?;
+        write!(lib_rs, "fn to_java_name(&self) -> Option<&'static str>;")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "fn from_java(name: String) -> duchess::Result<Self> {{")?;
+        write!(lib_rs, "Self::from_java_name(&name).ok_or_else(|| duchess::Error::JvmInternal(format!(\"no variant named `{{name}}`\")))")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "fn to_java(&self) -> duchess::Result<String> {{")?;
+        write!(lib_rs, "match self.to_java_name() {{")?;
+        write!(lib_rs, "Some(name) => Ok(name.to_string()),")?;
+        write!(lib_rs, "None => Err(duchess::Error::JvmInternal(\"variant is ignored by gluegun\".to_string())),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    /// Generate the function converting an error of the type `qname` into the exception carrying it,
+    /// which the native functions throw when the Rust function fails
+    /// (see `JavaCodeGenerator::generate_exception_class`).
+    ///
+    /// The exception is constructed from the error as passed to native methods:
+    /// a handle to a record or variant, or the name of an enum variant.
+    fn generate_exception_conversion(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName) -> anyhow::Result<()> {
+        let rust_ty = qname.colon_colon();
+        let JavaQName { package, class_name } = util::exception_package_and_name(self.idl, qname);
+        let exception_ty = format!("{}::{class_name}", package.colon_colon());
+        write!(
+            lib_rs,
+            "fn {into_exception}(error: {rust_ty}) -> duchess::Error<duchess::Java<duchess::java::lang::Throwable>> {{",
+            into_exception = exception_conversion_name(qname),
+        )?;
+        let argument = if let Some(Item::Enum(_)) = self.idl.definitions().get(qname) {
+            write!(lib_rs, "let error = match crate::JavaEnum::to_java(&error) {{")?;
+            write!(lib_rs, "Ok(name) => name,")?;
+            write!(lib_rs, "Err(err) => return err,")?;
+            write!(lib_rs, "}};")?;
+            "&error"
+        } else {
+            write!(lib_rs, "let error = gluegun_java_util::record::into_handle(error);")?;
+            "error"
+        };
+        write!(
+            lib_rs,
+            "let exception: duchess::Result<duchess::Java<{exception_ty}>> = duchess::JvmOp::execute({exception_ty}::new({argument}));"
+        )?;
+        write!(lib_rs, "match exception {{")?;
+        write!(lib_rs, "Ok(exception) => duchess::Error::Thrown(exception.upcast()),")?;
+        write!(lib_rs, "Err(err) => err,")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
     fn generate_build_rs(&mut self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
         let mut build_rs = lib.add_file("build.rs")?;
         write!(
@@ -58,22 +222,79 @@ impl<'idl> RustCodeGenerator<'idl> {
     }
 
 
+    /// Generate the function converting an `anyhow::Error` or `Box<dyn Error>` into the crate's `RustException`
+    /// (see `JavaCodeGenerator::generate_rust_exception_class`), which carries the error's `Display` string as its message.
+    fn generate_rust_exception_conversion(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
+        let JavaQName { package, class_name } = util::rust_exception_package_and_name(self.idl);
+        let exception_ty = format!("{}::{class_name}", package.colon_colon());
+        write!(
+            lib_rs,
+            "fn {RUST_EXCEPTION_CONVERSION}(error: impl std::fmt::Display) -> duchess::Error<duchess::Java<duchess::java::lang::Throwable>> {{",
+        )?;
+        write!(lib_rs, "let message = error.to_string();")?;
+        write!(
+            lib_rs,
+            "let exception: duchess::Result<duchess::Java<{exception_ty}>> = duchess::JvmOp::execute({exception_ty}::new(&message));"
+        )?;
+        write!(lib_rs, "match exception {{")?;
+        write!(lib_rs, "Ok(exception) => duchess::Error::Thrown(exception.upcast()),")?;
+        write!(lib_rs, "Err(err) => err,")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
     fn generate_java_classes(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
-        let mut map = BTreeMap::default();
+        // Each package may only be declared once, so group the class declarations by package.
+        let mut map: BTreeMap<QualifiedName, BTreeMap<Name, String>> = BTreeMap::default();
 
         for (qname, item) in self.idl.definitions() {
-            let java_qname = self.java_class(qname, item)?;
-            map.entry(java_qname).or_insert(vec![]).push(item);
+            let JavaQName { package, class_name } = self.java_class(qname, item)?;
+            let declaration = format!("class {class_name} {{ }}");
+            map.entry(package).or_default().insert(class_name, declaration);
+        }
+
+        for qname in util::tuple_types(self.idl).into_keys() {
+            let JavaQName { package, class_name } = util::class_package_and_name(self.idl, &qname);
+            let declaration = format!("class {class_name} {{ }}");
+            map.entry(package).or_default().insert(class_name, declaration);
+        }
+
+        // Exceptions are constructed by Rust, see `generate_exception_conversion`.
+        for qname in util::thrown_qnames(self.idl) {
+            let JavaQName { package, class_name } = util::exception_package_and_name(self.idl, qname);
+            let declaration = format!(
+                "public class {class_name} extends java.lang.RuntimeException {{ public {package}.{class_name}({native_ty}); }}",
+                package = package.dotted(),
+                native_ty = exception_native_ty(self.idl, qname),
+            );
+            map.entry(package).or_default().insert(class_name, declaration);
+        }
+
+        if util::throws_rust_exception(self.idl) {
+            let JavaQName { package, class_name } = util::rust_exception_package_and_name(self.idl);
+            let declaration = format!(
+                "public class {class_name} extends java.lang.RuntimeException {{ public {package}.{class_name}(java.lang.String); }}",
+                package = package.dotted(),
+            );
+            map.entry(package).or_default().insert(class_name, declaration);
+        }
+
+        if map.is_empty() {
+            return Ok(());
         }
 
-        for (java_qname, _items) in map {
+        // All packages are declared in one invocation: each invocation defines the Rust modules of its packages,
+        // so a package nested in another (e.g. for the functions of a submodule) could not be declared separately.
+        write!(lib_rs, "duchess::java_package! {{")?;
+        for (package, classes) in map {
             // FIXME: Do we want to generate items or Java-based members in any of these classes?
-            
-            write!(lib_rs, "duchess::java_package! {{")?;
-            write!(lib_rs, "package {};", java_qname.package.dotted())?;
-            write!(lib_rs, "class {} {{ }}", java_qname.class_name)?;
-            write!(lib_rs, "}}")?;
+            write!(lib_rs, "package {};", package.dotted())?;
+            for declaration in classes.values() {
+                write!(lib_rs, "{declaration}")?;
+            }
         }
+        write!(lib_rs, "}}")?;
 
         Ok(())
     }
@@ -81,17 +302,17 @@ impl<'idl> RustCodeGenerator<'idl> {
     fn java_class(&self, qname: &QualifiedName, item: &Item) -> anyhow::Result<JavaQName> {
         match item {
             Item::Resource(_) | Item::Record(_) | Item::Variant(_) | Item::Enum(_) => {
-                Ok(util::class_package_and_name(qname))
+                Ok(util::class_package_and_name(self.idl, qname))
             }
-            Item::Function(_) => {
-                let package = qname.module_name().camel_case();
+            Item::Function(_) | Item::Constant(_) => {
+                let package = qname.module_name().to_camel_case();
                 Ok(JavaQName {
                     package,
                     class_name: Name::from("Functions"),
                 })
             }
             _ => {
-                anyhow::bail!("unsupported item: {item:?}")
+                anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to Java yet"))
             }
         }
     }
@@ -108,11 +329,10 @@ impl<'idl> RustCodeGenerator<'idl> {
             Item::Variant(variant) => self.generate_variant(lib_rs, qname, variant),
             Item::Enum(an_enum) => self.generate_enum(lib_rs, qname, an_enum),
             Item::Function(f) => {
-                let module_name = qname.module_name();
-                let java_qname = module_name.join("Functions");
-                self.generate_native_function(
+                let java_qname = qname.module_name().join("Functions");
+                self.generate_native_functions(
                     lib_rs,
-                    &module_name,
+                    qname,
                     &java_qname,
                     f.name(),
                     &MethodCategory::StaticMethod,
@@ -120,7 +340,9 @@ impl<'idl> RustCodeGenerator<'idl> {
                 )?;
                 Ok(())
             }
-            _ => anyhow::bail!("unsupported item: {item:?}"),
+            // The value is written into the Java class (see `JavaCodeGenerator::generate_constants`), so no native is needed.
+            Item::Constant(_) => Ok(()),
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to Java yet")),
         }
     }
 
@@ -130,30 +352,123 @@ impl<'idl> RustCodeGenerator<'idl> {
         qname: &QualifiedName,
         resource: &Resource,
     ) -> Result<(), anyhow::Error> {
+        self.generate_resource_drop(lib_rs, qname)?;
         for method in resource.methods() {
             self.generate_method(lib_rs, qname, method)?;
         }
         Ok(())
     }
 
+    /// Generate the native function releasing the Rust value owned by a Java object of a resource class
+    /// (see `JavaCodeGenerator::generate_resource`).
+    fn generate_resource_drop(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName) -> anyhow::Result<()> {
+        let class_dot_name = util::class_dot_name(self.idl, qname);
+        let rust_ty = qname.colon_colon();
+        let drop = util::resource_native_name("drop");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{drop})]")?;
+        write!(lib_rs, "fn {drop}(handle: i64) {{")?;
+        write!(lib_rs, "unsafe {{ gluegun_java_util::resource::drop_handle::<{rust_ty}>(handle) }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+        Ok(())
+    }
+
     fn generate_record(
         &self,
         lib_rs: &mut CodeWriter<'_>,
         qname: &QualifiedName,
         record: &Record,
     ) -> Result<(), anyhow::Error> {
+        self.generate_record_natives(lib_rs, qname, record)?;
         for method in record.methods() {
             self.generate_method(lib_rs, qname, method)?;
         }
         Ok(())
     }
 
+    /// Generate the native functions used by the Java class for a record to pass it to Rust and back
+    /// (see `JavaCodeGenerator::generate_record_conversions`):
+    /// a constructor taking each field and returning a handle to the Rust value,
+    /// a getter for each public field, and a function releasing the handle.
+    /// A record with private fields has no constructor, as Java cannot supply them; nor has a `#[non_exhaustive]` one.
+    fn generate_record_natives(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        record: &Record,
+    ) -> anyhow::Result<()> {
+        let class_dot_name = util::class_dot_name(self.idl, qname);
+        let rust_ty = qname.colon_colon();
+
+        for field in record.public_fields() {
+            if util::handle_qname(self.idl, field.ty()).is_some() {
+                anyhow::bail!(
+                    "field `{}` of `{rust_ty}`: records and variants nested in records are not supported yet",
+                    field.name()
+                );
+            }
+        }
+
+        if record.is_constructible() {
+            let new = util::record_native_name("new");
+            write!(lib_rs, "const _: () = {{")?;
+            write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+            write!(lib_rs, "#[duchess::java_function({class_dot_name}::{new})]")?;
+            write!(lib_rs, "fn {new}(")?;
+            for field in record.fields() {
+                self.generate_java_parameter(lib_rs, field.name(), field.ty())?;
+            }
+            write!(lib_rs, ") -> duchess::Result<i64> {{")?;
+            for field in record.fields() {
+                self.generate_input_conversion(lib_rs, field.name(), field.ty())?;
+            }
+            write!(lib_rs, "Ok(gluegun_java_util::record::into_handle({rust_ty} {{")?;
+            for field in record.fields() {
+                if field.rust_name() == field.name() {
+                    write!(lib_rs, "{},", field.name())?;
+                } else {
+                    write!(lib_rs, "{}: {},", field.rust_name(), field.name())?;
+                }
+            }
+            write!(lib_rs, "}}))")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}};")?;
+        }
+
+        for field in record.public_fields() {
+            let get = util::record_native_name(&format!("get_{}", field.name()));
+            let value = self.java_field_value(field.ty(), &format!("record.{}", field.rust_name()));
+            write!(lib_rs, "const _: () = {{")?;
+            write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+            write!(lib_rs, "#[duchess::java_function({class_dot_name}::{get})]")?;
+            write!(lib_rs, "fn {get}(handle: i64) -> duchess::Result<{}> {{", self.java_return_ty(field.ty()))?;
+            write!(lib_rs, "let record = unsafe {{ gluegun_java_util::record::get::<{rust_ty}>(handle) }};")?;
+            write!(lib_rs, "{value}")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}};")?;
+        }
+
+        let drop = util::record_native_name("drop");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{drop})]")?;
+        write!(lib_rs, "fn {drop}(handle: i64) {{")?;
+        write!(lib_rs, "unsafe {{ gluegun_java_util::record::drop_handle::<{rust_ty}>(handle) }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        Ok(())
+    }
+
     fn generate_variant(
         &self,
         lib_rs: &mut CodeWriter<'_>,
         qname: &QualifiedName,
         variant: &Variant,
     ) -> Result<(), anyhow::Error> {
+        self.generate_variant_natives(lib_rs, qname, variant)?;
         for method in variant.methods() {
             self.generate_method(lib_rs, qname, method)?;
         }
@@ -166,78 +481,400 @@ impl<'idl> RustCodeGenerator<'idl> {
         qname: &QualifiedName,
         an_enum: &Enum,
     ) -> Result<(), anyhow::Error> {
+        self.generate_java_enum_impl(lib_rs, qname, an_enum)?;
         for method in an_enum.methods() {
             self.generate_method(lib_rs, qname, method)?;
         }
         Ok(())
     }
 
+    /// Expression for the `duchess::Result` returned to Java by a getter for the field at `place`, of type `ty`.
+    fn java_field_value(&self, ty: &Ty, place: &str) -> String {
+        if util::enum_qname(self.idl, ty).is_some() {
+            format!("crate::JavaEnum::to_java(&{place})")
+        } else {
+            match java_scalar_ty(ty) {
+                Some(java_ty) => format!("Ok({place}.clone() as {java_ty})"),
+                None => format!("Ok({place}.clone())"),
+            }
+        }
+    }
+
+    /// Generate the native functions used by the Java class generated for tuples of the type `ty`
+    /// (see `JavaCodeGenerator::generate_tuple_class`). These are like those for a record
+    /// (see [`Self::generate_record_natives`][]), with the fields `f0`, `f1`, etc. standing for the elements.
+    fn generate_tuple_natives(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, ty: &Ty) -> anyhow::Result<()> {
+        let TypeKind::Tuple { elements, repr: _ } = ty.kind() else {
+            anyhow::bail!("`{ty}` is not a tuple");
+        };
+        let class_dot_name = util::class_dot_name(self.idl, qname);
+        let rust_ty = self.rust_owned_ty(ty);
+        let fields: Vec<(Name, &Ty)> = elements
+            .iter()
+            .enumerate()
+            .map(|(index, element)| (Name::from(format!("f{index}")), element))
+            .collect();
+
+        for (_, element) in &fields {
+            if util::handle_qname(self.idl, element).is_some() {
+                anyhow::bail!("`{ty}`: records, variants, and tuples nested in tuples are not supported yet");
+            }
+        }
+
+        let new = util::record_native_name("new");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{new})]")?;
+        write!(lib_rs, "fn {new}(")?;
+        for (name, element) in &fields {
+            self.generate_java_parameter(lib_rs, name, element)?;
+        }
+        write!(lib_rs, ") -> duchess::Result<i64> {{")?;
+        for (name, element) in &fields {
+            self.generate_input_conversion(lib_rs, name, element)?;
+        }
+        write!(lib_rs, "Ok(gluegun_java_util::record::into_handle((")?;
+        for (name, _) in &fields {
+            write!(lib_rs, "{name},")?;
+        }
+        write!(lib_rs, ")))")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        for (index, (name, element)) in fields.iter().enumerate() {
+            let get = util::record_native_name(&format!("get_{name}"));
+            let value = self.java_field_value(element, &format!("tuple.{index}"));
+            write!(lib_rs, "const _: () = {{")?;
+            write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+            write!(lib_rs, "#[duchess::java_function({class_dot_name}::{get})]")?;
+            write!(lib_rs, "fn {get}(handle: i64) -> duchess::Result<{}> {{", self.java_return_ty(element))?;
+            write!(lib_rs, "let tuple = unsafe {{ gluegun_java_util::record::get::<{rust_ty}>(handle) }};")?;
+            write!(lib_rs, "{value}")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}};")?;
+        }
+
+        let drop = util::record_native_name("drop");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{drop})]")?;
+        write!(lib_rs, "fn {drop}(handle: i64) {{")?;
+        write!(lib_rs, "unsafe {{ gluegun_java_util::record::drop_handle::<{rust_ty}>(handle) }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        Ok(())
+    }
+
+    /// Generate the native functions used by the Java classes for a variant to pass it to Rust and back
+    /// (see `JavaCodeGenerator::generate_variant_conversions`). These are much like those for a record
+    /// (see [`Self::generate_record_natives`][]), except that there is a constructor for each arm,
+    /// the getters are per arm, and Java asks which arm a value has before reading its fields.
+    fn generate_variant_natives(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        variant: &Variant,
+    ) -> anyhow::Result<()> {
+        let class_dot_name = util::class_dot_name(self.idl, qname);
+        let rust_ty = qname.colon_colon();
+
+        for arm in variant.arms() {
+            for field in arm.fields() {
+                if util::handle_qname(self.idl, field.ty()).is_some() {
+                    anyhow::bail!(
+                        "field `{}` of `{rust_ty}::{}`: records and variants nested in variants are not supported yet",
+                        field.name(),
+                        arm.name()
+                    );
+                }
+            }
+        }
+
+        for arm in variant.arms() {
+            let new = util::variant_native_name(&format!("new_{}", arm.name().to_snake_case()));
+            write!(lib_rs, "const _: () = {{")?;
+            write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+            write!(lib_rs, "#[duchess::java_function({class_dot_name}::{new})]")?;
+            write!(lib_rs, "fn {new}(")?;
+            for field in arm.fields() {
+                self.generate_java_parameter(lib_rs, field.name(), field.ty())?;
+            }
+            write!(lib_rs, ") -> duchess::Result<i64> {{")?;
+            for field in arm.fields() {
+                self.generate_input_conversion(lib_rs, field.name(), field.ty())?;
+            }
+            match arm.fields_kind() {
+                FieldsKind::Unit => {
+                    write!(lib_rs, "Ok(gluegun_java_util::record::into_handle({rust_ty}::{}))", arm.name())?;
+                }
+                FieldsKind::Named | FieldsKind::Tuple => {
+                    write!(lib_rs, "Ok(gluegun_java_util::record::into_handle({rust_ty}::{} {{", arm.name())?;
+                    for (field, member) in arm.fields().iter().zip(field_members(arm)) {
+                        if member == field.name().to_string() {
+                            write!(lib_rs, "{member},")?;
+                        } else {
+                            write!(lib_rs, "{member}: {},", field.name())?;
+                        }
+                    }
+                    write!(lib_rs, "}}))")?;
+                }
+            }
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}};")?;
+        }
+
+        // The arm is identified by the name of its Java class; arms ignored by gluegun have none.
+        let arm_native = util::variant_native_name("arm");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{arm_native})]")?;
+        write!(lib_rs, "fn {arm_native}(handle: i64) -> duchess::Result<String> {{")?;
+        write!(lib_rs, "#[allow(unreachable_patterns)]")?;
+        write!(lib_rs, "match unsafe {{ gluegun_java_util::record::get::<{rust_ty}>(handle) }} {{")?;
+        for arm in variant.arms() {
+            write!(lib_rs, "{rust_ty}::{} {{ .. }} => Ok(\"{}\".to_string()),", arm.name(), arm.name().to_pascal_case())?;
+        }
+        write!(lib_rs, "_ => Err(duchess::Error::JvmInternal(\"variant arm is ignored by gluegun\".to_string())),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        for arm in variant.arms() {
+            for (field, member) in arm.fields().iter().zip(field_members(arm)) {
+                let get = util::variant_native_name(&format!("get_{}_{}", arm.name().to_snake_case(), field.name()));
+                write!(lib_rs, "const _: () = {{")?;
+                write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+                write!(lib_rs, "#[duchess::java_function({class_dot_name}::{get})]")?;
+                write!(lib_rs, "fn {get}(handle: i64) -> duchess::Result<{}> {{", self.java_return_ty(field.ty()))?;
+                write!(lib_rs, "#[allow(unreachable_patterns)]")?;
+                write!(lib_rs, "match unsafe {{ gluegun_java_util::record::get::<{rust_ty}>(handle) }} {{")?;
+                write!(
+                    lib_rs,
+                    "{rust_ty}::{} {{ {member}: value, .. }} => {},",
+                    arm.name(),
+                    self.java_field_value(field.ty(), "(*value)")
+                )?;
+                write!(lib_rs, "_ => Err(duchess::Error::JvmInternal(\"not a `{}`\".to_string())),", arm.name())?;
+                write!(lib_rs, "}}")?;
+                write!(lib_rs, "}}")?;
+                write!(lib_rs, "}};")?;
+            }
+        }
+
+        let drop = util::variant_native_name("drop");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{drop})]")?;
+        write!(lib_rs, "fn {drop}(handle: i64) {{")?;
+        write!(lib_rs, "unsafe {{ gluegun_java_util::record::drop_handle::<{rust_ty}>(handle) }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        Ok(())
+    }
+
+    /// Implement the `JavaEnum` trait (see `generate_java_enum_trait`) for a fieldless enum,
+    /// mapping each variant to the Java enum constant of the same name (see `JavaCodeGenerator::generate_enum`).
+    fn generate_java_enum_impl(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        an_enum: &Enum,
+    ) -> anyhow::Result<()> {
+        let rust_ty = qname.colon_colon();
+        write!(lib_rs, "impl crate::JavaEnum for {rust_ty} {{")?;
+
+        write!(lib_rs, "fn from_java_name(name: &str) -> Option<Self> {{")?;
+        write!(lib_rs, "match name {{")?;
+        for arm in an_enum.arms() {
+            write!(lib_rs, "\"{java}\" => Some({rust_ty}::{name}),", java = arm.name().to_pascal_case(), name = arm.name())?;
+        }
+        write!(lib_rs, "_ => None,")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "")?;
+
+        // Variants ignored by gluegun have no Java counterpart.
+        write!(lib_rs, "fn to_java_name(&self) -> Option<&'static str> {{")?;
+        write!(lib_rs, "#[allow(unreachable_patterns)]")?;
+        write!(lib_rs, "match self {{")?;
+        for arm in an_enum.arms() {
+            write!(lib_rs, "{rust_ty}::{name} => Some(\"{java}\"),", java = arm.name().to_pascal_case(), name = arm.name())?;
+        }
+        write!(lib_rs, "_ => None,")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
     fn generate_method(
         &self,
         lib_rs: &mut CodeWriter<'_>,
         qname: &QualifiedName,
         method: &Method,
     ) -> anyhow::Result<()> {
-        self.generate_native_function(
+        // A method from a trait impl is only found through the type if the trait is in scope.
+        if let Some(trait_name) = method.trait_name() {
+            write!(lib_rs, "const _: () = {{")?;
+            write!(lib_rs, "#[allow(unused_imports)] use {} as _;", trait_name.colon_colon())?;
+        }
+        self.generate_native_functions(
             lib_rs,
-            qname,
+            &qname.join(method.rust_name()),
             qname,
             method.name(),
             method.category(),
             method.signature(),
-        )
+        )?;
+        if method.trait_name().is_some() {
+            write!(lib_rs, "}};")?;
+        }
+        Ok(())
+    }
+
+    /// Generate the native function definitions backing a Java method:
+    /// one for the full signature and one for each overload generated for parameters with default values.
+    /// Parameters are as for [`Self::generate_native_function`][].
+    fn generate_native_functions(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        rust_fn_qname: &QualifiedName,
+        java_qname: &QualifiedName,
+        fn_name: &Name,
+        method_category: &MethodCategory,
+        signature: &Signature,
+    ) -> anyhow::Result<()> {
+        self.generate_native_function(lib_rs, rust_fn_qname, java_qname, fn_name, method_category, signature, &[])?;
+        for omitted in util::default_overloads(signature) {
+            self.generate_native_function(lib_rs, rust_fn_qname, java_qname, fn_name, method_category, signature, &omitted)?;
+        }
+        if let Some(item_ty) = util::iterator_item(signature.output_ty().main_ty().ty()) {
+            self.generate_iterator_natives(lib_rs, java_qname, fn_name, item_ty)?;
+        }
+        Ok(())
+    }
+
+    /// Generate the native functions used by the Java class holding the iterator returned by `fn_name`
+    /// (see `JavaCodeGenerator::generate_iterator_class`). The heavy lifting is in `gluegun_java_util::iterator`.
+    fn generate_iterator_natives(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        java_qname: &QualifiedName,
+        fn_name: &Name,
+        item_ty: &Ty,
+    ) -> anyhow::Result<()> {
+        let class_dot_name = util::class_dot_name(self.idl, java_qname);
+        let item = self.rust_owned_ty(item_ty);
+
+        // Java has no unsigned types, so unsigned items are widened to the next larger signed type (see `java_scalar_ty`).
+        let (java_item, cast) = match java_scalar_ty(item_ty) {
+            Some(java_item) => (java_item.to_string(), format!(" as {java_item}")),
+            None => (item.clone(), String::new()),
+        };
+
+        let has_next = util::iterator_native_name(fn_name, "has_next");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{has_next})]")?;
+        write!(lib_rs, "fn {has_next}(handle: i64) -> duchess::Result<i8> {{")?;
+        write!(lib_rs, "Ok(unsafe {{ gluegun_java_util::iterator::has_next::<{item}>(handle) }} as i8)")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        let next = util::iterator_native_name(fn_name, "next");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{next})]")?;
+        write!(lib_rs, "fn {next}(handle: i64) -> duchess::Result<{java_item}> {{")?;
+        write!(lib_rs, "match unsafe {{ gluegun_java_util::iterator::next::<{item}>(handle) }} {{")?;
+        write!(lib_rs, "Some(item) => Ok(item{cast}),")?;
+        write!(lib_rs, "None => Err(duchess::Error::JvmInternal(\"iterator is exhausted\".to_string())),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        let drop = util::iterator_native_name(fn_name, "drop");
+        write!(lib_rs, "const _: () = {{")?;
+        write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
+        write!(lib_rs, "#[duchess::java_function({class_dot_name}::{drop})]")?;
+        write!(lib_rs, "fn {drop}(handle: i64) {{")?;
+        write!(lib_rs, "unsafe {{ gluegun_java_util::iterator::drop_handle::<{item}>(handle) }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}};")?;
+
+        Ok(())
     }
 
     /// Generate a native function definition that will be the backing function for a Java method.
     ///
     /// # Parameters
     ///
-    /// * `lib_rs`, write-stream for the `lib.rs` file
-    /// * `rust_qname`, qname of the `Resource` type or, for free functions, the containing module
-    /// * `java_qname`, the qname of the Java class containing the method; often the same as `rust_qname` but (e.g. for free functions) not always
-    /// * `fn_name`, the name of the method/function
+    /// * `lib_rs`, write-stream for the `lib.rs` file (or the item's module, if splitting modules)
+    /// * `rust_fn_qname`, qname of the Rust method/function, within the `Resource` type or, for free functions, the containing module
+    /// * `java_qname`, the qname of the Java class containing the method; often the type of `rust_fn_qname` but (e.g. for free functions) not always
+    /// * `fn_name`, the exported name of the method/function, which can differ from its Rust name (see `Function::name`)
     /// * `method_category`, the category of method (e.g., static etc). Static for free functions.
     /// * `signature`, types of inputs/outputs apart from `self`
+    /// * `omitted`, parameters omitted from the Java overload, which are given their default values
+    #[expect(clippy::too_many_arguments)]
     fn generate_native_function(
         &self,
         lib_rs: &mut CodeWriter<'_>,
-        rust_qname: &QualifiedName,
+        rust_fn_qname: &QualifiedName,
         java_qname: &QualifiedName,
         fn_name: &Name,
         method_category: &MethodCategory,
         signature: &Signature,
+        omitted: &[&FunctionInput],
     ) -> anyhow::Result<()> {
+        let rust_qname = rust_fn_qname.module_name();
+
         write!(lib_rs, "const _: () = {{")?;
 
         write!(lib_rs, "use duchess::java;")?; // FIXME: duchess bug, this should not be needed
 
+        // Bind to the `native` method; the public Java method may be overloaded,
+        // but duchess requires the name to identify a single method.
+        let native_name = util::native_method_name(fn_name, omitted);
         write!(
             lib_rs,
-            "#[duchess::java_function({class_dot_name}::{fn_name})]",
-            class_dot_name = util::class_dot_name(java_qname)
+            "#[duchess::java_function({class_dot_name}::{native_name})]",
+            class_dot_name = util::class_dot_name(self.idl, java_qname)
         )?;
-        write!(lib_rs, "fn {fn_name}(")?;
+        write!(lib_rs, "fn {native_name}(")?;
 
+        // The receiver is passed as a handle to the Rust value or, for an enum, as the name of its variant;
+        // see `generate_receiver`.
         match method_category {
-            MethodCategory::Constructor => {}
-            MethodCategory::BuilderMethod(_self_kind)
-            | MethodCategory::InstanceMethod(_self_kind) => {
-                write!(lib_rs, "_self: &duchess::JavaObject")?; // FIXME
+            MethodCategory::Constructor => {
+                if !self.is_resource(&rust_qname) {
+                    anyhow::bail!("`{}`: only resources can have constructors", rust_fn_qname.colon_colon());
+                }
+            }
+            MethodCategory::BuilderMethod(_) | MethodCategory::InstanceMethod(_) => {
+                if let Some(Item::Enum(_)) = self.idl.definitions().get(&rust_qname) {
+                    write!(lib_rs, "self_name: &duchess::java::lang::String,")?;
+                } else {
+                    write!(lib_rs, "self_handle: i64,")?;
+                }
             }
             MethodCategory::StaticMethod => {}
             _ => anyhow::bail!("unsupported method category: {method_category:?}"),
         }
 
-        for input in signature.inputs() {
+        for input in util::remaining_inputs(signature, omitted) {
             let name = input.name();
             let ty = input.refd_ty().ty();
-            write!(lib_rs, "{name}: {ty},", ty = self.java_parameter_ty(ty)?)?;
+            self.generate_java_parameter(lib_rs, name, ty)?;
         }
 
         let output = signature.output_ty();
         write!(lib_rs, ") -> {} {{", self.rust_return_ty(output))?;
 
-        self.generate_fn_body(lib_rs, fn_name, rust_qname, signature, output)?;
+        self.generate_fn_body(lib_rs, rust_fn_qname, method_category, signature, omitted)?;
 
         write!(lib_rs, "}}")?;
         write!(lib_rs, "}};")?;
@@ -245,36 +882,79 @@ impl<'idl> RustCodeGenerator<'idl> {
     }
 
     fn rust_return_ty(&self, output: &FunctionOutput) -> String {
-        let main_ty = output.main_ty();
-        let main_str = self.rust_owned_ty(main_ty);
-
-        let Some(_err_ty) = output.error_ty() else {
-            return format!("duchess::Result<{main_str}>");
+        // See `util::is_void` for why `void` methods return an `i8`.
+        let main_ty = output.main_ty().ty();
+        let main_str = if util::is_void(main_ty) {
+            "i8".to_string()
+        } else if util::iterator_item(main_ty).is_some() {
+            "i64".to_string() // handle to the boxed iterator
+        } else if let Some(element) = util::primitive_option_element(self.idl, util::awaited_ty(main_ty)) {
+            format!("Vec<{}>", self.java_return_ty(element)) // zero or one element, see `generate_fn_body`
+        } else {
+            self.java_return_ty(util::awaited_ty(main_ty))
         };
 
-        // FIXME: fix the `err_ty` handling
-
+        // Errors are converted into a Java exception, see `generate_fn_body`.
         format!("duchess::Result<{main_str}>")
     }
 
+    /// Return the type we hand back to Java for a value of type `ty`.
+    fn java_return_ty(&self, ty: &Ty) -> String {
+        if util::handle_qname(self.idl, ty).is_some() || util::resource_qname(self.idl, ty).is_some() {
+            "i64".to_string() // handle to the boxed record, variant, or resource
+        } else if util::enum_qname(self.idl, ty).is_some() {
+            "String".to_string() // name of the variant
+        } else if let TypeKind::Path { repr: _ } = ty.kind() {
+            "String".to_string() // see `generate_fn_body`
+        } else if matches!(ty.kind(), TypeKind::Option { element, repr: _ } if matches!(element.kind(), TypeKind::Path { .. })) {
+            "Option<String>".to_string()
+        } else {
+            match java_scalar_ty(ty) {
+                Some(java_ty) => java_ty.to_string(),
+                None => self.rust_owned_ty(ty),
+            }
+        }
+    }
+
+    /// Generate the parameter(s) of a native function receiving the value `name` of type `ty` from Java.
+    ///
+    /// duchess cannot iterate over a Java map, so a map is received as a list of its keys
+    /// and a list of its values, in the same order (see `JavaCodeGenerator::public_to_native`).
+    ///
+    /// duchess cannot receive a `null` reference either, so an `Option` is received as a flag
+    /// that is non-zero if the value is present, followed by the value (a placeholder if absent).
+    fn generate_java_parameter(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        name: &Name,
+        ty: &Ty,
+    ) -> anyhow::Result<()> {
+        match ty.kind() {
+            TypeKind::Map { key, value, repr: _ } => {
+                write!(lib_rs, "{name}_keys: &duchess::java::util::List<{}>,", self.java_object_ty(key)?)?;
+                write!(lib_rs, "{name}_values: &duchess::java::util::List<{}>,", self.java_object_ty(value)?)?;
+            }
+            TypeKind::Option { element, repr: _ } => {
+                write!(lib_rs, "{name}_is_some: i8,")?;
+                self.generate_java_parameter(lib_rs, name, element)?;
+            }
+            _ => write!(lib_rs, "{name}: {},", self.java_parameter_ty(ty)?)?,
+        }
+        Ok(())
+    }
+
     /// Return the type we should expect to receive from Java.
     fn java_parameter_ty(&self, ty: impl AsTy) -> anyhow::Result<String> {
         let ty = ty.as_ty();
 
         // FIXME: Duchess's macro has bugs but these work more-or-less for now.
         match ty.kind() {
-            TypeKind::Map { key, value, repr: _ } => {
-                Ok(format!(
-                    "&duchess::java::util::Map<{}, {}>",
-                    self.java_parameter_ty(key)?,
-                    self.java_parameter_ty(value)?,
-                ))
-            }
-            TypeKind::Vec { element, repr: _ } => {
-                Ok(format!("&duchess::java::util::List<{}>", self.java_parameter_ty(element)?))
+            TypeKind::Map { key: _, value: _, repr: _ } => {
+                anyhow::bail!("`{ty}` is received as separate lists of keys and values")
             }
-            TypeKind::Set { element, repr: _ } => {
-                Ok(format!("&duchess::java::util::Set<{}>", self.java_parameter_ty(element)?))
+            // A set is copied into a list on the Java side, since duchess does not know `java.util.Set`.
+            TypeKind::Vec { element, repr: _ } | TypeKind::Set { element, repr: _ } => {
+                Ok(format!("&duchess::java::util::List<{}>", self.java_object_ty(element)?))
             }
             TypeKind::Path { repr: _ } => {
                 Ok(format!("&duchess::java::lang::String"))
@@ -282,27 +962,57 @@ impl<'idl> RustCodeGenerator<'idl> {
             TypeKind::String { repr: _ } => {
                 Ok(format!("&duchess::java::lang::String"))
             }
-            TypeKind::Option { element, repr: _ } => {
-                // in practice everything in Java is nullable...
-                self.java_parameter_ty(element)
+            TypeKind::Option { element: _, repr: _ } => {
+                anyhow::bail!("`{ty}` is received as a flag and the value, if present")
             }
+            // Errors are thrown to Java (see `generate_fn_body`), it has no way to pass one back.
             TypeKind::Result { ok: _, err: _, repr: _ } => {
-                Ok(format!("&duchess::java::lang::Object"))
+                anyhow::bail!(UnsupportedError::new(ty, "`Result` can only be returned to Java, not passed from it"))
+            }
+            TypeKind::Tuple { elements: _, repr: _ } if util::handle_qname(self.idl, ty).is_some() => {
+                Ok("i64".to_string()) // handle to the boxed tuple
             }
             TypeKind::Tuple { elements: _, repr: _ } => {
-                Ok(format!(
-                    "&[&duchess::lang::Object]",
-                ))
+                anyhow::bail!("`()` cannot be passed from Java")
+            }
+            // A `char` is received as its code point, see `generate_input_conversion`.
+            TypeKind::Scalar(Scalar::Char) => Ok("i32".to_string()),
+            TypeKind::Scalar(scalar) => match unsigned_java_parameter_ty(ty) {
+                Some(java_ty) => Ok(java_ty.to_string()),
+                None => Ok(scalar.to_string()),
+            },
+            TypeKind::Future { output: _, repr: _ } => {
+                anyhow::bail!(UnsupportedError::new(ty, "futures can only be returned to Java, not passed from it"))
             }
-            TypeKind::Scalar(scalar) => Ok(scalar.to_string()),
-            TypeKind::Future { output: _, repr: _ } => todo!(),
             TypeKind::Error { repr: _ } => {
                 Ok(format!("&duchess::java::lang::Exception"))
             }
-            TypeKind::UserType { qname: _ } => {
-                anyhow::bail!("user types not supported currently")
+            TypeKind::UserType { qname } if util::is_unconstructible_record(self.idl, qname) => {
+                anyhow::bail!(UnsupportedError::new(
+                    ty,
+                    "a record with private fields or `#[non_exhaustive]` can only be returned to Java, not passed from it",
+                ))
             }
-            _ => todo!(),
+            TypeKind::UserType { qname: _ } if util::handle_qname(self.idl, ty).is_some() => {
+                Ok("i64".to_string()) // handle to the boxed record or variant
+            }
+            TypeKind::UserType { qname: _ } if util::resource_qname(self.idl, ty).is_some() => {
+                Ok("i64".to_string()) // handle given up by the Java object, see `generate_input_conversion`
+            }
+            TypeKind::UserType { qname: _ } if util::enum_qname(self.idl, ty).is_some() => {
+                Ok("&duchess::java::lang::String".to_string()) // name of the variant
+            }
+            _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be passed from Java yet")),
+        }
+    }
+
+    /// Return the Java class of an element of a collection received from Java.
+    ///
+    /// FIXME: duchess only knows the boxed class for `long`, so only strings are supported for now.
+    fn java_object_ty(&self, ty: &Ty) -> anyhow::Result<String> {
+        match ty.kind() {
+            TypeKind::Path { repr: _ } | TypeKind::String { repr: _ } => Ok("duchess::java::lang::String".to_string()),
+            _ => anyhow::bail!(UnsupportedError::new(ty, "collections of this type cannot be passed from Java yet")),
         }
     }
 
@@ -312,9 +1022,10 @@ impl<'idl> RustCodeGenerator<'idl> {
 
         // FIXME: We really ought to be taking the Rust representation into account.
         match ty.kind() {
-            TypeKind::Map { key, value, repr: _ } => {
+            TypeKind::Map { key, value, repr } => {
                 format!(
-                    "HashMap<{}, {}>",
+                    "std::collections::{}<{}, {}>",
+                    if repr.is_ordered() { "BTreeMap" } else { "HashMap" },
                     self.rust_owned_ty(key),
                     self.rust_owned_ty(value),
                 )
@@ -322,11 +1033,19 @@ impl<'idl> RustCodeGenerator<'idl> {
             TypeKind::Vec { element, repr: _ } => {
                 format!("Vec<{}>", self.rust_owned_ty(element))
             }
-            TypeKind::Set { element, repr: _ } => {
-                format!("HashSet<{}>", self.rust_owned_ty(element),)
+            TypeKind::Array { element, len } => {
+                format!("[{}; {len}]", self.rust_owned_ty(element))
+            }
+            TypeKind::Bytes { repr: _ } => "Vec<u8>".to_string(),
+            TypeKind::Set { element, repr } => {
+                format!(
+                    "std::collections::{}<{}>",
+                    if repr.is_ordered() { "BTreeSet" } else { "HashSet" },
+                    self.rust_owned_ty(element),
+                )
             }
             TypeKind::Path { repr: _ } => {
-                format!("PathBuf")
+                "std::path::PathBuf".to_string()
             }
             TypeKind::String { repr: _ } => {
                 format!("String")
@@ -341,6 +1060,10 @@ impl<'idl> RustCodeGenerator<'idl> {
                     self.rust_owned_ty(err)
                 )
             }
+            // A 1-tuple needs a trailing comma to be distinguished from parentheses.
+            TypeKind::Tuple { elements, repr: _ } if elements.len() == 1 => {
+                format!("({},)", self.rust_owned_ty(&elements[0]))
+            }
             TypeKind::Tuple { elements, repr: _ } => {
                 format!(
                     "({})",
@@ -354,6 +1077,8 @@ impl<'idl> RustCodeGenerator<'idl> {
             TypeKind::Scalar(scalar) => scalar.to_string(),
             TypeKind::Future { output: _, repr: _ } => todo!(),
             TypeKind::Error { repr: _ } => format!("anyhow::Error"),
+            // A function that never returns is given a `()` return type on the Java side.
+            TypeKind::Never => "()".to_string(),
             TypeKind::UserType { qname } => qname.colon_colon(),
             _ => todo!(),
         }
@@ -364,36 +1089,257 @@ impl<'idl> RustCodeGenerator<'idl> {
     /// Adapt from Java arguments to the Rust argument.
     /// 
     /// If the result is an error, use `?` to adapt it.
+    ///
+    /// Parameters in `omitted` are not received from Java, they are given their default value instead.
     fn generate_fn_body(
         &self,
         lib_rs: &mut CodeWriter<'_>,
-        fn_name: &Name,
-        rust_qname: &QualifiedName,
+        rust_fn_qname: &QualifiedName,
+        method_category: &MethodCategory,
         signature: &Signature,
-        output: &FunctionOutput,
+        omitted: &[&FunctionInput],
     ) -> anyhow::Result<()> {
+        let output = signature.output_ty();
+        let receiver = match method_category {
+            MethodCategory::BuilderMethod(self_kind) | MethodCategory::InstanceMethod(self_kind) => {
+                Some(self.generate_receiver(lib_rs, &rust_fn_qname.module_name(), self_kind)?)
+            }
+            _ => None,
+        };
+
         for input in signature.inputs() {
             let name = input.name();
-            write!(
-                lib_rs, 
-                "let {name}: {ty} = duchess::JvmOp::execute({name})?;",
-                ty = self.rust_owned_ty(input.refd_ty().ty()),
-            )?;
+            match input.default_value() {
+                Some(default_value) if omitted.iter().any(|o| o.name() == name) => {
+                    // A string literal is a `&str`, and the owned type may be a `String` or a `PathBuf`.
+                    let ty = self.rust_owned_ty(input.refd_ty().ty());
+                    let into = if let Literal::String(_) = default_value { ".into()" } else { "" };
+                    write!(lib_rs, "let {name}: {ty} = {default_value}{into};")?;
+                }
+                _ => self.generate_input_conversion(lib_rs, name, input.refd_ty().ty())?,
+            }
         }
 
-        write!(lib_rs, "Ok({m}::{fn_name}(", m = rust_qname.colon_colon())?;
+        // A call that never returns needs no `Ok` wrapper, it already has any type we want.
+        // A unit call is evaluated for its effects and then reports success to the `byte` native method.
+        // An iterator, record, variant, or resource is boxed and handed to Java as a handle.
+        // An enum is handed to Java as the name of its variant.
+        // Scalars without a Java counterpart are cast to the Java scalar.
+        // A path (or an optional path) is handed to Java as a string, replacing anything that is not UTF-8.
+        // An `Option` of any of these (other than an iterator or an enum) is handed to Java
+        // as a `Vec` of zero or one element, since these values cannot be `null` in Java.
+        let main_ty = util::awaited_ty(output.main_ty().ty());
+        let (open, close) = if main_ty.is_never() {
+            ("", String::new())
+        } else if main_ty.is_unit() {
+            ("", "; Ok(0)".to_string())
+        } else if util::iterator_item(main_ty).is_some() {
+            ("Ok(gluegun_java_util::iterator::into_handle(", "))".to_string())
+        } else if let Some(element) = util::primitive_option_element(self.idl, main_ty) {
+            let value = if util::handle_qname(self.idl, element).is_some() {
+                "gluegun_java_util::record::into_handle(value)".to_string()
+            } else if util::resource_qname(self.idl, element).is_some() {
+                "gluegun_java_util::resource::into_handle(value)".to_string()
+            } else if let Some(java_ty) = java_scalar_ty(element) {
+                format!("value as {java_ty}")
+            } else {
+                "value".to_string()
+            };
+            ("Ok(", format!(".into_iter().map(|value| {value}).collect::<Vec<_>>())"))
+        } else if util::handle_qname(self.idl, main_ty).is_some() {
+            ("Ok(gluegun_java_util::record::into_handle(", "))".to_string())
+        } else if util::resource_qname(self.idl, main_ty).is_some() {
+            ("Ok(gluegun_java_util::resource::into_handle(", "))".to_string())
+        } else if util::enum_qname(self.idl, main_ty).is_some() {
+            ("crate::JavaEnum::to_java(&", ")".to_string())
+        } else if let Some(java_ty) = java_scalar_ty(main_ty) {
+            ("Ok((", format!(") as {java_ty})"))
+        } else if let TypeKind::Path { repr: _ } = main_ty.kind() {
+            ("Ok(", ".to_string_lossy().into_owned())".to_string())
+        } else if matches!(main_ty.kind(), TypeKind::Option { element, repr: _ } if matches!(element.kind(), TypeKind::Path { .. })) {
+            ("Ok(", ".map(|path| path.to_string_lossy().into_owned()))".to_string())
+        } else {
+            ("Ok(", ")".to_string())
+        };
+
+        // Futures are driven to completion on the shared runtime.
+        let future = util::is_async(signature);
+        let (block_on_open, block_on_close) = if future {
+            ("crate::gluegun_runtime::block_on(", ")")
+        } else {
+            ("", "")
+        };
+
+        // A value returned behind a smart pointer is taken out of it first.
+        let (pointer_open, pointer_close) = output.main_ty().owned_kind().map_or(("", ""), OwnedKind::rust_into_value);
+
+        write!(lib_rs, "{open}{pointer_open}{block_on_open}{f}(", f = rust_fn_qname.colon_colon())?;
+
+        if let Some(receiver) = receiver {
+            write!(lib_rs, "{receiver},")?;
+        }
 
         for input in signature.inputs() {
             self.generate_rust_argument(lib_rs, input)?;
         }
 
-        let qmark = if output.error_ty().is_some() {
-            "?"
-        } else {
-            ""
+        // Errors are thrown as the exception generated for the error type, if any,
+        // as the crate's `RustException` for an `anyhow::Error` or `Box<dyn Error>`,
+        // and otherwise as a Java `RuntimeException` carrying the error's message.
+        let qmark = match output.error_ty() {
+            Some(error_ty) => match util::thrown_qname(self.idl, error_ty) {
+                Some(qname) => format!(".map_err(crate::{})?", exception_conversion_name(qname)),
+                None if util::is_rust_error(error_ty) => format!(".map_err(crate::{RUST_EXCEPTION_CONVERSION})?"),
+                None => ".map_err(|err| duchess::Error::JvmInternal(err.to_string()))?".to_string(),
+            },
+            None => String::new(),
         };
 
-        write!(lib_rs, "){qmark})")?;
+        write!(lib_rs, "){block_on_close}{qmark}{pointer_close}{close}")?;
+        Ok(())
+    }
+
+    /// Recover the receiver of a method on the type `rust_qname` from the `self_handle` (or `self_name`) received from Java,
+    /// returning the argument to pass for `self`.
+    ///
+    /// A resource stays owned by its Java object, so it is only borrowed unless the method takes `self` by value.
+    /// A record or variant is copied into a fresh handle for each call (see `JavaCodeGenerator::receiver`),
+    /// so the method always works on its own value; changes made through `&mut self` are not seen by Java.
+    /// A record with private fields or `#[non_exhaustive]` cannot be copied that way, so it cannot be a receiver.
+    fn generate_receiver(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        rust_qname: &QualifiedName,
+        self_kind: &SelfKind,
+    ) -> anyhow::Result<&'static str> {
+        let rust_ty = rust_qname.colon_colon();
+        if util::is_unconstructible_record(self.idl, rust_qname) {
+            anyhow::bail!(UnsupportedError::new(
+                &rust_ty,
+                "methods taking `self` are not supported on a record with private fields or `#[non_exhaustive]`, which Java cannot pass back",
+            ));
+        }
+        if self.is_resource(rust_qname) {
+            let function = match self_kind {
+                SelfKind::ByValue => "from_handle",
+                SelfKind::ByRef => "get",
+                SelfKind::ByRefMut => "get_mut",
+                _ => anyhow::bail!("unsupported self kind: {self_kind:?}"),
+            };
+            write!(
+                lib_rs,
+                "let receiver = unsafe {{ gluegun_java_util::resource::{function}::<{rust_ty}>(self_handle) }};"
+            )?;
+            Ok("receiver")
+        } else {
+            let mutability = if let SelfKind::ByRefMut = self_kind { "mut " } else { "" };
+            if let Some(Item::Enum(_)) = self.idl.definitions().get(rust_qname) {
+                write!(lib_rs, "let receiver: String = duchess::JvmOp::execute(self_name)?;")?;
+                write!(lib_rs, "let {mutability}receiver: {rust_ty} = crate::JavaEnum::from_java(receiver)?;")?;
+            } else {
+                write!(
+                    lib_rs,
+                    "let {mutability}receiver = unsafe {{ gluegun_java_util::record::from_handle::<{rust_ty}>(self_handle) }};"
+                )?;
+            }
+            Ok(match self_kind {
+                SelfKind::ByValue => "receiver",
+                SelfKind::ByRef => "&receiver",
+                SelfKind::ByRefMut => "&mut receiver",
+                _ => anyhow::bail!("unsupported self kind: {self_kind:?}"),
+            })
+        }
+    }
+
+    /// True if `qname` names a resource.
+    fn is_resource(&self, qname: &QualifiedName) -> bool {
+        matches!(self.idl.definitions().get(qname), Some(Item::Resource(_)))
+    }
+
+    /// Convert the value `name` received from Java into a Rust value of type `ty`, shadowing it.
+    fn generate_input_conversion(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        name: &Name,
+        ty: &Ty,
+    ) -> anyhow::Result<()> {
+        let rust_ty = self.rust_owned_ty(ty);
+        if let TypeKind::Option { element, repr: _ } = ty.kind() {
+            // See `generate_java_parameter`; the placeholder for an absent value is not converted.
+            write!(lib_rs, "let {name}: {rust_ty} = if {name}_is_some != 0 {{")?;
+            self.generate_input_conversion(lib_rs, name, element)?;
+            write!(lib_rs, "Some({name})")?;
+            write!(lib_rs, "}} else {{")?;
+            write!(lib_rs, "None")?;
+            write!(lib_rs, "}};")?;
+        } else if util::handle_qname(self.idl, ty).is_some() {
+            // The handle was created by Java just for this call, so we take ownership of the value.
+            write!(lib_rs, "let {name}: {rust_ty} = unsafe {{ gluegun_java_util::record::from_handle({name}) }};")?;
+        } else if util::resource_qname(self.idl, ty).is_some() {
+            // The Java object gave up its handle (and was closed), so we take ownership of the value.
+            write!(lib_rs, "let {name}: {rust_ty} = unsafe {{ gluegun_java_util::resource::from_handle({name}) }};")?;
+        } else if util::enum_qname(self.idl, ty).is_some() {
+            write!(lib_rs, "let {name}: String = duchess::JvmOp::execute({name})?;")?;
+            write!(lib_rs, "let {name}: {rust_ty} = crate::JavaEnum::from_java({name})?;")?;
+        } else if let TypeKind::Vec { element, repr: _ } | TypeKind::Set { element, repr: _ } = ty.kind() {
+            write!(lib_rs, "let {name}: {rust_ty} = {{")?;
+            write!(lib_rs, "let mut elements = Vec::new();")?;
+            write!(lib_rs, "for index in 0..duchess::JvmOp::execute({name}.size())? {{")?;
+            self.generate_list_element(lib_rs, "element", name, element)?;
+            write!(lib_rs, "elements.push(element);")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "elements.into_iter().collect()")?;
+            write!(lib_rs, "}};")?;
+        } else if let TypeKind::Map { key, value, repr: _ } = ty.kind() {
+            // See `generate_java_parameter`.
+            write!(lib_rs, "let {name}: {rust_ty} = {{")?;
+            write!(lib_rs, "let mut entries = Vec::new();")?;
+            write!(lib_rs, "for index in 0..duchess::JvmOp::execute({name}_keys.size())? {{")?;
+            self.generate_list_element(lib_rs, "key", &format!("{name}_keys"), key)?;
+            self.generate_list_element(lib_rs, "value", &format!("{name}_values"), value)?;
+            write!(lib_rs, "entries.push((key, value));")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "entries.into_iter().collect()")?;
+            write!(lib_rs, "}};")?;
+        } else if unsigned_java_parameter_ty(ty).is_some() {
+            // Out-of-range values were rejected on the Java side (unless `checked-conversions` is off).
+            write!(lib_rs, "let {name}: {rust_ty} = {name} as {rust_ty};")?;
+        } else if let TypeKind::Scalar(Scalar::Char) = ty.kind() {
+            // Surrogates were rejected on the Java side (unless `checked-conversions` is off), but there is no `as` conversion to `char`.
+            write!(lib_rs, "let {name}: char = char::from_u32({name} as u32)")?;
+            write!(
+                lib_rs,
+                ".ok_or_else(|| duchess::Error::JvmInternal(format!(\"`{name}` is not a valid `char`: {{{name}}}\")))?;"
+            )?;
+        } else if let TypeKind::Path { repr: _ } = ty.kind() {
+            // A path is received as a string; a `&Path` argument borrows the `PathBuf`.
+            write!(lib_rs, "let {name}: String = duchess::JvmOp::execute({name})?;")?;
+            write!(lib_rs, "let {name}: {rust_ty} = std::path::PathBuf::from({name});")?;
+        } else if let TypeKind::Scalar(_) = ty.kind() {
+            // Scalars are received as Rust values already.
+        } else {
+            write!(lib_rs, "let {name}: {rust_ty} = duchess::JvmOp::execute({name})?;")?;
+        }
+        Ok(())
+    }
+
+    /// Read the element at `index` of the Java list `list` into the Rust value `binding` of type `ty`
+    /// (see [`Self::java_object_ty`][] for the supported types).
+    fn generate_list_element(
+        &self,
+        lib_rs: &mut CodeWriter<'_>,
+        binding: &str,
+        list: &(impl std::fmt::Display + ?Sized),
+        ty: &Ty,
+    ) -> anyhow::Result<()> {
+        self.java_object_ty(ty)?;
+        write!(
+            lib_rs,
+            "let {binding}: String = duchess::JvmOp::execute(duchess::JvmOp::assert_not_null({list}.get(index)))?;"
+        )?;
+        if let TypeKind::Path { repr: _ } = ty.kind() {
+            write!(lib_rs, "let {binding} = std::path::PathBuf::from({binding});")?;
+        }
         Ok(())
     }
 
@@ -403,9 +1349,63 @@ impl<'idl> RustCodeGenerator<'idl> {
     ) -> anyhow::Result<()> {
         let name = input.name();
         match input.refd_ty() {
-            RefdTy::Owned(..) => write!(lib_rs, "{name},")?,
+            RefdTy::Owned(kind, _) => write!(lib_rs, "{},", kind.rust_from_value(name))?,
+            // The Java object cannot lend its handle, since nothing stops another thread from using it during the call.
+            RefdTy::Ref(_, ty) if util::resource_qname(self.idl, ty).is_some() => {
+                anyhow::bail!(UnsupportedError::new(ty, "a resource can only be passed from Java by value"))
+            }
+            // Java passed a copy of its value, so changes made through the borrow are not seen by Java
+            // (as for `&mut self` on a record); the block moves the copy into a temporary that can be lent mutably.
+            RefdTy::Ref(kind, _) if kind.is_mut() => write!(lib_rs, "&mut {{ {name} }},")?,
             RefdTy::Ref(..) => write!(lib_rs, "&{name},")?,
         }
         Ok(())
     }
 }
+
+/// How each field of `arm` is named in a Rust struct expression or pattern, e.g. `radius` or `0`.
+fn field_members(arm: &VariantArm) -> Vec<String> {
+    match arm.fields_kind() {
+        FieldsKind::Tuple => (0..arm.fields().len()).map(|index| index.to_string()).collect(),
+        FieldsKind::Named | FieldsKind::Unit => arm.fields().iter().map(|field| field.rust_name().to_string()).collect(),
+    }
+}
+
+/// Name of the function converting an `anyhow::Error` or `Box<dyn Error>` into the crate's `RustException`,
+/// see [`RustCodeGenerator::generate_rust_exception_conversion`][].
+const RUST_EXCEPTION_CONVERSION: &str = "gluegun_rust_error_into_exception";
+
+/// Name of the function converting an error of the type `qname` into a Java exception,
+/// see [`RustCodeGenerator::generate_exception_conversion`][].
+fn exception_conversion_name(qname: &QualifiedName) -> String {
+    format!("{}_into_exception", qname.to_snake_case().to_string("_"))
+}
+
+/// The Java type of the error passed to the constructor of the exception for the error type `qname`.
+fn exception_native_ty(idl: &Idl, qname: &QualifiedName) -> &'static str {
+    match idl.definitions().get(qname) {
+        Some(Item::Enum(_)) => "java.lang.String",
+        _ => "long",
+    }
+}
+
+/// The Rust type of the Java scalar for `ty`, if it differs from `ty` itself (e.g., `i64` for `u32`).
+/// Values of `ty` convert to it with `as`; unsigned integers are widened so that they keep their value (except for `u64`).
+fn java_scalar_ty(ty: &Ty) -> Option<&'static str> {
+    match ty.kind() {
+        TypeKind::Scalar(Scalar::Boolean) => Some("i8"),
+        TypeKind::Scalar(Scalar::U8) => Some("i16"),
+        TypeKind::Scalar(Scalar::Char | Scalar::U16) => Some("i32"),
+        TypeKind::Scalar(Scalar::U32 | Scalar::U64) => Some("i64"),
+        _ => None,
+    }
+}
+
+/// The Rust type of the Java integer received for a parameter of type `ty`, if `ty` is an unsigned integer.
+fn unsigned_java_parameter_ty(ty: &Ty) -> Option<&'static str> {
+    match ty.kind() {
+        TypeKind::Scalar(Scalar::U8 | Scalar::U16 | Scalar::U32 | Scalar::U64) => java_scalar_ty(ty),
+        _ => None,
+    }
+}
+
@@ -1,6 +1,12 @@
-use std::path::PathBuf;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
 
-use gluegun_core::idl::{Name, QualifiedName, RefdTy, Ty};
+use gluegun_core::{
+    casing::Casing,
+    idl::{FunctionInput, Idl, IsAsync, Item, Literal, Name, QualifiedName, RefdTy, Scalar, Signature, Ty, TypeKind},
+};
 
 /// A qualified name following Java conventions.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
@@ -12,18 +18,25 @@ pub(crate) struct JavaQName {
     pub(crate) class_name: Name,
 }
 
-/// Convert a qualified name from Rust to Java conventions and break apart the module/class name
-pub(crate) fn class_package_and_name(qname: &QualifiedName) -> JavaQName  {
-    let (module_name, type_name) = qname.camel_case().split_module_name();
+/// Convert a qualified name from Rust to Java conventions and break apart the module/class name.
+/// The class is named after the exported name of the item, if `qname` is defined in the `idl` (see `Function::name`).
+pub(crate) fn class_package_and_name(idl: &Idl, qname: &QualifiedName) -> JavaQName  {
+    let (module_name, type_name) = qname.split_module_name();
+    let type_name = idl.definitions().get(qname).map_or(type_name, |item| item.name().clone());
     JavaQName {
-        package: module_name,
-        class_name: type_name.upper_camel_case(),
+        package: module_name.to_camel_case(),
+        class_name: type_name.to_pascal_case(),
     }
 }
 
 /// Return a path like `java/lang/String.java`
-pub(crate) fn class_file_name(qname: &QualifiedName) -> PathBuf {
-    let JavaQName { package, class_name } = class_package_and_name(qname);
+pub(crate) fn class_file_name(idl: &Idl, qname: &QualifiedName) -> PathBuf {
+    java_file_name(&class_package_and_name(idl, qname))
+}
+
+/// Return a path like `java/lang/String.java` for the class `java_qname`.
+pub(crate) fn java_file_name(java_qname: &JavaQName) -> PathBuf {
+    let JavaQName { package, class_name } = java_qname;
     let mut path = PathBuf::new();
     for name in package.names() {
         path.push(name.text());
@@ -34,11 +47,308 @@ pub(crate) fn class_file_name(qname: &QualifiedName) -> PathBuf {
 }
 
 /// Return a string like `java.lang.String`
-pub(crate) fn class_dot_name(qname: &QualifiedName) -> String {
-    let JavaQName { package, class_name } = class_package_and_name(qname);
+pub(crate) fn class_dot_name(idl: &Idl, qname: &QualifiedName) -> String {
+    let JavaQName { package, class_name } = class_package_and_name(idl, qname);
     format!("{}.{}", package.dotted(), class_name)
 }
 
+/// The exception class thrown when a Rust function fails with an error of the type `qname`,
+/// e.g. `ParseErrorException` for `ParseError` (see [`thrown_qname`][]).
+pub(crate) fn exception_package_and_name(idl: &Idl, qname: &QualifiedName) -> JavaQName {
+    let JavaQName { package, class_name } = class_package_and_name(idl, qname);
+    JavaQName {
+        package,
+        class_name: Name::from(format!("{class_name}Exception")),
+    }
+}
+
+/// Return a string like `errors.ParseErrorException`, see [`exception_package_and_name`][].
+pub(crate) fn exception_dot_name(idl: &Idl, qname: &QualifiedName) -> String {
+    let JavaQName { package, class_name } = exception_package_and_name(idl, qname);
+    format!("{}.{}", package.dotted(), class_name)
+}
+
+/// The qualified name of the record, variant, or enum, if the error type `ty` names one.
+/// Such an error is thrown as an exception class generated for the type, which carries the error value;
+/// an `anyhow::Error` or `Box<dyn Error>` is thrown as the crate's `RustException` (see [`is_rust_error`][]),
+/// and any other error as a `RuntimeException` carrying its message.
+pub(crate) fn thrown_qname<'t>(idl: &Idl, ty: &'t Ty) -> Option<&'t QualifiedName> {
+    match ty.kind() {
+        TypeKind::UserType { qname } => match idl.definitions().get(qname) {
+            Some(Item::Record(_) | Item::Variant(_) | Item::Enum(_)) => Some(qname),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The types for which an exception class is generated, i.e. those that are the error type
+/// of some function or method (see [`thrown_qname`][]).
+pub(crate) fn thrown_qnames(idl: &Idl) -> BTreeSet<&QualifiedName> {
+    signatures(idl)
+        .into_iter()
+        .filter_map(|signature| signature.output_ty().error_ty().as_ref())
+        .filter_map(|ty| thrown_qname(idl, ty))
+        .collect()
+}
+
+/// True if the error type `ty` is an `anyhow::Error` or `Box<dyn Error>`, which is thrown as the `RustException`
+/// generated for the crate (see [`rust_exception_package_and_name`][]), carrying the error's message.
+pub(crate) fn is_rust_error(ty: &Ty) -> bool {
+    matches!(ty.kind(), TypeKind::Error { .. })
+}
+
+/// True if some function or method fails with an error thrown as `RustException` (see [`is_rust_error`][]),
+/// so that the class is generated.
+pub(crate) fn throws_rust_exception(idl: &Idl) -> bool {
+    signatures(idl)
+        .into_iter()
+        .filter_map(|signature| signature.output_ty().error_ty().as_ref())
+        .any(is_rust_error)
+}
+
+/// The exception thrown for the errors that have no class of their own (see [`is_rust_error`][]):
+/// `RustException`, in the package of the crate.
+pub(crate) fn rust_exception_package_and_name(idl: &Idl) -> JavaQName {
+    JavaQName {
+        package: QualifiedName::from(idl.crate_name()).to_camel_case(),
+        class_name: Name::from("RustException"),
+    }
+}
+
+/// Return a string like `errors.RustException`, see [`rust_exception_package_and_name`][].
+pub(crate) fn rust_exception_dot_name(idl: &Idl) -> String {
+    let JavaQName { package, class_name } = rust_exception_package_and_name(idl);
+    format!("{}.{}", package.dotted(), class_name)
+}
+
+/// The signatures of all functions and methods in the IDL.
+fn signatures(idl: &Idl) -> Vec<&Signature> {
+    let mut signatures = vec![];
+    for item in idl.definitions().values() {
+        match item {
+            Item::Function(function) => signatures.push(function.signature()),
+            Item::Resource(resource) => signatures.extend(resource.methods().iter().map(|m| m.signature())),
+            Item::Record(record) => signatures.extend(record.methods().iter().map(|m| m.signature())),
+            Item::Variant(variant) => signatures.extend(variant.methods().iter().map(|m| m.signature())),
+            Item::Enum(an_enum) => signatures.extend(an_enum.methods().iter().map(|m| m.signature())),
+            _ => {}
+        }
+    }
+    signatures
+}
+
+/// The tuples used in the IDL (in signatures or fields, possibly nested in other types),
+/// keyed by the qualified name of the class generated for each shape (see [`tuple_qname`][]).
+pub(crate) fn tuple_types(idl: &Idl) -> BTreeMap<QualifiedName, &Ty> {
+    fn visit<'t>(idl: &Idl, ty: &'t Ty, tuples: &mut BTreeMap<QualifiedName, &'t Ty>) {
+        match ty.kind() {
+            TypeKind::Tuple { elements, repr: _ } => {
+                if let Some(qname) = tuple_qname(idl, ty) {
+                    tuples.insert(qname, ty);
+                }
+                for element in elements {
+                    visit(idl, element, tuples);
+                }
+            }
+            TypeKind::Vec { element, repr: _ }
+            | TypeKind::Array { element, len: _ }
+            | TypeKind::Set { element, repr: _ }
+            | TypeKind::Option { element, repr: _ }
+            | TypeKind::Future { output: element, repr: _ }
+            | TypeKind::Iterator { item: element, repr: _ } => visit(idl, element, tuples),
+            TypeKind::Map { key: first, value: second, repr: _ } | TypeKind::Result { ok: first, err: second, repr: _ } => {
+                visit(idl, first, tuples);
+                visit(idl, second, tuples);
+            }
+            _ => {}
+        }
+    }
+
+    let mut tuples = BTreeMap::new();
+    for signature in signatures(idl) {
+        for input in signature.inputs() {
+            visit(idl, input.refd_ty().ty(), &mut tuples);
+        }
+        visit(idl, signature.output_ty().main_ty().ty(), &mut tuples);
+    }
+    for item in idl.definitions().values() {
+        match item {
+            Item::Record(record) => record.public_fields().for_each(|field| visit(idl, field.ty(), &mut tuples)),
+            Item::Variant(variant) => variant
+                .arms()
+                .iter()
+                .flat_map(|arm| arm.fields())
+                .for_each(|field| visit(idl, field.ty(), &mut tuples)),
+            _ => {}
+        }
+    }
+    tuples
+}
+
+/// The qualified name of the class generated for the tuple type `ty` (unless it is `()`),
+/// which is in the crate's root package and named after the types of its elements, e.g. `TupleI32String`.
+pub(crate) fn tuple_qname(idl: &Idl, ty: &Ty) -> Option<QualifiedName> {
+    match ty.kind() {
+        TypeKind::Tuple { elements, repr: _ } if !elements.is_empty() => {
+            let shape = ty.to_string();
+            let words = shape.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty());
+            let class_name = std::iter::once("Tuple".to_string())
+                .chain(words.map(|word| word[..1].to_uppercase() + &word[1..]))
+                .collect::<String>();
+            Some(QualifiedName::from(idl.crate_name()).join(class_name))
+        }
+        _ => None,
+    }
+}
+
+/// True if the function is an `async fn` or returns `impl Future`.
+pub(crate) fn is_async(signature: &Signature) -> bool {
+    *signature.is_async() == IsAsync::Yes || matches!(signature.output_ty().main_ty().ty().kind(), TypeKind::Future { .. })
+}
+
+/// The type produced once a future has been awaited (or `ty` itself if it is not a future).
+/// Futures are driven to completion on the Rust side, so Java only ever sees their output.
+pub(crate) fn awaited_ty(ty: &Ty) -> &Ty {
+    match ty.kind() {
+        TypeKind::Future { output, repr: _ } => output,
+        _ => ty,
+    }
+}
+
+/// The item type if a function returning `ty` gives Java an iterator handle (i.e., `ty` is `impl Iterator<Item = T>`).
+pub(crate) fn iterator_item(ty: &Ty) -> Option<&Ty> {
+    match awaited_ty(ty).kind() {
+        TypeKind::Iterator { item, repr: _ } => Some(item),
+        _ => None,
+    }
+}
+
+/// Name of the nested Java class holding the iterator returned by the method `name`, e.g. `WordsIterator`.
+pub(crate) fn iterator_class_name(name: &Name) -> String {
+    format!("{}Iterator", name.to_pascal_case())
+}
+
+/// Name of the `native` Java method performing `op` (e.g. `next`) on the iterator returned by the method `name`.
+pub(crate) fn iterator_native_name(name: &Name, op: &str) -> String {
+    format!("native_{name}_iterator_{op}")
+}
+
+/// The qualified name of the record or variant, if `ty` names one, or of the class generated for a tuple.
+/// These cross between Java and Rust as a handle to the Rust value, see `gluegun_java_util::record`.
+pub(crate) fn handle_qname(idl: &Idl, ty: &Ty) -> Option<QualifiedName> {
+    match ty.kind() {
+        TypeKind::UserType { qname } => match idl.definitions().get(qname) {
+            Some(Item::Record(_) | Item::Variant(_)) => Some(qname.clone()),
+            _ => None,
+        },
+        TypeKind::Tuple { .. } => tuple_qname(idl, ty),
+        _ => None,
+    }
+}
+
+/// True if `qname` names a record that Java cannot build, as it has private fields or is `#[non_exhaustive]`
+/// (see `Record::is_constructible`): it can be returned to Java, but not passed from it.
+pub(crate) fn is_unconstructible_record(idl: &Idl, qname: &QualifiedName) -> bool {
+    matches!(idl.definitions().get(qname), Some(Item::Record(record)) if !record.is_constructible())
+}
+
+/// The qualified name of the enum, if `ty` names a fieldless enum.
+/// Enum values cross between Java and Rust as the name of the variant, so that the mapping does not depend on the order of the variants.
+pub(crate) fn enum_qname<'t>(idl: &Idl, ty: &'t Ty) -> Option<&'t QualifiedName> {
+    match ty.kind() {
+        TypeKind::UserType { qname } => match idl.definitions().get(qname) {
+            Some(Item::Enum(_)) => Some(qname),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The qualified name of the resource, if `ty` names one.
+/// The Java object owns the Rust value through a handle, see `gluegun_java_util::resource`.
+pub(crate) fn resource_qname<'t>(idl: &Idl, ty: &'t Ty) -> Option<&'t QualifiedName> {
+    match ty.kind() {
+        TypeKind::UserType { qname } => match idl.definitions().get(qname) {
+            Some(Item::Resource(_)) => Some(qname),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The element type if `ty` is an `Option` of a scalar, record, variant, or resource.
+/// These elements cross between Java and Rust as a primitive (the scalar or a handle), which cannot be `null`,
+/// so such an option is returned to Java as an array holding zero or one element.
+pub(crate) fn primitive_option_element<'t>(idl: &Idl, ty: &'t Ty) -> Option<&'t Ty> {
+    match ty.kind() {
+        TypeKind::Option { element, repr: _ }
+            if matches!(element.kind(), TypeKind::Scalar(_))
+                || handle_qname(idl, element).is_some()
+                || resource_qname(idl, element).is_some() =>
+        {
+            Some(element)
+        }
+        _ => None,
+    }
+}
+
+/// Name of the `native` Java method performing `op` (e.g. `new` or `get_x`) on the handle to a record.
+/// These live in the record's class and are prefixed so that they cannot clash with the natives of its methods.
+pub(crate) fn record_native_name(op: &str) -> String {
+    format!("native_record_{op}")
+}
+
+/// Like [`record_native_name`][] but for a resource.
+pub(crate) fn resource_native_name(op: &str) -> String {
+    format!("native_resource_{op}")
+}
+
+/// Like [`record_native_name`][] but for a variant; the natives for all of its arms live in the class of the variant itself.
+pub(crate) fn variant_native_name(op: &str) -> String {
+    format!("native_variant_{op}")
+}
+
+/// True if a function returning `ty` becomes a `void` method in Java (i.e., `()` and `!`).
+///
+/// duchess cannot report errors from a `void` native method,
+/// so the `native` counterparts of such methods return a `byte` (always `0`) instead.
+pub(crate) fn is_void(ty: &Ty) -> bool {
+    let ty = awaited_ty(ty);
+    ty.is_unit() || ty.is_never()
+}
+
+/// The parameters omitted by each additional overload generated for parameters with default values.
+/// As with Kotlin's `@JvmOverloads`, the `k`th overload omits the last `k` parameters that have defaults.
+pub(crate) fn default_overloads(signature: &Signature) -> Vec<Vec<&FunctionInput>> {
+    let defaulted: Vec<&FunctionInput> = signature
+        .inputs()
+        .iter()
+        .filter(|input| input.default_value().is_some())
+        .collect();
+    (1..=defaulted.len())
+        .map(|k| defaulted[defaulted.len() - k..].to_vec())
+        .collect()
+}
+
+/// The parameters of `signature` that remain once `omitted` have been removed.
+pub(crate) fn remaining_inputs<'s>(signature: &'s Signature, omitted: &[&FunctionInput]) -> Vec<&'s FunctionInput> {
+    signature
+        .inputs()
+        .iter()
+        .filter(|input| !omitted.iter().any(|o| o.name() == input.name()))
+        .collect()
+}
+
+/// Name of the `native` Java method backing the method `name` (or its overload omitting `omitted`).
+/// This must be a valid identifier in both Java and Rust.
+pub(crate) fn native_method_name(name: &Name, omitted: &[&FunctionInput]) -> String {
+    match omitted.first() {
+        None => format!("native_{name}"),
+        Some(first) => format!("native_{name}_without_{}", first.name()),
+    }
+}
+
 pub trait AsTy {
     fn as_ty(&self) -> &Ty;
 }
@@ -59,4 +369,56 @@ impl AsTy for RefdTy {
     fn as_ty(&self) -> &Ty {
         self.ty()
     }
-}
\ No newline at end of file
+}
+/// Java condition that is true when the parameter `name` of type `ty` does not fit in the Rust type.
+/// Unsigned Rust integers are received as a wider (signed) Java integer, see `JavaCodeGenerator::write_ty`,
+/// and a `char` as an `int` code point, which must not be a surrogate;
+/// every other type has no such condition.
+pub(crate) fn out_of_range_condition(name: &Name, ty: &Ty) -> Option<String> {
+    let max = match ty.kind() {
+        TypeKind::Scalar(Scalar::Char) => {
+            return Some(format!(
+                "!Character.isValidCodePoint({name}) || Character.getType({name}) == Character.SURROGATE"
+            ))
+        }
+        TypeKind::Scalar(Scalar::U8) => "255",
+        TypeKind::Scalar(Scalar::U16) => "65535",
+        TypeKind::Scalar(Scalar::U32) => "4294967295L",
+        TypeKind::Scalar(Scalar::U64) => return Some(format!("{name} < 0")),
+        _ => return None,
+    };
+    Some(format!("{name} < 0 || {name} > {max}"))
+}
+
+/// Java expression for the constant `value` of type `ty`, suiting the Java type for `ty` (see `JavaCodeGenerator::write_ty`):
+/// a `bool` is a `byte`, a `char` its code point, and a `u64` above `Long.MAX_VALUE` wraps around,
+/// as when such values are returned from Rust.
+pub(crate) fn java_literal(ty: &Ty, value: &Literal) -> anyhow::Result<String> {
+    Ok(match (value, ty.kind()) {
+        (Literal::Boolean(value), _) => u8::from(*value).to_string(),
+        (Literal::Char(value), _) => u32::from(*value).to_string(),
+        (Literal::Integer(digits), TypeKind::Scalar(Scalar::U64)) => format!("{}L", digits.parse::<u64>()? as i64),
+        (Literal::Integer(digits), TypeKind::Scalar(Scalar::I64 | Scalar::U32)) => format!("{digits}L"),
+        (Literal::Integer(digits), _) => digits.clone(),
+        (Literal::Float(digits), TypeKind::Scalar(Scalar::F32)) => format!("{digits}f"),
+        (Literal::Float(digits), _) => digits.clone(),
+        (Literal::String(value), _) => java_string_literal(value),
+    })
+}
+
+/// Java string literal for `value`.
+/// Java translates `\u` escapes before parsing string literals, so control characters use octal escapes instead.
+fn java_string_literal(value: &str) -> String {
+    let mut literal = String::from("\"");
+    for unit in value.encode_utf16() {
+        match char::from_u32(unit.into()) {
+            Some('"') => literal.push_str("\\\""),
+            Some('\\') => literal.push_str("\\\\"),
+            Some(c) if c.is_ascii_control() => literal.push_str(&format!("\\{unit:03o}")),
+            Some(c) if c.is_ascii() => literal.push(c),
+            _ => literal.push_str(&format!("\\u{unit:04x}")),
+        }
+    }
+    literal.push('"');
+    literal
+}
@@ -19,18 +19,25 @@ impl GlueGunHelper for GlueGunPython {
         format!("py")
     }
 
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "Python".to_string()
+    }
+
     fn generate(
         self,
         cx: &mut GenerateCx,
         _metadata: &Self::Metadata,
         output: &mut LibraryCrate,
     ) -> anyhow::Result<()> {
-        let features = RustCodeGenerator::new(cx.idl()).generate(output)?;
+        cx.reject_generics(&self.language())?;
+
+        RustCodeGenerator::new(cx.idl()).generate(output)?;
 
-        let mut dep = output.add_dependency("pyo3").version("0.23");
-        for feature in features {
-            dep = dep.feature(feature);
-        }
+        output.add_dependency("pyo3").version("0.23");
 
         Ok(())
     }
@@ -1,46 +1,61 @@
 use gluegun_core::{
+    cli::{for_each_supported, UnsupportedError},
     codegen::{CodeWriter, LibraryCrate},
     idl::{
-        Function, FunctionInput, FutureRepr, Idl, Item, MapSetRepr, OptionRepr,
-        PathRepr, QualifiedName, RefdTy, ResultRepr, StringRepr, TupleRepr, Ty, TypeKind,
-        VecRepr,
+        Constant, Enum, Function, FunctionInput, Idl, IsAsync, Item, MapSetRepr, OptionRepr, QualifiedName, Record, RefdTy,
+        StringRepr, Ty, TypeKind, VecRepr,
     },
 };
 
+/// Generates a PyO3 extension module for the crate:
+/// functions become `#[pyfunction]`s, records become `#[pyclass]` structs with a field for each field,
+/// and C-like enums become Python enums.
+///
+/// PyO3 attributes cannot be put on the types of another crate, so records and enums are mirrored
+/// by wrapper types (named after the full path of the item, e.g., `my_crate_Point`) converted with `From`.
 pub(crate) struct RustCodeGenerator<'idl> {
     idl: &'idl Idl,
-    features: Vec<&'static str>,
+
+    /// Wrapper functions to add to the module.
+    functions: Vec<String>,
+
+    /// Wrapper types to add to the module.
+    classes: Vec<String>,
+
+    /// Constants to add to the module, as their name and the expression giving their value.
+    constants: Vec<(String, String)>,
+}
+
+/// Which way a value is converted between the wrapped crate and Python, see [`RustCodeGenerator::convert`][].
+#[derive(Copy, Clone)]
+enum Direction {
+    /// From the types given by PyO3 to those of the wrapped crate.
+    FromPython,
+
+    /// From the types of the wrapped crate to those given to PyO3.
+    ToPython,
 }
 
 impl<'idl> RustCodeGenerator<'idl> {
     pub(crate) fn new(idl: &'idl Idl) -> Self {
         Self {
             idl,
-            features: Default::default(),
+            functions: vec![],
+            classes: vec![],
+            constants: vec![],
         }
     }
 
-    pub(crate) fn generate(mut self, lib: &mut LibraryCrate) -> anyhow::Result<Vec<&'static str>> {
-        self.generate_lib_rs(lib)?;
-        Ok(self.features)
-    }
-
-    fn generate_lib_rs(&mut self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
+    pub(crate) fn generate(mut self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
         let mut lib_rs = lib.add_file("src/lib.rs")?;
 
-        write!(lib_rs, "#![allow(non_snake_case)]")?; // FIXME: bug in duchess
+        write!(lib_rs, "#![allow(non_camel_case_types, non_snake_case)]")?;
 
-        self.generate_python_items(&mut lib_rs)?;
+        for_each_supported(self.idl.definitions(), |(qname, item)| {
+            self.generate_python_item(&mut lib_rs, qname, item)
+        })?;
 
-        Ok(())
-    }
-
-    fn generate_python_items(&mut self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
-        for (qname, item) in self.idl.definitions() {
-            self.generate_python_item(lib_rs, qname, item)?;
-        }
-
-        Ok(())
+        self.generate_module(&mut lib_rs)
     }
 
     fn generate_python_item(
@@ -50,24 +65,31 @@ impl<'idl> RustCodeGenerator<'idl> {
         item: &Item,
     ) -> anyhow::Result<()> {
         match item {
-            Item::Resource(_resource) => {
-                todo!()
-            }
-            Item::Enum(_enum_) => {
-                todo!()
-            }
-            Item::Record(_record) => {
-                todo!()
-            }
-            Item::Variant(_variant) => {
-                todo!()
-            }
-            Item::Function(function) => {
-                self.generate_python_function(lib_rs, qname, function)?;
-            }
-            _ => todo!(),
+            Item::Function(function) => self.generate_python_function(lib_rs, qname, function),
+            Item::Record(record) => self.generate_python_record(lib_rs, qname, record),
+            Item::Enum(e) => self.generate_python_enum(lib_rs, qname, e),
+            Item::Constant(constant) => self.generate_python_constant(qname, constant),
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to Python yet")),
         }
+    }
 
+    /// The `#[pymodule]`, named after the crate, with every function and class generated.
+    fn generate_module(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
+        write!(lib_rs, "#[pyo3::pymodule]")?;
+        write!(lib_rs, "#[pyo3(name = \"{}\")]", self.idl.crate_name())?;
+        write!(lib_rs, "fn gluegun_module(m: &pyo3::Bound<'_, pyo3::types::PyModule>) -> pyo3::PyResult<()> {{")?;
+        write!(lib_rs, "use pyo3::types::PyModuleMethods;")?;
+        for function in &self.functions {
+            write!(lib_rs, "m.add_function(pyo3::wrap_pyfunction!({function}, m)?)?;")?;
+        }
+        for class in &self.classes {
+            write!(lib_rs, "m.add_class::<{class}>()?;")?;
+        }
+        for (name, value) in &self.constants {
+            write!(lib_rs, "m.add(\"{name}\", {value})?;")?;
+        }
+        write!(lib_rs, "Ok(())")?;
+        write!(lib_rs, "}}")?;
         Ok(())
     }
 
@@ -77,216 +99,275 @@ impl<'idl> RustCodeGenerator<'idl> {
         qname: &QualifiedName,
         function: &Function,
     ) -> anyhow::Result<()> {
-        // Write function definition with #[pyfunction] attribute
-        write!(lib_rs, "#[pyo3::pyfunction]")?;
-        write!(lib_rs, "fn {}(", function.name())?;
+        let signature = function.signature();
+        if signature.is_async() == &IsAsync::Yes {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "async functions cannot be mapped to Python yet"));
+        }
+        if signature.output_ty().error_ty().is_some() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "`Result` cannot be returned to Python yet"));
+        }
 
-        // Write function parameters
-        for input in function.signature().inputs() {
-            let input_type = self.rust_argument_ty(input)?;
-            write!(lib_rs, "{}: {},", input.name(), input_type)?;
+        // Everything is mapped before writing, so that nothing is written for an unsupported function.
+        let mut parameters = vec![];
+        let mut arguments = vec![];
+        for input in signature.inputs() {
+            let (ty, argument) = self.python_parameter(input)?;
+            parameters.push(format!("{}: {ty}", input.name()));
+            arguments.push(argument);
         }
+        let call = format!("{}({})", qname.colon_colon(), arguments.join(", "));
+        let output = match signature.output_ty().main_ty() {
+            RefdTy::Owned(_, ty) if ty.is_unit() => None,
+            RefdTy::Owned(kind, ty) => {
+                // A value returned behind a smart pointer is taken out of it before it is converted.
+                let (open, close) = kind.rust_into_value();
+                Some((self.python_ty(ty)?, self.convert(ty, &format!("{open}{call}{close}"), Direction::ToPython)?))
+            }
+            RefdTy::Ref(_, ty) => anyhow::bail!(UnsupportedError::new(ty, "references cannot be returned to Python yet")),
+        };
 
-        // Write return type if function has output
-        let main_ty =
-            self.generic_ty(function.signature().output_ty().main_ty().owned_or_err()?)?;
-        write!(lib_rs, ") -> {main_ty} {{")?;
-
-        // Write function body. Arguments will a suitable Rust owned type
-        // but they may need to be borrowed or adapted to fit what the callee function
-        // expects.
-        write!(lib_rs, "{}(", qname.colon_colon())?;
-        for input in function.signature().inputs() {
-            let name = input.name();
-            match input.refd_ty() {
-                RefdTy::Owned(..) => write!(lib_rs, "&{name}, ")?,
-                RefdTy::Ref(..) => write!(lib_rs, "&{name}, ")?,
+        let wrapper = python_ident(qname);
+        write!(lib_rs, "#[pyo3::pyfunction]")?;
+        write!(lib_rs, "#[pyo3(name = \"{}\")]", function.name())?;
+        match output {
+            None => {
+                write!(lib_rs, "fn {wrapper}({}) {{", parameters.join(", "))?;
+                write!(lib_rs, "{call};")?;
+            }
+            Some((ty, expr)) => {
+                write!(lib_rs, "fn {wrapper}({}) -> {ty} {{", parameters.join(", "))?;
+                write!(lib_rs, "{expr}")?;
             }
         }
-        write!(lib_rs, ")")?;
         write!(lib_rs, "}}")?;
 
+        self.functions.push(wrapper);
         Ok(())
     }
 
-    /// Invoked with a function argument. Returns a pair `(ty, expr)` of a
-    /// Rust type (`ty`) that will be provided by pyo3 and an `expr` that will adapt
-    /// this value to what the wrapped Rust function requires.
-    ///
-    /// So, for example, imagine that we have an input like `Map { ..., repr: HashMap }`.
-    /// This means that (a) the Python code will provide a map; (b) the Rust code expects a `HashMap`.
-    /// We need to pick a good type to use with pyo3 to make that efficient. In this case, we ought to
-    /// prefer a `HashMap` so that we can just directly pass it in (and rely on pyo3 to efficiently and
-    /// correctly handle creating a Rust `HashMap` from a Python map).
-    ///
-    /// General rule:
-    ///
-    /// * Where possible, use the same type for the pyo3 argument as the Rust code wants.
-    /// * Otherwise, use a generic pyo3 argument and some form of interconversion.
-    fn rust_argument_ty(&mut self, input: &FunctionInput) -> anyhow::Result<String> {
-        let input_ty = input.refd_ty().ty();
-        match input_ty.kind() {
-            TypeKind::Map { key, value, repr } => {
-                let name = self.map_name(repr)?;
-                Ok(format!(
-                    "{name}<{}, {}>",
-                    self.generic_ty(key)?,
-                    self.generic_ty(value)?
-                ))
-            }
+    /// Constants become attributes of the module, holding the value of the Rust constant.
+    fn generate_python_constant(&mut self, qname: &QualifiedName, constant: &Constant) -> anyhow::Result<()> {
+        let value = self.convert(constant.ty(), &qname.colon_colon(), Direction::ToPython)?;
+        self.constants.push((constant.name().to_string(), value));
+        Ok(())
+    }
 
-            TypeKind::Set { element, repr } => {
-                let name = self.map_name(repr)?;
-                Ok(format!("{name}<{}>", self.generic_ty(element)?))
-            }
+    fn generate_python_record(
+        &mut self,
+        lib_rs: &mut CodeWriter<'_>,
+        qname: &QualifiedName,
+        record: &Record,
+    ) -> anyhow::Result<()> {
+        if !record.methods().is_empty() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "methods cannot be mapped to Python yet"));
+        }
+        if !record.is_constructible() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "records with private fields or `#[non_exhaustive]` cannot be mapped to Python yet"));
+        }
 
-            TypeKind::Vec {
-                element,
-                repr: VecRepr::Vec,
-            } => Ok(format!("Vec<{}>", self.generic_ty(element)?)),
-
-            TypeKind::Vec {
-                element,
-                repr: VecRepr::SliceRef,
-            } => Ok(format!("Vec<{}>", self.generic_ty(element)?)),
-
-            TypeKind::Path {
-                repr: PathRepr::PathBuf,
-            } => Ok(format!("PathBuf")),
-
-            TypeKind::Path {
-                repr: PathRepr::PathRef,
-            } => Ok(format!("&Path")),
-
-            TypeKind::String {
-                repr: StringRepr::String,
-            } => Ok(format!("String")),
-
-            TypeKind::String {
-                repr: StringRepr::StrRef,
-            } => Ok(format!("&str")),
-
-            TypeKind::Option {
-                element,
-                repr: OptionRepr::Option,
-            } => Ok(format!("Option<{}>", self.generic_ty(element)?)),
-
-            TypeKind::Result {
-                ok,
-                err,
-                repr: ResultRepr::Result,
-            } => Ok(format!(
-                "Result<{}, {}>",
-                self.generic_ty(ok)?,
-                self.generic_ty(err)?
-            )),
+        let mut fields = vec![];
+        for field in record.fields() {
+            fields.push((
+                field,
+                self.python_ty(field.ty())?,
+                self.convert(field.ty(), &format!("value.{}", field.name()), Direction::FromPython)?,
+                self.convert(field.ty(), &format!("value.{}", field.rust_name()), Direction::ToPython)?,
+            ));
+        }
 
-            TypeKind::Tuple { .. } => Ok(self.generic_ty(input_ty)?),
+        let wrapper = python_ident(qname);
+        let rust_path = qname.colon_colon();
+        write!(lib_rs, "#[pyo3::pyclass(name = \"{}\", get_all, set_all)]", record.name())?;
+        write!(lib_rs, "#[derive(Clone)]")?;
+        write!(lib_rs, "pub struct {wrapper} {{")?;
+        for (field, ty, _, _) in &fields {
+            write!(lib_rs, "pub {}: {ty},", field.name())?;
+        }
+        write!(lib_rs, "}}")?;
 
-            TypeKind::Scalar(scalar) => Ok(scalar.to_string()),
+        write!(lib_rs, "#[pyo3::pymethods]")?;
+        write!(lib_rs, "impl {wrapper} {{")?;
+        write!(lib_rs, "#[new]")?;
+        let parameters: Vec<_> = fields.iter().map(|(field, ty, _, _)| format!("{}: {ty}", field.name())).collect();
+        write!(lib_rs, "fn new({}) -> Self {{", parameters.join(", "))?;
+        let names: Vec<_> = fields.iter().map(|(field, ..)| field.name().to_string()).collect();
+        write!(lib_rs, "Self {{ {} }}", names.join(", "))?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl From<{rust_path}> for {wrapper} {{")?;
+        write!(lib_rs, "fn from(value: {rust_path}) -> Self {{")?;
+        write!(lib_rs, "Self {{")?;
+        for (field, _, _, to_python) in &fields {
+            write!(lib_rs, "{}: {to_python},", field.name())?;
+        }
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
 
-            TypeKind::Future { .. } => Ok(self.generic_ty(input_ty)?),
+        write!(lib_rs, "impl From<{wrapper}> for {rust_path} {{")?;
+        write!(lib_rs, "fn from(value: {wrapper}) -> Self {{")?;
+        write!(lib_rs, "Self {{")?;
+        for (field, _, from_python, _) in &fields {
+            write!(lib_rs, "{}: {from_python},", field.rust_name())?;
+        }
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
 
-            TypeKind::Error { .. } => Ok(self.generic_ty(input_ty)?),
+        self.classes.push(wrapper);
+        Ok(())
+    }
 
-            TypeKind::UserType { .. } => Ok(self.generic_ty(input_ty)?),
+    fn generate_python_enum(&mut self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, e: &Enum) -> anyhow::Result<()> {
+        if !e.methods().is_empty() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "methods cannot be mapped to Python yet"));
+        }
 
-            _ => anyhow::bail!(
-                "{span}: unsupported type for `{name}`: {ty} (`{ty:?}`)",
-                span = input.span(),
-                name = input.name(),
-                ty = input_ty,
-            ),
+        let wrapper = python_ident(qname);
+        let rust_path = qname.colon_colon();
+        write!(lib_rs, "#[pyo3::pyclass(name = \"{}\", eq, eq_int)]", e.name())?;
+        write!(lib_rs, "#[derive(Clone, Copy, PartialEq)]")?;
+        write!(lib_rs, "pub enum {wrapper} {{")?;
+        for arm in e.arms() {
+            write!(lib_rs, "{},", arm.name())?;
         }
-    }
+        write!(lib_rs, "}}")?;
 
-    fn map_name(&mut self, v: &MapSetRepr) -> anyhow::Result<String> {
-        match v {
-            MapSetRepr::Hash => Ok(format!("HashMap")),
-            MapSetRepr::BTree => Ok(format!("BTreeMap")),
-            MapSetRepr::Index => {
-                self.features.push("indexmap");
-                Ok(format!("IndexMap"))
-            }
-            _ => anyhow::bail!("unknown map representation: `{v:?}`"),
+        // Arms ignored by gluegun have no Python counterpart.
+        write!(lib_rs, "impl From<{rust_path}> for {wrapper} {{")?;
+        write!(lib_rs, "fn from(value: {rust_path}) -> Self {{")?;
+        write!(lib_rs, "#[allow(unreachable_patterns)]")?;
+        write!(lib_rs, "match value {{")?;
+        for arm in e.arms() {
+            write!(lib_rs, "{rust_path}::{name} => Self::{name},", name = arm.name())?;
+        }
+        write!(lib_rs, "_ => panic!(\"enum arm is ignored by gluegun\"),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl From<{wrapper}> for {rust_path} {{")?;
+        write!(lib_rs, "fn from(value: {wrapper}) -> Self {{")?;
+        write!(lib_rs, "match value {{")?;
+        for arm in e.arms() {
+            write!(lib_rs, "{wrapper}::{name} => Self::{name},", name = arm.name())?;
         }
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        self.classes.push(wrapper);
+        Ok(())
     }
 
-    fn set_name(&mut self, v: &MapSetRepr) -> anyhow::Result<String> {
-        match v {
-            MapSetRepr::Hash => Ok(format!("HashSet")),
-            MapSetRepr::BTree => Ok(format!("BTreeSet")),
-            MapSetRepr::Index => {
-                self.features.push("indexmap");
-                Ok(format!("IndexSet"))
+    /// The type of the `#[pyfunction]` parameter for `input`, and the argument passed on to the wrapped function.
+    /// Borrowed inputs (like `&str` or `&[T]`) are received as the corresponding owned type and passed by reference.
+    fn python_parameter(&self, input: &FunctionInput) -> anyhow::Result<(String, String)> {
+        let name = input.name().to_string();
+        match input.refd_ty() {
+            RefdTy::Owned(kind, ty) => {
+                let value = self.convert(ty, &name, Direction::FromPython)?;
+                Ok((self.python_ty(ty)?, kind.rust_from_value(value)))
+            }
+            RefdTy::Ref(kind, ty) if kind.is_mut() => {
+                anyhow::bail!(UnsupportedError::new(ty, "mutable references cannot be passed from Python yet"))
+            }
+            RefdTy::Ref(_, ty) => {
+                let owned_ty = match ty.kind() {
+                    TypeKind::String { .. } => "String".to_string(),
+                    TypeKind::Path { .. } => "std::path::PathBuf".to_string(),
+                    TypeKind::Bytes { .. } => "Vec<u8>".to_string(),
+                    TypeKind::Vec { element, repr: _ } => format!("Vec<{}>", self.python_ty(element)?),
+                    _ => self.python_ty(ty)?,
+                };
+                Ok((owned_ty, format!("&{}", self.convert(ty, &name, Direction::FromPython)?)))
             }
-            _ => anyhow::bail!("unknown set representation: `{v:?}`"),
         }
     }
 
-    /// Convert a type into a Rust type. This is used for type arguments that are generic
-    /// arguments of other types, so they are more limited.
-    ///
-    /// Will only returned owned values.
-    fn generic_ty(&mut self, ty: &Ty) -> anyhow::Result<String> {
+    /// The owned Rust type that PyO3 converts to and from the Python value for `ty`.
+    fn python_ty(&self, ty: &Ty) -> anyhow::Result<String> {
         match ty.kind() {
+            TypeKind::Scalar(scalar) => Ok(scalar.to_string()),
+            TypeKind::String { repr: StringRepr::String } => Ok("String".to_string()),
+            TypeKind::Bytes { .. } => Ok("Vec<u8>".to_string()),
+            TypeKind::Path { .. } => Ok("std::path::PathBuf".to_string()),
+            TypeKind::Vec { element, repr: VecRepr::Vec } => Ok(format!("Vec<{}>", self.python_ty(element)?)),
+            TypeKind::Option { element, repr: OptionRepr::Option } => {
+                Ok(format!("Option<{}>", self.python_ty(element)?))
+            }
             TypeKind::Map { key, value, repr } => Ok(format!(
-                "{}<{}, {}>",
-                self.map_name(repr)?,
-                self.generic_ty(key)?,
-                self.generic_ty(value)?
+                "std::collections::{}<{}, {}>",
+                collection_name(ty, repr, "HashMap", "BTreeMap")?,
+                self.python_ty(key)?,
+                self.python_ty(value)?
             )),
-            TypeKind::Vec { element, repr } => match repr {
-                VecRepr::Vec | VecRepr::SliceRef => Ok(format!("Vec<{}>", self.generic_ty(element)?)),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
-            },
             TypeKind::Set { element, repr } => Ok(format!(
-                "{}<{}>",
-                self.set_name(repr)?,
-                self.generic_ty(element)?,
+                "std::collections::{}<{}>",
+                collection_name(ty, repr, "HashSet", "BTreeSet")?,
+                self.python_ty(element)?
             )),
-            TypeKind::Path { repr } => match repr {
-                gluegun_core::idl::PathRepr::PathBuf => Ok(format!("PathBuf")),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
-            },
-            TypeKind::String { repr } => match repr {
-                StringRepr::String => Ok(format!("String")),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
-            },
-            TypeKind::Option { element, repr } => match repr {
-                OptionRepr::Option => Ok(format!("Option<{}>", self.generic_ty(element)?)),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
-            },
-            TypeKind::Result { ok, err, repr } => match repr {
-                ResultRepr::Result => Ok(format!(
-                    "Result<{}, {}>",
-                    self.generic_ty(ok)?,
-                    self.generic_ty(err)?
-                )),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
+            TypeKind::UserType { qname } => match self.idl.definitions().get(qname) {
+                Some(Item::Record(_) | Item::Enum(_)) => Ok(python_ident(qname)),
+                _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to Python yet")),
             },
-            TypeKind::Tuple { elements, repr } => match repr {
-                TupleRepr::Tuple(_) => Ok(format!(
-                    "({})",
-                    elements
-                        .iter()
-                        .map(|e| self.generic_ty(e))
-                        .collect::<anyhow::Result<Vec<_>>>()?
-                        .join(", ")
-                )),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
-            },
-            TypeKind::Scalar(scalar) => Ok(scalar.to_string()),
-            TypeKind::Future { output, repr } => match repr {
-                FutureRepr::PinBoxDynFuture(_auto_traits) => Ok(format!(
-                    "Pin<Box<dyn Future<Output = {}>>>",
-                    self.generic_ty(output)?
-                )),
-                _ => anyhow::bail!("unsupported: {repr:?}"),
-            },
-            TypeKind::Error { repr } => anyhow::bail!("unsupported: {repr:?}"),
-            TypeKind::UserType { qname } => Ok(format!("{}", qname.dotted())),
-            _ => todo!(),
+            _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to Python yet")),
         }
     }
+
+    /// An expression converting `expr` of type `ty` in the given `direction`:
+    /// records and enums are converted to and from their wrappers, including within collections.
+    fn convert(&self, ty: &Ty, expr: &str, direction: Direction) -> anyhow::Result<String> {
+        let convert_elements = |element: &Ty, collection: &str| -> anyhow::Result<String> {
+            let element_expr = self.convert(element, "e", direction)?;
+            Ok(if element_expr == "e" {
+                expr.to_string()
+            } else {
+                format!("{expr}.into_iter().map(|e| {element_expr}).collect::<{collection}<_>>()")
+            })
+        };
+
+        match ty.kind() {
+            TypeKind::Vec { element, repr: _ } => convert_elements(element, "Vec"),
+            TypeKind::Set { element, repr } => {
+                convert_elements(element, &format!("std::collections::{}", collection_name(ty, repr, "HashSet", "BTreeSet")?))
+            }
+            TypeKind::Option { element, repr: _ } => {
+                let element_expr = self.convert(element, "e", direction)?;
+                Ok(if element_expr == "e" { expr.to_string() } else { format!("{expr}.map(|e| {element_expr})") })
+            }
+            TypeKind::Map { key, value, repr } => {
+                let key_expr = self.convert(key, "k", direction)?;
+                let value_expr = self.convert(value, "v", direction)?;
+                Ok(if key_expr == "k" && value_expr == "v" {
+                    expr.to_string()
+                } else {
+                    format!(
+                        "{expr}.into_iter().map(|(k, v)| ({key_expr}, {value_expr})).collect::<std::collections::{}<_, _>>()",
+                        collection_name(ty, repr, "HashMap", "BTreeMap")?
+                    )
+                })
+            }
+            TypeKind::UserType { qname } => Ok(match direction {
+                Direction::FromPython => format!("{}::from({expr})", qname.colon_colon()),
+                Direction::ToPython => format!("{}::from({expr})", python_ident(qname)),
+            }),
+            _ => Ok(expr.to_string()),
+        }
+    }
+}
+
+/// The name of the wrapper generated for the item `qname`, unique across modules.
+fn python_ident(qname: &QualifiedName) -> String {
+    qname.to_string("_")
+}
+
+/// The name of the map or set type for `repr`; `hash` or `btree`.
+fn collection_name(ty: &Ty, repr: &MapSetRepr, hash: &'static str, btree: &'static str) -> anyhow::Result<&'static str> {
+    match repr {
+        MapSetRepr::Hash => Ok(hash),
+        MapSetRepr::BTree => Ok(btree),
+        _ => anyhow::bail!(UnsupportedError::new(ty, "this collection cannot be mapped to Python yet")),
+    }
 }
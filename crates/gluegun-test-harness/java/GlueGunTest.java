@@ -0,0 +1,10 @@
+/** Support for the Java tests of the demos, compiled along with them by the test harness. */
+public class GlueGunTest {
+    /**
+     * Load the native library `name` from `dir`, the directory holding the libraries built by cargo
+     * (which the harness passes to each test as its first argument).
+     */
+    public static void loadLibrary(String dir, String name) {
+        System.load(new java.io.File(dir, System.mapLibraryName(name)).getAbsolutePath());
+    }
+}
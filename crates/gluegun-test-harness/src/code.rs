@@ -0,0 +1,12 @@
+/// True if the generated `code` contains `expected`, ignoring whitespace and trailing commas,
+/// so that checks do not depend on how (or whether) `rustfmt` laid the code out.
+pub fn contains_code(code: &str, expected: &str) -> bool {
+    // A trailing comma in `expected` may have been dropped before a closing bracket in `code`.
+    let expected = normalize_code(expected);
+    normalize_code(code).contains(expected.strip_suffix(',').unwrap_or(&expected))
+}
+
+fn normalize_code(code: &str) -> String {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    code.replace(",)", ")").replace(",}", "}").replace(",]", "]")
+}
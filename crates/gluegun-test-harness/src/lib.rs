@@ -6,4 +6,7 @@ mod test_definition;
 pub use test_definition::Test;
 
 mod idl_test;
-pub use idl_test::idl_tests;
\ No newline at end of file
+pub use idl_test::idl_tests;
+
+mod code;
+pub use code::contains_code;
@@ -5,6 +5,13 @@ use camino::Utf8PathBuf;
 use cp_r::CopyOptions;
 use temp_dir::TempDir;
 
+use crate::contains_code;
+
+/// Where [`Test::run_java_test`][] writes the `GlueGunTest` support class, relative to the test directory.
+const JAVA_SUPPORT_PATH: &str = "gluegun_test/GlueGunTest.java";
+
+const JAVA_SUPPORT: &str = include_str!("../java/GlueGunTest.java");
+
 pub struct Test {
     test_crate: Arc<String>,
     source_directory: Utf8PathBuf,
@@ -20,12 +27,39 @@ pub enum TestAction {
     /// Invoke cargo-gluegun with the given `$OPTIONS`
     CargoGluegun { options: Vec<String> },
 
+    /// Run `$PROGRAM $ARGS` in the test directory
+    Run { program: String, args: Vec<String> },
+
     /// Find the given text and replace it
     Replace {
         path: Utf8PathBuf,
         find: String,
         replace: String,
     },
+
+    /// Read the file at `path` and check its contents
+    CheckFile {
+        path: Utf8PathBuf,
+        check: fn(&str) -> anyhow::Result<()>,
+    },
+
+    /// Check that the file at `path` contains each of `expected` (see [`Test::check_file_contains`][])
+    CheckFileContains {
+        path: Utf8PathBuf,
+        expected: Vec<String>,
+    },
+
+    /// Write `contents` to the file at `path`, creating its directory if needed
+    WriteFile {
+        path: Utf8PathBuf,
+        contents: &'static str,
+    },
+
+    /// List the files in the directory at `path` (sorted by name) and check them
+    CheckDir {
+        path: Utf8PathBuf,
+        check: fn(&[String]) -> anyhow::Result<()>,
+    },
 }
 
 impl Test {
@@ -85,6 +119,15 @@ impl Test {
         self
     }
 
+    /// Add a step to run `program` with `args` in the test directory; the test fails if it exits unsuccessfully
+    pub fn run(mut self, program: impl ToString, args: impl IntoIterator<Item: ToString>) -> Self {
+        self.actions.push(TestAction::Run {
+            program: program.to_string(),
+            args: args.into_iter().map(|a| a.to_string()).collect(),
+        });
+        self
+    }
+
     pub fn replace(
         mut self,
         path: impl Into<Utf8PathBuf>,
@@ -99,6 +142,49 @@ impl Test {
         self
     }
 
+    /// Add a step to check the contents of the file at `path` (relative to the test directory)
+    pub fn check_file(mut self, path: impl Into<Utf8PathBuf>, check: fn(&str) -> anyhow::Result<()>) -> Self {
+        self.actions.push(TestAction::CheckFile {
+            path: path.into(),
+            check,
+        });
+        self
+    }
+
+    /// Add a step to check that the file at `path` (relative to the test directory) contains each of `expected`.
+    /// Rust files are compared with [`contains_code`][], so the check does not depend on how they were formatted.
+    pub fn check_file_contains(mut self, path: impl Into<Utf8PathBuf>, expected: &[&str]) -> Self {
+        self.actions.push(TestAction::CheckFileContains {
+            path: path.into(),
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Add steps to compile the Java `sources` along with the `GlueGunTest` support class and run the `main` of `class`.
+    /// The test gets the directory of the native libraries built by cargo as its argument, to pass to `GlueGunTest.loadLibrary`.
+    pub fn run_java_test(mut self, class: impl ToString, sources: impl IntoIterator<Item: ToString>) -> Self {
+        self.actions.push(TestAction::WriteFile {
+            path: JAVA_SUPPORT_PATH.into(),
+            contents: JAVA_SUPPORT,
+        });
+        let javac_args = ["-d", "classes", JAVA_SUPPORT_PATH]
+            .into_iter()
+            .map(|a| a.to_string())
+            .chain(sources.into_iter().map(|s| s.to_string()));
+        self.run("javac", javac_args)
+            .run("java", ["-cp".to_string(), "classes".to_string(), class.to_string(), "target/debug".to_string()])
+    }
+
+    /// Add a step to check the names of the files in the directory at `path` (relative to the test directory)
+    pub fn check_dir(mut self, path: impl Into<Utf8PathBuf>, check: fn(&[String]) -> anyhow::Result<()>) -> Self {
+        self.actions.push(TestAction::CheckDir {
+            path: path.into(),
+            check,
+        });
+        self
+    }
+
     /// Execute the test from the given directory
     pub fn execute(self) -> anyhow::Result<()> {
         TestExecutor::new(self)?.execute()?;
@@ -176,12 +262,51 @@ impl TestExecutor {
         match action {
             TestAction::Cargo { options } => self.cargo_action(options),
 
+            TestAction::Run { program, args } => self.run_action(program, args),
+
             TestAction::Replace {
                 path,
                 find,
                 replace,
             } => self.replace_action(path, find, replace),
 
+            TestAction::CheckFile { path, check } => {
+                let content = std::fs::read_to_string(self.temp_dir.join(path))?;
+                check(&content)
+            }
+
+            TestAction::CheckFileContains { path, expected } => {
+                let content = std::fs::read_to_string(self.temp_dir.join(path))?;
+                let is_rust = path.extension() == Some("rs");
+                for expected in expected {
+                    let found = if is_rust {
+                        contains_code(&content, expected)
+                    } else {
+                        content.contains(expected.as_str())
+                    };
+                    anyhow::ensure!(found, "`{path}` is missing `{expected}`:\n{content}");
+                }
+                Ok(())
+            }
+
+            TestAction::WriteFile { path, contents } => {
+                let file_path = self.temp_dir.join(path);
+                if let Some(dir) = file_path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(file_path, contents)?;
+                Ok(())
+            }
+
+            TestAction::CheckDir { path, check } => {
+                let mut names = vec![];
+                for entry in std::fs::read_dir(self.temp_dir.join(path))? {
+                    names.push(entry?.file_name().to_string_lossy().into_owned());
+                }
+                names.sort();
+                check(&names)
+            }
+
             TestAction::CargoGluegun { options } => cargo_gluegun::Builder::new(
                 &self.temp_dir,
                 Some("cargo-gluegun")
@@ -201,7 +326,8 @@ impl TestExecutor {
                     .arg("--");
                 Ok(c)
             })
-            .execute(),
+            .execute()
+            .map(drop),
         }
     }
 
@@ -216,6 +342,18 @@ impl TestExecutor {
         Ok(())
     }
 
+    fn run_action(&self, program: &str, args: &[String]) -> anyhow::Result<()> {
+        let status = Command::new(program)
+            .current_dir(&self.temp_dir)
+            .args(args)
+            .status()
+            .with_context(|| format!("running `{program}`"))?;
+        if !status.success() {
+            anyhow::bail!("`{program}` failed with {status}");
+        }
+        Ok(())
+    }
+
     fn replace_action(&self, path: &Utf8PathBuf, find: &str, replace: &str) -> anyhow::Result<()> {
         let file_path = self.temp_dir.join(path);
 
@@ -226,7 +364,7 @@ impl TestExecutor {
         }
 
         let content = content.replace(find, replace);
-        std::fs::write(path, content)?;
+        std::fs::write(file_path, content)?;
         Ok(())
     }
 }
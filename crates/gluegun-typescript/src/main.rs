@@ -0,0 +1,51 @@
+use anyhow::Context;
+use gluegun_core::{
+    cli::{GenerateCx, GlueGunHelper},
+    codegen::LibraryCrate,
+};
+
+mod rs_gen;
+mod ts_gen;
+mod util;
+
+pub fn main() -> anyhow::Result<()> {
+    gluegun_core::cli::run(GlueGunTypeScript)
+}
+
+struct GlueGunTypeScript;
+
+impl GlueGunHelper for GlueGunTypeScript {
+    type Metadata = ();
+
+    fn name(&self) -> String {
+        "typescript".to_string()
+    }
+
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "TypeScript".to_string()
+    }
+
+    fn generate(self, cx: &mut GenerateCx, _metadata: &(), output: &mut LibraryCrate) -> anyhow::Result<()> {
+        cx.reject_generics(&self.language())?;
+
+        output.add_dependency("wasm-bindgen").version("0.2");
+        output.add_dependency("js-sys").version("0.3");
+
+        let ts_src_dir = output
+            .add_dir("ts_src")
+            .context("adding `ts_src` dir")?;
+        ts_gen::TypeScriptCodeGenerator::new(cx.idl())
+            .generate(ts_src_dir)
+            .context("generating TypeScript declarations")?;
+
+        rs_gen::RustCodeGenerator::new(cx.idl())
+            .generate(output)
+            .context("generating Rust sources")?;
+
+        Ok(())
+    }
+}
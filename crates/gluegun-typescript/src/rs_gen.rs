@@ -0,0 +1,269 @@
+use gluegun_core::{
+    cli::{for_each_supported, UnsupportedError},
+    codegen::{CodeWriter, LibraryCrate},
+    idl::{Enum, Function, FunctionInput, Idl, Item, OwnedKind, QualifiedName, Record, RefdTy, Ty, TypeKind},
+};
+
+use crate::util;
+
+/// Generates the wasm-bindgen crate exporting each function of the crate to JavaScript.
+///
+/// Scalars and strings are passed by wasm-bindgen itself. Other values are passed as a `JsValue`
+/// (records as plain objects, enums as the name of their arm, vectors as arrays, and `None` as `undefined`)
+/// and converted with the `FromJs` and `IntoJs` traits of the generated `gluegun_runtime` module.
+pub(crate) struct RustCodeGenerator<'idl> {
+    idl: &'idl Idl,
+}
+
+impl<'idl> RustCodeGenerator<'idl> {
+    pub(crate) fn new(idl: &'idl Idl) -> Self {
+        Self { idl }
+    }
+
+    pub(crate) fn generate(self, lib: &mut LibraryCrate) -> anyhow::Result<()> {
+        let mut lib_rs = lib.add_file("src/lib.rs")?;
+
+        write!(lib_rs, "#![allow(non_snake_case)]")?; // exported functions are named after their path, like `free_functions__text__shout`
+        write!(lib_rs, "use wasm_bindgen::prelude::*;")?;
+        write!(lib_rs, "use gluegun_runtime::{{FromJs, IntoJs}};")?;
+
+        self.generate_runtime(&mut lib_rs)?;
+
+        for_each_supported(self.idl.definitions(), |(qname, item)| match item {
+            Item::Record(record) => self.generate_record(&mut lib_rs, qname, record),
+            Item::Enum(e) => self.generate_enum(&mut lib_rs, qname, e),
+            Item::Function(function) => self.generate_function(&mut lib_rs, qname, function),
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to TypeScript yet")),
+        })
+    }
+
+    /// Generate the `gluegun_runtime` module with the conversions of values passed as a `JsValue`.
+    /// A value of the wrong type is reported to JavaScript as an exception.
+    fn generate_runtime(&self, lib_rs: &mut CodeWriter<'_>) -> anyhow::Result<()> {
+        write!(lib_rs, "mod gluegun_runtime {{")?;
+        write!(lib_rs, "#![allow(dead_code)]")?; // not every crate needs every conversion
+        write!(lib_rs, "use wasm_bindgen::{{throw_str, JsValue}};")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "pub(crate) trait FromJs: Sized {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self;")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "pub(crate) trait IntoJs {{")?;
+        write!(lib_rs, "fn into_js(self) -> JsValue;")?;
+        write!(lib_rs, "}}")?;
+
+        for ty in ["i8", "i16", "i32", "u8", "u16", "u32", "f32", "f64"] {
+            write!(lib_rs, "impl FromJs for {ty} {{")?;
+            write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+            write!(lib_rs, "match value.as_f64() {{ Some(n) => n as {ty}, None => throw_str(\"expected a number\") }}")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+        }
+        for ty in ["i64", "u64"] {
+            write!(lib_rs, "impl FromJs for {ty} {{")?;
+            write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+            write!(lib_rs, "{ty}::try_from(value).unwrap_or_else(|_| throw_str(\"expected a bigint\"))")?;
+            write!(lib_rs, "}}")?;
+            write!(lib_rs, "}}")?;
+        }
+        for ty in ["bool", "i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "f32", "f64", "String"] {
+            write!(lib_rs, "impl IntoJs for {ty} {{")?;
+            write!(lib_rs, "fn into_js(self) -> JsValue {{ JsValue::from(self) }}")?;
+            write!(lib_rs, "}}")?;
+        }
+
+        write!(lib_rs, "impl FromJs for bool {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "value.as_bool().unwrap_or_else(|| throw_str(\"expected a boolean\"))")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "impl FromJs for String {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "value.as_string().unwrap_or_else(|| throw_str(\"expected a string\"))")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "impl FromJs for char {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "let string = String::from_js(value);")?;
+        write!(lib_rs, "let mut chars = string.chars();")?;
+        write!(lib_rs, "match (chars.next(), chars.next()) {{ (Some(c), None) => c, _ => throw_str(\"expected a single character\") }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "impl IntoJs for char {{")?;
+        write!(lib_rs, "fn into_js(self) -> JsValue {{ JsValue::from(self.to_string()) }}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl<T: FromJs> FromJs for Vec<T> {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "if !js_sys::Array::is_array(&value) {{ throw_str(\"expected an array\") }}")?;
+        write!(lib_rs, "js_sys::Array::from(&value).iter().map(T::from_js).collect()")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "impl<T: IntoJs> IntoJs for Vec<T> {{")?;
+        write!(lib_rs, "fn into_js(self) -> JsValue {{")?;
+        write!(lib_rs, "self.into_iter().map(T::into_js).collect::<js_sys::Array>().into()")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl<T: FromJs> FromJs for Option<T> {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "if value.is_undefined() || value.is_null() {{ None }} else {{ Some(T::from_js(value)) }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "impl<T: IntoJs> IntoJs for Option<T> {{")?;
+        write!(lib_rs, "fn into_js(self) -> JsValue {{")?;
+        write!(lib_rs, "match self {{ Some(value) => value.into_js(), None => JsValue::UNDEFINED }}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "")?;
+        write!(lib_rs, "/// The value of the property `name` of the object `value`.")?;
+        write!(lib_rs, "pub(crate) fn get(value: &JsValue, name: &str) -> JsValue {{")?;
+        write!(lib_rs, "if !value.is_object() {{ throw_str(\"expected an object\") }}")?;
+        write!(lib_rs, "js_sys::Reflect::get(value, &JsValue::from_str(name)).unwrap_or_else(|err| wasm_bindgen::throw_val(err))")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "")?;
+        write!(lib_rs, "/// Set the property `name` of the plain object `object`, which cannot fail.")?;
+        write!(lib_rs, "pub(crate) fn set(object: &js_sys::Object, name: &str, value: JsValue) {{")?;
+        write!(lib_rs, "js_sys::Reflect::set(object, &JsValue::from_str(name), &value).unwrap();")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    /// Convert a record from and to a plain object with a property for each field.
+    fn generate_record(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, record: &Record) -> anyhow::Result<()> {
+        let rust_path = qname.colon_colon();
+
+        write!(lib_rs, "impl FromJs for {rust_path} {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "Self {{")?;
+        for field in record.fields() {
+            write!(
+                lib_rs,
+                "{}: FromJs::from_js(gluegun_runtime::get(&value, \"{}\")),",
+                field.rust_name(),
+                field.name()
+            )?;
+        }
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        write!(lib_rs, "impl IntoJs for {rust_path} {{")?;
+        write!(lib_rs, "fn into_js(self) -> JsValue {{")?;
+        write!(lib_rs, "let object = js_sys::Object::new();")?;
+        for field in record.fields() {
+            write!(
+                lib_rs,
+                "gluegun_runtime::set(&object, \"{}\", self.{}.into_js());",
+                field.name(),
+                field.rust_name()
+            )?;
+        }
+        write!(lib_rs, "object.into()")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    /// Convert an enum from and to the name of its arm, as in its TypeScript union.
+    fn generate_enum(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, e: &Enum) -> anyhow::Result<()> {
+        let rust_path = qname.colon_colon();
+        let ts_name = util::ts_type_name(qname);
+
+        write!(lib_rs, "impl FromJs for {rust_path} {{")?;
+        write!(lib_rs, "fn from_js(value: JsValue) -> Self {{")?;
+        write!(lib_rs, "match value.as_string().as_deref() {{")?;
+        for arm in e.arms() {
+            write!(lib_rs, "Some(\"{name}\") => Self::{name},", name = arm.name())?;
+        }
+        write!(lib_rs, "_ => wasm_bindgen::throw_str(\"expected a `{ts_name}`\"),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+
+        // Arms ignored by gluegun have no TypeScript counterpart.
+        write!(lib_rs, "impl IntoJs for {rust_path} {{")?;
+        write!(lib_rs, "fn into_js(self) -> JsValue {{")?;
+        write!(lib_rs, "#[allow(unreachable_patterns)]")?;
+        write!(lib_rs, "match self {{")?;
+        for arm in e.arms() {
+            write!(lib_rs, "Self::{name} => JsValue::from_str(\"{name}\"),", name = arm.name())?;
+        }
+        write!(lib_rs, "_ => wasm_bindgen::throw_str(\"enum arm is ignored by gluegun\"),")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    fn generate_function(&self, lib_rs: &mut CodeWriter<'_>, qname: &QualifiedName, function: &Function) -> anyhow::Result<()> {
+        let signature = function.signature();
+
+        let mut params = vec![];
+        let mut conversions = vec![];
+        let mut args = vec![];
+        for input in signature.inputs() {
+            let (param, conversion, arg) = self.parameter(input)?;
+            params.push(param);
+            conversions.extend(conversion);
+            args.push(arg);
+        }
+
+        // A value returned behind a smart pointer is taken out of it first.
+        let main_ty = signature.output_ty().main_ty();
+        let (open, close) = main_ty.owned_kind().map_or(("", ""), OwnedKind::rust_into_value);
+        let call = format!("{open}{}({}){close}", qname.colon_colon(), args.join(", "));
+        let (output, body) = match main_ty {
+            RefdTy::Owned(_, ty) if ty.is_unit() => (String::new(), format!("{call};")),
+            RefdTy::Owned(_, ty) if util::is_native(ty) => (format!(" -> {}", self.rust_ty(ty)?), call),
+            RefdTy::Owned(..) => (" -> JsValue".to_string(), format!("{call}.into_js()")),
+            RefdTy::Ref(_, ty) => anyhow::bail!(UnsupportedError::new(ty, "references cannot be returned to TypeScript yet")),
+        };
+
+        write!(lib_rs, "#[wasm_bindgen(js_name = \"{}\")]", util::js_function_name(qname))?;
+        write!(lib_rs, "pub fn {}({}){output} {{", qname.to_string("__"), params.join(", "))?;
+        for conversion in conversions {
+            write!(lib_rs, "{conversion}")?;
+        }
+        write!(lib_rs, "{body}")?;
+        write!(lib_rs, "}}")?;
+        Ok(())
+    }
+
+    /// The parameter of the exported function for `input`, the statement converting it (if any),
+    /// and the argument passed to the Rust function.
+    fn parameter(&self, input: &FunctionInput) -> anyhow::Result<(String, Option<String>, String)> {
+        let name = input.name();
+        let (ty, arg) = match input.refd_ty() {
+            RefdTy::Owned(kind, ty) => (ty, kind.rust_from_value(name)),
+            RefdTy::Ref(kind, ty) if kind.is_mut() => {
+                anyhow::bail!(UnsupportedError::new(ty, "mutable references cannot be passed from TypeScript yet"))
+            }
+            RefdTy::Ref(_, ty) => (ty, format!("&{name}")),
+        };
+        let rust_ty = self.rust_ty(ty)?;
+        if util::is_native(ty) {
+            Ok((format!("{name}: {rust_ty}"), None, arg))
+        } else {
+            Ok((
+                format!("{name}: JsValue"),
+                Some(format!("let {name} = <{rust_ty}>::from_js({name});")),
+                arg,
+            ))
+        }
+    }
+
+    /// The owned Rust type that values of `ty` are converted to, like `String` for `&str`.
+    fn rust_ty(&self, ty: &Ty) -> anyhow::Result<String> {
+        match ty.kind() {
+            TypeKind::Scalar(scalar) => Ok(scalar.to_string()),
+            TypeKind::String { .. } => Ok("String".to_string()),
+            TypeKind::Vec { element, .. } => Ok(format!("Vec<{}>", self.rust_ty(element)?)),
+            TypeKind::Option { element, .. } => Ok(format!("Option<{}>", self.rust_ty(element)?)),
+            TypeKind::UserType { qname } => Ok(qname.colon_colon()),
+            _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to TypeScript yet")),
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use gluegun_core::{
+    cli::{for_each_supported, UnsupportedError},
+    codegen::{CodeWriter, DirBuilder},
+    idl::{Enum, Function, Idl, IsAsync, Item, QualifiedName, Record, RefdTy},
+};
+
+use crate::util;
+
+/// Generates the TypeScript declarations (`<crate>.d.ts`) for the functions exported by the generated crate:
+/// records become interfaces, enums become unions of their arm names, and functions are declared with their types.
+pub(crate) struct TypeScriptCodeGenerator<'idl> {
+    idl: &'idl Idl,
+}
+
+impl<'idl> TypeScriptCodeGenerator<'idl> {
+    pub(crate) fn new(idl: &'idl Idl) -> Self {
+        Self { idl }
+    }
+
+    pub(crate) fn generate(self, mut dir: DirBuilder<'_>) -> anyhow::Result<()> {
+        let mut d_ts = dir.add_file(format!("{}.d.ts", self.idl.crate_name()))?;
+
+        for_each_supported(self.idl.definitions(), |(qname, item)| match item {
+            Item::Record(record) => self.generate_interface(&mut d_ts, qname, record),
+            Item::Enum(e) => self.generate_union(&mut d_ts, qname, e),
+            Item::Function(function) => self.generate_function(&mut d_ts, qname, function),
+            _ => anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "this kind of item cannot be mapped to TypeScript yet")),
+        })
+    }
+
+    fn generate_interface(&self, d_ts: &mut CodeWriter<'_>, qname: &QualifiedName, record: &Record) -> anyhow::Result<()> {
+        if !record.methods().is_empty() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "methods cannot be mapped to TypeScript yet"));
+        }
+        if !record.is_constructible() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "records with private fields or `#[non_exhaustive]` cannot be mapped to TypeScript yet"));
+        }
+
+        let mut fields = vec![];
+        for field in record.fields() {
+            fields.push(format!("{}: {};", field.name(), util::ts_ty(self.idl, field.ty())?));
+        }
+
+        write!(d_ts, "export interface {} {{", util::ts_type_name(qname))?;
+        for field in fields {
+            write!(d_ts, "{field}")?;
+        }
+        write!(d_ts, "}}")?;
+        Ok(())
+    }
+
+    fn generate_union(&self, d_ts: &mut CodeWriter<'_>, qname: &QualifiedName, e: &Enum) -> anyhow::Result<()> {
+        if !e.methods().is_empty() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "methods cannot be mapped to TypeScript yet"));
+        }
+
+        let arms = e.arms().iter().map(|arm| format!("\"{}\"", arm.name())).collect::<Vec<_>>();
+        write!(d_ts, "export type {} = {};", util::ts_type_name(qname), arms.join(" | "))?;
+        Ok(())
+    }
+
+    fn generate_function(&self, d_ts: &mut CodeWriter<'_>, qname: &QualifiedName, function: &Function) -> anyhow::Result<()> {
+        let signature = function.signature();
+        if *signature.is_async() == IsAsync::Yes {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "async functions cannot be mapped to TypeScript yet"));
+        }
+        if signature.output_ty().error_ty().is_some() {
+            anyhow::bail!(UnsupportedError::new(qname.colon_colon(), "`Result` cannot be returned to TypeScript yet"));
+        }
+
+        let mut params = vec![];
+        for input in signature.inputs() {
+            params.push(format!("{}: {}", input.name(), util::ts_ty(self.idl, input.refd_ty().ty())?));
+        }
+        let output = match signature.output_ty().main_ty() {
+            RefdTy::Owned(_, ty) if ty.is_unit() => "void".to_string(),
+            RefdTy::Owned(_, ty) => util::ts_ty(self.idl, ty)?,
+            RefdTy::Ref(_, ty) => anyhow::bail!(UnsupportedError::new(ty, "references cannot be returned to TypeScript yet")),
+        };
+
+        write!(
+            d_ts,
+            "export function {}({}): {output};",
+            util::js_function_name(qname),
+            params.join(", ")
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use gluegun_core::{
+    cli::UnsupportedError,
+    idl::{Idl, Item, OptionRepr, QualifiedName, Scalar, StringRepr, Ty, TypeKind, VecRepr},
+};
+
+/// The TypeScript name of the record or enum `qname`: its Rust name, like `Point` for `plain_records::Point`.
+pub(crate) fn ts_type_name(qname: &QualifiedName) -> String {
+    qname.tail_name().to_string()
+}
+
+/// The name under which the function `qname` is exported to JavaScript: its path within the crate joined by `_`,
+/// like `greet` for `hello_world::greet` and `text_shout` for `free_functions::text::shout`,
+/// as wasm-bindgen exports every function from the one module.
+pub(crate) fn js_function_name(qname: &QualifiedName) -> String {
+    qname.names()[1..]
+        .iter()
+        .map(|name| name.text().as_str())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// The TypeScript type for values of `ty`.
+pub(crate) fn ts_ty(idl: &Idl, ty: &Ty) -> anyhow::Result<String> {
+    match ty.kind() {
+        TypeKind::Scalar(scalar) => match ts_scalar_ty(scalar) {
+            Some(ts_ty) => Ok(ts_ty.to_string()),
+            None => anyhow::bail!(UnsupportedError::new(ty, "this scalar cannot be mapped to TypeScript yet")),
+        },
+        TypeKind::String { repr: StringRepr::String | StringRepr::StrRef } => Ok("string".to_string()),
+        TypeKind::Vec { element, repr: VecRepr::Vec | VecRepr::SliceRef } => {
+            let element = ts_ty(idl, element)?;
+            if element.contains(' ') {
+                Ok(format!("({element})[]"))
+            } else {
+                Ok(format!("{element}[]"))
+            }
+        }
+        TypeKind::Option { element, repr: OptionRepr::Option } => Ok(format!("{} | undefined", ts_ty(idl, element)?)),
+        TypeKind::UserType { qname } => match idl.definitions().get(qname) {
+            Some(Item::Record(_) | Item::Enum(_)) => Ok(ts_type_name(qname)),
+            _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to TypeScript yet")),
+        },
+        _ => anyhow::bail!(UnsupportedError::new(ty, "this type cannot be mapped to TypeScript yet")),
+    }
+}
+
+/// Whether wasm-bindgen passes values of `ty` itself; other values are passed as a `JsValue`
+/// and converted by the generated crate (see `rs_gen`).
+pub(crate) fn is_native(ty: &Ty) -> bool {
+    match ty.kind() {
+        TypeKind::Scalar(scalar) => ts_scalar_ty(scalar).is_some(),
+        TypeKind::String { .. } => true,
+        _ => false,
+    }
+}
+
+/// The TypeScript type of a scalar as passed by wasm-bindgen: 64-bit integers are `bigint`s, as not all fit in a `number`.
+fn ts_scalar_ty(scalar: &Scalar) -> Option<&'static str> {
+    match scalar {
+        Scalar::Boolean => Some("boolean"),
+        Scalar::Char => Some("string"),
+        Scalar::I8 | Scalar::I16 | Scalar::I32 | Scalar::U8 | Scalar::U16 | Scalar::U32 => Some("number"),
+        Scalar::F32 | Scalar::F64 => Some("number"),
+        Scalar::I64 | Scalar::U64 => Some("bigint"),
+        _ => None,
+    }
+}
@@ -19,6 +19,14 @@ impl GlueGunHelper for GlueGunWasm {
         format!("wasm")
     }
 
+    fn version(&self) -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+
+    fn language(&self) -> String {
+        "WebAssembly".to_string()
+    }
+
     fn generate(
         self,
         cx: &mut GenerateCx,
@@ -0,0 +1,32 @@
+import asyncFunctions.Functions;
+
+import java.util.concurrent.CompletableFuture;
+import java.util.concurrent.ExecutionException;
+
+public class AsyncFunctionsTest {
+    static void expect(Object actual, Object expected) {
+        if (!actual.equals(expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        GlueGunTest.loadLibrary(args[0], "async_functions_java");
+
+        CompletableFuture<String> first = Functions.fetch("a");
+        CompletableFuture<String> second = Functions.fetch("b");
+        expect(first.get(), "contents of a");
+        expect(second.get(), "contents of b");
+
+        CompletableFuture<Void> ping = Functions.ping();
+        ping.get();
+
+        Functions.check("a").get();
+        try {
+            Functions.check("").get();
+            throw new AssertionError("`check` did not fail");
+        } catch (ExecutionException e) {
+            expect(e.getCause().getMessage(), "no url given");
+        }
+    }
+}
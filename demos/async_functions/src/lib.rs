@@ -0,0 +1,12 @@
+pub async fn fetch(url: String) -> String {
+    format!("contents of {url}")
+}
+
+pub async fn ping() {}
+
+pub async fn check(url: String) -> Result<(), String> {
+    if url.is_empty() {
+        return Err("no url given".to_string());
+    }
+    Ok(())
+}
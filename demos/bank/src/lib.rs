@@ -0,0 +1,33 @@
+/// A bank account holding a balance in cents.
+pub struct Account {
+    balance: i64,
+    memo: Vec<u8>,
+}
+
+impl Account {
+    /// The largest amount, in cents, that can be withdrawn at once.
+    pub const MAX_WITHDRAWAL: i64 = 100_000;
+    pub const CURRENCY: &'static str = "EUR";
+
+    pub fn new(balance: i64) -> Self {
+        Self { balance, memo: Vec::new() }
+    }
+
+    /// Binary memo attached to the account.
+    pub fn memo(&self) -> Vec<u8> {
+        self.memo.clone()
+    }
+
+    pub fn set_memo(&mut self, memo: &[u8]) {
+        self.memo = memo.to_vec();
+    }
+
+    /// Withdraw `amount` cents, returning the remaining balance.
+    pub fn withdraw(&mut self, amount: i64) -> Result<i64, String> {
+        if amount > self.balance {
+            return Err(format!("insufficient funds: the balance is {}", self.balance));
+        }
+        self.balance -= amount;
+        Ok(self.balance)
+    }
+}
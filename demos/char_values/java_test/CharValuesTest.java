@@ -0,0 +1,30 @@
+import charValues.Functions;
+
+public class CharValuesTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "char_values_java");
+
+        // A `char` is passed as its code point.
+        expect(Functions.to_upper('\u00e9'), (int) '\u00c9'); // e-acute to E-acute
+        expect(Functions.to_upper('a'), (int) 'A');
+
+        // Code points outside the Basic Multilingual Plane do not fit in a Java `char`.
+        String crab = "\ud83e\udd80"; // U+1F980, a crab
+        expect(Functions.first(crab), crab.codePointAt(0));
+        expect(Functions.first(""), null);
+        expect(Functions.repeat(crab.codePointAt(0), 2), crab + crab);
+
+        // Surrogates are not Unicode scalar values, so they are rejected.
+        try {
+            Functions.to_upper(0xD800);
+            throw new AssertionError("a surrogate was not rejected");
+        } catch (IllegalArgumentException e) {
+        }
+    }
+}
@@ -0,0 +1,11 @@
+pub fn to_upper(c: char) -> char {
+    c.to_uppercase().next().unwrap_or(c)
+}
+
+pub fn first(s: &str) -> Option<char> {
+    s.chars().next()
+}
+
+pub fn repeat(c: char, count: u32) -> String {
+    std::iter::repeat_n(c, count as usize).collect()
+}
@@ -0,0 +1,19 @@
+import checkedConversions.Functions;
+
+public class CheckedConversionsTest {
+    static void expectRejected(String what, Runnable call) {
+        try {
+            call.run();
+        } catch (IllegalArgumentException e) {
+            return;
+        }
+        throw new AssertionError(what + " was not rejected");
+    }
+
+    public static void main(String[] args) {
+        expectRejected("`u32` above its range", () -> Functions.next_id(4294967296L));
+        expectRejected("negative `u32`", () -> Functions.next_id(-1));
+        expectRejected("`u8` above its range", () -> Functions.scale((short) 256, 1));
+        expectRejected("negative `u64`", () -> Functions.scale((short) 1, -1));
+    }
+}
@@ -0,0 +1,7 @@
+pub fn next_id(id: u32) -> u32 {
+    id.wrapping_add(1)
+}
+
+pub fn scale(level: u8, factor: u64) -> u64 {
+    factor * level as u64
+}
@@ -0,0 +1,32 @@
+import collectionParams.Functions;
+
+public class CollectionParamsTest {
+    static void expect(Object actual, Object expected) {
+        if (!actual.equals(expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "collection_params_java");
+
+        expect(Functions.join(java.util.List.of("a", "b", "c")), "a,b,c");
+        expect(Functions.join(java.util.List.of()), "");
+
+        expect(Functions.distinct_count(java.util.Set.of("x", "y")), 2L);
+
+        java.util.Map<String, String> table = new java.util.HashMap<>();
+        for (int i = 0; i < 100; i++) {
+            table.put("key" + i, "value" + i);
+        }
+        expect(Functions.lookup(table, "key42"), "value42");
+        expect(Functions.lookup(table, "missing"), "");
+
+        expect(Functions.sorted(java.util.Set.of("pear", "apple", "fig")), "apple,fig,pear");
+        expect(Functions.first_key(java.util.Map.of("b", "2", "a", "1")), "a");
+
+        java.util.List<String> words = java.util.List.of("pear", "apple", "fig");
+        expect(Functions.sort_in_place(words), "apple,fig,pear");
+        expect(words, java.util.List.of("pear", "apple", "fig"));
+    }
+}
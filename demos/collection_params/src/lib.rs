@@ -0,0 +1,29 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+pub fn join(parts: Vec<String>) -> String {
+    parts.join(",")
+}
+
+pub fn distinct_count(words: HashSet<String>) -> u32 {
+    words.len() as u32
+}
+
+/// Returns an empty string if `key` is absent.
+pub fn lookup(table: HashMap<String, String>, key: String) -> String {
+    table.get(&key).cloned().unwrap_or_default()
+}
+
+/// Ordered collections are received as such, so Rust sees the words in sorted order.
+pub fn sorted(words: BTreeSet<String>) -> String {
+    words.into_iter().collect::<Vec<_>>().join(",")
+}
+
+pub fn first_key(table: BTreeMap<String, String>) -> String {
+    table.into_keys().next().unwrap_or_default()
+}
+
+/// Sorts a copy of `words` in place; the caller's list is left unchanged.
+pub fn sort_in_place(words: &mut [String]) -> String {
+    words.sort();
+    words.join(",")
+}
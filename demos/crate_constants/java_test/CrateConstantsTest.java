@@ -0,0 +1,17 @@
+import crateConstants.Functions;
+
+public class CrateConstantsTest {
+    static void expect(Object actual, Object expected) {
+        if (!actual.equals(expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "crate_constants_java");
+
+        expect(Functions.VERSION, "1.0");
+        expect(Functions.GREETING, "hello");
+        expect(Functions.MAX_RETRIES, (long) Functions.retries());
+    }
+}
@@ -0,0 +1,10 @@
+/// Version of the protocol.
+pub const VERSION: &str = "1.0";
+
+pub const MAX_RETRIES: u32 = 3;
+
+pub static GREETING: &str = "hello";
+
+pub fn retries() -> u32 {
+    MAX_RETRIES
+}
@@ -0,0 +1,11 @@
+use gluegun::gluegun;
+
+#[gluegun(default(count = 1))]
+pub fn repeat(text: &str, count: i32) -> String {
+    text.repeat(count as usize)
+}
+
+#[gluegun(default(greeting = "Hello"))]
+pub fn greet(name: &str, greeting: &str) -> String {
+    format!("{greeting}, {name}!")
+}
@@ -0,0 +1,30 @@
+import dynErrors.Functions;
+import dynErrors.RustException;
+
+public class DynErrorsTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "dyn_errors_java");
+
+        expect(Functions.parse_number("42"), 42L);
+        try {
+            Functions.parse_number("4x2");
+            throw new AssertionError("`parse_number` did not throw");
+        } catch (RustException e) {
+            // The message is the `to_string()` of the Rust error.
+            expect(e.getMessage(), Functions.parse_number_message("4x2"));
+        }
+
+        try {
+            Functions.read_setting("colour");
+            throw new AssertionError("`read_setting` did not throw");
+        } catch (RustException e) {
+            expect(e.getMessage(), "no setting named `colour`");
+        }
+    }
+}
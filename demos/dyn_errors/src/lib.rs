@@ -0,0 +1,17 @@
+use std::error::Error;
+
+pub fn parse_number(text: &str) -> Result<u32, Box<dyn Error + Send + Sync>> {
+    Ok(text.parse()?)
+}
+
+pub fn read_setting(name: &str) -> anyhow::Result<String> {
+    anyhow::bail!("no setting named `{name}`")
+}
+
+/// The message Java sees when `parse_number` fails.
+pub fn parse_number_message(text: &str) -> String {
+    match text.parse::<u32>() {
+        Ok(_) => String::new(),
+        Err(err) => err.to_string(),
+    }
+}
@@ -0,0 +1,36 @@
+import errorPayloads.Functions;
+import errorPayloads.MyError;
+import errorPayloads.MyErrorException;
+import errorPayloads.Problem;
+import errorPayloads.ProblemException;
+
+public class ErrorPayloadsTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "error_payloads_java");
+
+        expect(Functions.parse("42"), 42);
+        try {
+            Functions.parse("12x");
+            throw new AssertionError("`parse` did not throw");
+        } catch (MyErrorException e) {
+            // The exception carries the `Err` value.
+            MyError error = e.error;
+            expect(error.input, "12x");
+            expect(error.position, 2L);
+        }
+
+        expect(Functions.check(7), 7);
+        try {
+            Functions.check(-1);
+            throw new AssertionError("`check` did not throw");
+        } catch (ProblemException e) {
+            expect(e.error, Problem.Negative);
+        }
+    }
+}
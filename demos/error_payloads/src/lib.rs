@@ -0,0 +1,28 @@
+/// Why `parse` failed.
+pub struct MyError {
+    pub input: String,
+    /// Byte offset of the first character that is not a digit.
+    pub position: u32,
+}
+
+pub fn parse(s: String) -> Result<i32, MyError> {
+    match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(position) => Err(MyError { input: s, position: position as u32 }),
+        None => Ok(s.parse().unwrap_or(i32::MAX)),
+    }
+}
+
+pub enum Problem {
+    Negative,
+    TooLarge,
+}
+
+pub fn check(value: i32) -> Result<i32, Problem> {
+    if value < 0 {
+        Err(Problem::Negative)
+    } else if value > 100 {
+        Err(Problem::TooLarge)
+    } else {
+        Ok(value)
+    }
+}
@@ -0,0 +1,6 @@
+pub fn save(path: &str) -> Result<(), String> {
+    if path.is_empty() {
+        return Err("no path given".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,23 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "free_functions.h"
+
+static void expect(char *actual, const char *expected) {
+    if (strcmp(actual, expected) != 0) {
+        fprintf(stderr, "expected `%s`, got `%s`\n", expected, actual);
+        exit(1);
+    }
+    free_functions_gluegun_free_string(actual);
+}
+
+int main(void) {
+    if (free_functions_add(2, 3) != 5) {
+        fprintf(stderr, "expected 2 + 3 = 5\n");
+        return 1;
+    }
+    expect(free_functions_greet("C"), "Hello, C!");
+    expect(free_functions_text_shout("hey"), "HEY");
+    return 0;
+}
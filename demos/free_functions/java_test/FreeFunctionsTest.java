@@ -0,0 +1,20 @@
+import freeFunctions.Functions;
+
+public class FreeFunctionsTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "free_functions_java");
+
+        // The functions of the crate root are static methods of one class...
+        expect(Functions.add(2, 3), 5);
+        expect(Functions.greet("Ferris"), "Hello, Ferris!");
+
+        // ...and those of a module are static methods of the class for that module.
+        expect(freeFunctions.text.Functions.shout("hi"), "HI");
+    }
+}
@@ -0,0 +1,13 @@
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+pub mod text {
+    pub fn shout(text: &str) -> String {
+        text.to_uppercase()
+    }
+}
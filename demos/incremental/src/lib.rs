@@ -0,0 +1,16 @@
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+pub fn farewell(name: &str) -> String {
+    format!("Goodbye, {name}!")
+}
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn origin() -> Point {
+    Point { x: 0, y: 0 }
+}
@@ -0,0 +1,3 @@
+pub fn count_up(limit: i32) -> impl Iterator<Item = u32> {
+    0..limit as u32
+}
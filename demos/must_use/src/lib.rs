@@ -0,0 +1,17 @@
+#[must_use]
+pub struct Receipt {
+    pub total: i32,
+}
+
+pub fn checkout(total: i32) -> Receipt {
+    Receipt { total }
+}
+
+#[must_use]
+pub fn double(value: i32) -> i32 {
+    value * 2
+}
+
+pub fn triple(value: i32) -> i32 {
+    value * 3
+}
@@ -0,0 +1,8 @@
+pub struct Profile {
+    pub name: String,
+    pub nickname: Option<String>,
+}
+
+pub fn greeting(nickname: Option<String>) -> Option<String> {
+    nickname.map(|nickname| format!("Hi, {nickname}!"))
+}
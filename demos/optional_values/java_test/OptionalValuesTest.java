@@ -0,0 +1,25 @@
+import optionalValues.Functions;
+
+public class OptionalValuesTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "optional_values_java");
+
+        // `None` comes back as `null`.
+        expect(Functions.find(2), "item 2");
+        expect(Functions.find(100), null);
+
+        // `null` goes to Rust as `None`.
+        expect(Functions.describe("Ferris"), "named Ferris");
+        expect(Functions.describe(null), "anonymous");
+
+        // Scalars are boxed so that they can be `null`.
+        expect(Functions.twice(21L), 42L);
+        expect(Functions.twice(null), null);
+    }
+}
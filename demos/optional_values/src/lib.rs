@@ -0,0 +1,15 @@
+/// Only ids below 100 are known.
+pub fn find(id: u32) -> Option<String> {
+    (id < 100).then(|| format!("item {id}"))
+}
+
+pub fn describe(name: Option<String>) -> String {
+    match name {
+        Some(name) => format!("named {name}"),
+        None => "anonymous".to_string(),
+    }
+}
+
+pub fn twice(value: Option<u32>) -> Option<u32> {
+    value.map(|value| value * 2)
+}
@@ -0,0 +1,23 @@
+import pathValues.Functions;
+
+public class PathValuesTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        GlueGunTest.loadLibrary(args[0], "path_values_java");
+
+        // Paths are passed to and returned from Rust as strings.
+        String path = Functions.join("java_test", "greeting.txt");
+        expect(path, "java_test" + java.io.File.separator + "greeting.txt");
+        java.nio.file.Files.writeString(java.nio.file.Path.of(path), "hello");
+        expect(Functions.read(path), "hello");
+        expect(Functions.file_name(path), "greeting.txt");
+        expect(Functions.file_name("/"), null);
+        expect(Functions.parent(path), "java_test");
+        expect(Functions.parent("/"), null);
+    }
+}
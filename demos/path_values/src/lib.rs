@@ -0,0 +1,17 @@
+use std::path::{Path, PathBuf};
+
+pub fn read(path: PathBuf) -> String {
+    std::fs::read_to_string(path).unwrap_or_default()
+}
+
+pub fn file_name(path: &Path) -> Option<String> {
+    path.file_name().map(|name| name.to_string_lossy().into_owned())
+}
+
+pub fn join(dir: &Path, name: String) -> PathBuf {
+    dir.join(name)
+}
+
+pub fn parent(path: &Path) -> Option<PathBuf> {
+    path.parent().map(Path::to_path_buf)
+}
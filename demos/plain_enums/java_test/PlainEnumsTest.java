@@ -0,0 +1,24 @@
+import plainEnums.Functions;
+import plainEnums.Signal;
+
+public class PlainEnumsTest {
+    static void expect(Signal actual, Signal expected) {
+        if (actual != expected) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "plain_enums_java");
+
+        for (Signal signal : Signal.values()) {
+            expect(Functions.same_signal(signal), signal);
+        }
+
+        // Rust -> Java -> Rust -> Java
+        Signal first = Functions.first_signal();
+        expect(first, Signal.Stop);
+        expect(Functions.next_signal(first), Signal.Go);
+        expect(Functions.next_signal(Signal.Go), Signal.Caution);
+    }
+}
@@ -0,0 +1,22 @@
+/// The discriminants do not follow declaration order, which must not matter to Java.
+pub enum Signal {
+    Go = 2,
+    Stop = 0,
+    Caution = 1,
+}
+
+pub fn first_signal() -> Signal {
+    Signal::Stop
+}
+
+pub fn next_signal(signal: Signal) -> Signal {
+    match signal {
+        Signal::Go => Signal::Caution,
+        Signal::Caution => Signal::Stop,
+        Signal::Stop => Signal::Go,
+    }
+}
+
+pub fn same_signal(signal: Signal) -> Signal {
+    signal
+}
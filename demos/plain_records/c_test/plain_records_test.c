@@ -0,0 +1,22 @@
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "plain_records.h"
+
+static void expect(int32_t actual, int32_t expected) {
+    if (actual != expected) {
+        fprintf(stderr, "expected %d, got %d\n", expected, actual);
+        exit(1);
+    }
+}
+
+int main(void) {
+    // Records are passed by value both ways.
+    plain_records_Point origin = plain_records_origin();
+    expect(origin.x, 0);
+    expect(origin.y, 0);
+
+    plain_records_Point to = {.x = 3, .y = -4};
+    expect(plain_records_manhattan_distance(origin, to), 7);
+    return 0;
+}
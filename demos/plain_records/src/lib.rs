@@ -0,0 +1,13 @@
+/// A point on the plane.
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+pub fn origin() -> Point {
+    Point { x: 0, y: 0 }
+}
+
+pub fn manhattan_distance(from: &Point, to: Point) -> i32 {
+    (from.x - to.x).abs() + (from.y - to.y).abs()
+}
@@ -0,0 +1,33 @@
+import plainVariants.Functions;
+import plainVariants.Shape;
+
+public class PlainVariantsTest {
+    static void expect(boolean condition, String what) {
+        if (!condition) {
+            throw new AssertionError(what);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "plain_variants_java");
+
+        // Rust -> Java
+        Shape circle = Functions.unit_circle();
+        expect(circle instanceof Shape.Circle, "`unit_circle` is not a circle: " + circle);
+        expect(((Shape.Circle) circle).radius == 1.0, "wrong radius");
+
+        // Java -> Rust
+        Shape.Rectangle rectangle = new Shape.Rectangle();
+        rectangle.f0 = 2.0;
+        rectangle.f1 = 3.0;
+        expect(Functions.area(rectangle) == 6.0, "wrong area for the rectangle");
+        expect(Functions.area(new Shape.Empty()) == 0.0, "wrong area for an empty shape");
+
+        // Java -> Rust -> Java
+        Shape scaled = Functions.scale(rectangle, 2.0);
+        expect(scaled instanceof Shape.Rectangle, "scaled rectangle is not a rectangle: " + scaled);
+        expect(((Shape.Rectangle) scaled).f0 == 4.0 && ((Shape.Rectangle) scaled).f1 == 6.0, "wrong scaled rectangle");
+        expect(Functions.scale(new Shape.Empty(), 2.0) instanceof Shape.Empty, "scaled empty shape is not empty");
+        expect(((Shape.Circle) Functions.scale(circle, 3.0)).radius == 3.0, "wrong scaled circle");
+    }
+}
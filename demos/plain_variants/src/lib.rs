@@ -0,0 +1,25 @@
+pub enum Shape {
+    Circle { radius: f64 },
+    Rectangle(f64, f64),
+    Empty,
+}
+
+pub fn unit_circle() -> Shape {
+    Shape::Circle { radius: 1.0 }
+}
+
+pub fn area(shape: &Shape) -> f64 {
+    match shape {
+        Shape::Circle { radius } => std::f64::consts::PI * radius * radius,
+        Shape::Rectangle(width, height) => width * height,
+        Shape::Empty => 0.0,
+    }
+}
+
+pub fn scale(shape: Shape, factor: f64) -> Shape {
+    match shape {
+        Shape::Circle { radius } => Shape::Circle { radius: radius * factor },
+        Shape::Rectangle(width, height) => Shape::Rectangle(width * factor, height * factor),
+        Shape::Empty => Shape::Empty,
+    }
+}
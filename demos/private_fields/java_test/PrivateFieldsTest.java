@@ -0,0 +1,33 @@
+import privateFields.Account;
+import privateFields.Functions;
+import privateFields.Version;
+
+public class PrivateFieldsTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        GlueGunTest.loadLibrary(args[0], "private_fields_java");
+
+        // The public fields are copied from Rust...
+        Account account = Functions.open_account("Ferris", 7);
+        expect(account.owner, "Ferris");
+        expect(account.number, 7L);
+        expect(Functions.opening_balance("Ferris"), 0L);
+
+        // ...but the private one is not visible, and Java cannot create an account itself.
+        for (java.lang.reflect.Field field : Account.class.getFields()) {
+            expect(field.getName().equals("balance"), false);
+        }
+        expect(Account.class.getConstructors().length, 0);
+
+        // A `#[non_exhaustive]` struct can only come from Rust as well.
+        Version version = Functions.current_version();
+        expect(version.major, 1L);
+        expect(version.minor, 2L);
+        expect(Version.class.getConstructors().length, 0);
+    }
+}
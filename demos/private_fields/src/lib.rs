@@ -0,0 +1,25 @@
+/// The balance is private, so Java sees the owner and number of an account but cannot make one up.
+pub struct Account {
+    pub owner: String,
+    pub number: u32,
+    balance: i64,
+}
+
+pub fn open_account(owner: &str, number: u32) -> Account {
+    Account { owner: owner.to_string(), number, balance: 0 }
+}
+
+pub fn opening_balance(owner: &str) -> i64 {
+    open_account(owner, 0).balance
+}
+
+/// More fields may be added, so Java cannot make up a version either.
+#[non_exhaustive]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+pub fn current_version() -> Version {
+    Version { major: 1, minor: 2 }
+}
@@ -0,0 +1,32 @@
+import readonlyFields.Functions;
+import readonlyFields.OrderLine;
+
+public class ReadonlyFieldsTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) throws Exception {
+        GlueGunTest.loadLibrary(args[0], "readonly_fields_java");
+
+        // Every field has a getter, but only the quantity has a setter.
+        OrderLine line = Functions.order("tea");
+        expect(line.getProduct(), "tea");
+        expect(line.getQuantity(), 1L);
+        line.setQuantity(3);
+        expect(Functions.describe(line), "3 x tea");
+
+        expect(OrderLine.class.getMethod("getProduct").getReturnType(), String.class);
+        for (java.lang.reflect.Method method : OrderLine.class.getMethods()) {
+            expect(method.getName().equals("setProduct"), false);
+        }
+
+        // The readonly field is private, so a line made in Java gets its product from the constructor.
+        for (java.lang.reflect.Field field : OrderLine.class.getFields()) {
+            expect(field.getName().equals("product"), false);
+        }
+        expect(Functions.describe(new OrderLine("coffee", 2)), "2 x coffee");
+    }
+}
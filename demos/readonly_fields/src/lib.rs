@@ -0,0 +1,17 @@
+use gluegun::gluegun;
+
+/// The product of a line is fixed once the line is made, but its quantity can change.
+#[gluegun]
+pub struct OrderLine {
+    #[gluegun(readonly)]
+    pub product: String,
+    pub quantity: u32,
+}
+
+pub fn order(product: &str) -> OrderLine {
+    OrderLine { product: product.to_string(), quantity: 1 }
+}
+
+pub fn describe(line: OrderLine) -> String {
+    format!("{} x {}", line.quantity, line.product)
+}
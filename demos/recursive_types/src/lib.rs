@@ -0,0 +1,18 @@
+/// Contains itself, but is never passed to or returned from a function, so it is left out.
+pub struct Node {
+    pub label: String,
+    pub children: Vec<Node>,
+}
+
+/// Refers to `Node`, so it is left out too.
+pub struct Forest {
+    pub roots: Vec<Node>,
+}
+
+fn leaf(label: &str) -> Node {
+    Node { label: label.to_string(), children: vec![] }
+}
+
+pub fn greet(name: &str) -> String {
+    format!("Hello, {}!", leaf(name).label)
+}
@@ -0,0 +1,23 @@
+use gluegun::gluegun;
+
+#[gluegun(name = "salute")]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+/// Exported as the class `Tally`.
+#[gluegun(name = "Tally")]
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new(start: u32) -> Self {
+        Self { count: start }
+    }
+
+    #[gluegun(name = "increment")]
+    pub fn bump(&mut self) {
+        self.count += 1;
+    }
+}
@@ -0,0 +1,64 @@
+import resourceMethods.Counter;
+
+public class ResourceMethodsTest {
+    static void expect(Object actual, Object expected) {
+        if (!actual.equals(expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "resource_methods_java");
+
+        // Constants are static fields, available without a Rust value.
+        expect(Counter.LIMIT, 1000L);
+        expect(Counter.SEED, -1L); // `u64::MAX` wraps around
+        expect(Counter.UNIT, "clicks");
+        expect(Counter.LABEL, "\"tally\"\tcaf\u00e9 \ud83e\udd80");
+
+        // `&mut self` changes the Rust value owned by the Java object, which `&self` then sees.
+        Counter counter = new Counter(1);
+        expect(counter.get(), 1L);
+        counter.increment(2);
+        counter.increment(3);
+        expect(counter.get(), 6L);
+
+        // `self` by value closes the object.
+        expect(counter.finish(), "counted to 6");
+        try {
+            counter.get();
+            throw new AssertionError("a closed counter was used");
+        } catch (IllegalStateException e) {
+            // expected
+        }
+
+        try (Counter other = new Counter(10)) {
+            expect(other.get(), 10L);
+        }
+
+        // Functions returning the resource give Java a new object owning the new value.
+        Counter total = Counter.zero();
+        Counter copy = new Counter(4).copy();
+        copy.increment(1);
+        expect(copy.get(), 5L);
+
+        // Passing a resource by value moves it into Rust and closes its object.
+        total.absorb(copy);
+        expect(total.get(), 5L);
+        try {
+            copy.get();
+            throw new AssertionError("an absorbed counter was used");
+        } catch (IllegalStateException e) {
+            // expected
+        }
+
+        // The object a method is called on cannot also be given up as an argument.
+        try {
+            total.absorb(total);
+            throw new AssertionError("a counter absorbed itself");
+        } catch (IllegalArgumentException e) {
+            // expected
+        }
+        expect(total.get(), 5L);
+    }
+}
@@ -0,0 +1,44 @@
+/// A resource: Java objects of this class own the Rust value.
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    /// Constants are copied into static fields of the Java class.
+    pub const LIMIT: u32 = 1_000;
+    pub const SEED: u64 = 18_446_744_073_709_551_615;
+    pub const UNIT: &'static str = "clicks";
+    pub const LABEL: &str = "\"tally\"\tcaf\u{e9} \u{1F980}";
+
+    pub fn new(start: u32) -> Self {
+        Self { count: start }
+    }
+
+    /// Another function returning `Self`, which Java receives as a new object.
+    pub fn zero() -> Self {
+        Self { count: 0 }
+    }
+
+    /// Returns the type by name rather than as `Self`.
+    pub fn copy(&self) -> Counter {
+        Counter { count: self.count }
+    }
+
+    /// Takes another counter by value; its Java object is closed afterwards.
+    pub fn absorb(&mut self, other: Counter) {
+        self.count += other.count;
+    }
+
+    pub fn get(&self) -> u32 {
+        self.count
+    }
+
+    pub fn increment(&mut self, by: u32) {
+        self.count += by;
+    }
+
+    /// Consumes the counter; the Java object is closed afterwards.
+    pub fn finish(self) -> String {
+        format!("counted to {}", self.count)
+    }
+}
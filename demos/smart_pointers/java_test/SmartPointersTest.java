@@ -0,0 +1,34 @@
+import smartPointers.Functions;
+import smartPointers.Point;
+
+public class SmartPointersTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "smart_pointers_java");
+
+        // The smart pointers are invisible: Java passes and receives the values they point to.
+        expect(Functions.shout("hi"), "HI");
+
+        Point point = new Point();
+        point.x = 1;
+        point.y = 2;
+        Point mirrored = Functions.mirror(point);
+        expect(mirrored.x, -1);
+        expect(mirrored.y, -2);
+        expect(Functions.origin().x, 0);
+
+        expect(Functions.count(new java.util.ArrayList<>(java.util.List.of("a", "b"))), 2L);
+        expect(Functions.parse_name("ferris"), "ferris");
+        try {
+            Functions.parse_name("");
+            throw new AssertionError("an empty name is an error");
+        } catch (RuntimeException e) {
+            expect(e.getMessage(), "empty name");
+        }
+    }
+}
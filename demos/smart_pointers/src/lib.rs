@@ -0,0 +1,34 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A point on the plane; shared points are cloned out of their `Arc`.
+#[derive(Clone)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[allow(clippy::boxed_local, reason = "the demo takes a `Box` across the boundary")]
+pub fn shout(text: Box<str>) -> Box<str> {
+    text.to_uppercase().into()
+}
+
+pub fn mirror(point: Arc<Point>) -> Arc<Point> {
+    Arc::new(Point { x: -point.x, y: -point.y })
+}
+
+pub fn origin() -> Box<Point> {
+    Box::new(Point { x: 0, y: 0 })
+}
+
+pub fn count(names: Rc<[String]>) -> u32 {
+    names.len() as u32
+}
+
+pub fn parse_name(text: &str) -> Result<Arc<str>, String> {
+    if text.is_empty() {
+        Err("empty name".to_string())
+    } else {
+        Ok(text.into())
+    }
+}
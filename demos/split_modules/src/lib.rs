@@ -0,0 +1,11 @@
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+pub fn farewell(name: &str) -> String {
+    format!("Goodbye, {name}!")
+}
+
+pub fn shout(name: &str) -> String {
+    name.to_uppercase()
+}
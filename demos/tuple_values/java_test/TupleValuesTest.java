@@ -0,0 +1,29 @@
+import tupleValues.Functions;
+import tupleValues.TupleI32I32;
+import tupleValues.TupleStringU32F64;
+
+public class TupleValuesTest {
+    static void expect(Object actual, Object expected) {
+        if (!java.util.Objects.equals(actual, expected)) {
+            throw new AssertionError("expected " + expected + ", got " + actual);
+        }
+    }
+
+    public static void main(String[] args) {
+        GlueGunTest.loadLibrary(args[0], "tuple_values_java");
+
+        TupleI32I32 quotientAndRemainder = Functions.divmod(17, 5);
+        expect(quotientAndRemainder.f0, 3);
+        expect(quotientAndRemainder.f1, 2);
+
+        TupleStringU32F64 summary = Functions.summary("ferris");
+        expect(summary.f0, "FERRIS");
+        expect(summary.f1, 2L);
+        expect(summary.f2, 2.0 / 6.0);
+
+        // Tuples can be passed to Rust too.
+        TupleI32I32 swapped = Functions.swap(new TupleI32I32(1, 2));
+        expect(swapped.f0, 2);
+        expect(swapped.f1, 1);
+    }
+}
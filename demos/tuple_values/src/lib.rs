@@ -0,0 +1,12 @@
+pub fn divmod(a: i32, b: i32) -> (i32, i32) {
+    (a / b, a % b)
+}
+
+pub fn summary(name: &str) -> (String, u32, f64) {
+    let vowels = name.chars().filter(|c| "aeiou".contains(*c)).count() as u32;
+    (name.to_uppercase(), vowels, vowels as f64 / name.len() as f64)
+}
+
+pub fn swap(pair: (i32, i32)) -> (i32, i32) {
+    (pair.1, pair.0)
+}
@@ -0,0 +1,11 @@
+pub struct Point {
+    pub coordinates: [f32; 3],
+}
+
+pub fn hash(data: &[u8; 32]) -> [u8; 32] {
+    *data
+}
+
+pub fn identity_matrix() -> [[f64; 3]; 3] {
+    [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+}
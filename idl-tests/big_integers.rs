@@ -0,0 +1,3 @@
+pub fn big(x: u128) -> i128 {
+    x as i128
+}
@@ -0,0 +1,42 @@
+pub struct Point {
+    pub x: u32,
+    pub y: u32,
+}
+
+pub struct Buffer {
+    data: Vec<u8>,
+}
+
+impl Buffer {
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+pub fn length(text: &str) -> u32 {
+    text.len() as u32
+}
+
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |a, b| a.wrapping_add(*b))
+}
+
+pub fn sum(values: &[u32]) -> u32 {
+    values.iter().sum()
+}
+
+pub fn sort(values: &mut [u32]) {
+    values.sort();
+}
+
+pub fn shout(text: &mut str) {
+    text.make_ascii_uppercase();
+}
+
+pub fn shift(point: &mut Point) {
+    point.x += 1;
+}
+
+pub fn fill(buffer: &mut Buffer, byte: u8) {
+    buffer.data.fill(byte);
+}
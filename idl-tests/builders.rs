@@ -0,0 +1,45 @@
+use gluegun::gluegun;
+
+pub struct RequestBuilder {
+    name: String,
+    retries: u32,
+}
+
+impl RequestBuilder {
+    pub fn new() -> Self {
+        RequestBuilder { name: String::new(), retries: 0 }
+    }
+
+    /// Takes `self` and returns `Self`: a builder method.
+    pub fn with_name(self, n: String) -> Self {
+        RequestBuilder { name: n, ..self }
+    }
+
+    /// Takes `&mut self` and returns the type by name: a builder method.
+    pub fn with_retries(&mut self, retries: u32) -> RequestBuilder {
+        self.retries = retries;
+        RequestBuilder { name: self.name.clone(), retries }
+    }
+
+    /// Takes `&self`: an instance method, even though it returns `Self`.
+    pub fn duplicate(&self) -> Self {
+        RequestBuilder { name: self.name.clone(), retries: self.retries }
+    }
+
+    /// Would be a builder method, but is marked otherwise.
+    #[gluegun(not_builder)]
+    pub fn reset(self) -> Self {
+        RequestBuilder::new()
+    }
+
+    /// Does not return `Self`, but is marked as a builder method.
+    #[gluegun(builder)]
+    pub fn add_header(&mut self, header: String) {
+        self.name.push_str(&header);
+    }
+
+    /// Consumes the builder: an instance method.
+    pub fn build(self) -> String {
+        format!("{} ({} retries)", self.name, self.retries)
+    }
+}
@@ -0,0 +1,27 @@
+pub fn checksum(data: &[u8]) -> u32 {
+    data.iter().map(|&b| b as u32).sum()
+}
+
+pub fn compress(data: Vec<u8>) -> Option<Vec<u8>> {
+    Some(data)
+}
+
+pub fn widen(data: Vec<u16>) -> Vec<u16> {
+    data
+}
+
+/// Small numbers, not binary data.
+#[gluegun(no_bytes)]
+pub fn histogram(counts: Vec<u8>) -> Vec<u8> {
+    counts
+}
+
+/// Binary data in a record; `no_bytes` on the struct applies to its fields.
+pub struct Packet {
+    pub payload: Vec<u8>,
+}
+
+#[gluegun(no_bytes)]
+pub struct Levels {
+    pub values: Vec<u8>,
+}
@@ -0,0 +1,20 @@
+// The globs are written before the modules they refer to, and the first one refers to a glob of the second.
+pub use a::*;
+
+mod a {
+    pub use crate::b::*;
+}
+
+mod b {
+    pub fn hello() -> u32 {
+        1
+    }
+
+    pub use crate::c::Greeting;
+}
+
+mod c {
+    pub struct Greeting {
+        pub text: String,
+    }
+}
@@ -0,0 +1,22 @@
+/// A buffer with a fixed capacity.
+pub struct Buffer {
+    data: Vec<u8>,
+}
+
+impl Buffer {
+    /// The largest number of bytes a buffer holds.
+    pub const CAPACITY: u32 = 4_096;
+
+    pub const MIN_OFFSET: i64 = -1;
+
+    pub const NAME: &'static str = "buffer";
+
+    pub const DEFAULT_FILE: &str = "out.bin";
+
+    /// Private constants are not part of the interface.
+    const SECRET: u32 = 42;
+
+    pub fn new() -> Self {
+        Buffer { data: Vec::with_capacity(Self::CAPACITY as usize + Self::SECRET as usize) }
+    }
+}
@@ -0,0 +1,14 @@
+/// The version of the library.
+pub const VERSION: &str = "1.0";
+
+pub const MAX_RETRIES: u32 = 3;
+
+/// Statics are constants too, as long as they are not `mut`.
+pub static GREETING: &'static str = "hello";
+
+/// Private constants are not part of the interface.
+const SECRET: u32 = 42;
+
+pub fn retries() -> u32 {
+    MAX_RETRIES + SECRET - SECRET
+}
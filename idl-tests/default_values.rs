@@ -0,0 +1,22 @@
+use gluegun::gluegun;
+
+#[gluegun(default(count = 1))]
+pub fn repeat(text: &str, count: i32) -> String {
+    text.repeat(count as usize)
+}
+
+#[gluegun(default(greeting = "Hello", punctuation = '!'))]
+pub fn greet(name: &str, greeting: String, punctuation: char) -> String {
+    format!("{greeting}, {name}{punctuation}")
+}
+
+pub struct Client {
+    url: String,
+}
+
+impl Client {
+    #[gluegun(default(timeout = 30, retries = 3, backoff = -0.5))]
+    pub fn fetch(&self, path: &str, timeout: u64, retries: u32, backoff: f64) -> String {
+        format!("{}/{path} ({timeout}s, {retries} retries, {backoff} backoff)", self.url)
+    }
+}
@@ -0,0 +1,48 @@
+/// A point on the plane.
+///
+/// Both coordinates are in pixels.
+pub struct Point {
+    /// Distance from the left edge.
+    pub x: i32,
+
+    #[doc = "Distance from the top edge."]
+    pub y: i32,
+}
+
+#[doc = "An opaque counter."]
+pub struct Counter {
+    count: i32,
+}
+
+impl Counter {
+    /// Increment the counter,
+    /// returning the new count.
+    pub fn bump(&mut self) -> i32 {
+        self.count += 1;
+        self.count
+    }
+
+    pub fn count(&self) -> i32 {
+        self.count
+    }
+}
+
+/**
+ * Compass directions.
+ */
+pub enum Direction {
+    North,
+    South,
+}
+
+/// Add two numbers.
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+/// Pick a direction:
+/// * `north` if true
+/// * `south` otherwise
+pub fn pick(north: i32) -> i32 {
+    north
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+pub fn sleep(d: Duration) {}
+
+pub fn timeout() -> Option<core::time::Duration> {
+    None
+}
+
+pub struct Retry {
+    pub attempts: u32,
+    pub backoff: std::time::Duration,
+}
+
+pub mod clock {
+    /// The crate's own `Duration`, in ticks of the clock.
+    pub struct Duration {
+        pub ticks: u64,
+    }
+
+    /// `no_duration` makes `Duration` refer to the struct above.
+    #[gluegun(no_duration)]
+    pub fn advance(by: Duration) -> Duration {
+        by
+    }
+}
@@ -0,0 +1,13 @@
+use std::error::Error;
+
+pub fn parse_number(text: &str) -> Result<u32, Box<dyn Error>> {
+    Ok(text.parse()?)
+}
+
+pub fn parse_shared(text: &str) -> Result<u32, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    Ok(text.parse()?)
+}
+
+pub fn load(path: &str) -> Result<String, anyhow::Error> {
+    Ok(std::fs::read_to_string(path)?)
+}
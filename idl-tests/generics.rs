@@ -0,0 +1,38 @@
+/// A value of any type.
+pub struct Wrapper<T> {
+    value: T,
+}
+
+impl<T: Clone> Wrapper<T> {
+    pub fn new(value: T) -> Self {
+        Wrapper { value }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.clone()
+    }
+
+    pub fn map<U>(&self, value: U) -> Vec<U> {
+        vec![value]
+    }
+}
+
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+pub trait Visitor<T> {
+    fn visit(&mut self, value: &T);
+}
+
+pub fn identity<T>(x: T) -> T {
+    x
+}
+
+pub fn first<T: Clone + Send, U>(pair: (T, U)) -> Option<T>
+where
+    U: Clone,
+{
+    Some(pair.0)
+}
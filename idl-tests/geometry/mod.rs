@@ -0,0 +1,5 @@
+pub mod shapes;
+
+pub fn origin() -> i32 {
+    0
+}
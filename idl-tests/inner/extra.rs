@@ -0,0 +1,3 @@
+pub fn bonus() -> i32 {
+    1
+}
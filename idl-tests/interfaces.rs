@@ -0,0 +1,20 @@
+/// Notified as a download makes progress.
+pub trait ProgressListener {
+    /// Called with the number of bytes received so far.
+    fn on_progress(&mut self, received: u64, total: u64);
+
+    fn on_complete(&self) -> String {
+        String::from("done")
+    }
+
+    fn consume(self);
+
+    /// Associated functions become static methods, even if they return `Self`.
+    fn new() -> Self;
+
+    fn describe(code: u32) -> String;
+}
+
+trait Private {
+    fn hidden(&self);
+}
@@ -0,0 +1,10 @@
+pub fn count_up(limit: i32) -> impl Iterator<Item = u32> {
+    0..limit as u32
+}
+
+pub fn words(text: String) -> impl Iterator<Item = String> + Send {
+    text.split_whitespace()
+        .map(|word| word.to_string())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
@@ -0,0 +1,22 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Type aliases are as ordered as the type they stand for.
+pub type Index = BTreeMap<String, u32>;
+pub type Tags = HashSet<String>;
+
+pub struct Catalog {
+    pub counts: HashMap<String, u32>,
+    pub sorted_counts: BTreeMap<String, u32>,
+    pub tags: HashSet<String>,
+    pub sorted_tags: BTreeSet<String>,
+    pub index: Index,
+    pub aliases: Tags,
+}
+
+pub fn lookup(table: std::collections::hash_map::HashMap<String, String>, key: String) -> Option<String> {
+    table.get(&key).cloned()
+}
+
+pub fn first(names: std::collections::btree_set::BTreeSet<String>) -> Option<String> {
+    names.into_iter().next()
+}
@@ -0,0 +1,13 @@
+pub mod geometry;
+
+pub mod inline {
+    pub fn answer() -> i32 {
+        42
+    }
+}
+
+mod private_helpers;
+
+pub fn unit_square() -> crate::geometry::shapes::Square {
+    geometry::shapes::Square { side: 1 }
+}
@@ -0,0 +1,28 @@
+#[must_use]
+pub struct Receipt {
+    pub total: i32,
+}
+
+pub struct Counter {
+    count: i32,
+}
+
+impl Counter {
+    #[must_use = "the count is the only way to observe the counter"]
+    pub fn count(&self) -> i32 {
+        self.count
+    }
+
+    pub fn bump(&mut self) {
+        self.count += 1;
+    }
+}
+
+pub fn checkout(total: i32) -> Receipt {
+    Receipt { total }
+}
+
+#[must_use]
+pub fn double(value: i32) -> i32 {
+    value * 2
+}
@@ -0,0 +1,26 @@
+/// More fields may be added, so the struct cannot be built outside of this crate.
+#[non_exhaustive]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+}
+
+/// More levels may be added.
+#[non_exhaustive]
+pub enum Level {
+    Low,
+    High,
+}
+
+/// More events may be added.
+#[non_exhaustive]
+pub enum Event {
+    Click { x: i32, y: i32 },
+    Quit,
+}
+
+/// Without the attribute, for comparison.
+pub enum Direction {
+    Up,
+    Down,
+}
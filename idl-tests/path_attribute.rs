@@ -0,0 +1,7 @@
+#[path = "platforms/linux.rs"]
+pub mod platform;
+
+pub mod inner {
+    #[path = "extra.rs"]
+    pub mod extra;
+}
@@ -0,0 +1,5 @@
+pub mod detail;
+
+pub fn name() -> String {
+    "linux".to_string()
+}
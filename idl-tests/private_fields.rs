@@ -0,0 +1,16 @@
+/// Only `name` and `size` are visible outside of Rust.
+pub struct Mixed {
+    pub name: String,
+    secret: u64,
+    pub size: u32,
+}
+
+/// Fields of enum variants are always public.
+pub enum Shape {
+    Circle { radius: f64 },
+    Square(f64),
+}
+
+pub fn make(name: &str) -> Mixed {
+    Mixed { name: name.to_string(), secret: 42, size: 0 }
+}
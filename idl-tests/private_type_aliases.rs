@@ -0,0 +1,9 @@
+/// Private aliases can be used in public signatures too;
+/// references to them are replaced by the aliased type, but they are not part of the interface.
+type Name = String;
+
+pub(crate) type Greeting = Vec<Name>;
+
+pub fn greet(name: Name) -> Greeting {
+    vec![format!("hello, {name}")]
+}
@@ -0,0 +1,19 @@
+use gluegun::gluegun;
+
+/// The product of a line is fixed once the line is made, but its quantity can change.
+#[gluegun]
+pub struct OrderLine {
+    #[gluegun(readonly)]
+    pub product: String,
+    pub quantity: u32,
+}
+
+/// Options on the fields of an enum are accepted as well.
+#[gluegun]
+pub enum Delivery {
+    Pickup,
+    Courier {
+        #[gluegun(readonly)]
+        tracking: String,
+    },
+}
@@ -0,0 +1,32 @@
+/// Contains itself directly.
+pub struct Node {
+    pub label: String,
+    pub children: Vec<Node>,
+}
+
+/// Contains itself through `Branch`.
+pub enum Tree {
+    Leaf(u32),
+    Branch(Branch),
+}
+
+pub struct Branch {
+    pub trees: Vec<Tree>,
+}
+
+/// Refers to `Node` without being contained in it, so it is not recursive.
+pub struct Forest {
+    pub roots: Vec<Node>,
+}
+
+/// Methods returning `Self` do not make a type recursive.
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn flipped(&self) -> Point {
+        Point { x: self.y, y: self.x }
+    }
+}
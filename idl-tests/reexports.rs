@@ -0,0 +1,44 @@
+mod internal {
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+
+    pub fn origin() -> Point {
+        Point { x: 0, y: 0 }
+    }
+
+    pub fn distance(from: &Point, to: &Point) -> f64 {
+        (((to.x - from.x).pow(2) + (to.y - from.y).pow(2)) as f64).sqrt()
+    }
+}
+
+mod colors {
+    pub enum Color {
+        Red,
+        Green,
+        Blue,
+    }
+
+    pub fn favorite() -> Color {
+        Color::Green
+    }
+
+    /// Re-exported by the glob below.
+    pub struct Palette {
+        pub colors: Vec<Color>,
+    }
+}
+
+pub use internal::{Point, distance, origin as zero};
+
+pub use colors::*;
+
+pub mod shapes {
+    pub use crate::internal::Point as Corner;
+
+    pub struct Rectangle {
+        pub top_left: Corner,
+        pub bottom_right: Corner,
+    }
+}
@@ -0,0 +1,24 @@
+/// Exported as `salute`; generated Rust still calls `greet`.
+#[gluegun(name = "salute")]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+#[gluegun(name = "Point2D")]
+pub struct Point {
+    #[gluegun(name = "xCoord")]
+    pub x: u32,
+    pub y: u32,
+}
+
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    /// `final` is a keyword in Java.
+    #[gluegun(name = "finish")]
+    pub fn r#final(&self) -> u32 {
+        self.count
+    }
+}
@@ -0,0 +1,49 @@
+pub fn exported() -> u32 {
+    0
+}
+
+/// Rust-only helper, left out of the generated interface.
+#[gluegun(skip)]
+pub fn rust_only(callback: impl Fn()) {
+    callback()
+}
+
+/// Skipped types need not follow the gluegun conventions.
+#[gluegun(skip)]
+pub struct Internal<'a> {
+    pub data: &'a str,
+}
+
+/// A record whose skipped field is left out; the other fields are still public.
+pub struct Settings {
+    pub name: String,
+    #[gluegun(skip)]
+    pub cache: std::rc::Rc<String>,
+    pub level: u32,
+}
+
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    pub fn get(&self) -> u32 {
+        self.count
+    }
+
+    #[gluegun(skip)]
+    pub fn as_mut(&mut self) -> &mut u32 {
+        &mut self.count
+    }
+}
+
+pub trait Listener {
+    fn on_event(&mut self, value: u32);
+
+    #[gluegun(skip)]
+    fn boxed(self: Box<Self>) -> Box<dyn Listener>;
+}
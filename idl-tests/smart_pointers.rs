@@ -0,0 +1,26 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub struct Config {
+    pub name: String,
+}
+
+pub fn shout(text: Box<str>) -> Box<str> {
+    text.to_uppercase().into()
+}
+
+pub fn share(config: Arc<Config>) -> Arc<Config> {
+    config
+}
+
+pub fn checksum(data: Box<[u8]>) -> u32 {
+    data.iter().map(|&byte| u32::from(byte)).sum()
+}
+
+pub fn names(names: Rc<[String]>) -> Rc<Vec<String>> {
+    Rc::new(names.to_vec())
+}
+
+pub fn boxed(config: Config) -> Box<Config> {
+    Box::new(config)
+}
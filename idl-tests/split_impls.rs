@@ -0,0 +1,71 @@
+//! The methods of a type are gathered from all of its `impl` blocks, wherever they are in the crate,
+//! including its impls of exported traits.
+
+/// Something with a name.
+pub trait Named {
+    /// The name of the thing.
+    fn name(&self) -> String;
+}
+
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter { count: 0 }
+    }
+}
+
+impl Counter {
+    pub fn increment(&mut self) {
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// The trait is exported, so its methods are methods of the type.
+impl Named for Counter {
+    fn name(&self) -> String {
+        format!("counter at {}", self.count)
+    }
+}
+
+/// Impls of traits that are not exported are not part of the interface.
+impl std::fmt::Display for Counter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.count)
+    }
+}
+
+/// Impls in other modules, whether inline or in their own file, count too.
+mod more {
+    impl super::Counter {
+        pub fn decrement(&mut self) {
+            self.count -= 1;
+        }
+    }
+}
+
+#[path = "split_impls/ops.rs"]
+mod ops;
+
+pub struct Tag {
+    label: String,
+}
+
+impl Tag {
+    /// Also provided by `Named`: this one is kept, as it is the one `tag.name()` calls in Rust.
+    pub fn name(&self) -> String {
+        self.label.to_uppercase()
+    }
+}
+
+impl Named for Tag {
+    fn name(&self) -> String {
+        self.label.clone()
+    }
+}
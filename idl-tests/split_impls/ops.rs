@@ -0,0 +1,20 @@
+use crate::{Counter, Named};
+
+impl Counter {
+    pub fn reset(&mut self) {
+        self.count = 0;
+    }
+
+    /// Names in the block are resolved in this module.
+    pub fn add(&mut self, other: &crate::Counter) {
+        self.count += other.count;
+    }
+}
+
+pub struct Unrelated;
+
+impl Named for Unrelated {
+    fn name(&self) -> String {
+        "unrelated".to_string()
+    }
+}
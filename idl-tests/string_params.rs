@@ -0,0 +1,13 @@
+pub struct Greeter {
+    greeting: String,
+}
+
+impl Greeter {
+    pub fn greet(&self, name: impl AsRef<str>) -> String {
+        format!("{}, {}!", self.greeting, name.as_ref())
+    }
+}
+
+pub fn shout(text: impl Into<String>) -> String {
+    text.into().to_uppercase()
+}
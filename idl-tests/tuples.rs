@@ -0,0 +1,11 @@
+pub fn divmod(a: i32, b: i32) -> (i32, i32) {
+    (a / b, a % b)
+}
+
+pub fn swap(pair: (String, u32)) -> (u32, String) {
+    (pair.1, pair.0)
+}
+
+pub fn single(value: f64) -> (f64,) {
+    (value,)
+}
@@ -0,0 +1,23 @@
+pub type UserId = u64;
+
+pub type Tags = Vec<String>;
+
+/// Aliases can stand for user types too.
+pub type Friends = Vec<Friend>;
+
+pub struct Friend {
+    pub id: UserId,
+    pub tags: Tags,
+}
+
+pub fn tags(id: UserId) -> Tags {
+    vec![format!("user{id}")]
+}
+
+pub fn count(tags: &Tags) -> u32 {
+    tags.len() as u32
+}
+
+pub fn friends(id: UserId) -> Friends {
+    vec![Friend { id, tags: tags(id) }]
+}
@@ -0,0 +1,7 @@
+pub fn implicit_unit() {}
+
+pub fn explicit_unit() -> () {}
+
+pub fn never_returns() -> ! {
+    panic!("never returns")
+}
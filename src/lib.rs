@@ -7,3 +7,40 @@ pub fn ignore(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     item
 }
+
+/// `#[gluegun(...)]` has no effect on the Rust code but supplies options to gluegun tooling, e.g.
+/// `#[gluegun(default(timeout = "30"))]` gives a default value for the parameter `timeout`,
+/// `#[gluegun(name = "newName")]` exports an item or method under another name,
+/// and `#[gluegun(skip)]` leaves an item or method out of the generated interface.
+///
+/// Attribute macros cannot be placed on fields or enum variants, so their options are only accepted
+/// within a struct or enum marked with `#[gluegun]` (with or without options), which removes them.
+#[proc_macro_attribute]
+pub fn gluegun(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let Ok(mut input) = syn::parse::<syn::DeriveInput>(item.clone()) else {
+        return item;
+    };
+
+    match &mut input.data {
+        syn::Data::Struct(data) => remove_field_options(&mut data.fields),
+        syn::Data::Enum(data) => {
+            for variant in &mut data.variants {
+                variant.attrs.retain(|attr| !is_gluegun_attribute(attr));
+                remove_field_options(&mut variant.fields);
+            }
+        }
+        syn::Data::Union(_) => {}
+    }
+    quote::ToTokens::into_token_stream(input).into()
+}
+
+fn remove_field_options(fields: &mut syn::Fields) {
+    for field in fields {
+        field.attrs.retain(|attr| !is_gluegun_attribute(attr));
+    }
+}
+
+/// True if `attr` is a `#[gluegun(...)]` attribute (possibly written `#[gluegun::gluegun(...)]`).
+fn is_gluegun_attribute(attr: &syn::Attribute) -> bool {
+    attr.path().segments.last().is_some_and(|segment| segment.ident == "gluegun")
+}
@@ -0,0 +1,1534 @@
+use temp_dir::TempDir;
+
+/// Create an executable file named `name` in `dir`.
+fn create_executable(dir: &TempDir, name: &str) -> anyhow::Result<()> {
+    let path = dir.child(name);
+    std::fs::write(&path, "#!/bin/sh\n")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+/// Shell command by which stub plugins answer the plugin protocol handshake.
+fn handshake() -> String {
+    format!("echo {}", gluegun_core::protocol::handshake(gluegun_core::protocol::PROTOCOL_VERSION))
+}
+
+#[test]
+fn discover_plugins_on_path() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    create_executable(&dir, &format!("gluegun-java{}", std::env::consts::EXE_SUFFIX))?;
+    create_executable(&dir, &format!("gluegun-py{}", std::env::consts::EXE_SUFFIX))?;
+    create_executable(&dir, &format!("unrelated{}", std::env::consts::EXE_SUFFIX))?;
+
+    let plugins = cargo_gluegun::discover_plugins(Some(dir.path().as_os_str()));
+    assert_eq!(plugins, ["java", "py"]);
+    Ok(())
+}
+
+#[test]
+fn find_plugin_in_plugin_dirs() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let exe = |name: &str| format!("{name}{}", std::env::consts::EXE_SUFFIX);
+    std::fs::create_dir_all(dir.child("plugins"))?;
+    std::fs::create_dir_all(dir.child("bin"))?;
+    create_executable(&dir, &format!("plugins/{}", exe("gluegun-both")))?;
+    create_executable(&dir, &format!("bin/{}", exe("gluegun-both")))?;
+    create_executable(&dir, &format!("bin/{}", exe("gluegun-path")))?;
+    let plugin_dirs = [dir.child("plugins")];
+    let path = dir.child("bin").into_os_string();
+
+    // The plugin directories come first, then the `PATH`...
+    let found = cargo_gluegun::find_plugin("both", &plugin_dirs, Some(&path))?;
+    assert_eq!(found, dir.child("plugins").join(exe("gluegun-both")));
+    let found = cargo_gluegun::find_plugin("path", &plugin_dirs, Some(&path))?;
+    assert_eq!(found, dir.child("bin").join(exe("gluegun-path")));
+
+    // ...and a plugin found in neither is an error naming where it was looked for.
+    let err = cargo_gluegun::find_plugin("missing", &plugin_dirs, Some(&path)).expect_err("no `gluegun-missing`");
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "plugin `missing` not found: there is no executable `{}` in `{}`, or on the `PATH`",
+            exe("gluegun-missing"),
+            dir.child("plugins").display(),
+        )
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn run_plugin_from_plugin_dir() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // A plugin built in the workspace, which records the input it is given next to itself.
+    std::fs::create_dir_all(dir.child("target/debug"))?;
+    create_executable(&dir, "target/debug/gluegun-stub")?;
+    std::fs::write(
+        dir.child("target/debug/gluegun-stub"),
+        format!("#!/bin/sh\n[ \"$1\" = --gg-info ] && exit 1\ncat > \"$0.json\"\n{}\n", handshake()),
+    )?;
+
+    // A relative `--plugin-dir` is relative to the current directory.
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "--plugin-dir", "target/debug", "stub"])?
+        .log_to(std::io::sink())
+        .execute()?;
+    let input: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.child("target/debug/gluegun-stub.json"))?)?;
+    assert_eq!(input["dest_crate"]["crate_name"], "greeter-stub");
+
+    // Without it, the plugin is not found (it is not on the `PATH`).
+    let err = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+        .log_to(std::io::sink())
+        .execute()
+        .expect_err("`gluegun-stub` is not on the `PATH`");
+    assert!(format!("{err:#}").contains("plugin `stub` not found"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+fn bash_completions() -> anyhow::Result<()> {
+    let mut script = vec![];
+    cargo_gluegun::write_completions("bash".parse().unwrap(), &["java".to_string(), "py".to_string()], &mut script)?;
+    let script = String::from_utf8(script)?;
+
+    // Flags and the plugins positional (completed from the discovered plugins)
+    let opts = script
+        .lines()
+        .find(|line| line.trim().starts_with("opts=\"") && line.contains("--manifest-path"))
+        .ok_or_else(|| anyhow::anyhow!("no `opts` line in completion script:\n{script}"))?;
+    for expected in ["--manifest-path", "--package", "--workspace", "--jobs", "java", "py"] {
+        assert!(opts.split_whitespace().any(|w| w.trim_matches('"') == expected), "`{expected}` not completed: {opts}");
+    }
+    Ok(())
+}
+
+#[test]
+fn unsupported_type_diagnostic() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let rs_path = dir.child("lib.rs");
+    std::fs::write(&rs_path, "pub fn f(x: &'static u32) {}\n")?;
+
+    let err = gluegun_idl::Parser::new()
+        .parse_crate_named("bad", dir.path(), &rs_path)
+        .map_err(anyhow::Error::from)
+        .expect_err("named lifetimes are not supported");
+
+    let json = serde_json::to_value(cargo_gluegun::Diagnostic::from_error(&err))?;
+    assert_eq!(json["level"], "error");
+
+    // The span covers the lifetime
+    let span = &json["spans"][0];
+    assert_eq!(span["file_name"], rs_path.display().to_string());
+    assert_eq!(span["line_start"], 1);
+    assert_eq!(span["line_end"], 1);
+    assert_eq!(span["column_start"], 14);
+    assert_eq!(span["column_end"], 21);
+    assert_eq!(span["byte_start"], 13);
+    assert_eq!(span["byte_end"], 20);
+    assert_eq!(span["is_primary"], true);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn raw_mode_runs_plugin_without_cargo() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::create_dir(dir.child("src"))?;
+    std::fs::write(
+        dir.child("src/lib.rs"),
+        "pub fn greet(name: &str) -> String { format!(\"Hello, {name}!\") }\n",
+    )?;
+    let input_path = dir.child("input.json");
+
+    // A stub plugin that just records the JSON it is given on stdin.
+    let stub_input_path = input_path.clone();
+    cargo_gluegun::Builder::new(
+        dir.path(),
+        [
+            "cargo-gluegun", "--raw", "--package-name", "demo", "--src", "src/lib.rs", "--plugin", "stub", "--out",
+            "out/demo-stub",
+        ],
+    )?
+    .plugin_command(move |_gluegun_metadata, plugin| {
+        assert_eq!(plugin, "stub");
+        let mut c = std::process::Command::new("sh");
+        c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+        Ok(c)
+    })
+    .execute()?;
+
+    let input: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&input_path)?)?;
+    assert_eq!(input["dest_crate"]["crate_name"], "demo-stub");
+    assert_eq!(input["dest_crate"]["path"], dir.child("out/demo-stub").display().to_string());
+    assert_eq!(input["metadata"], serde_json::Value::Null);
+
+    let idl: gluegun_idl::Idl = serde_json::from_value(input["idl"].clone())?;
+    assert_eq!(idl.crate_name().text(), "demo");
+    assert!(
+        idl.definitions().keys().any(|qname| qname.to_string("::") == "demo::greet"),
+        "`greet` missing from IDL: {:?}",
+        idl.definitions().keys()
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn skip_packages_without_library() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"app\", \"greeter\"]\nresolver = \"2\"\n")?;
+    for (name, file, contents) in [
+        ("app", "main.rs", "fn main() {}\n"),
+        ("greeter", "greet.rs", "pub fn greet(name: &str) -> String { format!(\"Hello, {name}!\") }\n"),
+    ] {
+        std::fs::create_dir_all(dir.child(name).join("src"))?;
+        std::fs::write(dir.child(name).join("src").join(file), contents)?;
+    }
+    std::fs::write(
+        dir.child("app/Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/greet.rs\"\n",
+    )?;
+
+    // A stub plugin that appends the JSON it is given on stdin to `inputs`.
+    let stub = |inputs_path: std::path::PathBuf| {
+        move |_gluegun_metadata: &serde_json::Value, _plugin: &str| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat >> \"$STUB_INPUTS\"; echo >> \"$STUB_INPUTS\"; {}", handshake())).env("STUB_INPUTS", &inputs_path);
+            Ok(c)
+        }
+    };
+
+    // Across the workspace, the binary-only `app` is skipped and `greeter` is processed from its `[lib].path`.
+    let inputs_path = dir.child("inputs");
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "--workspace", "stub"])?
+        .plugin_command(stub(inputs_path.clone()))
+        .execute()?;
+    let inputs = std::fs::read_to_string(&inputs_path)?;
+    let inputs: Vec<serde_json::Value> = serde_json::Deserializer::from_str(&inputs)
+        .into_iter()
+        .collect::<Result<_, _>>()?;
+    assert_eq!(inputs.len(), 1, "plugin should only run for `greeter`");
+    assert_eq!(inputs[0]["dest_crate"]["crate_name"], "greeter-stub");
+
+    // Naming the binary-only package on its own is still an error.
+    let err = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "app", "stub"])?
+        .plugin_command(stub(dir.child("unused")))
+        .execute()
+        .expect_err("`app` has no library");
+    assert!(
+        format!("{err:#}").contains("package `app` has no library target; gluegun only processes libraries"),
+        "unexpected error: {err:#}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn include_dependencies() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\", \"helper\"]\nresolver = \"2\"\n")?;
+    for name in ["greeter", "helper"] {
+        std::fs::create_dir_all(dir.child(name).join("src"))?;
+        std::fs::write(dir.child(name).join("src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    }
+    std::fs::write(
+        dir.child("helper/Cargo.toml"),
+        "[package]\nname = \"helper\"\nversion = \"0.3.1\"\nedition = \"2021\"\n",
+    )?;
+    let greeter_manifest = "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+        [dependencies]\nhelper = { path = \"../helper\", version = \"0.3\" }\n";
+    std::fs::write(dir.child("greeter/Cargo.toml"), greeter_manifest)?;
+
+    // A stub plugin that records the JSON it is given on stdin.
+    let run = |input_path: std::path::PathBuf| -> anyhow::Result<serde_json::Value> {
+        let stub_input_path = input_path.clone();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+                Ok(c)
+            })
+            .execute()?;
+        Ok(serde_json::from_str(&std::fs::read_to_string(&input_path)?)?)
+    };
+
+    // Dependencies are only given to plugins when requested.
+    let input = run(dir.child("without.json"))?;
+    assert_eq!(input["dependencies"], serde_json::Value::Null);
+
+    // The version is the resolved one, not the `0.3` requirement.
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        format!("{greeter_manifest}\n[package.metadata.gluegun]\ninclude-dependencies = true\n"),
+    )?;
+    let input = run(dir.child("with.json"))?;
+    assert_eq!(
+        input["dependencies"],
+        serde_json::json!([{ "name": "helper", "version": "0.3.1", "source": null, "kinds": ["normal"] }])
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn merge_metadata_arrays() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.child("Cargo.toml"),
+        "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n\n\
+        [workspace.metadata.gluegun.stub]\nflags = [\"--workspace-flag\"]\n",
+    )?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+
+    // A stub plugin that records the JSON it is given on stdin.
+    let run = |package_metadata: &str, input_path: std::path::PathBuf| -> anyhow::Result<serde_json::Value> {
+        std::fs::write(
+            dir.child("greeter/Cargo.toml"),
+            format!(
+                "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                [package.metadata.gluegun.stub]\n{package_metadata}\n"
+            ),
+        )?;
+        let stub_input_path = input_path.clone();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+                Ok(c)
+            })
+            .execute()?;
+        Ok(serde_json::from_str(&std::fs::read_to_string(&input_path)?)?)
+    };
+
+    // By default, the package array replaces the workspace array.
+    let input = run("flags = [\"--package-flag\"]", dir.child("replace.json"))?;
+    assert_eq!(input["metadata"]["flags"], serde_json::json!(["--package-flag"]));
+
+    // A `$concat` directive appends to it instead.
+    let input = run("flags = { \"$concat\" = [\"--package-flag\"] }", dir.child("concat.json"))?;
+    assert_eq!(input["metadata"]["flags"], serde_json::json!(["--workspace-flag", "--package-flag"]));
+
+    // Without a workspace array, the directive stands for its elements.
+    let input = run("extra = { \"$concat\" = [\"--package-flag\"] }", dir.child("alone.json"))?;
+    assert_eq!(input["metadata"]["extra"], serde_json::json!(["--package-flag"]));
+    assert_eq!(input["metadata"]["flags"], serde_json::json!(["--workspace-flag"]));
+    Ok(())
+}
+
+#[test]
+fn merge_plugin_metadata() -> anyhow::Result<()> {
+    let workspace = serde_json::json!({
+        "split-modules": true,
+        "java": { "edition": "2021", "flags": ["--workspace-flag"] },
+    });
+    let package = serde_json::json!({
+        "java": { "flags": { "$concat": ["--package-flag"] } },
+        "py": { "edition": "2024" },
+    });
+
+    // The plugin's table in the package replaces the workspace's table as a whole...
+    let merged = cargo_gluegun::merge_gluegun_metadata(Some(&workspace), Some(&package))?;
+    assert_eq!(merged["split-modules"], serde_json::json!(true));
+    assert_eq!(merged["java"], serde_json::json!({ "flags": ["--package-flag"] }));
+
+    // ...but the plugin's own configuration merges the two tables key by key.
+    let java = cargo_gluegun::merge_plugin_metadata(Some(&workspace), Some(&package), "java")?;
+    assert_eq!(java, serde_json::json!({ "edition": "2021", "flags": ["--workspace-flag", "--package-flag"] }));
+    let py = cargo_gluegun::merge_plugin_metadata(Some(&workspace), Some(&package), "py")?;
+    assert_eq!(py, serde_json::json!({ "edition": "2024" }));
+    let c = cargo_gluegun::merge_plugin_metadata(Some(&workspace), None, "c")?;
+    assert_eq!(c, serde_json::Value::Null);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn quoted_plugin_command() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let args_path = dir.child("my args.txt");
+
+    // The stub plugin writes each of its arguments on a line of its own to the file named by its first argument.
+    let plugin_command = format!(
+        r#"sh -c 'cat > /dev/null; printf "%s\n" "$@" > "$0"; {}' "{}" gluegun-{{plugin}} "with space" "it's" '{{plugin}} quoted'"#,
+        handshake(),
+        args_path.display()
+    );
+    std::fs::write(
+        dir.child("Cargo.toml"),
+        format!(
+            "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n\n\
+            [workspace.metadata.gluegun]\nplugin-command = {}\n",
+            serde_json::to_string(&plugin_command)?
+        ),
+    )?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?.execute()?;
+
+    // The configured arguments come first, followed by those added by cargo-gluegun.
+    let args = std::fs::read_to_string(&args_path)?;
+    let args: Vec<_> = args.lines().collect();
+    assert_eq!(args[..4], ["gluegun-stub", "with space", "it's", "stub quoted"], "unexpected arguments: {args:?}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn parallel_jobs() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\", \"helper\"]\nresolver = \"2\"\n")?;
+    for name in ["greeter", "helper"] {
+        std::fs::create_dir_all(dir.child(name).join("src"))?;
+        std::fs::write(dir.child(name).join("src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+        std::fs::write(
+            dir.child(name).join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"),
+        )?;
+    }
+    let inputs_dir = dir.child("inputs");
+    std::fs::create_dir(&inputs_dir)?;
+
+    // The `stub` plugin records the JSON it is given on stdin in a file of its own
+    // (when it is run to generate a crate, not when asked about itself); the `broken` plugin fails.
+    let stub_inputs_dir = inputs_dir.clone();
+    let err = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "--workspace", "--jobs", "2", "stub", "broken"])?
+        .plugin_command(move |_gluegun_metadata, plugin| {
+            let mut c = std::process::Command::new("sh");
+            match plugin {
+                "stub" => c
+                    .arg("-c")
+                    .arg(format!("[ \"$0\" = gg-stub ] || exit 1; cat > \"$STUB_INPUTS/$$.json\"; {}", handshake()))
+                    .env("STUB_INPUTS", &stub_inputs_dir),
+                _ => c.arg("-c").arg(format!("cat > /dev/null; {}; exit 3", handshake())),
+            };
+            Ok(c)
+        })
+        .execute()
+        .expect_err("`broken` fails");
+
+    // Both packages were processed by `stub`, and both failures of `broken` were reported.
+    let mut crate_names = vec![];
+    for entry in std::fs::read_dir(&inputs_dir)? {
+        let input: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(entry?.path())?)?;
+        crate_names.push(input["dest_crate"]["crate_name"].as_str().unwrap_or_default().to_string());
+    }
+    crate_names.sort();
+    assert_eq!(crate_names, ["greeter-stub", "helper-stub"]);
+    assert!(format!("{err:#}").contains("2 failure(s)"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn plugin_args() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // A stub plugin that records its arguments, one per line (`$0` is the first, as it is run by `sh -c`).
+    let argv_path = dir.child("argv.txt");
+    let stub_argv_path = argv_path.clone();
+    cargo_gluegun::Builder::new(
+        dir.path(),
+        ["cargo-gluegun", "-p", "greeter", "stub", "--", "--trace", "two words"],
+    )?
+    .log_to(std::io::sink())
+    .plugin_command(move |_gluegun_metadata, _plugin| {
+        let mut c = std::process::Command::new("sh");
+        c.arg("-c")
+            .arg(format!("[ \"$0\" = --gg-info ] && exit 1; printf '%s\\n' \"$0\" \"$@\" > \"$STUB_ARGV\"; cat > /dev/null; {}", handshake()))
+            .env("STUB_ARGV", &stub_argv_path);
+        Ok(c)
+    })
+    .execute()?;
+
+    // The arguments after `--` follow the `gg-stub` argument, each as given.
+    assert_eq!(std::fs::read_to_string(&argv_path)?, "gg-stub\n--trace\ntwo words\n");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn dry_run() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let workspace_manifest = "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n\n\
+        [workspace.metadata.gluegun]\nexclude-from-workspace = true\ndestination-path = \"sibling\"\n";
+    std::fs::write(dir.child("Cargo.toml"), workspace_manifest)?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // A stub plugin that records the JSON it is given on stdin, if it is ever run.
+    let input_path = dir.child("input.json");
+    let stub_input_path = input_path.clone();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "--dry-run", "stub"])?
+        .plugin_command(move |_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+            Ok(c)
+        })
+        .execute()?;
+
+    // Neither the plugin nor the workspace update were run.
+    assert!(!input_path.exists(), "plugin was spawned");
+    assert_eq!(std::fs::read_to_string(dir.child("Cargo.toml"))?, workspace_manifest);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn plugin_input_round_trip() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\nrust-version = \"1.80.0\"\n\n\
+        [package.metadata.gluegun]\nsplit-modules = true\ninclude-dependencies = true\n\n\
+        [package.metadata.gluegun.stub]\nverbose = true\n",
+    )?;
+
+    // A stub plugin that records the JSON it is given on stdin.
+    let input_path = dir.child("input.json");
+    let stub_input_path = input_path.clone();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+        .plugin_command(move |_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+            Ok(c)
+        })
+        .execute()?;
+
+    // Plugins read the payload with their own metadata type...
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&input_path)?)?;
+    let input: gluegun_core::protocol::PluginInput<std::collections::BTreeMap<String, bool>> =
+        serde_json::from_value(json.clone())?;
+    assert_eq!(input.protocol_version, gluegun_core::protocol::PROTOCOL_VERSION);
+    assert!(input.metadata["verbose"]);
+    assert_eq!(input.idl.crate_name().text(), "greeter");
+    assert_eq!(input.dependencies, Some(vec![]));
+    assert_eq!(input.dest_crate.crate_name, "greeter-stub");
+    assert_eq!(input.dest_crate.path, dir.child("greeter/greeter-stub"));
+    assert!(input.dest_crate.split_modules);
+    assert_eq!(input.dest_crate.edition.as_deref(), Some("2021"));
+    assert_eq!(input.dest_crate.rust_version.as_deref(), Some("1.80.0"));
+
+    // ...and nothing is lost along the way.
+    assert_eq!(serde_json::to_value(&input)?, json);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn per_plugin_destination_path() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(
+        dir.child("Cargo.toml"),
+        "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n\n\
+        [workspace.metadata.gluegun]\ndestination-path = \"sibling\"\n",
+    )?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+        [package.metadata.gluegun.child]\ndestination-path = \"child\"\n",
+    )?;
+
+    // Stub plugins that record the JSON they are given on stdin, in a file named after the plugin.
+    let stub_dir = dir.path().to_path_buf();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "child", "sibling"])?
+        .plugin_command(move |_gluegun_metadata, plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c")
+                .arg(format!("cat > \"$STUB_INPUT\"; {}", handshake()))
+                .env("STUB_INPUT", stub_dir.join(format!("{plugin}.json")));
+            Ok(c)
+        })
+        .execute()?;
+
+    let input = |plugin: &str| -> anyhow::Result<gluegun_core::protocol::PluginInput<serde_json::Value>> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(dir.child(format!("{plugin}.json")))?)?)
+    };
+
+    // The plugin's own setting overrides the workspace's, and is not passed on to the plugin...
+    let child = input("child")?;
+    assert_eq!(child.dest_crate.path, dir.child("greeter/greeter-child"));
+    assert_eq!(child.metadata, serde_json::Value::Null);
+
+    // ...while other plugins fall back to the setting for all plugins.
+    let sibling = input("sibling")?;
+    assert_eq!(sibling.dest_crate.path, dir.child("greeter-sibling"));
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn invalid_destination_path() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+        [package.metadata.gluegun.stub]\ndestination-path = \"elsewhere\"\n",
+    )?;
+
+    let err = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+        .plugin_command(|_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > /dev/null; {}", handshake()));
+            Ok(c)
+        })
+        .execute()
+        .expect_err("`elsewhere` is not a destination path");
+    assert!(format!("{err:#}").contains("found `\"elsewhere\"`"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+/// Run a stub plugin on a `greeter` package whose `package.metadata.gluegun` is `gluegun_metadata`,
+/// returning the input it was given.
+#[cfg(unix)]
+fn stub_input_with_metadata(
+    dir: &TempDir,
+    gluegun_metadata: &str,
+) -> anyhow::Result<gluegun_core::protocol::PluginInput<serde_json::Value>> {
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        format!("[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[package.metadata.gluegun]\n{gluegun_metadata}"),
+    )?;
+
+    let input_path = dir.child("input.json");
+    let stub_input_path = input_path.clone();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+        .plugin_command(move |_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+            Ok(c)
+        })
+        .execute()?;
+    Ok(serde_json::from_str(&std::fs::read_to_string(&input_path)?)?)
+}
+
+#[test]
+#[cfg(unix)]
+fn crate_name_template() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let input = stub_input_with_metadata(&dir, "crate-name-template = \"{package}_ffi_{plugin}\"\n")?;
+    assert_eq!(input.dest_crate.crate_name, "greeter_ffi_stub");
+    assert_eq!(input.dest_crate.path, dir.child("greeter/greeter_ffi_stub"));
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn merge_into_existing_crate() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::create_dir_all(dir.child("app"))?;
+    std::fs::write(dir.child("app/Cargo.toml"), "[package]\nname = \"greeter-app\"\nversion = \"0.1.0\"\n")?;
+
+    // The plugin generates the `generated` subdirectory of the crate, under the crate's name.
+    let input = stub_input_with_metadata(&dir, "merge-into = \"../app\"\n")?;
+    assert!(input.dest_crate.merge);
+    assert_eq!(input.dest_crate.crate_name, "greeter-app");
+    assert_eq!(input.dest_crate.path, dir.child("greeter/../app/generated"));
+
+    // The path must name a crate.
+    let err = stub_input_with_metadata(&dir, "merge-into = \"../nowhere\"\n").expect_err("`nowhere` is not a crate");
+    assert!(format!("{err:#}").contains("must name an existing crate"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn invalid_crate_name_template() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let err = stub_input_with_metadata(&dir, "crate-name-template = \"{package}.{plugin}\"\n")
+        .expect_err("`greeter.stub` is not a valid crate name");
+    assert!(
+        format!("{err:#}").contains("invalid crate name `greeter.stub`: invalid character `.`"),
+        "unexpected error: {err:#}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn idl_is_cached_until_sources_change() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+    let lib_rs = dir.child("greeter/src/lib.rs");
+    std::fs::write(&lib_rs, "pub fn greet() -> String { String::new() }\n")?;
+
+    // Run a stub plugin, returning the names of the functions in the IDL it is given.
+    let input_path = dir.child("input.json");
+    let run = || -> anyhow::Result<Vec<String>> {
+        let stub_input_path = input_path.clone();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub"])?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+                Ok(c)
+            })
+            .execute()?;
+        let input: gluegun_core::protocol::PluginInput<serde_json::Value> =
+            serde_json::from_str(&std::fs::read_to_string(&input_path)?)?;
+        Ok(input.idl.definitions().keys().map(|qname| qname.to_string("::")).collect())
+    };
+
+    assert_eq!(run()?, ["greeter::greet"]);
+    assert_eq!(std::fs::read_dir(dir.child("target/gluegun/idl"))?.count(), 1);
+
+    // While the modification time of `lib.rs` is unchanged, the cached IDL is used...
+    let modified = std::fs::metadata(&lib_rs)?.modified()?;
+    std::fs::write(&lib_rs, "pub fn greet() -> String { String::new() }\npub fn wave() {}\n")?;
+    std::fs::File::options().write(true).open(&lib_rs)?.set_modified(modified)?;
+    assert_eq!(run()?, ["greeter::greet"]);
+
+    // ...and once it changes, the crate is parsed again.
+    std::fs::File::options().write(true).open(&lib_rs)?.set_modified(modified + std::time::Duration::from_secs(1))?;
+    assert_eq!(run()?, ["greeter::greet", "greeter::wave"]);
+    Ok(())
+}
+
+/// Run a stub plugin on a `shapes` crate with the given `package.metadata.gluegun.stub`,
+/// returning the names of the items in the IDL it is given.
+#[cfg(unix)]
+fn items_bound_with(plugin_metadata: &str) -> anyhow::Result<Vec<String>> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"shapes\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("shapes/src"))?;
+    std::fs::write(
+        dir.child("shapes/src/lib.rs"),
+        "pub struct Point { pub x: i32, pub y: i32 }\n\
+        pub struct Segment { pub from: Point, pub to: Point }\n\
+        pub struct Label { pub text: String }\n\
+        pub fn length(segment: &Segment) -> f64 { 0.0 }\n\
+        pub fn label() -> Label { Label { text: String::new() } }\n\
+        pub mod extra { pub fn wave() {} }\n",
+    )?;
+    std::fs::write(
+        dir.child("shapes/Cargo.toml"),
+        format!("[package]\nname = \"shapes\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[package.metadata.gluegun.stub]\n{plugin_metadata}"),
+    )?;
+
+    let input_path = dir.child("input.json");
+    let stub_input_path = input_path.clone();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "shapes", "stub"])?
+        .plugin_command(move |_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &stub_input_path);
+            Ok(c)
+        })
+        .execute()?;
+    let input: gluegun_core::protocol::PluginInput<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(&input_path)?)?;
+
+    // The filter is not passed on to the plugin.
+    assert_eq!(input.metadata, serde_json::Value::Null);
+    Ok(input.idl.definitions().keys().map(|qname| qname.colon_colon()).collect())
+}
+
+#[test]
+#[cfg(unix)]
+fn include_items_with_referenced_types() -> anyhow::Result<()> {
+    // `length` takes a `Segment`, whose fields are `Point`s.
+    assert_eq!(
+        items_bound_with("include = [\"shapes::length\", \"shapes::extra\"]\n")?,
+        ["shapes::Point", "shapes::Segment", "shapes::extra::wave", "shapes::length"]
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn exclude_items() -> anyhow::Result<()> {
+    assert_eq!(
+        items_bound_with("exclude = [\"shapes::label\", \"shapes::Label\", \"shapes::extra\"]\n")?,
+        ["shapes::Point", "shapes::Segment", "shapes::length"]
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn plugin_protocol_version_mismatch() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // Stub plugins that give the handshake line `handshake`.
+    let run = |handshake: &'static str, args: &[&str]| {
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter"].iter().chain(args))?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("cat > /dev/null; echo {handshake}"));
+                Ok(c)
+            })
+            .execute()
+    };
+
+    // A plugin speaking another version is named along with both versions.
+    let err = run("gluegun-protocol 999", &["stub"]).expect_err("version mismatch");
+    let message = format!("{err:#}");
+    assert!(message.contains("gluegun-stub uses plugin protocol version 999"), "unexpected error: {message}");
+    let expected = format!("cargo-gluegun uses version {}", gluegun_core::protocol::PROTOCOL_VERSION);
+    assert!(message.contains(&expected), "unexpected error: {message}");
+
+    // The handshake is also checked when the plugin's output is captured.
+    run("gluegun-protocol 999", &["--jobs", "2", "stub"]).expect_err("version mismatch");
+
+    // So is a plugin that predates the handshake.
+    let err = run("Hello", &["stub"]).expect_err("no handshake");
+    assert!(format!("{err:#}").contains("gluegun-stub did not report"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn unsupported_items_are_reported_together() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // A stub plugin that reports two unsupported constructs;
+    // it exits successfully, so that only the reports can make the run fail.
+    let lines: Vec<_> = [("greeter::Widget", "traits cannot be mapped yet"), ("i128", "no such type")]
+        .into_iter()
+        .map(|(subject, reason)| {
+            let error = gluegun_core::protocol::UnsupportedError::new(subject, reason);
+            format!("'{}'", gluegun_core::protocol::unsupported_line(&error))
+        })
+        .collect();
+    let script = format!("cat > /dev/null; {}; printf '%s\\n' {}", handshake(), lines.join(" "));
+    let run = |args: &[&str]| {
+        let script = script.clone();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter"].iter().chain(args))?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(&script);
+                Ok(c)
+            })
+            .execute()
+    };
+
+    // Both are counted...
+    let err = run(&["stub"]).expect_err("`stub` does not support everything");
+    let message = format!("{err:#}");
+    assert!(message.contains("binding `greeter` with gluegun-stub"), "unexpected error: {message}");
+    assert!(message.contains("2 items could not be bound"), "unexpected error: {message}");
+
+    // ...also when the plugin's output is captured.
+    let err = run(&["--jobs", "2", "stub"]).expect_err("`stub` does not support everything");
+    assert!(format!("{err:#}").contains("1 failure(s)"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+/// A log sink whose contents the test can read once cargo-gluegun is done with it.
+#[derive(Clone, Default)]
+struct SharedLog(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for SharedLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn verbosity_flags() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // The messages logged when running a stub plugin (predating `--gg-info`) with `args`.
+    let run = |args: &[&str]| -> anyhow::Result<String> {
+        let log = SharedLog::default();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter"].iter().chain(args))?
+            .plugin_command(|_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("[ \"$0\" = --gg-info ] && exit 1; cat > /dev/null; {}", handshake()));
+                Ok(c)
+            })
+            .log_to(log.clone())
+            .execute()?;
+        let log = log.0.lock().unwrap();
+        Ok(String::from_utf8(log.clone())?)
+    };
+
+    // By default, one line per plugin run and a summary of the crates generated...
+    let normal = run(&["stub"])?;
+    assert_eq!(normal.lines().count(), 3, "unexpected log:\n{normal}");
+    assert!(normal.starts_with("gluegun-stub: generating `greeter-stub`"), "unexpected log:\n{normal}");
+    assert!(normal.contains("\ngenerated 1 crate(s)\n"), "unexpected log:\n{normal}");
+
+    // ...nothing with `--quiet`...
+    let quiet = run(&["-q", "stub"])?;
+    assert_eq!(quiet, "", "unexpected log:\n{quiet}");
+
+    // ...and also the command and the size of the input with `--verbose`.
+    let verbose = run(&["--verbose", "stub"])?;
+    assert!(normal.lines().all(|line| verbose.contains(line)), "unexpected log:\n{verbose}");
+    assert!(verbose.contains("gluegun-stub: running ") && verbose.contains("\"gg-stub\""), "unexpected log:\n{verbose}");
+    assert!(verbose.contains("gluegun-stub: sent "), "unexpected log:\n{verbose}");
+
+    // The two flags cannot be combined.
+    run(&["-q", "-v", "stub"]).expect_err("`--quiet` conflicts with `--verbose`");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn summary_report() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() {}\npub fn wave() {}\npub fn nod() {}\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+        [package.metadata.gluegun.other]\nexclude = [\"greeter::wave\", \"greeter::nod\"]\n",
+    )?;
+
+    // A stub plugin, predating `--gg-info`, so that the log holds nothing about asking it for its metadata keys.
+    let run = |args: &[&str]| -> anyhow::Result<String> {
+        let log = SharedLog::default();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter"].iter().chain(args))?
+            .plugin_command(|_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("[ \"$0\" = --gg-info ] && exit 1; cat > /dev/null; {}", handshake()));
+                Ok(c)
+            })
+            .log_to(log.clone())
+            .execute()?;
+        let log = log.0.lock().unwrap();
+        Ok(String::from_utf8(log.clone())?)
+    };
+
+    // The summary lists each crate generated, in the order of the plugins, with the items left out by `exclude`.
+    let greeter = dir.child("greeter");
+    let expected = format!(
+        "generated 2 crate(s)\n    \
+        `greeter-stub` in `{stub}` (gluegun-stub on `greeter`): 3 item(s) bound, 0 skipped\n    \
+        `greeter-other` in `{other}` (gluegun-other on `greeter`): 1 item(s) bound, 2 skipped\n",
+        stub = greeter.join("greeter-stub").display(),
+        other = greeter.join("greeter-other").display(),
+    );
+    for args in [&["stub", "other"][..], &["--jobs", "2", "stub", "other"]] {
+        let log = run(args)?;
+        assert!(log.ends_with(&expected), "unexpected log:\n{log}");
+    }
+
+    // There is no summary with `--quiet`, nor for a `--dry-run`, which generates nothing.
+    assert_eq!(run(&["-q", "stub"])?, "");
+    assert!(!run(&["--dry-run", "stub"])?.contains("generated"));
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn captured_plugin_output() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // Stub plugins that print their name on stdout and stderr; `broken` then fails.
+    let run = |args: &[&str]| {
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter"].iter().chain(args))?
+            .plugin_command(|_gluegun_metadata, plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!(
+                    "cat > /dev/null; {}; echo \"out from {plugin}\"; echo \"err from {plugin}\" >&2; [ {plugin} != broken ]",
+                    handshake()
+                ));
+                Ok(c)
+            })
+            .capture_output(true)
+            .log_to(std::io::sink())
+            .execute()
+    };
+
+    // The output of each run is returned, less the handshake, in the order of the plugins...
+    for args in [&["stub", "other"][..], &["--jobs", "2", "stub", "other"]] {
+        let outputs = run(args)?;
+        let outputs: Vec<_> = outputs
+            .iter()
+            .map(|output| {
+                (
+                    &output.package[..],
+                    &output.plugin[..],
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                ("greeter", "stub", "out from stub\n".into(), "err from stub\n".into()),
+                ("greeter", "other", "out from other\n".into(), "err from other\n".into()),
+            ],
+            "with {args:?}"
+        );
+    }
+
+    // ...and the stderr of a failing plugin is part of the error.
+    let err = run(&["broken"]).expect_err("`broken` fails");
+    assert!(format!("{err:#}").contains("printing:\nerr from broken"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn plugin_command_with_dest() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // Stub plugins that record their input next to the crate they are given by the hook.
+    let inputs_dir = dir.child("inputs");
+    std::fs::create_dir(&inputs_dir)?;
+    let stub_inputs_dir = inputs_dir.clone();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "stub", "other"])?
+        .plugin_command_with_dest(move |_gluegun_metadata, _plugin, dest_crate| {
+            let dest_crate = dest_crate.expect("plugins are run for a destination crate");
+            assert_eq!(dest_crate.path.file_name(), Some(dest_crate.crate_name.as_ref()));
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c")
+                .arg(format!("cat > \"$STUB_INPUTS/$CRATE_NAME.json\"; {}", handshake()))
+                .env("STUB_INPUTS", &stub_inputs_dir)
+                .env("CRATE_NAME", &dest_crate.crate_name);
+            Ok(c)
+        })
+        .execute()?;
+
+    for crate_name in ["greeter-stub", "greeter-other"] {
+        let input: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(inputs_dir.join(format!("{crate_name}.json")))?)?;
+        assert_eq!(input["dest_crate"]["crate_name"], crate_name);
+    }
+    Ok(())
+}
+
+#[test]
+fn extract_idl() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"app\", \"greeter\"]\nresolver = \"2\"\n")?;
+    for (name, file, contents) in [
+        ("app", "main.rs", "fn main() {}\n"),
+        ("greeter", "greet.rs", "pub fn greet(name: &str) -> String { format!(\"Hello, {name}!\") }\n"),
+    ] {
+        std::fs::create_dir_all(dir.child(name).join("src"))?;
+        std::fs::write(dir.child(name).join("src").join(file), contents)?;
+    }
+    std::fs::write(
+        dir.child("app/Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[lib]\npath = \"src/greet.rs\"\n",
+    )?;
+
+    // The binary-only package is skipped, and the library is found wherever `[lib].path` puts it, as for plugins.
+    let idls = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "--workspace"])?
+        .log_to(std::io::sink())
+        .extract_idl()?;
+    assert_eq!(idls.len(), 1);
+    let (package_id, idl) = &idls[0];
+    assert!(package_id.repr.contains("greeter"), "unexpected package: {package_id}");
+    let expected =
+        gluegun_idl::Parser::new().parse_crate_named("greeter", dir.child("greeter"), dir.child("greeter/src/greet.rs"))?;
+    assert_eq!(serde_json::to_value(idl)?, serde_json::to_value(&expected)?);
+
+    // Naming the binary-only package on its own is an error.
+    let err = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "app"])?
+        .extract_idl()
+        .expect_err("`app` has no library");
+    assert!(format!("{err:#}").contains("package `app` has no library target"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+fn package_glob() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let names = ["billing_api", "orders_api", "users_api", "core"];
+    std::fs::write(
+        dir.child("Cargo.toml"),
+        format!("[workspace]\nmembers = {names:?}\ndefault-members = [\"core\"]\nresolver = \"2\"\n"),
+    )?;
+    for name in names {
+        std::fs::create_dir_all(dir.child(name).join("src"))?;
+        std::fs::write(dir.child(name).join("src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+        std::fs::write(
+            dir.child(name).join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n"),
+        )?;
+    }
+
+    // The names of the crates whose interface is extracted with the given arguments.
+    let selected = |args: &[&str]| -> anyhow::Result<Vec<String>> {
+        let idls = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun"].iter().chain(args))?
+            .log_to(std::io::sink())
+            .extract_idl()?;
+        let mut names: Vec<_> = idls.iter().map(|(_, idl)| idl.crate_name().to_string()).collect();
+        names.sort();
+        Ok(names)
+    };
+
+    // On its own, a glob selects the packages it matches, not the default ones as well.
+    assert_eq!(selected(&["--package-glob", "*_api"])?, ["billing_api", "orders_api", "users_api"]);
+
+    // Excludes win over glob matches.
+    assert_eq!(
+        selected(&["--package-glob", "*_api", "--exclude", "billing_api"])?,
+        ["orders_api", "users_api"],
+    );
+    assert_eq!(
+        selected(&["--package-glob", "*_api", "--package-glob", "c*", "--exclude", "users_api", "--exclude", "core"])?,
+        ["billing_api", "orders_api"],
+    );
+
+    // Glob matches add to the packages selected with `-p`.
+    assert_eq!(selected(&["-p", "core", "--package-glob", "u*"])?, ["core", "users_api"]);
+
+    // A glob matching nothing selects nothing.
+    let err = selected(&["--package-glob", "*_cli"]).expect_err("no package matches");
+    assert!(format!("{err:#}").contains("no packages selected"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn existing_destination_is_not_overwritten() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    // An unrelated crate that happens to be where `stub` generates `greeter-stub`.
+    std::fs::create_dir_all(dir.child("greeter/greeter-stub"))?;
+    std::fs::write(dir.child("greeter/greeter-stub/Cargo.toml"), "[package]\nname = \"mine\"\n")?;
+
+    // A stub plugin that records that it ran.
+    let ran_path = dir.child("ran");
+    let run = |args: &[&str]| {
+        let ran_path = ran_path.clone();
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter"].iter().chain(args))?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(format!("cat > /dev/null; touch \"$RAN\"; {}", handshake())).env("RAN", &ran_path);
+                Ok(c)
+            })
+            .execute()
+    };
+
+    // The plugin is not run...
+    let err = run(&["stub"]).expect_err("`greeter-stub` was not generated by gluegun");
+    assert!(
+        format!("{err:#}").contains("greeter-stub` already exists and was not generated by gluegun"),
+        "unexpected error: {err:#}"
+    );
+    run(&["--jobs", "2", "stub"]).expect_err("`greeter-stub` was not generated by gluegun");
+    assert!(!ran_path.exists(), "the plugin ran");
+
+    // ...unless `--force` is given...
+    run(&["--force", "stub"])?;
+    assert!(ran_path.exists());
+    std::fs::remove_file(&ran_path)?;
+
+    // ...or the crate was generated by gluegun after all.
+    std::fs::write(dir.child("greeter/greeter-stub").join(gluegun_core::protocol::GENERATED_MARKER), "")?;
+    run(&["stub"])?;
+    assert!(ran_path.exists());
+    Ok(())
+}
+
+#[test]
+fn generated_marker_round_trip() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let marker = gluegun_core::protocol::GeneratedMarker::new("greeter", "java");
+    assert!(marker.generated_at > 0);
+    marker.write(dir.path())?;
+    assert_eq!(gluegun_core::protocol::GeneratedMarker::read(dir.path())?, marker);
+
+    std::fs::write(dir.child(gluegun_core::protocol::GENERATED_MARKER), "not a marker")?;
+    gluegun_core::protocol::GeneratedMarker::read(dir.path()).expect_err("the marker is not JSON");
+    Ok(())
+}
+
+#[test]
+fn dump_idl() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(
+        dir.child("greeter/src/lib.rs"),
+        "pub fn wave() {}\npub fn greet(name: &str) -> String { name.to_string() }\n",
+    )?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "dump-idl", "greeter", "--compact"])?.execute()?;
+    let err = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "dump-idl", "nobody"])?
+        .execute()
+        .expect_err("there is no `nobody` package");
+    assert!(format!("{err:#}").contains("no package named `nobody`"), "unexpected error: {err:#}");
+
+    // The dump is the IDL given to plugins, with items sorted by name.
+    let idl = gluegun_idl::Parser::new().parse_crate_named("greeter", dir.child("greeter"), dir.child("greeter/src/lib.rs"))?;
+    let mut pretty = vec![];
+    cargo_gluegun::write_idl(&idl, false, &mut pretty)?;
+    let mut compact = vec![];
+    cargo_gluegun::write_idl(&idl, true, &mut compact)?;
+    assert_eq!(String::from_utf8(compact.clone())?.lines().count(), 1);
+
+    let json: serde_json::Value = serde_json::from_slice(&pretty)?;
+    assert_eq!(json, serde_json::from_slice::<serde_json::Value>(&compact)?);
+    // Source paths are relative to the package root, so the dump does not depend on where the package lives.
+    assert_eq!(json["crate_path"], ".");
+    assert_eq!(json["definitions"][0][1]["Function"]["span"]["path"], "src/lib.rs");
+    assert!(!String::from_utf8(pretty)?.contains(&dir.path().display().to_string()));
+    let names: Vec<_> = json["definitions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|definition| definition[0]["names"][1]["text"].clone())
+        .collect();
+    assert_eq!(names, ["greet", "wave"]);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn list_plugins() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    create_executable(&dir, "gluegun-good")?;
+    std::fs::write(
+        dir.child("gluegun-good"),
+        "#!/bin/sh\n[ \"$1\" = --gg-info ] && echo '{\"name\":\"good\",\"version\":\"1.2.3\",\"language\":\"Klingon\"}'\n",
+    )?;
+    create_executable(&dir, "gluegun-old")?;
+    std::fs::write(dir.child("gluegun-old"), "#!/bin/sh\necho 'expected to be invoked by `cargo gluegun`' >&2\nexit 1\n")?;
+
+    // Plugins are run with the command given, plus `--gg-info`.
+    let plugins = cargo_gluegun::discover_plugins(Some(dir.path().as_os_str()));
+    let (listings, errors): (Vec<_>, Vec<_>) = cargo_gluegun::query_plugins(&plugins, |plugin| {
+        Ok(std::process::Command::new(dir.child(format!("gluegun-{plugin}"))))
+    })
+    .into_iter()
+    .unzip();
+
+    // The plugin that cannot be queried is listed all the same, with the error for the caller to report.
+    assert!(errors[0].is_none());
+    let err = errors[1].as_ref().expect("`old` cannot be queried");
+    assert!(format!("{err:#}").contains("gluegun-old does not support --gg-info"), "unexpected error: {err:#}");
+
+    let mut table = vec![];
+    cargo_gluegun::write_plugin_list(&listings, cargo_gluegun::ListFormat::Human, &mut table)?;
+    assert_eq!(
+        String::from_utf8(table)?,
+        "PLUGIN  VERSION  LANGUAGE\n\
+        good    1.2.3    Klingon\n\
+        old     ?        ?\n"
+    );
+
+    let mut json = vec![];
+    cargo_gluegun::write_plugin_list(&listings, cargo_gluegun::ListFormat::Json, &mut json)?;
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&json)?,
+        serde_json::json!([
+            { "name": "good", "version": "1.2.3", "language": "Klingon" },
+            { "name": "old", "version": null, "language": null },
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn required_metadata_keys() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub fn greet() -> String { String::new() }\n")?;
+
+    // A plugin requiring `package-name`, which records the metadata it is given when run to generate a crate,
+    // and runs `gg_info` when asked about itself.
+    let info = r#"{"name":"req","version":"0.1.0","language":"Req","metadata_keys":[{"name":"package-name","required":true},{"name":"style"}]}"#;
+    let echo_info = format!("echo '{info}'");
+    let input_path = dir.child("input.json");
+    let run = |gg_info: &str, plugin_metadata: &str| -> anyhow::Result<String> {
+        std::fs::write(
+            dir.child("greeter/Cargo.toml"),
+            format!("[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[package.metadata.gluegun.req]\n{plugin_metadata}"),
+        )?;
+        let log = SharedLog::default();
+        let stub_input_path = input_path.clone();
+        let script = format!("if [ \"$0\" = --gg-info ]; then {gg_info}; else cat > \"$STUB_INPUT\"; {}; fi", handshake());
+        cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "req"])?
+            .plugin_command(move |_gluegun_metadata, _plugin| {
+                let mut c = std::process::Command::new("sh");
+                c.arg("-c").arg(&script).env("STUB_INPUT", &stub_input_path);
+                Ok(c)
+            })
+            .log_to(log.clone())
+            .execute()?;
+        let log = log.0.lock().unwrap();
+        Ok(String::from_utf8(log.clone())?)
+    };
+
+    // Without the required key, the plugin is not run...
+    let err = run(&echo_info, "style = \"plain\"\n").expect_err("`package-name` is missing");
+    assert!(
+        format!("{err:#}").contains("missing key `package-name` required by plugin req"),
+        "unexpected error: {err:#}"
+    );
+    assert!(!input_path.exists(), "plugin was run");
+
+    // ...and with it, it is, with a warning about the key it does not know.
+    let log = run(&echo_info, "package-name = \"com.example\"\ncolour = \"blue\"\n")?;
+    assert!(log.contains("warning: gluegun-req does not know the key `colour` of `gluegun.req`"), "unexpected log:\n{log}");
+    let input: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&input_path)?)?;
+    assert_eq!(input["metadata"]["package-name"], "com.example");
+
+    // A plugin that cannot be asked for its keys is run without checking its metadata, with a warning...
+    let log = run(&format!("{}; echo oops", handshake()), "colour = \"blue\"\n")?;
+    assert!(
+        log.contains("warning: could not ask gluegun-req for its metadata keys, so its metadata is not checked"),
+        "unexpected log:\n{log}"
+    );
+
+    // ...unless it predates `--gg-info`, rejecting it without printing anything.
+    let log = run("exit 1", "colour = \"blue\"\n")?;
+    assert!(!log.contains("warning"), "unexpected log:\n{log}");
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn plugin_queried_once() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"hello\", \"goodbye\"]\nresolver = \"2\"\n")?;
+    for package in ["hello", "goodbye"] {
+        std::fs::create_dir_all(dir.child(package).join("src"))?;
+        std::fs::write(dir.child(package).join("src/lib.rs"), "pub fn greet() {}\n")?;
+        std::fs::write(
+            dir.child(package).join("Cargo.toml"),
+            format!("[package]\nname = \"{package}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[package.metadata.gluegun.req]\npackage-name = \"{package}\"\n"),
+        )?;
+    }
+
+    // A plugin declaring its keys, which counts the times it is asked about itself, and the times it is run.
+    let info = r#"{"name":"req","version":"0.1.0","language":"Req","metadata_keys":[{"name":"package-name","required":true}]}"#;
+    let runs_path = dir.child("runs");
+    let stub_runs_path = runs_path.clone();
+    cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "--workspace", "--jobs", "2", "req"])?
+        .plugin_command(move |_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c")
+                .arg(format!(
+                    "if [ \"$0\" = --gg-info ]; then echo query >> \"$RUNS\"; echo '{info}'; else echo run >> \"$RUNS\"; cat > /dev/null; {}; fi",
+                    handshake()
+                ))
+                .env("RUNS", &stub_runs_path);
+            Ok(c)
+        })
+        .log_to(SharedLog::default())
+        .execute()?;
+
+    // The plugin was run for each package, but asked for its keys once.
+    let mut runs: Vec<_> = std::fs::read_to_string(&runs_path)?.lines().map(str::to_string).collect();
+    runs.sort();
+    assert_eq!(runs, ["query", "run", "run"]);
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn watch_regenerates_on_change() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+    let lib_rs = dir.child("greeter/src/lib.rs");
+    std::fs::write(&lib_rs, "pub fn greet() {}\n")?;
+
+    // Watch with a stub plugin recording the IDL it is given, until stopped.
+    let log = SharedLog::default();
+    let input_path = dir.child("input.json");
+    let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let builder = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "--watch", "stub"])?
+        .plugin_command(move |_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > \"$STUB_INPUT\"; {}", handshake())).env("STUB_INPUT", &input_path);
+            Ok(c)
+        })
+        .log_to(log.clone())
+        .stop_watching(stop.clone());
+    let watcher = std::thread::spawn(move || builder.execute());
+
+    // Wait (for up to a minute) until `lines` lines of the log satisfy `check`.
+    let wait_for = |lines: usize, check: fn(&str) -> bool| -> anyhow::Result<String> {
+        for _ in 0..600 {
+            let text = String::from_utf8(log.0.lock().unwrap().clone())?;
+            if text.lines().filter(|line| check(line)).count() >= lines {
+                return Ok(text);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        anyhow::bail!("timed out waiting for the watcher:\n{}", String::from_utf8_lossy(&log.0.lock().unwrap()))
+    };
+
+    // The crate is generated once, and then each time its sources change.
+    let text = wait_for(1, |line| line.starts_with("watching 1 source file(s) of 1 package(s)"))?;
+    assert_eq!(text.lines().filter(|line| line.starts_with("regenerated 1 crate(s) for `greeter`")).count(), 1, "unexpected log:\n{text}");
+    let modified = std::fs::metadata(&lib_rs)?.modified()?;
+    std::fs::write(&lib_rs, "pub fn greet() {}\npub fn wave() {}\n")?;
+    std::fs::File::options().write(true).open(&lib_rs)?.set_modified(modified + std::time::Duration::from_secs(1))?;
+    wait_for(2, |line| line.starts_with("regenerated 1 crate(s) for `greeter`"))?;
+
+    let input: gluegun_core::protocol::PluginInput<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(dir.child("input.json"))?)?;
+    let names: Vec<_> = input.idl.definitions().keys().map(|qname| qname.to_string("::")).collect();
+    assert_eq!(names, ["greeter::greet", "greeter::wave"]);
+
+    // A failure is reported, and the sources are watched on.
+    std::fs::write(&lib_rs, "pub fn greet( {}\n")?;
+    std::fs::File::options().write(true).open(&lib_rs)?.set_modified(modified + std::time::Duration::from_secs(2))?;
+    wait_for(1, |line| line.starts_with("error: failed to regenerate the crates for `greeter`"))?;
+
+    stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    let outputs = watcher.join().unwrap()?;
+    assert!(outputs.is_empty());
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn watch_modules_of_failed_parse() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    std::fs::write(dir.child("Cargo.toml"), "[workspace]\nmembers = [\"greeter\"]\nresolver = \"2\"\n")?;
+    std::fs::create_dir_all(dir.child("greeter/src"))?;
+    std::fs::write(
+        dir.child("greeter/Cargo.toml"),
+        "[package]\nname = \"greeter\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )?;
+    std::fs::write(dir.child("greeter/src/lib.rs"), "pub mod hello;\n")?;
+    let hello_rs = dir.child("greeter/src/hello.rs");
+    std::fs::write(&hello_rs, "pub fn greet( {}\n")?;
+
+    let log = SharedLog::default();
+    let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let builder = cargo_gluegun::Builder::new(dir.path(), ["cargo-gluegun", "-p", "greeter", "--watch", "stub"])?
+        .plugin_command(|_gluegun_metadata, _plugin| {
+            let mut c = std::process::Command::new("sh");
+            c.arg("-c").arg(format!("cat > /dev/null; {}", handshake()));
+            Ok(c)
+        })
+        .log_to(log.clone())
+        .stop_watching(stop.clone());
+    let watcher = std::thread::spawn(move || builder.execute());
+
+    // Wait (for up to a minute) until a line of the log satisfies `check`.
+    let wait_for = |check: fn(&str) -> bool| -> anyhow::Result<String> {
+        for _ in 0..600 {
+            let text = String::from_utf8(log.0.lock().unwrap().clone())?;
+            if text.lines().any(check) {
+                return Ok(text);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        anyhow::bail!("timed out waiting for the watcher:\n{}", String::from_utf8_lossy(&log.0.lock().unwrap()))
+    };
+
+    // The first parse fails in a module, which is watched along with the root file, so fixing it regenerates the crate.
+    let text = wait_for(|line| line.starts_with("watching"))?;
+    assert!(text.contains("watching 2 source file(s) of 1 package(s)"), "unexpected log:\n{text}");
+    let modified = std::fs::metadata(&hello_rs)?.modified()?;
+    std::fs::write(&hello_rs, "pub fn greet() {}\n")?;
+    std::fs::File::options().write(true).open(&hello_rs)?.set_modified(modified + std::time::Duration::from_secs(1))?;
+    wait_for(|line| line.starts_with("regenerated 1 crate(s) for `greeter`"))?;
+
+    stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    let outputs = watcher.join().unwrap()?;
+    assert!(outputs.is_empty());
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use gluegun_core::casing::{Casing, NameStyle};
+use gluegun_idl::{Name, QualifiedName};
+
+/// Check that `text` is converted to each of `snake`, `camel`, `pascal`, and `kebab`.
+fn check(text: &str, snake: &str, camel: &str, pascal: &str, kebab: &str) {
+    for (style, expected) in [
+        (NameStyle::Snake, snake),
+        (NameStyle::Camel, camel),
+        (NameStyle::Pascal, pascal),
+        (NameStyle::Kebab, kebab),
+    ] {
+        assert_eq!(style.apply(text), expected, "converting `{text}` to {style:?}");
+    }
+}
+
+#[test]
+fn simple_words() {
+    check("get_name", "get_name", "getName", "GetName", "get-name");
+    check("getName", "get_name", "getName", "GetName", "get-name");
+    check("GetName", "get_name", "getName", "GetName", "get-name");
+    check("get-name", "get_name", "getName", "GetName", "get-name");
+    check("name", "name", "name", "Name", "name");
+}
+
+#[test]
+fn acronyms() {
+    check("HTTPClient", "http_client", "httpClient", "HttpClient", "http-client");
+    check("getHTTPResponse", "get_http_response", "getHttpResponse", "GetHttpResponse", "get-http-response");
+    check("IOError", "io_error", "ioError", "IoError", "io-error");
+    check("URL", "url", "url", "Url", "url");
+}
+
+#[test]
+fn leading_and_trailing_underscores() {
+    check("_unused", "_unused", "_unused", "_Unused", "_unused");
+    check("__private_field", "__private_field", "__privateField", "__PrivateField", "__private-field");
+    check("type_", "type_", "type_", "Type_", "type_");
+    check("_", "_", "_", "_", "_");
+}
+
+#[test]
+fn digit_boundaries() {
+    // Digits stay with the word they follow.
+    check("utf8_string", "utf8_string", "utf8String", "Utf8String", "utf8-string");
+    check("Http2Client", "http2_client", "http2Client", "Http2Client", "http2-client");
+    check("Vec2D", "vec2_d", "vec2D", "Vec2D", "vec2-d");
+    check("f0", "f0", "f0", "F0", "f0");
+
+    // Two numbers are not run together.
+    check("x86_64", "x86_64", "x86_64", "X86_64", "x86-64");
+    check("version_2", "version_2", "version2", "Version2", "version-2");
+}
+
+#[test]
+fn names_and_qualified_names() {
+    assert_eq!(Name::from("http_client").to_pascal_case(), Name::from("HttpClient"));
+    assert_eq!(Name::from("HttpClient").to_snake_case(), Name::from("http_client"));
+    assert_eq!(Name::from("HttpClient").to_kebab_case(), Name::from("http-client"));
+
+    let qname = QualifiedName::from(&["my_crate", "net", "HTTPClient"][..]);
+    assert_eq!(qname.to_camel_case().dotted(), "myCrate.net.httpClient");
+    assert_eq!(qname.to_pascal_case().dotted(), "MyCrate.Net.HttpClient");
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_casing_methods() {
+    // The casing methods of the IDL convert names the same way as `Casing`.
+    assert_eq!(Name::from("http_client").upper_camel_case(), Name::from("HttpClient"));
+    assert_eq!(Name::from("HttpClient").camel_case(), Name::from("httpClient"));
+    assert_eq!(Name::from("HttpClient").snake_case(), Name::from("http_client"));
+
+    let qname = QualifiedName::from(&["my_crate", "net", "HTTPClient"][..]);
+    assert_eq!(qname.camel_case(), qname.to_camel_case());
+    assert_eq!(qname.upper_camel_case(), qname.to_pascal_case());
+    assert_eq!(qname.snake_case(), qname.to_snake_case());
+}
@@ -0,0 +1,26 @@
+use gluegun_core::codegen::CodeWriter;
+
+#[test]
+fn nested_blocks_are_indented() -> anyhow::Result<()> {
+    let mut output = vec![];
+    {
+        let mut cw = CodeWriter::new(&mut output);
+        write!(cw, "mod outer {{")?;
+        write!(cw, "fn f() {{")?;
+        write!(cw, "g(")?;
+        write!(cw, "1,")?;
+        write!(cw, ");")?;
+        write!(cw, "}}")?;
+        write!(cw, "// explicit")?;
+        cw.push_indent();
+        write!(cw, "a\n\nb")?;
+        cw.pop_indent();
+        write!(cw, "}}")?;
+    }
+
+    assert_eq!(
+        String::from_utf8(output)?,
+        "mod outer {\n    fn f() {\n        g(\n            1,\n        );\n    }\n    // explicit\n        a\n\n        b\n}\n",
+    );
+    Ok(())
+}
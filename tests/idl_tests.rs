@@ -19,6 +19,1290 @@ fn idl_tests() -> anyhow::Result<()> {
 fn hello_world() -> anyhow::Result<()> {
     gluegun_test_harness::Test::new("hello_world", PLUGINS, demo_directory("hello_world"))
     .cargo_glue_gun()
+    .check_file("hello_world-java/src/lib.rs", |lib_rs| {
+        // The wrapped function is called exactly once, with the converted argument.
+        anyhow::ensure!(
+            lib_rs.matches("hello_world::greet(").count() == 1
+                && gluegun_test_harness::contains_code(lib_rs, "Ok(hello_world::greet(&name))"),
+            "`greet` is not called once with `name`:\n{lib_rs}"
+        );
+        anyhow::ensure!(
+            lib_rs.contains(") -> duchess::Result<String> {"),
+            "`greet` does not return a `String`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .check_file("hello_world-java/Cargo.toml", |cargo_toml| {
+        // The manifest is scaffolded with the destination crate name and the dependencies the plugin asked for.
+        anyhow::ensure!(
+            cargo_toml.contains("name = \"hello_world-java\"\n")
+                && cargo_toml.lines().any(|line| line.starts_with("duchess = ")),
+            "manifest is missing the crate name or the `duchess` dependency:\n{cargo_toml}"
+        );
+        Ok(())
+    })
     .cargo_build_plugin_crates()
     .execute()
-}
\ No newline at end of file
+}
+#[test]
+fn hello_world_no_format() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("hello_world", PLUGINS, demo_directory("hello_world"))
+    .cargo_glue_gun_builder()
+    .option("--no-format")
+    .option("--package")
+    .option("hello_world")
+    .options(PLUGINS)
+    .finish()
+    .check_file("hello_world-java/src/lib.rs", |lib_rs| {
+        // Without formatting, each argument is left on its own line as the plugin wrote it.
+        anyhow::ensure!(
+            lib_rs.contains("Ok(hello_world::greet(\n            &name,\n        ))"),
+            "generated code was formatted despite `--no-format`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+#[test]
+fn parallel_jobs_share_the_workspace() -> anyhow::Result<()> {
+    // Both plugins create their crate at the same time, each adding it to the workspace's members.
+    gluegun_test_harness::Test::new("hello_world", PLUGINS, demo_directory("hello_world"))
+    .replace("Cargo.toml", "[dependencies]", "[dependencies]\n\n[workspace]\nmembers = [\".\"]")
+    .cargo_glue_gun_builder()
+    .option("--jobs")
+    .option("2")
+    .option("--package")
+    .option("hello_world")
+    .options(PLUGINS)
+    .finish()
+    .check_file("Cargo.toml", |cargo_toml| {
+        anyhow::ensure!(
+            cargo_toml.contains("\"hello_world-java\"") && cargo_toml.contains("\"hello_world-py\""),
+            "a generated crate is missing from the workspace members:\n{cargo_toml}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn split_modules() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("split_modules", &["java"], demo_directory("split_modules"))
+    .cargo_glue_gun()
+    .check_dir("split_modules-java/src/items", |names| {
+        anyhow::ensure!(
+            names == ["split_modules_farewell.rs", "split_modules_greet.rs", "split_modules_shout.rs"],
+            "unexpected item modules: {names:?}"
+        );
+        Ok(())
+    })
+    .check_file("split_modules-java/src/lib.rs", |lib_rs| {
+        let mod_lines = lib_rs
+            .lines()
+            .filter(|line| line.trim().starts_with("mod ") && line.ends_with(';'))
+            .count();
+        anyhow::ensure!(mod_lines == 3, "expected 3 `mod` lines in lib.rs, found {mod_lines}:\n{lib_rs}");
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn incremental_regeneration() -> anyhow::Result<()> {
+    // Both item modules are marked after the first run; only the module of the changed item loses its marker.
+    gluegun_test_harness::Test::new("incremental", &["java"], demo_directory("incremental"))
+    .cargo_glue_gun()
+    .replace("incremental-java/src/items/incremental_greet.rs", "use crate::*;", "use crate::*; // marker")
+    .replace("incremental-java/src/items/incremental_farewell.rs", "use crate::*;", "use crate::*; // marker")
+    .replace("src/lib.rs", "farewell(name: &str)", "farewell(name: &str, _polite: i32)")
+    .cargo_glue_gun()
+    .check_file("incremental-java/src/items/incremental_greet.rs", |greet_rs| {
+        anyhow::ensure!(greet_rs.contains("// marker"), "unchanged item module was rewritten:\n{greet_rs}");
+        Ok(())
+    })
+    .check_file("incremental-java/src/items/incremental_farewell.rs", |farewell_rs| {
+        anyhow::ensure!(
+            !farewell_rs.contains("// marker") && farewell_rs.contains("_polite"),
+            "changed item module was not rewritten:\n{farewell_rs}"
+        );
+        Ok(())
+    })
+    .check_file("incremental-java/.gluegun-generated", |marker| {
+        let marker = gluegun_core::protocol::GeneratedMarker::parse(marker)?;
+        anyhow::ensure!(marker.source_crate == "incremental" && marker.plugin == "java", "unexpected marker: {marker:?}");
+        anyhow::ensure!(marker.generated_at > 0, "unexpected marker: {marker:?}");
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn incremental_referenced_type() -> anyhow::Result<()> {
+    // `origin` itself does not change, but the glue returning a `Point` does when `Point` becomes a class.
+    gluegun_test_harness::Test::new("incremental", &["java"], demo_directory("incremental"))
+    .cargo_glue_gun()
+    .replace("incremental-java/src/items/incremental_origin.rs", "use crate::*;", "use crate::*; // marker")
+    .replace("src/lib.rs", "pub x: i32,\n    pub y: i32,", "x: i32,\n    y: i32,")
+    .cargo_glue_gun()
+    .check_file("incremental-java/src/items/incremental_origin.rs", |origin_rs| {
+        anyhow::ensure!(
+            !origin_rs.contains("// marker") && origin_rs.contains("gluegun_java_util::resource::into_handle"),
+            "item module depending on a changed type was not rewritten:\n{origin_rs}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn default_values() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("default_values", &["java"], demo_directory("default_values"))
+    .replace("Cargo.toml", "../..", project_root_directory())
+    .cargo_glue_gun()
+    .check_file("default_values-java/java_src/defaultValues/Functions.java", |java| {
+        anyhow::ensure!(
+            java.contains("public static String repeat(\n        String text,\n        int count\n    )"),
+            "missing two-argument `repeat`:\n{java}"
+        );
+        anyhow::ensure!(
+            java.contains("public static String repeat(\n        String text\n    )"),
+            "missing one-argument overload of `repeat`:\n{java}"
+        );
+        anyhow::ensure!(
+            java.contains("public static String greet(\n        String name\n    )"),
+            "missing one-argument overload of `greet`:\n{java}"
+        );
+        Ok(())
+    })
+    .check_file("default_values-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains("let count: i32 = 1;"),
+            "overload does not supply the default for `count`:\n{lib_rs}"
+        );
+        anyhow::ensure!(
+            lib_rs.contains("let greeting: String = \"Hello\".into();"),
+            "overload does not supply the default for `greeting`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn fallible_unit() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("fallible", &["java"], demo_directory("fallible"))
+    .cargo_glue_gun()
+    .check_file("fallible-java/java_src/fallible/Functions.java", |java| {
+        anyhow::ensure!(
+            java.contains("public static void save(\n        String path\n    ) throws RuntimeException {"),
+            "`save` is not a throwing `void` method:\n{java}"
+        );
+        Ok(())
+    })
+    .check_file("fallible-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains(".map_err(|err| duchess::Error::JvmInternal(err.to_string()))?"),
+            "error from `save` is not thrown:\n{lib_rs}"
+        );
+        // See `util::is_void` for why a `void` method's native counterpart returns an `i8`.
+        anyhow::ensure!(
+            lib_rs.contains(") -> duchess::Result<i8> {"),
+            "`save` does not return an `i8`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn package_settings() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("package_settings", PLUGINS, demo_directory("package_settings"))
+    .cargo_glue_gun()
+    .check_file("package_settings-java/Cargo.toml", |cargo_toml| {
+        anyhow::ensure!(
+            cargo_toml.contains("edition = \"2021\"\nrust-version = \"1.80\"\n"),
+            "configured edition/rust-version missing:\n{cargo_toml}"
+        );
+        Ok(())
+    })
+    .check_file("package_settings-py/Cargo.toml", |cargo_toml| {
+        anyhow::ensure!(
+            cargo_toml.contains("edition = \"2018\"\n") && !cargo_toml.contains("rust-version"),
+            "edition does not default to the source crate's:\n{cargo_toml}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn iterators() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("iterators", &["java"], demo_directory("iterators"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "iterators-java/java_src/iterators/Functions.java",
+        &[
+            "public static CountUpIterator count_up(",
+            "public static final class CountUpIterator implements java.util.Iterator<Long>, AutoCloseable {",
+            "public synchronized boolean hasNext() {",
+            "public synchronized Long next() {",
+            "public synchronized void close() {",
+        ],
+    )
+    .check_file("iterators-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            gluegun_test_harness::contains_code(lib_rs, "gluegun_java_util::iterator::into_handle(iterators::count_up(limit))"),
+            "iterator is not stored as a handle:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn plain_records() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("plain_records", &["java"], demo_directory("plain_records"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_records-java/java_src/plainRecords/Point.java",
+        &[
+            "public static native long native_record_new(",
+            "public static native int native_record_get_x(long handle);",
+            "public long toNativeHandle() {",
+            "public static plainRecords.Point fromNativeHandle(long handle) {",
+        ],
+    )
+    .check_file_contains(
+        "plain_records-java/src/lib.rs",
+        &[
+            "#[duchess::java_function(plainRecords.Point::native_record_new)]",
+            "#[duchess::java_function(plainRecords.Point::native_record_get_y)]",
+            "let to: plain_records::Point = unsafe { gluegun_java_util::record::from_handle(to) };",
+            "Ok(gluegun_java_util::record::into_handle(plain_records::origin()))",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn plain_enums() -> anyhow::Result<()> {
+    // The Java test loads the native library and passes enum values from Rust to Java and back.
+    gluegun_test_harness::Test::new("plain_enums", &["java"], demo_directory("plain_enums"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_enums-java/src/lib.rs",
+        &[
+            "impl crate::JavaEnum for plain_enums::Signal {",
+            "\"Go\" => Some(plain_enums::Signal::Go),",
+            "plain_enums::Signal::Caution => Some(\"Caution\"),",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "PlainEnumsTest",
+        [
+            "plain_enums-java/java_src/plainEnums/Functions.java",
+            "plain_enums-java/java_src/plainEnums/Signal.java",
+            "java_test/PlainEnumsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn plain_records_py() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("plain_records", &["py"], demo_directory("plain_records"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_records-py/src/lib.rs",
+        &[
+            "#[pyo3::pyclass(name = \"Point\", get_all, set_all)]",
+            "m.add_class::<plain_records_Point>()?;",
+            "fn plain_records_manhattan_distance(",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn plain_enums_py() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("plain_enums", &["py"], demo_directory("plain_enums"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_enums-py/src/lib.rs",
+        &[
+            "#[pyo3::pyclass(name = \"Signal\", eq, eq_int)]",
+            "m.add_class::<plain_enums_Signal>()?;",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn plain_variants() -> anyhow::Result<()> {
+    // The Java test loads the native library and passes each arm of `Shape` from Rust to Java and back.
+    gluegun_test_harness::Test::new("plain_variants", &["java"], demo_directory("plain_variants"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_variants-java/java_src/plainVariants/Shape.java",
+        &[
+            "public abstract sealed class Shape {",
+            "public static final class Circle extends plainVariants.Shape {",
+            "public static final class Rectangle extends plainVariants.Shape {",
+            "public static final class Empty extends plainVariants.Shape {",
+        ],
+    )
+    .check_file_contains(
+        "plain_variants-java/src/lib.rs",
+        &[
+            "Ok(gluegun_java_util::record::into_handle(plain_variants::Shape::Circle { radius }))",
+            "Ok(gluegun_java_util::record::into_handle(plain_variants::Shape::Rectangle { 0: f0, 1: f1 }))",
+            "Ok(gluegun_java_util::record::into_handle(plain_variants::Shape::Empty))",
+            "plain_variants::Shape::Rectangle { 1: value, .. } => Ok((*value).clone()),",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "PlainVariantsTest",
+        [
+            "plain_variants-java/java_src/plainVariants/Functions.java",
+            "plain_variants-java/java_src/plainVariants/Shape.java",
+            "java_test/PlainVariantsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn collection_params() -> anyhow::Result<()> {
+    // The Java test passes a list, a set, and a map to Rust, and lends a copy of a list mutably.
+    gluegun_test_harness::Test::new("collection_params", &["java"], demo_directory("collection_params"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "collection_params-java/src/lib.rs",
+        &[
+            "parts: &duchess::java::util::List<duchess::java::lang::String>,",
+            "let parts: Vec<String> = {",
+            "table_keys: &duchess::java::util::List<duchess::java::lang::String>,",
+            "let table: std::collections::HashMap<String, String> = {",
+            "let words: std::collections::BTreeSet<String> = {",
+            "let table: std::collections::BTreeMap<String, String> = {",
+            "collection_params::sort_in_place(&mut { words })",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "CollectionParamsTest",
+        [
+            "collection_params-java/java_src/collectionParams/Functions.java",
+            "java_test/CollectionParamsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn resource_methods() -> anyhow::Result<()> {
+    // The Java test calls methods taking `&self`, `&mut self`, and `self` on the Rust value owned by a Java object.
+    gluegun_test_harness::Test::new("resource_methods", &["java"], demo_directory("resource_methods"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "resource_methods-java/src/lib.rs",
+        &[
+            "gluegun_java_util::resource::get::<resource_methods::Counter>(self_handle)",
+            "gluegun_java_util::resource::get_mut::<resource_methods::Counter>(self_handle)",
+            "gluegun_java_util::resource::from_handle::<resource_methods::Counter>(self_handle)",
+            "gluegun_java_util::resource::from_handle(other)",
+        ],
+    )
+    .check_file_contains(
+        "resource_methods-java/java_src/resourceMethods/Counter.java",
+        &[
+            "public static final long LIMIT = 1000L;",
+            "public static final String UNIT = \"clicks\";",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "ResourceMethodsTest",
+        [
+            "resource_methods-java/java_src/resourceMethods/Counter.java",
+            "java_test/ResourceMethodsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn async_functions() -> anyhow::Result<()> {
+    // With `async-style = "completable-future"`, the Java test waits on the futures returned by `async fn`s.
+    gluegun_test_harness::Test::new("async_functions", &["java"], demo_directory("async_functions"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "async_functions-java/java_src/asyncFunctions/Functions.java",
+        &[
+            "public static java.util.concurrent.CompletableFuture<String> fetch(",
+            "public static java.util.concurrent.CompletableFuture<Void> ping(",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "AsyncFunctionsTest",
+        [
+            "async_functions-java/java_src/asyncFunctions/Functions.java",
+            "java_test/AsyncFunctionsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn exclude_from_workspace() -> anyhow::Result<()> {
+    // Generating twice checks that the crate is only excluded once.
+    gluegun_test_harness::Test::new("excluded", &["java"], demo_directory("excluded"))
+    .cargo_glue_gun()
+    .cargo_glue_gun()
+    .check_file("Cargo.toml", |cargo_toml| {
+        anyhow::ensure!(
+            cargo_toml.matches("\"excluded-java\"").count() == 1
+                && cargo_toml.contains("exclude = [\"excluded-java\"]"),
+            "generated crate should be excluded (once) and not a member:\n{cargo_toml}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn must_use() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("must_use", &["java"], demo_directory("must_use"))
+    .cargo_glue_gun()
+    .check_file("must_use-java/java_src/mustUse/Functions.java", |java| {
+        for annotated in ["checkout", "double"] {
+            anyhow::ensure!(
+                java.lines().zip(java.lines().skip(1)).any(|(annotation, method)| {
+                    annotation.trim() == "@com.google.errorprone.annotations.CheckReturnValue"
+                        && method.contains(&format!(" {annotated}("))
+                }),
+                "`{annotated}` is missing the check-return annotation:\n{java}"
+            );
+        }
+        anyhow::ensure!(
+            java.matches("@com.google.errorprone.annotations.CheckReturnValue").count() == 2,
+            "only `checkout` and `double` should be annotated:\n{java}"
+        );
+        Ok(())
+    })
+    .execute()
+}
+
+#[test]
+fn checked_conversions() -> anyhow::Result<()> {
+    // The range checks happen in Java before the native method is called, so no Rust library needs to be loaded.
+    gluegun_test_harness::Test::new("checked_conversions", &["java"], demo_directory("checked_conversions"))
+    .cargo_glue_gun()
+    .check_file("checked_conversions-java/src/lib.rs", |lib_rs| {
+        // `u32` and `u64` are both returned as a Java `long`.
+        anyhow::ensure!(
+            lib_rs.matches(") -> duchess::Result<i64> {").count() == 2,
+            "`next_id` and `scale` do not return an `i64`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .run_java_test(
+        "CheckedConversionsTest",
+        [
+            "checked_conversions-java/java_src/checkedConversions/Functions.java",
+            "java_test/CheckedConversionsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn option_style() -> anyhow::Result<()> {
+    // The demo starts out with `option-style = "nullable"` and is regenerated with `"optional"`.
+    gluegun_test_harness::Test::new("option_style", &["java"], demo_directory("option_style"))
+    .cargo_glue_gun()
+    .check_file("option_style-java/java_src/optionStyle/Functions.java", |java| {
+        anyhow::ensure!(
+            java.contains("@javax.annotation.Nullable\n    public static String greeting(\n        @javax.annotation.Nullable String nickname\n    )"),
+            "`greeting` does not take and return a nullable `String`:\n{java}"
+        );
+        Ok(())
+    })
+    .replace("Cargo.toml", "option-style = \"nullable\"", "option-style = \"optional\"")
+    .cargo_glue_gun()
+    .check_file_contains(
+        "option_style-java/java_src/optionStyle/Functions.java",
+        &[
+            "public static java.util.Optional<String> greeting(\n        java.util.Optional<String> nickname\n    )",
+            "return java.util.Optional.ofNullable(native_greeting(\n            nickname.isPresent() ? (byte) 1 : (byte) 0, nickname.isPresent() ? nickname.get() : \"\"\n        ));",
+        ],
+    )
+    .check_file("option_style-java/java_src/optionStyle/Functions.java", |java| {
+        anyhow::ensure!(!java.contains("@javax.annotation.Nullable"), "`Optional` should not be annotated as nullable:\n{java}");
+        Ok(())
+    })
+    .check_file("option_style-java/java_src/optionStyle/Profile.java", |java| {
+        anyhow::ensure!(
+            java.contains("public java.util.Optional<String> nickname = java.util.Optional.empty();"),
+            "`nickname` field is not an empty `Optional`:\n{java}"
+        );
+        Ok(())
+    })
+    .run("javac", ["-d", "classes", "option_style-java/java_src/optionStyle/Functions.java"])
+    .execute()
+}
+
+#[test]
+fn csharp_resource() -> anyhow::Result<()> {
+    // No .NET toolchain is assumed, so the C# is only checked against the shim, which must build.
+    gluegun_test_harness::Test::new("bank", &["csharp"], demo_directory("bank"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "bank-csharp/src/lib.rs",
+        &[
+            "pub unsafe extern \"C\" fn bank__Account__gluegun__free(handle: *mut bank::Account) {",
+            "pub unsafe extern \"C\" fn bank__Account__new(balance: i64) -> *mut bank::Account {",
+            "pub unsafe extern \"C\" fn bank__Account__withdraw(handle: *mut bank::Account, amount: i64, error: *mut *mut std::ffi::c_char) -> i64 {",
+        ],
+    )
+    .check_file_contains(
+        "bank-csharp/cs_src/Bank/Account.cs",
+        &[
+            "private static extern void bank__Account__gluegun__free(IntPtr handle);",
+            "private static extern global::Bank.Account.Handle bank__Account__new(long balance);",
+            "private static extern long bank__Account__withdraw(global::Bank.Account.Handle handle, long amount, out IntPtr error);",
+            "GlueGunRuntime.ThrowIfError(ggError);",
+            "public const long MAX_WITHDRAWAL = 100000;",
+            "public const string CURRENCY = \"EUR\";",
+            // Binary data is a `byte[]`, not a `List<byte>`.
+            "public byte[] Memo()",
+            "public void SetMemo(byte[] memo)",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn unsupported_impl_trait() -> anyhow::Result<()> {
+    let dir = temp_dir::TempDir::new()?;
+    let rs_path = dir.child("lib.rs");
+    std::fs::write(&rs_path, "pub fn show(value: impl std::fmt::Display) {}\n")?;
+
+    let err = gluegun_idl::Parser::new()
+        .parse_crate_named("bad", dir.path(), &rs_path)
+        .expect_err("`impl Display` is not supported");
+    anyhow::ensure!(
+        matches!(&err, gluegun_idl::Error::UnsupportedImplTrait(_, name) if name == "std::fmt::Display"),
+        "unexpected error: {err}"
+    );
+    Ok(())
+}
+
+#[test]
+fn parse_error_location() -> anyhow::Result<()> {
+    let dir = temp_dir::TempDir::new()?;
+    let rs_path = dir.child("lib.rs");
+
+    // An unsupported construct is located at the construct (here, the trait)...
+    std::fs::write(&rs_path, "pub fn greet() {}\n\npub fn show(value: impl std::fmt::Display) {}\n")?;
+    let err = gluegun_idl::Parser::new()
+        .parse_crate_named("bad", dir.path(), &rs_path)
+        .expect_err("`impl Display` is not supported");
+    let parse_error = err.to_parse_error().ok_or_else(|| anyhow::anyhow!("the error has no location: {err}"))?;
+    anyhow::ensure!(parse_error.file == rs_path, "unexpected file: {parse_error:?}");
+    anyhow::ensure!((parse_error.line, parse_error.column) == (3, 25), "unexpected location: {parse_error:?}");
+    anyhow::ensure!(parse_error.message.starts_with("`impl std::fmt::Display` is not supported"), "unexpected message: {parse_error:?}");
+
+    // ...and a syntax error at the offending token.
+    std::fs::write(&rs_path, "pub fn greet() {}\n\npub fn broken( {}\n")?;
+    let err = gluegun_idl::Parser::new()
+        .parse_crate_named("bad", dir.path(), &rs_path)
+        .expect_err("`broken` does not parse");
+    let parse_error = err.to_parse_error().ok_or_else(|| anyhow::anyhow!("the error has no location: {err}"))?;
+    anyhow::ensure!((parse_error.file.as_path(), parse_error.line) == (rs_path.as_path(), 3), "unexpected location: {parse_error:?}");
+    Ok(())
+}
+
+#[test]
+fn smart_pointer_errors() -> anyhow::Result<()> {
+    let dir = temp_dir::TempDir::new()?;
+    let rs_path = dir.child("lib.rs");
+    let parse = |source: &str| {
+        std::fs::write(&rs_path, source)?;
+        let err = gluegun_idl::Parser::new()
+            .parse_crate_named("bad", dir.path(), &rs_path)
+            .expect_err("the smart pointer is not supported");
+        err.to_parse_error().ok_or_else(|| anyhow::anyhow!("the error has no location: {err}"))
+    };
+
+    // A value behind a lock cannot be unwrapped, the error points at the lock...
+    let parse_error = parse("use std::sync::{Arc, Mutex};\n\npub fn count(counter: Arc<Mutex<u32>>) {}\n")?;
+    anyhow::ensure!((parse_error.line, parse_error.column) == (3, 27), "unexpected location: {parse_error:?}");
+    anyhow::ensure!(parse_error.message.starts_with("`Mutex` cannot be mapped"), "unexpected message: {parse_error:?}");
+
+    // ...and smart pointers are only unwrapped in parameters and return values, not inside other types.
+    let parse_error = parse("pub struct Node {\n    pub next: Option<Box<Node>>,\n}\n")?;
+    anyhow::ensure!(parse_error.line == 2, "unexpected location: {parse_error:?}");
+    anyhow::ensure!(parse_error.message.contains("only supported as the type of a parameter"), "unexpected message: {parse_error:?}");
+    Ok(())
+}
+
+#[test]
+fn missing_module_file() -> anyhow::Result<()> {
+    let dir = temp_dir::TempDir::new()?;
+    let rs_path = dir.child("lib.rs");
+    std::fs::write(&rs_path, "pub mod shapes;\n")?;
+
+    let err = gluegun_idl::Parser::new()
+        .parse_crate_named("bad", dir.path(), &rs_path)
+        .expect_err("neither `shapes.rs` nor `shapes/mod.rs` exists");
+    anyhow::ensure!(
+        matches!(&err, gluegun_idl::Error::ModuleNotFound(_, name) if name.text() == "shapes"),
+        "unexpected error: {err}"
+    );
+    Ok(())
+}
+
+#[test]
+fn generic_items() -> anyhow::Result<()> {
+    let dir = temp_dir::TempDir::new()?;
+    let rs_path = dir.child("lib.rs");
+    std::fs::write(&rs_path, "pub fn identity<T: Clone>(x: T) -> T { x }\npub fn shout(s: String) -> String { s }\n")?;
+
+    let idl = gluegun_idl::Parser::new().parse_crate_named("generic", dir.path(), &rs_path)?;
+    let item = |name: &str| &idl.definitions()[&gluegun_idl::QualifiedName::from(&["generic", name][..])];
+
+    let identity = item("identity");
+    anyhow::ensure!(identity.is_generic(), "`identity` is not generic: {identity:?}");
+    let gluegun_idl::Item::Function(function) = identity else {
+        anyhow::bail!("`identity` is not a function: {identity:?}");
+    };
+    anyhow::ensure!(
+        matches!(
+            function.signature().output_ty().main_ty().ty().kind(),
+            gluegun_idl::TypeKind::TypeParameter { name } if name.text() == "T"
+        ),
+        "`identity` does not return `T`: {function:?}"
+    );
+    anyhow::ensure!(!item("shout").is_generic(), "`shout` is generic");
+
+    // A C-like enum cannot use a type parameter.
+    std::fs::write(&rs_path, "pub enum Unit<T> { A, B }\n")?;
+    let err = gluegun_idl::Parser::new()
+        .parse_crate_named("generic", dir.path(), &rs_path)
+        .expect_err("generic C-like enums are not supported");
+    anyhow::ensure!(matches!(err, gluegun_idl::Error::GenericsNotPermitted(_)), "unexpected error: {err}");
+    Ok(())
+}
+
+#[test]
+fn renamed_items() -> anyhow::Result<()> {
+    // Java names come from `#[gluegun(name = "...")]`, but the Rust glue still calls the original items.
+    gluegun_test_harness::Test::new("renamed_items", &["java"], demo_directory("renamed_items"))
+    .replace("Cargo.toml", "../..", project_root_directory())
+    .cargo_glue_gun()
+    .check_file("renamed_items-java/java_src/renamedItems/Functions.java", |java| {
+        anyhow::ensure!(java.contains("public static String salute("), "`greet` is not exported as `salute`:\n{java}");
+        Ok(())
+    })
+    .check_file_contains(
+        "renamed_items-java/java_src/renamedItems/Tally.java",
+        &["public class Tally implements AutoCloseable {", "public Tally(", "public synchronized void increment("],
+    )
+    .check_file_contains(
+        "renamed_items-java/src/lib.rs",
+        &["renamed_items::greet(", "renamed_items::Counter::new(", "renamed_items::Counter::bump(", "renamedItems.Tally::"],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn optional_values() -> anyhow::Result<()> {
+    // The Java test passes `null` to Rust, where it is `None`, and gets `null` back for `None`.
+    gluegun_test_harness::Test::new("optional_values", &["java"], demo_directory("optional_values"))
+    .cargo_glue_gun()
+    .check_file("optional_values-java/src/lib.rs", |lib_rs| {
+        // `None` is received as a flag rather than a `null` reference.
+        anyhow::ensure!(lib_rs.contains("name_is_some: i8,"), "no presence flag for `name`");
+        anyhow::ensure!(lib_rs.contains("if name_is_some != 0 {"), "`name` not converted to an `Option`");
+        // An optional `u32` comes back as a `long[]` of zero or one element.
+        anyhow::ensure!(lib_rs.contains("duchess::Result<Vec<i64>>"), "`twice` does not return a `Vec`");
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "OptionalValuesTest",
+        [
+            "optional_values-java/java_src/optionalValues/Functions.java",
+            "java_test/OptionalValuesTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn error_payloads() -> anyhow::Result<()> {
+    // The Java test catches the exception generated for each error type and reads the error from it.
+    gluegun_test_harness::Test::new("error_payloads", &["java"], demo_directory("error_payloads"))
+    .cargo_glue_gun()
+    .check_file("error_payloads-java/java_src/errorPayloads/Functions.java", |java| {
+        anyhow::ensure!(
+            java.contains(") throws errorPayloads.MyErrorException {"),
+            "`parse` does not throw `MyErrorException`:\n{java}"
+        );
+        Ok(())
+    })
+    .check_file("error_payloads-java/java_src/errorPayloads/MyErrorException.java", |java| {
+        anyhow::ensure!(
+            java.contains("public class MyErrorException extends RuntimeException {\n    public final errorPayloads.MyError error;"),
+            "`MyErrorException` does not carry a `MyError`:\n{java}"
+        );
+        Ok(())
+    })
+    .check_file("error_payloads-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains(".map_err(crate::error_payloads_my_error_into_exception)?"),
+            "error from `parse` is not converted to a `MyErrorException`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "ErrorPayloadsTest",
+        [
+            "error_payloads-java/java_src/errorPayloads/Functions.java",
+            "error_payloads-java/java_src/errorPayloads/MyError.java",
+            "error_payloads-java/java_src/errorPayloads/MyErrorException.java",
+            "error_payloads-java/java_src/errorPayloads/Problem.java",
+            "error_payloads-java/java_src/errorPayloads/ProblemException.java",
+            "java_test/ErrorPayloadsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn dyn_errors() -> anyhow::Result<()> {
+    // The Java test catches the `RustException` thrown for a `Box<dyn Error>` or `anyhow::Error` and checks its message.
+    gluegun_test_harness::Test::new("dyn_errors", &["java"], demo_directory("dyn_errors"))
+    .cargo_glue_gun()
+    .check_file("dyn_errors-java/java_src/dynErrors/Functions.java", |java| {
+        anyhow::ensure!(
+            java.contains(") throws dynErrors.RustException {"),
+            "`parse_number` does not throw `RustException`:\n{java}"
+        );
+        Ok(())
+    })
+    .check_file("dyn_errors-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains(".map_err(crate::gluegun_rust_error_into_exception)?"),
+            "error from `parse_number` is not converted to a `RustException`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "DynErrorsTest",
+        [
+            "dyn_errors-java/java_src/dynErrors/Functions.java",
+            "dyn_errors-java/java_src/dynErrors/RustException.java",
+            "java_test/DynErrorsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn tuple_values() -> anyhow::Result<()> {
+    // A class is generated for each shape of tuple, named after the types of its elements.
+    gluegun_test_harness::Test::new("tuple_values", &["java"], demo_directory("tuple_values"))
+    .cargo_glue_gun()
+    .check_file("tuple_values-java/java_src/tupleValues/TupleI32I32.java", |java| {
+        anyhow::ensure!(java.contains("public final class TupleI32I32"));
+        anyhow::ensure!(java.contains("public int f0;"));
+        Ok(())
+    })
+    .check_file("tuple_values-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(lib_rs.contains("record::get::<(String, u32, f64)>(handle)"));
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "TupleValuesTest",
+        [
+            "tuple_values-java/java_src/tupleValues/Functions.java",
+            "tuple_values-java/java_src/tupleValues/TupleI32I32.java",
+            "tuple_values-java/java_src/tupleValues/TupleStringU32F64.java",
+            "java_test/TupleValuesTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn char_values() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("char_values", &["java"], demo_directory("char_values"))
+    .cargo_glue_gun()
+    .check_file("char_values-java/java_src/charValues/Functions.java", |java| {
+        anyhow::ensure!(
+            java.contains("public static int to_upper(\n        int c\n    )"),
+            "`to_upper` does not take and return a code point:\n{java}"
+        );
+        Ok(())
+    })
+    .check_file("char_values-java/src/lib.rs", |lib_rs| {
+        // Java has no way to rule out surrogates by type, so the conversion is checked.
+        anyhow::ensure!(lib_rs.contains("let c: char = char::from_u32(c as u32)"), "`c` is not converted to a `char`:\n{lib_rs}");
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "CharValuesTest",
+        [
+            "char_values-java/java_src/charValues/Functions.java",
+            "java_test/CharValuesTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn path_values() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("path_values", &["java"], demo_directory("path_values"))
+    .cargo_glue_gun()
+    // Paths are strings on the Java side, whether borrowed, owned or returned.
+    .check_file_contains(
+        "path_values-java/java_src/pathValues/Functions.java",
+        &[
+            "public static String read(",
+            "public static String file_name(",
+            "public static String join(",
+            "public static String parent(",
+        ],
+    )
+    .check_file("path_values-java/java_src/pathValues/Functions.java", |java| {
+        anyhow::ensure!(!java.contains("java.nio.file.Path"), "a path is not mapped to a `String`:\n{java}");
+        Ok(())
+    })
+    .check_file("path_values-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains("let path: std::path::PathBuf ="),
+            "`path` is not converted to a `PathBuf`:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "PathValuesTest",
+        [
+            "path_values-java/java_src/pathValues/Functions.java",
+            "java_test/PathValuesTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn private_fields() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("private_fields", &["java"], demo_directory("private_fields"))
+    .cargo_glue_gun()
+    .check_file("private_fields-java/java_src/privateFields/Account.java", |java| {
+        // Only the public fields are mapped, and Java cannot construct the record itself.
+        anyhow::ensure!(java.contains("public String owner;"), "`owner` is not mapped:\n{java}");
+        anyhow::ensure!(!java.contains("balance"), "the private `balance` is mapped:\n{java}");
+        anyhow::ensure!(java.contains("private Account() {}"), "`Account` can be constructed:\n{java}");
+        anyhow::ensure!(!java.contains("toNativeHandle"), "`Account` can be passed to Rust:\n{java}");
+        Ok(())
+    })
+    .check_file("private_fields-java/java_src/privateFields/Version.java", |java| {
+        anyhow::ensure!(java.contains("private Version() {}"), "the `#[non_exhaustive]` `Version` can be constructed:\n{java}");
+        Ok(())
+    })
+    .check_file("private_fields-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(!lib_rs.contains("native_record_new"), "a record can be built from Java:\n{lib_rs}");
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "PrivateFieldsTest",
+        [
+            "private_fields-java/java_src/privateFields/Account.java",
+            "private_fields-java/java_src/privateFields/Functions.java",
+            "private_fields-java/java_src/privateFields/Version.java",
+            "java_test/PrivateFieldsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn readonly_fields() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("readonly_fields", &["java"], demo_directory("readonly_fields"))
+    .replace("Cargo.toml", "../..", project_root_directory())
+    .cargo_glue_gun()
+    // Both fields get a getter, only the one that is not readonly gets a setter.
+    .check_file_contains(
+        "readonly_fields-java/java_src/readonlyFields/OrderLine.java",
+        &[
+            "private String product;",
+            "public long quantity;",
+            "public String getProduct() {",
+            "public long getQuantity() {",
+            "public void setQuantity(long quantity) {",
+            "public OrderLine(\n        String product,\n        long quantity\n    ) {",
+        ],
+    )
+    .check_file("readonly_fields-java/java_src/readonlyFields/OrderLine.java", |java| {
+        anyhow::ensure!(!java.contains("setProduct"), "the readonly `product` has a setter:\n{java}");
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "ReadonlyFieldsTest",
+        [
+            "readonly_fields-java/java_src/readonlyFields/Functions.java",
+            "readonly_fields-java/java_src/readonlyFields/OrderLine.java",
+            "java_test/ReadonlyFieldsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn crate_constants() -> anyhow::Result<()> {
+    // The Java test reads the constants written into `Functions` and compares them with the Rust values.
+    gluegun_test_harness::Test::new("crate_constants", &["java"], demo_directory("crate_constants"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "crate_constants-java/java_src/crateConstants/Functions.java",
+        &[
+            "public static final String VERSION = \"1.0\";",
+            "public static final long MAX_RETRIES = 3L;",
+            "public static final String GREETING = \"hello\";",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "CrateConstantsTest",
+        [
+            "crate_constants-java/java_src/crateConstants/Functions.java",
+            "java_test/CrateConstantsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn crate_constants_py() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("crate_constants", &["py"], demo_directory("crate_constants"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "crate_constants-py/src/lib.rs",
+        &[
+            "m.add(\"VERSION\", crate_constants::VERSION)?;",
+            "m.add(\"MAX_RETRIES\", crate_constants::MAX_RETRIES)?;",
+            "m.add(\"GREETING\", crate_constants::GREETING)?;",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn smart_pointers() -> anyhow::Result<()> {
+    // The Java test passes and receives the values behind `Box`, `Rc`, and `Arc`, which the glue unwraps.
+    gluegun_test_harness::Test::new("smart_pointers", &["java"], demo_directory("smart_pointers"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "smart_pointers-java/java_src/smartPointers/Functions.java",
+        &["public static String shout(", "public static smartPointers.Point mirror("],
+    )
+    .check_file("smart_pointers-java/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains("Ok(String::from(smart_pointers::shout(text.into())))"),
+            "`shout` does not box its argument and unbox its result:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "SmartPointersTest",
+        [
+            "smart_pointers-java/java_src/smartPointers/Functions.java",
+            "smart_pointers-java/java_src/smartPointers/Point.java",
+            "java_test/SmartPointersTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn shared_resource_rejected() -> anyhow::Result<()> {
+    // A class need not be `Clone`, so it cannot be taken out of an `Arc` that may be shared.
+    let err = gluegun_test_harness::Test::new("smart_pointers", &["java"], demo_directory("smart_pointers"))
+    .replace(
+        "src/lib.rs",
+        "pub fn count(",
+        "pub struct Session {\n    id: u32,\n}\n\n\
+        impl Session {\n    pub fn id(&self) -> u32 {\n        self.id\n    }\n}\n\n\
+        pub fn session() -> Arc<Session> {\n    Arc::new(Session { id: 1 })\n}\n\n\
+        pub fn count(",
+    )
+    .cargo_glue_gun()
+    .execute()
+    .expect_err("a class in an `Arc` cannot be returned");
+    let unsupported = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<gluegun_core::cli::UnsupportedErrors>())
+        .ok_or_else(|| anyhow::anyhow!("unexpected error: {err:#}"))?;
+    let subjects: Vec<&str> = unsupported.0.iter().map(|error| error.subject.as_str()).collect();
+    anyhow::ensure!(subjects == ["smart_pointers::session"], "unexpected unsupported items: {subjects:?}");
+    Ok(())
+}
+
+#[test]
+fn smart_pointers_py() -> anyhow::Result<()> {
+    // `Result` cannot be returned to Python yet, so the fallible function is left out.
+    gluegun_test_harness::Test::new("smart_pointers", &["py"], demo_directory("smart_pointers"))
+    .replace("src/lib.rs", "pub fn parse_name", "fn parse_name")
+    .cargo_glue_gun()
+    .check_file("smart_pointers-py/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            lib_rs.contains("String::from(smart_pointers::shout(text.into()))"),
+            "`shout` does not box its argument and unbox its result:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn free_functions() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("free_functions", &["java"], demo_directory("free_functions"))
+    .cargo_glue_gun()
+    // Both functions of the crate root are collected into the one class, as static methods.
+    .check_file_contains(
+        "free_functions-java/java_src/freeFunctions/Functions.java",
+        &["public static int add(", "public static String greet("],
+    )
+    .check_file("free_functions-java/java_src/freeFunctions/Functions.java", |java| {
+        anyhow::ensure!(!java.contains("shout"), "`text::shout` is not in its module's class:\n{java}");
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run_java_test(
+        "FreeFunctionsTest",
+        [
+            "free_functions-java/java_src/freeFunctions/Functions.java",
+            "free_functions-java/java_src/freeFunctions/text/Functions.java",
+            "java_test/FreeFunctionsTest.java",
+        ],
+    )
+    .execute()
+}
+
+#[test]
+fn c_records() -> anyhow::Result<()> {
+    // The C test includes the generated header, links against the shim, and passes records both ways.
+    gluegun_test_harness::Test::new("plain_records", &["c"], demo_directory("plain_records"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_records-c/include/plain_records.h",
+        &[
+            "typedef struct plain_records_Point {\n    int32_t x;\n    int32_t y;\n} plain_records_Point;",
+            "plain_records_Point plain_records_origin(void);",
+            "int32_t plain_records_manhattan_distance(plain_records_Point from, plain_records_Point to);",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run(
+        "cc",
+        [
+            "-std=c11",
+            "-Wall",
+            "-Werror",
+            "-I",
+            "plain_records-c/include",
+            "c_test/plain_records_test.c",
+            "-o",
+            "plain_records_test",
+            "-L",
+            "target/debug",
+            "-lplain_records_c",
+            "-Wl,-rpath,$ORIGIN/target/debug",
+        ],
+    )
+    .run("./plain_records_test", [] as [&str; 0])
+    .execute()
+}
+
+#[test]
+fn c_strings() -> anyhow::Result<()> {
+    // Strings returned to C are freed by the C test with the generated `free_string` function.
+    gluegun_test_harness::Test::new("free_functions", &["c"], demo_directory("free_functions"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "free_functions-c/include/free_functions.h",
+        &[
+            "char *free_functions_greet(const char *name);",
+            "char *free_functions_text_shout(const char *text);",
+            "void free_functions_gluegun_free_string(char *s);",
+            " * String parameters must be non-null, NUL-terminated UTF-8 strings.",
+        ],
+    )
+    .check_file("free_functions-c/src/lib.rs", |lib_rs| {
+        anyhow::ensure!(
+            gluegun_test_harness::contains_code(lib_rs, "let name = unsafe { crate::gluegun_runtime::from_c_string(name, \"name\") };"),
+            "missing the checked string conversion:\n{lib_rs}"
+        );
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .run(
+        "cc",
+        [
+            "-std=c11",
+            "-Wall",
+            "-Werror",
+            "-I",
+            "free_functions-c/include",
+            "c_test/free_functions_test.c",
+            "-o",
+            "free_functions_test",
+            "-L",
+            "target/debug",
+            "-lfree_functions_c",
+            "-Wl,-rpath,$ORIGIN/target/debug",
+        ],
+    )
+    .run("./free_functions_test", [] as [&str; 0])
+    .execute()
+}
+
+#[test]
+fn c_null_string_aborts() -> anyhow::Result<()> {
+    // A null string breaks the header's precondition, which the shim checks rather than reading through the pointer.
+    let err = gluegun_test_harness::Test::new("free_functions", &["c"], demo_directory("free_functions"))
+    .cargo_glue_gun()
+    .cargo_build_plugin_crates()
+    .replace("c_test/free_functions_test.c", "free_functions_greet(\"C\")", "free_functions_greet(NULL)")
+    .run(
+        "cc",
+        [
+            "-std=c11",
+            "-Wall",
+            "-Werror",
+            "-I",
+            "free_functions-c/include",
+            "c_test/free_functions_test.c",
+            "-o",
+            "free_functions_test",
+            "-L",
+            "target/debug",
+            "-lfree_functions_c",
+            "-Wl,-rpath,$ORIGIN/target/debug",
+        ],
+    )
+    .run("./free_functions_test", [] as [&str; 0])
+    .execute()
+    .expect_err("passing null should abort");
+    anyhow::ensure!(format!("{err:#}").contains("SIGABRT"), "unexpected error: {err:?}");
+    Ok(())
+}
+
+#[test]
+fn typescript_records() -> anyhow::Result<()> {
+    // No JavaScript runtime is assumed, so the generated crate is only built for the host.
+    gluegun_test_harness::Test::new("plain_records", &["typescript"], demo_directory("plain_records"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_records-typescript/ts_src/plain_records.d.ts",
+        &[
+            "export interface Point {\n    x: number;\n    y: number;\n}",
+            "export function origin(): Point;",
+            "export function manhattan_distance(from: Point, to: Point): number;",
+        ],
+    )
+    .check_file_contains(
+        "plain_records-typescript/src/lib.rs",
+        &[
+            "#[wasm_bindgen(js_name = \"manhattan_distance\")]",
+            "let from = <plain_records::Point>::from_js(from);",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn typescript_enums() -> anyhow::Result<()> {
+    gluegun_test_harness::Test::new("plain_enums", &["typescript"], demo_directory("plain_enums"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "plain_enums-typescript/ts_src/plain_enums.d.ts",
+        &[
+            "export type Signal = \"Go\" | \"Stop\" | \"Caution\";",
+            "export function next_signal(signal: Signal): Signal;",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn recursive_types_left_out() -> anyhow::Result<()> {
+    // `Node` contains itself, but Java never has to copy it, so only `greet` is bound.
+    gluegun_test_harness::Test::new("recursive_types", &["java"], demo_directory("recursive_types"))
+    .cargo_glue_gun()
+    .check_dir("recursive_types-java/java_src/recursiveTypes", |names| {
+        anyhow::ensure!(names == ["Functions.java"], "unexpected classes: {names:?}");
+        Ok(())
+    })
+    .cargo_build_plugin_crates()
+    .execute()
+}
+
+#[test]
+fn recursive_types_rejected() -> anyhow::Result<()> {
+    // Once a function returns a `Node`, Java has to copy it, which it cannot do yet.
+    let err = gluegun_test_harness::Test::new("recursive_types", &["java"], demo_directory("recursive_types"))
+    .replace("src/lib.rs", "fn leaf(", "pub fn leaf(")
+    .cargo_glue_gun()
+    .execute()
+    .expect_err("a recursive type cannot be returned");
+    let unsupported = err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<gluegun_core::cli::UnsupportedErrors>())
+        .ok_or_else(|| anyhow::anyhow!("unexpected error: {err:#}"))?;
+    let subjects: Vec<&str> = unsupported.0.iter().map(|error| error.subject.as_str()).collect();
+    anyhow::ensure!(subjects == ["recursive_types::Node"], "unexpected unsupported items: {subjects:?}");
+    Ok(())
+}
+
+#[test]
+fn unit_and_never() -> anyhow::Result<()> {
+    // Functions returning `()` or `!` are `void` in Java; the latter throw if the native call ever returns.
+    gluegun_test_harness::Test::new("unit_and_never", &["java"], demo_directory("unit_and_never"))
+    .cargo_glue_gun()
+    .check_file_contains(
+        "unit_and_never-java/java_src/unitAndNever/Functions.java",
+        &[
+            "public static void implicit_unit(",
+            "public static void explicit_unit(",
+            "public static void never_returns(\n    ) {\n        native_never_returns(\n        );\n        throw new AssertionError(\"`never_returns` is declared as never returning\");\n    }",
+        ],
+    )
+    .cargo_build_plugin_crates()
+    .run("javac", ["-d", "classes", "unit_and_never-java/java_src/unitAndNever/Functions.java"])
+    .execute()
+}
@@ -0,0 +1,78 @@
+use gluegun_java_util::{jar, javac::Javac};
+use temp_dir::TempDir;
+
+#[test]
+fn javac_compiles_java_sources() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let package_dir = dir.child("java_src/com/example");
+    std::fs::create_dir_all(&package_dir)?;
+    std::fs::write(
+        package_dir.join("Greeter.java"),
+        "package com.example;\npublic class Greeter { public static String greet() { return Hello.TEXT; } }\n",
+    )?;
+    std::fs::write(
+        package_dir.join("Hello.java"),
+        "package com.example;\nclass Hello { static final String TEXT = \"Hello\"; }\n",
+    )?;
+    let class_files = dir.child("classes");
+    std::fs::create_dir(&class_files)?;
+
+    let javac = Javac::locate(std::env::var_os("JAVA_HOME").as_deref())?;
+    javac.compile(&dir.child("java_src"), &class_files, Some(11))?;
+    assert!(class_files.join("com/example/Greeter.class").is_file());
+    assert!(class_files.join("com/example/Hello.class").is_file());
+
+    // Compilation errors are reported (with javac's diagnostics on stderr).
+    std::fs::write(package_dir.join("Broken.java"), "package com.example;\nclass Broken { int x = \"\"; }\n")?;
+    let err = javac
+        .compile(&dir.child("java_src"), &class_files, None)
+        .expect_err("`Broken.java` does not compile");
+    assert!(format!("{err:#}").contains("`javac` failed"), "unexpected error: {err:#}");
+    Ok(())
+}
+
+#[test]
+fn javac_missing_from_java_home() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let err = Javac::locate(Some(dir.path().as_os_str())).expect_err("no JDK in an empty directory");
+    let message = format!("{err:#}");
+    assert!(message.contains("`JAVA_HOME` is set to"), "unexpected error: {message}");
+    assert!(message.contains("point `JAVA_HOME` at a JDK"), "unexpected error: {message}");
+    Ok(())
+}
+
+/// A `Javac` found in a fake `JAVA_HOME` under `dir`; it is never run.
+fn fake_javac(dir: &TempDir) -> anyhow::Result<Javac> {
+    let bin = dir.child("jdk/bin");
+    std::fs::create_dir_all(&bin)?;
+    std::fs::write(bin.join(format!("javac{}", std::env::consts::EXE_SUFFIX)), "")?;
+    Javac::locate(Some(dir.child("jdk").as_os_str()))
+}
+
+/// The arguments `javac` is run with to compile a single source for `target_version`.
+fn javac_args(target_version: Option<u32>) -> anyhow::Result<Vec<String>> {
+    let dir = TempDir::new()?;
+    let javac = fake_javac(&dir)?;
+    std::fs::create_dir_all(dir.child("java_src/demo"))?;
+    std::fs::write(dir.child("java_src/demo/Functions.java"), "package demo; public class Functions {}\n")?;
+
+    let command = javac.command(&dir.child("java_src"), &dir.child("classes"), target_version)?;
+    Ok(command.get_args().map(|arg| arg.to_string_lossy().into_owned()).collect())
+}
+
+#[test]
+fn javac_release_is_the_target_version() -> anyhow::Result<()> {
+    let args = javac_args(Some(11))?;
+    let release = args.iter().position(|arg| arg == "--release").expect("no `--release` argument");
+    assert_eq!(args[release + 1], "11");
+
+    let args = javac_args(None)?;
+    assert!(!args.iter().any(|arg| arg == "--release"), "unexpected `--release` in {args:?}");
+    Ok(())
+}
+
+#[test]
+fn manifest_records_the_target_version() {
+    assert_eq!(jar::manifest(Some(17)), "Manifest-Version: 1.0\nCreated-By: gluegun-java-util\nX-GlueGun-Target-Version: 17\n");
+    assert!(!jar::manifest(None).contains(jar::TARGET_VERSION_ATTRIBUTE));
+}
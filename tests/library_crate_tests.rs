@@ -0,0 +1,89 @@
+use gluegun_core::codegen::LibraryCrate;
+use gluegun_core::protocol::GlueGunDestinationCrate;
+use temp_dir::TempDir;
+
+/// A crate to be generated at `dir/bindings`, binding a crate with a single function.
+fn library_crate(dir: &TempDir) -> anyhow::Result<LibraryCrate> {
+    library_crate_at(dir, dir.child("bindings"), false)
+}
+
+/// A crate to be generated at `path`, or merged into the crate `path` is a subdirectory of if `merge`.
+fn library_crate_at(dir: &TempDir, path: std::path::PathBuf, merge: bool) -> anyhow::Result<LibraryCrate> {
+    std::fs::write(dir.child("lib.rs"), "pub fn greet() {}\n")?;
+    let idl = gluegun_idl::Parser::new().parse_crate_named("greeter", dir.path(), dir.child("lib.rs"))?;
+    let dest_crate = GlueGunDestinationCrate {
+        path,
+        crate_name: "bindings".to_string(),
+        split_modules: false,
+        edition: None,
+        rust_version: None,
+        no_format: true,
+        merge,
+    };
+    LibraryCrate::from_args(&dest_crate, String::new(), &idl, "test")
+}
+
+#[test]
+fn nested_files_are_written() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let mut krate = library_crate(&dir)?;
+    write!(krate.add_file("src/lib.rs")?, "mod models;")?;
+    write!(krate.add_dir("src/models")?.add_file("mod.rs")?, "mod shapes;")?;
+    write!(krate.add_file("./src/models/shapes/circle.rs")?, "pub struct Circle;")?;
+
+    // The same file cannot be added twice, however it is named.
+    krate.add_file("src/./models/mod.rs").map(drop).expect_err("`src/models/mod.rs` was already added");
+
+    krate.generate()?;
+    assert_eq!(std::fs::read_to_string(dir.child("bindings/src/models/mod.rs"))?, "mod shapes;\n");
+    assert_eq!(std::fs::read_to_string(dir.child("bindings/src/models/shapes/circle.rs"))?, "pub struct Circle;\n");
+    assert!(dir.child("bindings").join(gluegun_core::protocol::GENERATED_MARKER).exists());
+    Ok(())
+}
+
+#[test]
+fn paths_outside_the_crate_are_rejected() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let mut krate = library_crate(&dir)?;
+
+    for path in ["../outside.rs", "src/../../outside.rs", "/tmp/outside.rs", "."] {
+        let err = krate.add_file(path).map(drop).expect_err("the path is not within the crate");
+        assert!(format!("{err:#}").contains(&format!("path `{path}`")), "unexpected error: {err:#}");
+    }
+    krate.add_dir("..").map(drop).expect_err("the directory is not within the crate");
+    krate.add_dir("src")?.add_file("../../outside.rs").map(drop).expect_err("the path is not within the crate");
+    Ok(())
+}
+
+#[test]
+fn merged_crate_keeps_other_files() -> anyhow::Result<()> {
+    let dir = TempDir::new()?;
+    let manifest = "[package]\nname = \"bindings\"\nversion = \"0.1.0\"\nedition = \"2021\"\n";
+    let lib_rs = "#[path = \"../generated/src/lib.rs\"]\nmod generated;\n";
+    std::fs::create_dir_all(dir.child("bindings/src"))?;
+    std::fs::write(dir.child("bindings/Cargo.toml"), manifest)?;
+    std::fs::write(dir.child("bindings/src/lib.rs"), lib_rs)?;
+
+    let generate = |files: &[&str]| -> anyhow::Result<()> {
+        let mut krate = library_crate_at(&dir, dir.child("bindings/generated"), true)?;
+        krate.add_dependency("serde").version("1");
+        for file in files {
+            write!(krate.add_file(file)?, "// {file}")?;
+        }
+        krate.generate()
+    };
+
+    // The first run creates the subdirectory; the next one updates it in place, removing what is no longer generated.
+    generate(&["src/lib.rs", "src/extra.rs"])?;
+    assert!(dir.child("bindings/generated/src/extra.rs").exists());
+    generate(&["src/lib.rs"])?;
+    assert!(!dir.child("bindings/generated/src/extra.rs").exists());
+    assert_eq!(std::fs::read_to_string(dir.child("bindings/generated/src/lib.rs"))?, "// src/lib.rs\n");
+    assert!(dir.child("bindings/generated").join(gluegun_core::protocol::GENERATED_MARKER).exists());
+
+    // The files of the crate outside of the subdirectory are left alone, including its manifest.
+    assert_eq!(std::fs::read_to_string(dir.child("bindings/Cargo.toml"))?, manifest);
+    assert_eq!(std::fs::read_to_string(dir.child("bindings/src/lib.rs"))?, lib_rs);
+    assert!(!dir.child("bindings").join(gluegun_core::protocol::GENERATED_MARKER).exists());
+    Ok(())
+}